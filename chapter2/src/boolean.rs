@@ -0,0 +1,566 @@
+//! Boolean operations of closed polygon meshes: union, intersection and difference.
+//!
+//! The triangles are split along the intersection curves, and the pieces are classified
+//! by the signed distance to the other mesh. The predicates deciding the topology are evaluated
+//! exactly by the crate `robust`, so the result is closed if the meshes are in general position,
+//! i.e. no vertex of one mesh is on a face of the other. The crossing points themselves are
+//! interpolated in floating point, so the result is not exact.
+//!
+//! The coplanar or touching faces, which the exact predicates detect, are moved apart by
+//! translating the second mesh by a tiny vector, a billionth of the size of the meshes.
+//! The vertices of the second mesh are put back afterwards, so only the crossing points deviate
+//! from the exact intersection curves, by the length of the translation at most. The coplanar
+//! regions become slivers without areas, which keep the result closed.
+
+use crate::bvh::{SignedDistance, TriangleTree};
+use crate::triangulate::{signed_area, triangulate_polygon};
+use robust::Coord3D;
+use std::collections::{HashMap, HashSet};
+use truck_meshalgo::prelude::*;
+
+/// The kinds of the boolean operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BooleanOperation {
+    /// the points in either mesh
+    Union,
+    /// the points in both meshes
+    Intersection,
+    /// the points in the first mesh and not in the second one
+    Difference,
+}
+
+/// Errors of the boolean operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BooleanError {
+    /// The operands have to be closed to determine the insides.
+    NotClosed,
+    /// The meshes are not in general position even after the perturbation, e.g. they have
+    /// triangles without areas.
+    Degenerate,
+    /// Some triangle could not be split along the intersection curves.
+    TriangulationFailed,
+}
+
+impl std::fmt::Display for BooleanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BooleanError::NotClosed => f.write_str("the mesh is not closed"),
+            BooleanError::Degenerate => f.write_str("the meshes are not in general position"),
+            BooleanError::TriangulationFailed => {
+                f.write_str("failed to split a triangle along the intersection curves")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BooleanError {}
+
+/// Returns the union of the closed meshes.
+pub fn union(mesh0: &PolygonMesh, mesh1: &PolygonMesh) -> Result<PolygonMesh, BooleanError> {
+    boolean(mesh0, mesh1, BooleanOperation::Union)
+}
+
+/// Returns the intersection of the closed meshes.
+pub fn intersection(mesh0: &PolygonMesh, mesh1: &PolygonMesh) -> Result<PolygonMesh, BooleanError> {
+    boolean(mesh0, mesh1, BooleanOperation::Intersection)
+}
+
+/// Returns `mesh0` carved by `mesh1`.
+pub fn difference(mesh0: &PolygonMesh, mesh1: &PolygonMesh) -> Result<PolygonMesh, BooleanError> {
+    boolean(mesh0, mesh1, BooleanOperation::Difference)
+}
+
+// A point of the result is identified by its origin, so that the triangles
+// sharing the point use the same index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum PointKey {
+    // the position of the mesh `side`
+    Vertex(usize, usize),
+    // the crossing of the edge, the sorted position indices, of the mesh `side`
+    // and the triangle of the other mesh
+    Crossing(usize, [usize; 2], usize),
+}
+
+struct Operand {
+    positions: Vec<Point3>,
+    triangles: Vec<[usize; 3]>,
+}
+
+impl Operand {
+    // the triangles of `mesh` translated by `shift`
+    fn new(mesh: &PolygonMesh, shift: Vector3) -> Self {
+        let triangles = mesh.faces().triangle_iter();
+        Self {
+            positions: mesh.positions().iter().map(|p| p + shift).collect(),
+            triangles: triangles.map(|tri| tri.map(|v| v.pos)).collect(),
+        }
+    }
+
+    fn points(&self, t: usize) -> [Point3; 3] { self.triangles[t].map(|i| self.positions[i]) }
+
+    // the mesh of the triangles, which has only the positions
+    fn to_mesh(&self) -> PolygonMesh {
+        let attrs = StandardAttributes {
+            positions: self.positions.clone(),
+            ..Default::default()
+        };
+        let mut mesh = PolygonMesh::new(attrs, Faces::from_iter(&self.triangles));
+        mesh.remove_unused_attrs();
+        mesh
+    }
+}
+
+#[derive(Default)]
+struct Points {
+    keys: Vec<PointKey>,
+    points: Vec<Point3>,
+    map: HashMap<PointKey, usize>,
+}
+
+impl Points {
+    fn get(&mut self, key: PointKey, point: impl FnOnce() -> Point3) -> usize {
+        *self.map.entry(key).or_insert_with(|| {
+            self.keys.push(key);
+            self.points.push(point());
+            self.points.len() - 1
+        })
+    }
+}
+
+fn orient3d(a: Point3, b: Point3, c: Point3, d: Point3) -> f64 {
+    let coord = |p: Point3| Coord3D {
+        x: p.x,
+        y: p.y,
+        z: p.z,
+    };
+    robust::orient3d(coord(a), coord(b), coord(c), coord(d))
+}
+
+// whether the signs are strictly opposite, without the underflow of the product
+fn opposite(a: f64, b: f64) -> bool { (a > 0.0 && b < 0.0) || (a < 0.0 && b > 0.0) }
+
+/// Returns the result of the boolean operation `op` of the closed meshes.
+///
+/// The polygons are divided into triangles, and the result has only the positions.
+/// The shared positions of each mesh must be put together in advance.
+pub fn boolean(
+    mesh0: &PolygonMesh,
+    mesh1: &PolygonMesh,
+    op: BooleanOperation,
+) -> Result<PolygonMesh, BooleanError> {
+    if mesh0.shell_condition() != ShellCondition::Closed
+        || mesh1.shell_condition() != ShellCondition::Closed
+    {
+        return Err(BooleanError::NotClosed);
+    }
+    let operands = [mesh0, mesh1].map(|mesh| Operand::new(mesh, Vector3::zero()));
+    // The empty mesh has no points: the result is one of the operands or empty.
+    let empty = operands.each_ref().map(|op| op.triangles.is_empty());
+    if empty[0] || empty[1] {
+        let result = match (op, empty) {
+            (BooleanOperation::Union, [true, _]) => &operands[1],
+            (BooleanOperation::Union | BooleanOperation::Difference, _) => &operands[0],
+            (BooleanOperation::Intersection, [true, _]) => &operands[0],
+            (BooleanOperation::Intersection, _) => &operands[1],
+        };
+        return Ok(result.to_mesh());
+    }
+    // The meshes in general position are not translated. Otherwise, a few lengths are tried
+    // in case the translated meshes happen to be degenerate again.
+    let size: BoundingBox<Point3> = mesh0.positions().iter().chain(mesh1.positions()).collect();
+    let direction = Vector3::new(1.0, f64::sqrt(2.0), f64::sqrt(3.0)).normalize();
+    let mut result = boolean_in_general_position(operands, op);
+    for scale in [1.0e-9, 1.0e-8, 1.0e-7] {
+        if result.as_ref().err() != Some(&BooleanError::Degenerate) {
+            break;
+        }
+        let shift = direction * (size.diameter() * scale);
+        let operands = [Operand::new(mesh0, Vector3::zero()), Operand::new(mesh1, shift)];
+        result = boolean_in_general_position(operands, op).map(|(mut points, faces)| {
+            points.keys.iter().zip(&mut points.points).for_each(|(key, p)| match key {
+                PointKey::Vertex(1, i) => *p = mesh1.positions()[*i],
+                PointKey::Crossing(1, ..) => *p -= shift,
+                _ => {}
+            });
+            (points, faces)
+        });
+    }
+    let (points, faces) = result?;
+    let attrs = StandardAttributes {
+        positions: points.points,
+        ..Default::default()
+    };
+    let mut result = PolygonMesh::new(attrs, Faces::from_iter(faces));
+    result.remove_unused_attrs();
+    Ok(result)
+}
+
+// Returns the points and the triangles of the result, or `BooleanError::Degenerate` if
+// the exact predicates find the coplanar or touching faces.
+fn boolean_in_general_position(
+    operands: [Operand; 2],
+    op: BooleanOperation,
+) -> Result<(Points, Vec<[usize; 3]>), BooleanError> {
+    let mut points = Points::default();
+    let segments = intersection_segments(&operands, &mut points)?;
+
+    // the triangles of each side split along the segments, and the edges on the curves
+    let mut constrained = HashSet::<[usize; 2]>::new();
+    let mut pieces: [Vec<[usize; 3]>; 2] = Default::default();
+    for side in 0..2 {
+        for (t, segs) in segments[side].iter().enumerate() {
+            let tri = operands[side].triangles[t];
+            let corners = tri.map(|i| {
+                let positions = &operands[side].positions;
+                points.get(PointKey::Vertex(side, i), || positions[i])
+            });
+            match segs.is_empty() {
+                true => pieces[side].push(corners),
+                false => {
+                    segs.iter().for_each(|[a, b]| {
+                        constrained.insert([usize::min(*a, *b), usize::max(*a, *b)]);
+                    });
+                    let normal = {
+                        let [a, b, c] = operands[side].points(t);
+                        (b - a).cross(c - a)
+                    };
+                    let split = split_triangle(side, tri, corners, normal, segs, &points)?;
+                    pieces[side].extend(split);
+                }
+            }
+        }
+    }
+
+    // classify the connected patches bounded by the curves
+    let keep = |side: usize, inside: bool| match (op, side) {
+        (BooleanOperation::Union, _) => !inside,
+        (BooleanOperation::Intersection, _) => inside,
+        (BooleanOperation::Difference, 0) => !inside,
+        (BooleanOperation::Difference, _) => inside,
+    };
+    let mut faces = Vec::<[usize; 3]>::new();
+    for side in 0..2 {
+        let other = SignedDistance::new(&operands[1 - side].to_mesh());
+        let other = other.ok_or(BooleanError::NotClosed)?;
+        let patches = patches(&pieces[side], &constrained);
+        patches.iter().for_each(|patch| {
+            // the piece farthest from the other mesh is the most reliable one
+            let distance = patch
+                .iter()
+                .map(|t| {
+                    let [a, b, c] = pieces[side][*t].map(|i| points.points[i].to_vec());
+                    other.distance(Point3::from_vec((a + b + c) / 3.0))
+                })
+                .max_by(|d0, d1| d0.abs().total_cmp(&d1.abs()))
+                .unwrap();
+            if keep(side, distance < 0.0) {
+                let flip = op == BooleanOperation::Difference && side == 1;
+                faces.extend(patch.iter().map(|t| {
+                    let [a, b, c] = pieces[side][*t];
+                    match flip {
+                        true => [a, c, b],
+                        false => [a, b, c],
+                    }
+                }));
+            }
+        });
+    }
+    Ok((points, faces))
+}
+
+// Returns the segments of the intersection curves in each triangle of each side.
+// The endpoints of the segments are the crossings of the edges and the triangles.
+fn intersection_segments(
+    operands: &[Operand; 2],
+    points: &mut Points,
+) -> Result<[Vec<Vec<[usize; 2]>>; 2], BooleanError> {
+    let tree =
+        |op: &Operand| TriangleTree::new((0..op.triangles.len()).map(|t| op.points(t)).collect());
+    let pairs = tree(&operands[0]).overlapping_pairs(&tree(&operands[1]));
+    let mut segments = operands
+        .each_ref()
+        .map(|op| vec![Vec::new(); op.triangles.len()]);
+    for (t0, t1) in pairs {
+        let tris = [(0, t0, t1), (1, t1, t0)];
+        let sides = tris.map(|(side, t, s)| {
+            let [a, b, c] = operands[1 - side].points(s);
+            operands[side].points(t).map(|p| orient3d(a, b, c, p))
+        });
+        let separated =
+            |sides: [f64; 3]| sides.iter().all(|s| *s > 0.0) || sides.iter().all(|s| *s < 0.0);
+        if sides.iter().any(|sides| separated(*sides)) {
+            continue;
+        }
+        if sides.iter().flatten().any(|s| *s == 0.0) {
+            return Err(BooleanError::Degenerate);
+        }
+        let mut crossings = Vec::with_capacity(2);
+        for (side, t, s) in tris {
+            let (op, other) = (&operands[side], &operands[1 - side]);
+            let tri = op.triangles[t];
+            for k in 0..3 {
+                let edge = [tri[k], tri[(k + 1) % 3]];
+                let [p, q] = edge.map(|i| op.positions[i]);
+                if !opposite(sides[side][k], sides[side][(k + 1) % 3]) {
+                    continue;
+                }
+                // the edge pierces the triangle if it passes the three edges in the same direction
+                let [a, b, c] = other.points(s);
+                let turns = [
+                    orient3d(p, q, a, b),
+                    orient3d(p, q, b, c),
+                    orient3d(p, q, c, a),
+                ];
+                if turns.contains(&0.0) {
+                    return Err(BooleanError::Degenerate);
+                }
+                if turns.iter().all(|x| *x > 0.0) || turns.iter().all(|x| *x < 0.0) {
+                    let edge = [usize::min(edge[0], edge[1]), usize::max(edge[0], edge[1])];
+                    let key = PointKey::Crossing(side, edge, s);
+                    crossings.push(points.get(key, || {
+                        // computed from the sorted edge, so that the point does not depend on the pair
+                        let [p, q] = edge.map(|i| op.positions[i]);
+                        let (dp, dq) = (orient3d(a, b, c, p), orient3d(a, b, c, q));
+                        p + (q - p) * (dp / (dp - dq))
+                    }));
+                }
+            }
+        }
+        match crossings.len() {
+            0 => {}
+            2 => {
+                let segment = [crossings[0], crossings[1]];
+                segments[0][t0].push(segment);
+                segments[1][t1].push(segment);
+            }
+            _ => return Err(BooleanError::Degenerate),
+        }
+    }
+    Ok(segments)
+}
+
+// a polygon with holes, of the indices of the points
+struct Region {
+    outer: Vec<usize>,
+    holes: Vec<Vec<usize>>,
+}
+
+// Splits the triangle along the segments. The pieces have the orientation of the triangle.
+fn split_triangle(
+    side: usize,
+    tri: [usize; 3],
+    corners: [usize; 3],
+    normal: Vector3,
+    segments: &[[usize; 2]],
+    points: &Points,
+) -> Result<Vec<[usize; 3]>, BooleanError> {
+    // project onto the coordinate plane keeping the orientation counter-clockwise
+    let axis = match (normal.x.abs(), normal.y.abs(), normal.z.abs()) {
+        (x, y, z) if x >= y && x >= z => 0,
+        (_, y, z) if y >= z => 1,
+        _ => 2,
+    };
+    let sign = normal[axis].signum();
+    let project = |i: usize| {
+        let p = points.points[i];
+        match axis {
+            0 => Point2::new(p.y, sign * p.z),
+            1 => Point2::new(p.z, sign * p.x),
+            _ => Point2::new(p.x, sign * p.y),
+        }
+    };
+
+    // the boundary of the triangle with the crossings on the edges
+    let mut adjacency = HashMap::<usize, Vec<usize>>::new();
+    segments.iter().for_each(|[a, b]| {
+        adjacency.entry(*a).or_default().push(*b);
+        adjacency.entry(*b).or_default().push(*a);
+    });
+    let mut ring = Vec::new();
+    for k in 0..3 {
+        let (i, j) = (tri[k], tri[(k + 1) % 3]);
+        let edge = [usize::min(i, j), usize::max(i, j)];
+        let start = points.points[corners[k]];
+        let mut on_edge: Vec<usize> = adjacency
+            .keys()
+            .copied()
+            .filter(|p| matches!(points.keys[*p], PointKey::Crossing(s, e, _) if s == side && e == edge))
+            .collect();
+        on_edge.sort_by(|p, q| {
+            let (dp, dq) = (
+                start.distance2(points.points[*p]),
+                start.distance2(points.points[*q]),
+            );
+            dp.total_cmp(&dq)
+        });
+        ring.push(corners[k]);
+        ring.extend(on_edge);
+    }
+    let on_boundary: HashSet<usize> = ring.iter().copied().collect();
+    if adjacency
+        .iter()
+        .any(|(p, adj)| adj.len() != 2 - on_boundary.contains(p) as usize)
+    {
+        return Err(BooleanError::Degenerate);
+    }
+
+    // the chains from the boundary to the boundary, and the loops in the triangle
+    let mut visited = HashSet::<[usize; 2]>::new();
+    let mut walk = |start: usize| {
+        let mut chain = vec![start];
+        loop {
+            let cur = *chain.last().unwrap();
+            let next = adjacency[&cur]
+                .iter()
+                .copied()
+                .find(|next| visited.insert([usize::min(cur, *next), usize::max(cur, *next)]));
+            match next {
+                Some(next) if next != start => chain.push(next),
+                _ => return chain,
+            }
+        }
+    };
+    let chains: Vec<Vec<usize>> = ring
+        .iter()
+        .filter(|p| adjacency.contains_key(p))
+        .filter_map(|p| {
+            let chain = walk(*p);
+            (chain.len() > 1).then_some(chain)
+        })
+        .collect();
+    let mut loops: Vec<Vec<usize>> = segments
+        .iter()
+        .filter_map(|[a, _]| {
+            let chain = walk(*a);
+            (chain.len() > 2).then_some(chain)
+        })
+        .collect();
+
+    let mut regions = vec![Region {
+        outer: ring,
+        holes: Vec::new(),
+    }];
+    for chain in chains {
+        let (u, v) = (chain[0], *chain.last().unwrap());
+        let (r, iu, iv) = regions
+            .iter()
+            .enumerate()
+            .find_map(|(r, region)| {
+                let iu = region.outer.iter().position(|p| *p == u)?;
+                let iv = region.outer.iter().position(|p| *p == v)?;
+                Some((r, iu, iv))
+            })
+            .ok_or(BooleanError::TriangulationFailed)?;
+        let outer = std::mem::take(&mut regions[r].outer);
+        let len = outer.len();
+        let arc = |from: usize, to: usize| {
+            let count = (to + len - from) % len + 1;
+            (0..count)
+                .map(|k| outer[(from + k) % len])
+                .collect::<Vec<_>>()
+        };
+        let interior = &chain[1..chain.len() - 1];
+        let (mut outer0, mut outer1) = (arc(iu, iv), arc(iv, iu));
+        outer0.extend(interior.iter().rev());
+        outer1.extend(interior);
+        regions[r].outer = outer0;
+        regions.push(Region {
+            outer: outer1,
+            holes: Vec::new(),
+        });
+    }
+    // The loops are holes of the regions, from the outermost ones.
+    let area =
+        |polygon: &[usize]| signed_area(&polygon.iter().map(|i| project(*i)).collect::<Vec<_>>());
+    loops.sort_by(|l0, l1| area(l1).abs().total_cmp(&area(l0).abs()));
+    for mut hole in loops {
+        if area(&hole) < 0.0 {
+            hole.reverse();
+        }
+        let point = project(hole[0]);
+        let contains = |polygon: &[usize]| {
+            let polygon: Vec<Point2> = polygon.iter().map(|i| project(*i)).collect();
+            point_in_polygon(&polygon, point)
+        };
+        let region = regions
+            .iter_mut()
+            .find(|region| contains(&region.outer) && !region.holes.iter().any(|h| contains(h)))
+            .ok_or(BooleanError::TriangulationFailed)?;
+        region.holes.push(hole.clone());
+        regions.push(Region {
+            outer: hole,
+            holes: Vec::new(),
+        });
+    }
+
+    let mut triangles = Vec::new();
+    for region in regions {
+        let indices: Vec<usize> = region
+            .outer
+            .iter()
+            .chain(region.holes.iter().flatten())
+            .copied()
+            .collect();
+        let outer: Vec<Point2> = region.outer.iter().map(|i| project(*i)).collect();
+        let holes: Vec<Vec<Point2>> = region
+            .holes
+            .iter()
+            .map(|hole| hole.iter().map(|i| project(*i)).collect())
+            .collect();
+        let tris = triangulate_polygon(&outer, &holes).ok_or(BooleanError::TriangulationFailed)?;
+        triangles.extend(tris.into_iter().map(|tri| tri.map(|k| indices[k])));
+    }
+    Ok(triangles)
+}
+
+// whether `point` is in `polygon`, by the crossing number
+fn point_in_polygon(polygon: &[Point2], point: Point2) -> bool {
+    let len = polygon.len();
+    (0..len)
+        .filter(|i| {
+            let (a, b) = (polygon[*i], polygon[(*i + 1) % len]);
+            (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y)
+        })
+        .count()
+        % 2
+        == 1
+}
+
+// Returns the triangles of each patch, the connected components not crossing the constrained edges.
+fn patches(triangles: &[[usize; 3]], constrained: &HashSet<[usize; 2]>) -> Vec<Vec<usize>> {
+    // union-find of the triangles
+    let mut parents: Vec<usize> = (0..triangles.len()).collect();
+    fn root(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+    let mut edge_map = HashMap::<[usize; 2], usize>::new();
+    triangles.iter().enumerate().for_each(|(t, tri)| {
+        (0..3).for_each(|k| {
+            let (i, j) = (tri[k], tri[(k + 1) % 3]);
+            let edge = [usize::min(i, j), usize::max(i, j)];
+            if constrained.contains(&edge) {
+                return;
+            }
+            if let Some(s) = edge_map.insert(edge, t) {
+                let (r, q) = (root(&mut parents, s), root(&mut parents, t));
+                parents[usize::max(r, q)] = usize::min(r, q);
+            }
+        })
+    });
+    let mut patch_map = HashMap::<usize, usize>::new();
+    let mut patches = Vec::<Vec<usize>>::new();
+    (0..triangles.len()).for_each(|t| {
+        let root = root(&mut parents, t);
+        let idx = *patch_map.entry(root).or_insert_with(|| {
+            patches.push(Vec::new());
+            patches.len() - 1
+        });
+        patches[idx].push(t);
+    });
+    patches
+}
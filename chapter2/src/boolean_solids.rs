@@ -0,0 +1,112 @@
+use chapter2::{boolean::*, fixtures, polyhedra::*};
+use std::iter::FromIterator;
+use truck_meshalgo::prelude::*;
+
+/// Output the contents of `polygon` to the file specified by `path`.
+fn write_polygon(mut polygon: PolygonMesh, path: &str) {
+    let mut obj = std::fs::File::create(path).unwrap();
+    polygon.add_naive_normals(true);
+    obj::write(&polygon, &mut obj).unwrap();
+}
+
+fn main() {
+    let (cube, octahedron) = (hexahedron(), octahedron());
+    // The vertices of the octahedron stick out of the faces of the cube. Each of the six tips
+    // out of the cube is a pyramid of the height 1 - a whose base is the square of the diagonal 2(1 - a).
+    let a = f64::sqrt(3.0) / 3.0;
+    let cube_volume = f64::powi(2.0 * a, 3);
+    let octahedron_volume = 4.0 / 3.0;
+    let common = octahedron_volume - 6.0 * 2.0 * f64::powi(1.0 - a, 3) / 3.0;
+    let expected = [
+        ("union", cube_volume + octahedron_volume - common),
+        ("intersection", common),
+        ("difference", cube_volume - common),
+    ];
+    let results = [
+        union(&cube, &octahedron),
+        intersection(&cube, &octahedron),
+        difference(&cube, &octahedron),
+    ];
+    for ((name, exact), result) in expected.into_iter().zip(results) {
+        let result = result.unwrap();
+        println!("{name}: volume {:.6} (exact: {exact:.6})", result.volume());
+        assert_eq!(result.shell_condition(), ShellCondition::Closed);
+        assert!(f64::abs(result.volume() - exact) < 1.0e-6);
+        write_polygon(result, &format!("cube-{name}-octahedron.obj"));
+    }
+
+    // the octahedron carved by the cube: only the six tips are left
+    let tips = difference(&octahedron, &cube).unwrap();
+    assert_eq!(tips.shell_condition(), ShellCondition::Closed);
+    assert!(f64::abs(tips.volume() - (octahedron_volume - common)) < 1.0e-6);
+
+    // the spheres of section 2.4, one of which is shifted to be in general position
+    let sphere = fixtures::sphere();
+    let mut shifted = sphere.clone();
+    shifted
+        .positions_mut()
+        .iter_mut()
+        .for_each(|p| *p += Vector3::new(0.61, 0.23, 0.17));
+    let volume = sphere.volume();
+    let united = union(&sphere, &shifted).unwrap();
+    let common = intersection(&sphere, &shifted).unwrap();
+    println!(
+        "spheres: union {:.6}, intersection {:.6}",
+        united.volume(),
+        common.volume()
+    );
+    assert_eq!(united.shell_condition(), ShellCondition::Closed);
+    assert_eq!(common.shell_condition(), ShellCondition::Closed);
+    // the inclusion-exclusion principle
+    assert!(f64::abs(united.volume() + common.volume() - 2.0 * volume) < 1.0e-6);
+
+    // The shifted cube has faces on the same planes as the original one.
+    let mut shifted = cube.clone();
+    shifted.positions_mut().iter_mut().for_each(|p| p.x += 0.5);
+    let common = f64::powi(2.0 * a, 2) * (2.0 * a - 0.5);
+    let united = union(&cube, &shifted).unwrap();
+    assert_eq!(united.shell_condition(), ShellCondition::Closed);
+    assert!(f64::abs(united.volume() - (2.0 * cube_volume - common)) < 1.0e-6);
+    let overlap = intersection(&cube, &shifted).unwrap();
+    assert_eq!(overlap.shell_condition(), ShellCondition::Closed);
+    assert!(f64::abs(overlap.volume() - common) < 1.0e-6);
+    // The cubes touching on a face are united into a cuboid.
+    let mut touching = cube.clone();
+    touching
+        .positions_mut()
+        .iter_mut()
+        .for_each(|p| p.x += 2.0 * a);
+    let united = union(&cube, &touching).unwrap();
+    println!("touching cubes: union {:.6}", united.volume());
+    assert_eq!(united.shell_condition(), ShellCondition::Closed);
+    assert!(f64::abs(united.volume() - 2.0 * cube_volume) < 1.0e-6);
+    // The octahedron has its vertices on the faces of the scaled cube.
+    let mut scaled = cube.clone();
+    scaled
+        .positions_mut()
+        .iter_mut()
+        .for_each(|p| *p *= 1.0 / a);
+    let carved = difference(&scaled, &octahedron).unwrap();
+    assert_eq!(carved.shell_condition(), ShellCondition::Closed);
+    assert!(f64::abs(carved.volume() - (8.0 - octahedron_volume)) < 1.0e-6);
+    // The empty mesh is the identity of the union and the difference.
+    let empty = PolygonMesh::default();
+    let volumes = [
+        union(&empty, &cube),
+        union(&cube, &empty),
+        difference(&cube, &empty),
+    ];
+    for result in volumes {
+        assert!(f64::abs(result.unwrap().volume() - cube_volume) < 1.0e-10);
+    }
+    assert!(intersection(&cube, &empty).unwrap().faces().is_empty());
+    assert!(difference(&empty, &cube).unwrap().faces().is_empty());
+    // The operands have to be closed.
+    let faces: Vec<Vec<StandardVertex>> =
+        octahedron.face_iter().skip(1).map(<[_]>::to_vec).collect();
+    let open = PolygonMesh::new(octahedron.attributes().clone(), Faces::from_iter(faces));
+    assert_eq!(
+        difference(&cube, &open).err(),
+        Some(BooleanError::NotClosed)
+    );
+}
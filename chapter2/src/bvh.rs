@@ -0,0 +1,345 @@
+//! Bounding volume hierarchy of the triangles of a polygon mesh.
+//!
+//! The tree accelerates the nearest point searches and the overlap queries,
+//! which are used by the offsetting and the analyzers of meshes.
+
+use std::collections::HashMap;
+use truck_meshalgo::prelude::*;
+
+// the maximum number of triangles in a leaf
+const LEAF_SIZE: usize = 4;
+
+/// The feature of a triangle on which the nearest point lies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feature {
+    /// the `i`th vertex
+    Vertex(usize),
+    /// the edge from the `i`th vertex to the `(i + 1) % 3`th vertex
+    Edge(usize),
+    /// the interior of the triangle
+    Face,
+}
+
+/// The result of the nearest point search.
+#[derive(Clone, Copy, Debug)]
+pub struct Nearest {
+    /// the index of the triangle
+    pub triangle: usize,
+    /// the nearest point on the triangle
+    pub point: Point3,
+    /// the feature of the triangle on which `point` lies
+    pub feature: Feature,
+    /// the distance from the query point
+    pub distance: f64,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    bdb: BoundingBox<Point3>,
+    // leaf: the range of `indices`, branch: the indices of the children in `nodes`
+    range: (usize, usize),
+    is_leaf: bool,
+}
+
+/// Bounding volume hierarchy of triangles.
+#[derive(Clone, Debug)]
+pub struct TriangleTree {
+    triangles: Vec<[Point3; 3]>,
+    indices: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+impl TriangleTree {
+    /// Creates the tree of `triangles`. The indices in the search results are those of `triangles`.
+    pub fn new(triangles: Vec<[Point3; 3]>) -> Self {
+        let mut tree = Self {
+            indices: (0..triangles.len()).collect(),
+            triangles,
+            nodes: Vec::new(),
+        };
+        if !tree.triangles.is_empty() {
+            tree.build(0, tree.triangles.len());
+        }
+        tree
+    }
+
+    /// Creates the tree of the triangles of `mesh`.
+    /// The triangles are indexed in the order of `mesh.faces().triangle_iter()`.
+    pub fn from_mesh(mesh: &PolygonMesh) -> Self {
+        let positions = mesh.positions();
+        let triangles = mesh.faces().triangle_iter();
+        Self::new(triangles.map(|tri| tri.map(|v| positions[v.pos])).collect())
+    }
+
+    /// Returns the triangles.
+    pub fn triangles(&self) -> &[[Point3; 3]] { &self.triangles }
+
+    // Creates the node of `indices[start..end]`, and returns the index of the node.
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let bdb: BoundingBox<Point3> = self.indices[start..end]
+            .iter()
+            .flat_map(|i| self.triangles[*i])
+            .collect();
+        let node_idx = self.nodes.len();
+        self.nodes.push(Node {
+            bdb,
+            range: (start, end),
+            is_leaf: true,
+        });
+        if end - start <= LEAF_SIZE {
+            return node_idx;
+        }
+        // split at the median of the centroids along the longest axis
+        let centroid =
+            |tri: &[Point3; 3]| (tri[0].to_vec() + tri[1].to_vec() + tri[2].to_vec()) / 3.0;
+        let diag = bdb.diagonal();
+        let axis = match (diag.x >= diag.y, diag.y >= diag.z, diag.x >= diag.z) {
+            (true, _, true) => 0,
+            (false, true, _) => 1,
+            _ => 2,
+        };
+        let mid = (start + end) / 2;
+        let triangles = &self.triangles;
+        self.indices[start..end].select_nth_unstable_by(mid - start, |i, j| {
+            let ci = centroid(&triangles[*i])[axis];
+            let cj = centroid(&triangles[*j])[axis];
+            ci.total_cmp(&cj)
+        });
+        let left = self.build(start, mid);
+        let right = self.build(mid, end);
+        let node = &mut self.nodes[node_idx];
+        node.range = (left, right);
+        node.is_leaf = false;
+        node_idx
+    }
+
+    /// Returns the nearest point on the triangles from `point`.
+    /// Returns `None` if there is no triangles.
+    pub fn nearest(&self, point: Point3) -> Option<Nearest> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let mut best: Option<Nearest> = None;
+        let mut stack = vec![0];
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx];
+            if best
+                .is_some_and(|best| box_distance2(node.bdb, point) > best.distance * best.distance)
+            {
+                continue;
+            }
+            let (i, j) = node.range;
+            match node.is_leaf {
+                true => self.indices[i..j].iter().for_each(|idx| {
+                    let (pt, feature) = closest_point_on_triangle(point, self.triangles[*idx]);
+                    let distance = pt.distance(point);
+                    if best.is_none_or(|best| distance < best.distance) {
+                        best = Some(Nearest {
+                            triangle: *idx,
+                            point: pt,
+                            feature,
+                            distance,
+                        });
+                    }
+                }),
+                false => {
+                    // visit the nearer child first
+                    let di = box_distance2(self.nodes[i].bdb, point);
+                    let dj = box_distance2(self.nodes[j].bdb, point);
+                    match di < dj {
+                        true => stack.extend([j, i]),
+                        false => stack.extend([i, j]),
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Returns the pairs of the indices of the triangles, of `self` and `other` respectively,
+    /// whose bounding boxes overlap. The pairs are in no particular order.
+    pub fn overlapping_pairs(&self, other: &TriangleTree) -> Vec<(usize, usize)> {
+        if self.nodes.is_empty() || other.nodes.is_empty() {
+            return Vec::new();
+        }
+        let mut pairs = Vec::new();
+        let mut stack = vec![(0, 0)];
+        while let Some((a, b)) = stack.pop() {
+            let (node0, node1) = (&self.nodes[a], &other.nodes[b]);
+            if !boxes_overlap(node0.bdb, node1.bdb) {
+                continue;
+            }
+            let ((i0, j0), (i1, j1)) = (node0.range, node1.range);
+            match (node0.is_leaf, node1.is_leaf) {
+                (true, true) => self.indices[i0..j0].iter().for_each(|s| {
+                    let bdb0: BoundingBox<Point3> = self.triangles[*s].iter().collect();
+                    other.indices[i1..j1].iter().for_each(|t| {
+                        let bdb1: BoundingBox<Point3> = other.triangles[*t].iter().collect();
+                        if boxes_overlap(bdb0, bdb1) {
+                            pairs.push((*s, *t));
+                        }
+                    })
+                }),
+                (true, false) => stack.extend([(a, i1), (a, j1)]),
+                (false, true) => stack.extend([(i0, b), (j0, b)]),
+                // descend into the larger node
+                (false, false) => match node0.bdb.diameter() >= node1.bdb.diameter() {
+                    true => stack.extend([(i0, b), (j0, b)]),
+                    false => stack.extend([(a, i1), (a, j1)]),
+                },
+            }
+        }
+        pairs
+    }
+}
+
+/// Signed distance function of an oriented polygon mesh, negative behind the faces.
+///
+/// The sign is determined by the angle-weighted pseudonormals of J. A. Bærentzen and H. Aanæs,
+/// so the mesh must be oriented and have the shared positions put together.
+/// If the mesh is closed and oriented outward, the distance is negative inside.
+///
+/// The open meshes are accepted so that the deviation is signed by the side of the surface.
+/// The callers which need the inside and the outside, such as the offsetting, have to check
+/// that the mesh is closed by themselves.
+#[derive(Clone, Debug)]
+pub struct SignedDistance {
+    tree: TriangleTree,
+    // the position indices of the triangles
+    triangles: Vec<[usize; 3]>,
+    face_normals: Vec<Vector3>,
+    edge_normals: HashMap<(usize, usize), Vector3>,
+    vertex_normals: Vec<Vector3>,
+}
+
+impl SignedDistance {
+    /// Creates the signed distance function of `mesh`.
+    /// Returns `None` if `mesh` is not oriented or has no triangles with positive areas.
+    pub fn new(mesh: &PolygonMesh) -> Option<Self> {
+        match mesh.shell_condition() {
+            ShellCondition::Oriented | ShellCondition::Closed => {}
+            _ => return None,
+        }
+        let positions = mesh.positions();
+        // The triangles without area have no normals, and the nearest points on them are also
+        // on the adjacent triangles, so they are skipped.
+        let (triangles, face_normals): (Vec<[usize; 3]>, Vec<Vector3>) = mesh
+            .faces()
+            .triangle_iter()
+            .map(|tri| tri.map(|v| v.pos))
+            .filter_map(|tri| {
+                let [a, b, c] = tri.map(|i| positions[i]);
+                let normal = (b - a).cross(c - a);
+                (normal.magnitude() > 0.0).then(|| (tri, normal.normalize()))
+            })
+            .unzip();
+        if triangles.is_empty() {
+            return None;
+        }
+        let mut edge_normals = HashMap::<(usize, usize), Vector3>::new();
+        let mut vertex_normals = vec![Vector3::zero(); positions.len()];
+        triangles
+            .iter()
+            .zip(&face_normals)
+            .for_each(|(tri, normal)| {
+                (0..3).for_each(|k| {
+                    let (i, j) = (tri[k], tri[(k + 1) % 3]);
+                    *edge_normals
+                        .entry((usize::min(i, j), usize::max(i, j)))
+                        .or_insert_with(Vector3::zero) += *normal;
+                    let p = positions[tri[k]];
+                    let u = (positions[tri[(k + 1) % 3]] - p).normalize();
+                    let v = (positions[tri[(k + 2) % 3]] - p).normalize();
+                    vertex_normals[tri[k]] += *normal * u.dot(v).clamp(-1.0, 1.0).acos();
+                });
+            });
+        Some(Self {
+            tree: TriangleTree::new(
+                triangles
+                    .iter()
+                    .map(|tri| tri.map(|i| positions[i]))
+                    .collect(),
+            ),
+            triangles,
+            face_normals,
+            edge_normals,
+            vertex_normals,
+        })
+    }
+
+    /// Returns the signed distance from `point` to the mesh, negative behind the faces.
+    pub fn distance(&self, point: Point3) -> f64 {
+        let nearest = self.tree.nearest(point).unwrap();
+        let tri = self.triangles[nearest.triangle];
+        let normal = match nearest.feature {
+            Feature::Face => self.face_normals[nearest.triangle],
+            Feature::Edge(k) => {
+                let (i, j) = (tri[k], tri[(k + 1) % 3]);
+                self.edge_normals[&(usize::min(i, j), usize::max(i, j))]
+            }
+            Feature::Vertex(k) => self.vertex_normals[tri[k]],
+        };
+        match (point - nearest.point).dot(normal) < 0.0 {
+            true => -nearest.distance,
+            false => nearest.distance,
+        }
+    }
+
+    /// Returns the bounding box of the mesh.
+    pub fn bounding_box(&self) -> BoundingBox<Point3> {
+        self.tree.triangles().iter().flatten().collect()
+    }
+}
+
+/// the squared distance from `point` to the box
+fn box_distance2(bdb: BoundingBox<Point3>, point: Point3) -> f64 {
+    let (min, max) = (bdb.min(), bdb.max());
+    (0..3)
+        .map(|i| f64::max(f64::max(min[i] - point[i], point[i] - max[i]), 0.0))
+        .map(|d| d * d)
+        .sum()
+}
+
+/// whether the closed boxes have a common point
+fn boxes_overlap(bdb0: BoundingBox<Point3>, bdb1: BoundingBox<Point3>) -> bool {
+    let ((min0, max0), (min1, max1)) = ((bdb0.min(), bdb0.max()), (bdb1.min(), bdb1.max()));
+    (0..3).all(|i| min0[i] <= max1[i] && min1[i] <= max0[i])
+}
+
+/// Returns the nearest point on the triangle and its feature.
+/// The algorithm is the one of "Real-Time Collision Detection" by C. Ericson.
+pub fn closest_point_on_triangle(p: Point3, [a, b, c]: [Point3; 3]) -> (Point3, Feature) {
+    let (ab, ac, ap) = (b - a, c - a, p - a);
+    let (d1, d2) = (ab.dot(ap), ac.dot(ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return (a, Feature::Vertex(0));
+    }
+    let bp = p - b;
+    let (d3, d4) = (ab.dot(bp), ac.dot(bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return (b, Feature::Vertex(1));
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return (a + ab * v, Feature::Edge(0));
+    }
+    let cp = p - c;
+    let (d5, d6) = (ab.dot(cp), ac.dot(cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return (c, Feature::Vertex(2));
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return (a + ac * w, Feature::Edge(2));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return (b + (c - b) * w, Feature::Edge(1));
+    }
+    let denom = 1.0 / (va + vb + vc);
+    let (v, w) = (vb * denom, vc * denom);
+    (a + ab * v + ac * w, Feature::Face)
+}
@@ -0,0 +1,116 @@
+//! Splitting polygon meshes into connected components, and merging them again.
+
+use std::collections::HashMap;
+use truck_meshalgo::prelude::*;
+
+/// Returns the indices of the faces of each connected component of `mesh`.
+///
+/// Two faces are connected if they share a position. The components are sorted by
+/// their first faces, and the faces in each component keep the order in `mesh`.
+pub fn component_faces(mesh: &PolygonMesh) -> Vec<Vec<usize>> {
+    // union-find of the positions
+    let mut parents: Vec<usize> = (0..mesh.positions().len()).collect();
+    fn root(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+    mesh.face_iter().for_each(|face| {
+        face.windows(2).for_each(|pair| {
+            let (i, j) = (
+                root(&mut parents, pair[0].pos),
+                root(&mut parents, pair[1].pos),
+            );
+            parents[usize::max(i, j)] = usize::min(i, j);
+        })
+    });
+
+    let mut component_map = HashMap::<usize, usize>::new();
+    let mut components = Vec::<Vec<usize>>::new();
+    mesh.face_iter().enumerate().for_each(|(i, face)| {
+        let Some(v) = face.first() else { return };
+        let root = root(&mut parents, v.pos);
+        let idx = *component_map.entry(root).or_insert_with(|| {
+            components.push(Vec::new());
+            components.len() - 1
+        });
+        components[idx].push(i);
+    });
+    components
+}
+
+/// Creates the mesh of the faces of `mesh` specified by `indices`.
+///
+/// Unlike `Splitting::create_mesh_by_face_indices`, the result has only the attributes
+/// referred by the faces, and they are reindexed in order of the first reference.
+pub fn extract_faces(mesh: &PolygonMesh, indices: &[usize]) -> PolygonMesh {
+    #[derive(Default)]
+    struct Reindexer(HashMap<usize, usize>);
+    impl Reindexer {
+        fn get(&mut self, idx: usize) -> usize {
+            let len = self.0.len();
+            *self.0.entry(idx).or_insert(len)
+        }
+        // the old indices ordered by the new ones
+        fn into_indices(self) -> Vec<usize> {
+            let mut indices = vec![0; self.0.len()];
+            self.0.into_iter().for_each(|(old, new)| indices[new] = old);
+            indices
+        }
+    }
+
+    let (mut pos, mut uv, mut nor) = <(Reindexer, Reindexer, Reindexer)>::default();
+    let faces = mesh.faces();
+    let new_faces: Vec<Vec<StandardVertex>> = indices
+        .iter()
+        .map(|i| {
+            faces[*i]
+                .iter()
+                .map(|v| StandardVertex {
+                    pos: pos.get(v.pos),
+                    uv: v.uv.map(|i| uv.get(i)),
+                    nor: v.nor.map(|i| nor.get(i)),
+                })
+                .collect()
+        })
+        .collect();
+    let attrs = StandardAttributes {
+        positions: pos
+            .into_indices()
+            .into_iter()
+            .map(|i| mesh.positions()[i])
+            .collect(),
+        uv_coords: uv
+            .into_indices()
+            .into_iter()
+            .map(|i| mesh.uv_coords()[i])
+            .collect(),
+        normals: nor
+            .into_indices()
+            .into_iter()
+            .map(|i| mesh.normals()[i])
+            .collect(),
+    };
+    PolygonMesh::new(attrs, Faces::from_iter(new_faces))
+}
+
+/// Splits `mesh` into the connected components, each of which has only its own attributes.
+pub fn split_components(mesh: &PolygonMesh) -> Vec<PolygonMesh> {
+    component_faces(mesh)
+        .iter()
+        .map(|indices| extract_faces(mesh, indices))
+        .collect()
+}
+
+/// Merges `meshes` into one mesh, offsetting the indices of the attributes.
+pub fn merge_meshes(meshes: impl IntoIterator<Item = PolygonMesh>) -> PolygonMesh {
+    meshes.into_iter().fold(
+        PolygonMesh::new(StandardAttributes::default(), Faces::default()),
+        |mut merged, mesh| {
+            merged.merge(mesh);
+            merged
+        },
+    )
+}
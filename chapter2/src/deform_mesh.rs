@@ -0,0 +1,147 @@
+use chapter2::{deformation::*, polyhedra};
+use std::time::Instant;
+use truck_meshalgo::prelude::*;
+
+/// Output the contents of `polygon` to the file specified by `path`.
+fn write_polygon(polygon: &PolygonMesh, path: &str) {
+    let mut obj = std::fs::File::create(path).unwrap();
+    obj::write(polygon, &mut obj).unwrap();
+}
+
+fn main() {
+    // the teapot of chapter 4, whose patches are welded
+    let mut teapot = obj::read(include_bytes!("../../chapter4/src/teapot.obj").as_slice()).unwrap();
+    teapot.put_together_same_attrs(1.0e-6).remove_unused_attrs();
+    let positions = teapot.positions().to_vec();
+    let bdb = teapot.bounding_box();
+
+    // The bottom and the lid are pinned, and the tip of the spout is the moved handle.
+    let pinned = |p: &Point3| p.y < bdb.min().y + 0.3 || p.y > bdb.max().y - 0.8;
+    let moved = |p: &Point3| p.x > bdb.max().x - 0.3;
+    let handles: Vec<usize> = (0..positions.len())
+        .filter(|i| pinned(&positions[*i]) || moved(&positions[*i]))
+        .collect();
+    let instant = Instant::now();
+    let arap = ArapDeformation::new(&teapot, &handles).unwrap();
+    println!("factorization: {:?}", instant.elapsed());
+
+    // A rigid motion of the handles is a fixed point of the iterations.
+    let rotation = Matrix3::from_angle_y(Rad(0.5)) * Matrix3::from_angle_z(Rad(0.3));
+    let rigid = |p: Point3| Point3::from_vec(rotation * p.to_vec() + Vector3::new(1.0, 2.0, 3.0));
+    let targets: Vec<Point3> = handles.iter().map(|i| rigid(positions[*i])).collect();
+    let expected: Vec<Point3> = positions.iter().copied().map(rigid).collect();
+    let result = arap.deform_from(&expected, &targets, 3).unwrap();
+    assert!(result
+        .iter()
+        .zip(&expected)
+        .all(|(p, q)| p.distance(*q) < 1.0e-9));
+
+    // the spout raised
+    let targets: Vec<Point3> = handles
+        .iter()
+        .map(|i| match moved(&positions[*i]) {
+            true => positions[*i] + Vector3::new(0.0, 0.8, 0.0),
+            false => positions[*i],
+        })
+        .collect();
+    let laplacian = arap.deform(&targets, 0).unwrap();
+    let instant = Instant::now();
+    let deformed = arap.deform(&targets, 10).unwrap();
+    println!("10 iterations: {:?}", instant.elapsed());
+    println!(
+        "energy: {:.5} (Laplacian editing) -> {:.5} (ARAP)",
+        arap.energy(&laplacian),
+        arap.energy(&deformed)
+    );
+    assert!(arap.energy(&deformed) < arap.energy(&laplacian));
+    handles
+        .iter()
+        .zip(&targets)
+        .for_each(|(i, p)| assert!(deformed[*i].distance(*p) < 1.0e-9));
+    // the next frame, starting from the previous one
+    let next = arap.deform_from(&deformed, &targets, 1).unwrap();
+    assert!(arap.energy(&next) <= arap.energy(&deformed) * (1.0 + 1.0e-9));
+    let mut mesh = teapot.clone();
+    mesh.positions_mut().copy_from_slice(&deformed);
+    write_polygon(&mesh, "teapot-arap.obj");
+
+    // errors of the handles
+    assert_eq!(
+        ArapDeformation::new(&teapot, &[]).err(),
+        Some(DeformationError::UnconstrainedVertices)
+    );
+    assert_eq!(
+        ArapDeformation::new(&teapot, &[0, 0]).err(),
+        Some(DeformationError::InvalidHandle(0))
+    );
+    assert_eq!(
+        arap.deform(&targets[1..], 1).err(),
+        Some(DeformationError::TargetCount)
+    );
+
+    // The thin rhombus has the obtuse angles opposite to the diagonal, whose cotangent weight
+    // is clamped, so the single handle still translates it rigidly.
+    let attrs = StandardAttributes {
+        positions: vec![
+            Point3::new(-1.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 0.05, 0.0),
+            Point3::new(0.0, -0.05, 0.0),
+        ],
+        ..Default::default()
+    };
+    let rhombus = PolygonMesh::new(attrs, Faces::from_iter([[0, 1, 2], [1, 0, 3]]));
+    let arap = ArapDeformation::new(&rhombus, &[0]).unwrap();
+    let shift = Vector3::new(0.5, 1.0, 0.0);
+    let result = arap.deform(&[rhombus.positions()[0] + shift], 3).unwrap();
+    assert!(result
+        .iter()
+        .zip(rhombus.positions())
+        .all(|(p, q)| p.distance(*q + shift) < 1.0e-9));
+
+    // the cage: the box around the teapot
+    let (center, diagonal) = (bdb.center(), bdb.diagonal());
+    let mut cage = polyhedra::hexahedron();
+    cage.positions_mut().iter_mut().for_each(|p| {
+        let v = p.to_vec() * f64::sqrt(3.0) * 0.6;
+        *p = center + Vector3::new(v.x * diagonal.x, v.y * diagonal.y, v.z * diagonal.z);
+    });
+    let instant = Instant::now();
+    let coordinates = CageDeformation::new(&cage, &positions).unwrap();
+    println!("mean value coordinates: {:?}", instant.elapsed());
+    // The coordinates reproduce the points and the affine maps.
+    let same = coordinates.deform(cage.positions()).unwrap();
+    assert!(same
+        .iter()
+        .zip(&positions)
+        .all(|(p, q)| p.distance(*q) < 1.0e-9));
+    let moved_cage: Vec<Point3> = cage.positions().iter().copied().map(rigid).collect();
+    let result = coordinates.deform(&moved_cage).unwrap();
+    assert!(result
+        .iter()
+        .zip(&expected)
+        .all(|(p, q)| p.distance(*q) < 1.0e-9));
+
+    // The top of the cage is twisted and enlarged.
+    let twisted: Vec<Point3> = cage
+        .positions()
+        .iter()
+        .map(|p| match p.y > center.y {
+            true => {
+                let v = Matrix3::from_angle_y(Rad(0.6)) * (*p - center) * 1.3;
+                center + v + Vector3::new(0.0, 0.5, 0.0)
+            }
+            false => *p,
+        })
+        .collect();
+    let result = coordinates.deform(&twisted).unwrap();
+    let twisted_bdb: BoundingBox<Point3> = result.iter().collect();
+    assert!(twisted_bdb.max().y > bdb.max().y + 0.3);
+    assert!(f64::abs(twisted_bdb.min().y - bdb.min().y) < 0.2);
+    mesh.positions_mut().copy_from_slice(&result);
+    write_polygon(&mesh, "teapot-cage.obj");
+    assert_eq!(
+        coordinates.deform(&twisted[1..]).err(),
+        Some(DeformationError::TargetCount)
+    );
+}
@@ -0,0 +1,598 @@
+//! Deformation of polygon meshes by handles and by cages.
+//!
+//! - [`ArapDeformation`] is the as-rigid-as-possible deformation of O. Sorkine and M. Alexa,
+//!   "As-Rigid-As-Possible Surface Modeling". The handle vertices are moved to the targets,
+//!   and the others follow them so that the neighborhoods are rotated as rigidly as possible.
+//! - [`CageDeformation`] moves the points by the mean value coordinates of T. Ju, S. Schaefer
+//!   and J. Warren, "Mean Value Coordinates for Closed Triangular Meshes", with respect to the
+//!   vertices of an enclosing cage.
+//!
+//! Both precompute everything which does not depend on the targets, that is, the factorized
+//! system of ARAP and the coordinates of the cage, so that the deformation can be solved
+//! again for each frame. The vertices are identified by the indices of the positions, so the
+//! meshes have to be welded by `put_together_same_attrs` in advance.
+
+use std::collections::{HashMap, VecDeque};
+use truck_meshalgo::prelude::*;
+
+// the tolerance of the degenerate triangles and the points on the cage
+const EPSILON: f64 = 1.0e-12;
+
+// the minimum of the cotangent weights of the edges, which are negative at the obtuse angles
+const MIN_WEIGHT: f64 = 1.0e-3;
+
+/// The errors of the deformations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeformationError {
+    /// A handle is not the index of a position, or is given twice.
+    InvalidHandle(usize),
+    /// Some vertices are not connected to any handle, so they cannot be determined.
+    UnconstrainedVertices,
+    /// The system is not positive definite, e.g. by the rounding errors of the degenerate faces.
+    NotPositiveDefinite,
+    /// The number of the targets is not the one of the handles or the vertices of the cage.
+    TargetCount,
+    /// The cage has no faces.
+    EmptyCage,
+}
+
+impl std::fmt::Display for DeformationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeformationError::InvalidHandle(idx) => write!(f, "invalid handle: {idx}"),
+            DeformationError::UnconstrainedVertices => {
+                f.write_str("some vertices are not connected to the handles")
+            }
+            DeformationError::NotPositiveDefinite => {
+                f.write_str("the system of the deformation is not positive definite")
+            }
+            DeformationError::TargetCount => f.write_str("the number of the targets is wrong"),
+            DeformationError::EmptyCage => f.write_str("the cage has no faces"),
+        }
+    }
+}
+
+impl std::error::Error for DeformationError {}
+
+/// As-rigid-as-possible deformation of a mesh with fixed handles.
+#[derive(Clone, Debug)]
+pub struct ArapDeformation {
+    mesh: PolygonMesh,
+    handles: Vec<usize>,
+    // the neighbors of the vertices and the cotangent weights of the edges
+    neighbors: Vec<Vec<(usize, f64)>>,
+    // the index of each vertex in the system, `None` for the handles
+    free_index: Vec<Option<usize>>,
+    system: SkylineCholesky,
+}
+
+impl ArapDeformation {
+    /// Factorizes the system of `mesh` whose vertices `handles` are moved by the user.
+    ///
+    /// The pinned vertices are the handles whose targets are their original positions.
+    /// Each connected component of the mesh needs at least one handle.
+    pub fn new(mesh: &PolygonMesh, handles: &[usize]) -> Result<Self, DeformationError> {
+        let len = mesh.positions().len();
+        let mut free_index = vec![Some(0); len];
+        for idx in handles {
+            match free_index.get(*idx) {
+                Some(Some(_)) => free_index[*idx] = None,
+                _ => return Err(DeformationError::InvalidHandle(*idx)),
+            }
+        }
+        let mut count = 0;
+        free_index.iter_mut().flatten().for_each(|idx| {
+            *idx = count;
+            count += 1;
+        });
+        let neighbors = cotangent_weights(mesh);
+        // the vertices reached from the handles through the edges
+        let mut reached = vec![false; len];
+        let mut queue: VecDeque<usize> = handles.iter().copied().collect();
+        handles.iter().for_each(|i| reached[*i] = true);
+        while let Some(i) = queue.pop_front() {
+            neighbors[i].iter().for_each(|(j, _)| {
+                if !reached[*j] {
+                    reached[*j] = true;
+                    queue.push_back(*j);
+                }
+            });
+        }
+        if reached.contains(&false) {
+            return Err(DeformationError::UnconstrainedVertices);
+        }
+        let free = |i: usize| free_index[i];
+        let entries = neighbors.iter().enumerate().filter_map(|(i, adjacency)| {
+            let row = free(i)?;
+            let diagonal = (row, row, adjacency.iter().map(|(_, w)| w).sum::<f64>());
+            let off_diagonals = adjacency
+                .iter()
+                .filter_map(move |(j, w)| Some((row, free(*j)?, -w)));
+            Some(std::iter::once(diagonal).chain(off_diagonals))
+        });
+        let system = SkylineCholesky::new(count, entries.flatten())
+            .ok_or(DeformationError::NotPositiveDefinite)?;
+        Ok(Self {
+            mesh: mesh.clone(),
+            handles: handles.to_vec(),
+            neighbors,
+            free_index,
+            system,
+        })
+    }
+
+    /// Returns the handles.
+    pub fn handles(&self) -> &[usize] { &self.handles }
+
+    /// Returns the positions of the vertices when the handles are moved to `targets`.
+    ///
+    /// The iterations start from the Laplacian editing, the solution without the rotations,
+    /// and a few iterations are enough for the interactive use.
+    pub fn deform(
+        &self,
+        targets: &[Point3],
+        iterations: usize,
+    ) -> Result<Vec<Point3>, DeformationError> {
+        let mut current = self.moved_handles(self.mesh.positions(), targets)?;
+        let identities = vec![Matrix3::identity(); current.len()];
+        self.global_step(&identities, &mut current);
+        self.iterate(&mut current, iterations);
+        Ok(current)
+    }
+
+    /// Same as [`ArapDeformation::deform`], but the iterations start from `initial`, e.g. the
+    /// result of the previous frame.
+    pub fn deform_from(
+        &self,
+        initial: &[Point3],
+        targets: &[Point3],
+        iterations: usize,
+    ) -> Result<Vec<Point3>, DeformationError> {
+        let mut current = self.moved_handles(initial, targets)?;
+        self.iterate(&mut current, iterations);
+        Ok(current)
+    }
+
+    /// Returns the mesh whose positions are deformed by [`ArapDeformation::deform`].
+    pub fn deform_mesh(
+        &self,
+        targets: &[Point3],
+        iterations: usize,
+    ) -> Result<PolygonMesh, DeformationError> {
+        let mut mesh = self.mesh.clone();
+        let positions = self.deform(targets, iterations)?;
+        mesh.positions_mut().copy_from_slice(&positions);
+        Ok(mesh)
+    }
+
+    /// Returns the ARAP energy of `positions`, the sum of the weighted squared differences
+    /// of the rotated edges and the deformed ones.
+    pub fn energy(&self, positions: &[Point3]) -> f64 {
+        let original = self.mesh.positions();
+        let rotations = self.rotations(positions);
+        self.neighbors
+            .iter()
+            .enumerate()
+            .flat_map(|(i, adjacency)| adjacency.iter().map(move |(j, w)| (i, *j, *w)))
+            .map(|(i, j, w)| {
+                let edge = positions[i] - positions[j];
+                let rotated = rotations[i] * (original[i] - original[j]);
+                w * (edge - rotated).magnitude2()
+            })
+            .sum()
+    }
+
+    fn moved_handles(
+        &self,
+        positions: &[Point3],
+        targets: &[Point3],
+    ) -> Result<Vec<Point3>, DeformationError> {
+        if targets.len() != self.handles.len() || positions.len() != self.mesh.positions().len() {
+            return Err(DeformationError::TargetCount);
+        }
+        let mut positions = positions.to_vec();
+        self.handles
+            .iter()
+            .zip(targets)
+            .for_each(|(i, p)| positions[*i] = *p);
+        Ok(positions)
+    }
+
+    fn iterate(&self, current: &mut [Point3], iterations: usize) {
+        (0..iterations).for_each(|_| {
+            let rotations = self.rotations(current);
+            self.global_step(&rotations, current);
+        });
+    }
+
+    // the local step: the best rotations of the neighborhoods
+    fn rotations(&self, current: &[Point3]) -> Vec<Matrix3> {
+        let original = self.mesh.positions();
+        self.neighbors
+            .iter()
+            .enumerate()
+            .map(|(i, adjacency)| {
+                let mut covariance = Matrix3::zero();
+                adjacency.iter().for_each(|(j, w)| {
+                    let e0 = original[i] - original[*j];
+                    let e1 = current[i] - current[*j];
+                    // covariance[b][a] is the sum of e0[a] * e1[b].
+                    covariance += Matrix3::from_cols(e1 * e0.x, e1 * e0.y, e1 * e0.z) * *w;
+                });
+                best_rotation(covariance)
+            })
+            .collect()
+    }
+
+    // the global step: the positions for the rotations, with the handles fixed
+    fn global_step(&self, rotations: &[Matrix3], current: &mut [Point3]) {
+        let original = self.mesh.positions();
+        let mut rhs = vec![Vector3::zero(); self.system.len()];
+        self.neighbors
+            .iter()
+            .enumerate()
+            .for_each(|(i, adjacency)| {
+                if let Some(row) = self.free_index[i] {
+                    adjacency.iter().for_each(|(j, w)| {
+                        let rotated = (rotations[i] + rotations[*j]) * (original[i] - original[*j]);
+                        rhs[row] += rotated * (w / 2.0);
+                        if self.free_index[*j].is_none() {
+                            rhs[row] += current[*j].to_vec() * *w;
+                        }
+                    });
+                }
+            });
+        let solution: Vec<Vec<f64>> = (0..3)
+            .map(|axis| self.system.solve(rhs.iter().map(|v| v[axis]).collect()))
+            .collect();
+        self.free_index.iter().enumerate().for_each(|(i, row)| {
+            if let Some(row) = row {
+                current[i] = Point3::new(solution[0][*row], solution[1][*row], solution[2][*row]);
+            }
+        });
+    }
+}
+
+/// Deformation of points by the mean value coordinates with respect to a cage.
+#[derive(Clone, Debug)]
+pub struct CageDeformation {
+    // the number of the vertices of the cage
+    cage_len: usize,
+    // the coordinates of the points, the pairs of the vertices of the cage and the weights
+    coordinates: Vec<Vec<(usize, f64)>>,
+}
+
+impl CageDeformation {
+    /// Computes the coordinates of `points` with respect to the closed mesh `cage`.
+    ///
+    /// The polygons of the cage are divided into fans of triangles. The points should be
+    /// inside the cage, although the coordinates of the outside points are also defined.
+    pub fn new(cage: &PolygonMesh, points: &[Point3]) -> Result<Self, DeformationError> {
+        let triangles: Vec<[usize; 3]> = cage
+            .face_iter()
+            .flat_map(|face| {
+                (2..face.len()).map(move |k| [face[0].pos, face[k - 1].pos, face[k].pos])
+            })
+            .collect();
+        if triangles.is_empty() {
+            return Err(DeformationError::EmptyCage);
+        }
+        let coordinates = points
+            .iter()
+            .map(|x| mean_value_coordinates(cage.positions(), &triangles, *x))
+            .collect();
+        Ok(Self {
+            cage_len: cage.positions().len(),
+            coordinates,
+        })
+    }
+
+    /// Returns the points when the vertices of the cage are moved to `cage_positions`.
+    pub fn deform(&self, cage_positions: &[Point3]) -> Result<Vec<Point3>, DeformationError> {
+        if cage_positions.len() != self.cage_len {
+            return Err(DeformationError::TargetCount);
+        }
+        let points = self.coordinates.iter().map(|weights| {
+            let sum = weights
+                .iter()
+                .map(|(j, w)| cage_positions[*j].to_vec() * *w)
+                .sum::<Vector3>();
+            Point3::from_vec(sum)
+        });
+        Ok(points.collect())
+    }
+}
+
+// Returns the cotangent weights of the edges of the fan triangles, halved as the ones of
+// the cotangent Laplacian. The weights of the edges opposite to the obtuse angles may be
+// negative, which makes the system indefinite, so they are clamped to `MIN_WEIGHT`.
+fn cotangent_weights(mesh: &PolygonMesh) -> Vec<Vec<(usize, f64)>> {
+    let positions = mesh.positions();
+    let mut weights = HashMap::<(usize, usize), f64>::new();
+    mesh.face_iter()
+        .flat_map(|face| (2..face.len()).map(move |k| [face[0].pos, face[k - 1].pos, face[k].pos]))
+        .for_each(|tri| {
+            (0..3).for_each(|k| {
+                // the angle at tri[k] is opposite to the edge (tri[k + 1], tri[k + 2]).
+                let (o, i, j) = (tri[k], tri[(k + 1) % 3], tri[(k + 2) % 3]);
+                let (a, b) = (positions[i] - positions[o], positions[j] - positions[o]);
+                let cross = a.cross(b).magnitude();
+                if cross > EPSILON * a.magnitude() * b.magnitude() && i != j {
+                    *weights.entry((i.min(j), i.max(j))).or_default() += a.dot(b) / cross / 2.0;
+                }
+            })
+        });
+    let mut neighbors = vec![Vec::new(); positions.len()];
+    let mut edges: Vec<_> = weights.into_iter().collect();
+    edges.sort_by_key(|(edge, _)| *edge);
+    edges.into_iter().for_each(|((i, j), w)| {
+        let w = f64::max(w, MIN_WEIGHT);
+        neighbors[i].push((j, w));
+        neighbors[j].push((i, w));
+    });
+    neighbors
+}
+
+// Returns the rotation `r` maximizing the trace of `r^T covariance` by the quaternion method
+// of B. K. P. Horn, which never returns reflections.
+fn best_rotation(covariance: Matrix3) -> Matrix3 {
+    // s[a][b] is the sum of e0[a] * e1[b].
+    let s = |a: usize, b: usize| covariance[a][b];
+    let n = [
+        [
+            s(0, 0) + s(1, 1) + s(2, 2),
+            s(1, 2) - s(2, 1),
+            s(2, 0) - s(0, 2),
+            s(0, 1) - s(1, 0),
+        ],
+        [
+            s(1, 2) - s(2, 1),
+            s(0, 0) - s(1, 1) - s(2, 2),
+            s(0, 1) + s(1, 0),
+            s(2, 0) + s(0, 2),
+        ],
+        [
+            s(2, 0) - s(0, 2),
+            s(0, 1) + s(1, 0),
+            -s(0, 0) + s(1, 1) - s(2, 2),
+            s(1, 2) + s(2, 1),
+        ],
+        [
+            s(0, 1) - s(1, 0),
+            s(2, 0) + s(0, 2),
+            s(1, 2) + s(2, 1),
+            -s(0, 0) - s(1, 1) + s(2, 2),
+        ],
+    ];
+    let q = largest_eigenvector(n);
+    let norm2 = q.iter().map(|x| x * x).sum::<f64>();
+    if norm2 == 0.0 {
+        return Matrix3::identity();
+    }
+    let [w, x, y, z] = q.map(|x| x / norm2.sqrt());
+    Matrix3::new(
+        1.0 - 2.0 * (y * y + z * z),
+        2.0 * (x * y + w * z),
+        2.0 * (x * z - w * y),
+        2.0 * (x * y - w * z),
+        1.0 - 2.0 * (x * x + z * z),
+        2.0 * (y * z + w * x),
+        2.0 * (x * z + w * y),
+        2.0 * (y * z - w * x),
+        1.0 - 2.0 * (x * x + y * y),
+    )
+}
+
+// Returns the eigenvector of the largest eigenvalue of the symmetric matrix by the Jacobi
+// eigenvalue method.
+fn largest_eigenvector(mut a: [[f64; 4]; 4]) -> [f64; 4] {
+    let mut v = [[0.0; 4]; 4];
+    (0..4).for_each(|i| v[i][i] = 1.0);
+    for _ in 0..32 {
+        let off: f64 = (0..4)
+            .flat_map(|i| (0..4).filter(move |j| *j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        let scale: f64 = (0..4).map(|i| a[i][i] * a[i][i]).sum();
+        if off <= 1.0e-30 * scale.max(f64::MIN_POSITIVE) {
+            break;
+        }
+        for p in 0..4 {
+            for q in p + 1..4 {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + f64::sqrt(theta * theta + 1.0));
+                let (c, s) = (1.0 / f64::sqrt(t * t + 1.0), t / f64::sqrt(t * t + 1.0));
+                for row in &mut a {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (row_p, row_q) = (a[p], a[q]);
+                a[p] = std::array::from_fn(|k| c * row_p[k] - s * row_q[k]);
+                a[q] = std::array::from_fn(|k| s * row_p[k] + c * row_q[k]);
+                for row in &mut v {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    let max = (0..4)
+        .max_by(|i, j| a[*i][*i].total_cmp(&a[*j][*j]))
+        .unwrap();
+    [0, 1, 2, 3].map(|k| v[k][max])
+}
+
+// Returns the mean value coordinates of `x` with respect to the triangles.
+fn mean_value_coordinates(
+    positions: &[Point3],
+    triangles: &[[usize; 3]],
+    x: Point3,
+) -> Vec<(usize, f64)> {
+    let distances: Vec<f64> = positions.iter().map(|p| p.distance(x)).collect();
+    let scale = distances.iter().copied().fold(0.0, f64::max);
+    if let Some(j) = distances.iter().position(|d| *d <= EPSILON * scale) {
+        return vec![(j, 1.0)];
+    }
+    let units: Vec<Vector3> = positions
+        .iter()
+        .zip(&distances)
+        .map(|(p, d)| (*p - x) / *d)
+        .collect();
+    let mut weights = HashMap::<usize, f64>::new();
+    for tri in triangles {
+        let u = tri.map(|j| units[j]);
+        let d = tri.map(|j| distances[j]);
+        let theta: [f64; 3] = std::array::from_fn(|i| {
+            2.0 * f64::asin(((u[(i + 1) % 3] - u[(i + 2) % 3]).magnitude() / 2.0).min(1.0))
+        });
+        let h = theta.iter().sum::<f64>() / 2.0;
+        if std::f64::consts::PI - h < 1.0e-9 {
+            // `x` is on the triangle, where the coordinates are the barycentric ones.
+            let w: [f64; 3] =
+                std::array::from_fn(|i| f64::sin(theta[i]) * d[(i + 1) % 3] * d[(i + 2) % 3]);
+            let sum: f64 = w.iter().sum();
+            return (0..3).map(|i| (tri[i], w[i] / sum)).collect();
+        }
+        let c: [f64; 3] = std::array::from_fn(|i| {
+            let (next, prev) = (theta[(i + 1) % 3], theta[(i + 2) % 3]);
+            2.0 * f64::sin(h) * f64::sin(h - theta[i]) / (f64::sin(next) * f64::sin(prev)) - 1.0
+        });
+        let sign = u[0].dot(u[1].cross(u[2])).signum();
+        let s = c.map(|c| sign * f64::sqrt((1.0 - c * c).max(0.0)));
+        if s.iter().any(|s| s.abs() <= 1.0e-9) {
+            // `x` is on the plane of the triangle but outside it, where it has no effect.
+            continue;
+        }
+        (0..3).for_each(|i| {
+            let (next, prev) = ((i + 1) % 3, (i + 2) % 3);
+            let w = (theta[i] - c[next] * theta[prev] - c[prev] * theta[next])
+                / (d[i] * f64::sin(theta[next]) * s[prev]);
+            *weights.entry(tri[i]).or_default() += w;
+        });
+    }
+    let sum: f64 = weights.values().sum();
+    let mut coordinates: Vec<(usize, f64)> =
+        weights.into_iter().map(|(j, w)| (j, w / sum)).collect();
+    coordinates.sort_by_key(|(j, _)| *j);
+    coordinates
+}
+
+// The Cholesky factorization of a sparse symmetric positive definite matrix, stored by the
+// profiles of the rows in the reverse Cuthill-McKee order.
+#[derive(Clone, Debug)]
+struct SkylineCholesky {
+    // `order[k]` is the original index of the `k`th row.
+    order: Vec<usize>,
+    // the first column of each row of the factor
+    first: Vec<usize>,
+    // the offset of each row in `values`
+    offsets: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl SkylineCholesky {
+    // Factorizes the matrix of the entries `(row, column, value)`, where the duplicated
+    // entries are added up. Returns `None` if the matrix is not positive definite.
+    fn new(len: usize, entries: impl Iterator<Item = (usize, usize, f64)>) -> Option<Self> {
+        let mut rows = vec![HashMap::<usize, f64>::new(); len];
+        entries.for_each(|(i, j, value)| *rows[i].entry(j).or_default() += value);
+        let order = reverse_cuthill_mckee(&rows);
+        let mut position = vec![0; len];
+        order.iter().enumerate().for_each(|(k, i)| position[*i] = k);
+        let first: Vec<usize> = order
+            .iter()
+            .enumerate()
+            .map(|(k, i)| rows[*i].keys().map(|j| position[*j]).fold(k, usize::min))
+            .collect();
+        let mut offsets = Vec::with_capacity(len);
+        let mut size = 0;
+        first.iter().enumerate().for_each(|(k, first)| {
+            offsets.push(size);
+            size += k + 1 - first;
+        });
+        let mut factor = Self {
+            order,
+            first,
+            offsets,
+            values: vec![0.0; size],
+        };
+        for (k, i) in factor.order.iter().enumerate() {
+            for (j, value) in &rows[*i] {
+                if position[*j] <= k {
+                    let idx = factor.offsets[k] + position[*j] - factor.first[k];
+                    factor.values[idx] = *value;
+                }
+            }
+        }
+        for i in 0..len {
+            for j in factor.first[i]..=i {
+                let start = usize::max(factor.first[i], factor.first[j]);
+                let dot: f64 = (start..j)
+                    .map(|k| factor.get(i, k) * factor.get(j, k))
+                    .sum();
+                let value = factor.get(i, j) - dot;
+                let idx = factor.offsets[i] + j - factor.first[i];
+                factor.values[idx] = match j < i {
+                    true => value / factor.get(j, j),
+                    false if value > 0.0 => value.sqrt(),
+                    false => return None,
+                };
+            }
+        }
+        Some(factor)
+    }
+
+    fn len(&self) -> usize { self.order.len() }
+
+    fn get(&self, i: usize, j: usize) -> f64 { self.values[self.offsets[i] + j - self.first[i]] }
+
+    // Solves the system with the right-hand side `b`.
+    fn solve(&self, b: Vec<f64>) -> Vec<f64> {
+        let len = self.len();
+        let mut y: Vec<f64> = self.order.iter().map(|i| b[*i]).collect();
+        for i in 0..len {
+            let dot: f64 = (self.first[i]..i).map(|k| self.get(i, k) * y[k]).sum();
+            y[i] = (y[i] - dot) / self.get(i, i);
+        }
+        for i in (0..len).rev() {
+            y[i] /= self.get(i, i);
+            for k in self.first[i]..i {
+                y[k] -= self.get(i, k) * y[i];
+            }
+        }
+        let mut x = vec![0.0; len];
+        self.order.iter().zip(y).for_each(|(i, y)| x[*i] = y);
+        x
+    }
+}
+
+// Returns the reverse Cuthill-McKee order of the rows, which reduces the profile.
+fn reverse_cuthill_mckee(rows: &[HashMap<usize, f64>]) -> Vec<usize> {
+    let len = rows.len();
+    let degree = |i: usize| rows[i].len();
+    let mut visited = vec![false; len];
+    let mut order = Vec::with_capacity(len);
+    let mut starts: Vec<usize> = (0..len).collect();
+    starts.sort_by_key(|i| (degree(*i), *i));
+    for start in starts {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut queue = VecDeque::from([start]);
+        while let Some(i) = queue.pop_front() {
+            order.push(i);
+            let mut next: Vec<usize> = rows[i].keys().copied().filter(|j| !visited[*j]).collect();
+            next.sort_by_key(|j| (degree(*j), *j));
+            next.iter().for_each(|j| visited[*j] = true);
+            queue.extend(next);
+        }
+    }
+    order.reverse();
+    order
+}
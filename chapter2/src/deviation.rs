@@ -0,0 +1,171 @@
+//! Deviation between two polygon meshes: Hausdorff distances and distance maps.
+
+use crate::bvh::{SignedDistance, TriangleTree};
+use std::io::{self, Write};
+use truck_meshalgo::prelude::*;
+
+/// The statistics of the distances from the samples on a mesh to another mesh.
+#[derive(Clone, Debug)]
+pub struct Deviation {
+    /// the maximum of the distances, i.e. the one-sided Hausdorff distance
+    pub max: f64,
+    /// the mean of the absolute values of the distances
+    pub mean: f64,
+    /// the root mean square of the distances
+    pub rms: f64,
+    /// the distances from the positions of the mesh.
+    /// They are signed, negative behind the faces, if the target mesh is oriented.
+    pub vertex_distances: Vec<f64>,
+}
+
+/// Measures the deviation from `from` to `to`.
+///
+/// The samples are the positions of `from` and the points of the barycentric grid dividing
+/// each triangle into `division * division` triangles, so `division = 1` means the positions only.
+/// The statistics are not weighted by the areas. Returns `None` if `to` has no faces.
+pub fn deviation(from: &PolygonMesh, to: &PolygonMesh, division: usize) -> Option<Deviation> {
+    enum Distance {
+        Signed(SignedDistance),
+        Unsigned(TriangleTree),
+    }
+    let distance = match SignedDistance::new(to) {
+        Some(sdf) => Distance::Signed(sdf),
+        None => Distance::Unsigned(TriangleTree::from_mesh(to)),
+    };
+    let distance = |point: Point3| match &distance {
+        Distance::Signed(sdf) => Some(sdf.distance(point)),
+        Distance::Unsigned(tree) => tree.nearest(point).map(|nearest| nearest.distance),
+    };
+
+    let vertex_distances: Vec<f64> = from
+        .positions()
+        .iter()
+        .map(|p| distance(*p))
+        .collect::<Option<_>>()?;
+    let division = usize::max(division, 1);
+    let positions = from.positions();
+    let samples = from.faces().triangle_iter().flat_map(|tri| {
+        let [a, b, c] = tri.map(|v| positions[v.pos]);
+        (0..=division)
+            .flat_map(move |i| (0..=division - i).map(move |j| (i, j, division - i - j)))
+            // the corners are the positions
+            .filter(move |(i, j, k)| *i != division && *j != division && *k != division)
+            .map(move |(i, j, k)| {
+                let (s, t, u) = (i as f64, j as f64, k as f64);
+                Point3::from_vec(
+                    (a.to_vec() * s + b.to_vec() * t + c.to_vec() * u) / division as f64,
+                )
+            })
+    });
+    let distances = vertex_distances
+        .iter()
+        .copied()
+        .chain(samples.map(|p| distance(p).unwrap()));
+
+    let (mut max, mut sum, mut sum2, mut count) = (0.0, 0.0, 0.0, 0);
+    distances.for_each(|d: f64| {
+        max = f64::max(max, d.abs());
+        sum += d.abs();
+        sum2 += d * d;
+        count += 1;
+    });
+    let count = usize::max(count, 1) as f64;
+    Some(Deviation {
+        max,
+        mean: sum / count,
+        rms: f64::sqrt(sum2 / count),
+        vertex_distances,
+    })
+}
+
+/// Returns the symmetric Hausdorff distance between `mesh0` and `mesh1`.
+/// Returns `None` if one of the meshes has no faces.
+pub fn hausdorff_distance(
+    mesh0: &PolygonMesh,
+    mesh1: &PolygonMesh,
+    division: usize,
+) -> Option<f64> {
+    let d0 = deviation(mesh0, mesh1, division)?;
+    let d1 = deviation(mesh1, mesh0, division)?;
+    Some(f64::max(d0.max, d1.max))
+}
+
+/// Writes `mesh` with the scalar `values` at the positions as an ASCII PLY.
+/// The values are written as the vertex property `deviation`.
+pub fn write_ply<W: Write>(mesh: &PolygonMesh, values: &[f64], mut writer: W) -> io::Result<()> {
+    let positions = mesh.positions();
+    writeln!(writer, "ply\nformat ascii 1.0")?;
+    writeln!(writer, "element vertex {}", positions.len())?;
+    writeln!(
+        writer,
+        "property double x\nproperty double y\nproperty double z"
+    )?;
+    writeln!(writer, "property double deviation")?;
+    writeln!(writer, "element face {}", mesh.faces().len())?;
+    writeln!(writer, "property list uchar int vertex_indices\nend_header")?;
+    positions
+        .iter()
+        .zip(values)
+        .try_for_each(|(p, value)| writeln!(writer, "{} {} {} {value}", p.x, p.y, p.z))?;
+    mesh.face_iter().try_for_each(|face| {
+        write!(writer, "{}", face.len())?;
+        face.iter().try_for_each(|v| write!(writer, " {}", v.pos))?;
+        writeln!(writer)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{cube_sphere, sphere};
+
+    #[test]
+    fn scaled_sphere() {
+        // The sphere is inside the sphere scaled by 1.01.
+        let sphere = sphere();
+        let mut scaled = sphere.clone();
+        scaled.positions_mut().iter_mut().for_each(|p| *p *= 1.01);
+        let dev = deviation(&sphere, &scaled, 4).unwrap();
+        assert!(dev.max < 0.01 + TOLERANCE);
+        assert!(dev.rms <= dev.max && dev.mean <= dev.rms);
+        assert!(dev
+            .vertex_distances
+            .iter()
+            .all(|d| -0.01 - TOLERANCE < *d && *d < 0.0));
+        // the scaled vertices are the farthest points from the sphere
+        let hausdorff = hausdorff_distance(&sphere, &scaled, 4).unwrap();
+        assert!(f64::abs(hausdorff - 0.01) < TOLERANCE);
+    }
+
+    #[test]
+    fn coarse_spheres() {
+        // The coarser spheres have the larger deviations.
+        let fine = cube_sphere(16);
+        let distances: Vec<f64> = [2, 4, 8]
+            .into_iter()
+            .map(|division| hausdorff_distance(&cube_sphere(division), &fine, 4).unwrap())
+            .collect();
+        assert!(distances.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn unsigned_distances() {
+        // The distances to a mesh which is not oriented are unsigned.
+        let fine = cube_sphere(8);
+        let half = fine.faces().len() / 2;
+        let indices: Vec<usize> = (0..fine.faces().len()).collect();
+        let mut irregular = fine.create_mesh_by_face_indices(&indices[..half]);
+        irregular.merge(fine.create_mesh_by_face_indices(&indices[half..]).inverse());
+        irregular.put_together_same_attrs(1.0e-6);
+        assert_eq!(irregular.shell_condition(), ShellCondition::Regular);
+        let dev = deviation(&sphere(), &irregular, 1).unwrap();
+        assert!(dev.vertex_distances.iter().all(|d| *d >= 0.0));
+    }
+
+    #[test]
+    fn empty_mesh() {
+        let empty = PolygonMesh::default();
+        assert!(deviation(&sphere(), &empty, 1).is_none());
+        assert!(hausdorff_distance(&empty, &sphere(), 1).is_none());
+    }
+}
@@ -0,0 +1,79 @@
+use chapter2::{fixtures, self_intersection::*};
+use truck_meshalgo::prelude::*;
+
+/// Output the contents of `polygon` to the file specified by `path`.
+fn write_polygon(polygon: &PolygonMesh, path: &str) {
+    let mut obj = std::fs::File::create(path).unwrap();
+    obj::write(polygon, &mut obj).unwrap();
+}
+
+fn main() {
+    // the unit triangles on the plane z = 0 and the vertical ones
+    let tri = [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+    ];
+    let crossing = [
+        Point3::new(0.25, -1.0, -1.0),
+        Point3::new(0.25, -1.0, 1.0),
+        Point3::new(0.25, 2.0, 0.0),
+    ];
+    match triangle_intersection(tri, crossing) {
+        Some(Intersection::Segment(p, q)) => {
+            // the segment is on the line x = 0.25 and z = 0 in the triangle
+            let (p, q) = if p.y < q.y { (p, q) } else { (q, p) };
+            assert!(p.near(&Point3::new(0.25, 0.0, 0.0)));
+            assert!(q.near(&Point3::new(0.25, 0.75, 0.0)));
+        }
+        other => panic!("unexpected intersection: {other:?}"),
+    }
+    let touching = [
+        Point3::new(0.25, 0.25, 0.0),
+        Point3::new(0.25, 0.0, 1.0),
+        Point3::new(0.0, 0.25, 1.0),
+    ];
+    match triangle_intersection(tri, touching) {
+        Some(Intersection::Segment(p, q)) => assert!(p.near(&q)),
+        other => panic!("unexpected intersection: {other:?}"),
+    }
+    let above = tri.map(|p| p + Vector3::new(0.0, 0.0, 1.0e-12));
+    assert!(triangle_intersection(tri, above).is_none());
+    let shifted = tri.map(|p| p + Vector3::new(0.5, 0.0, 0.0));
+    assert!(matches!(
+        triangle_intersection(tri, shifted),
+        Some(Intersection::Coplanar)
+    ));
+
+    // The sphere of section 2.4 is closed and has no self-intersections.
+    let sphere = fixtures::sphere();
+    assert_eq!(sphere.shell_condition(), ShellCondition::Closed);
+    assert!(self_intersections(&sphere).is_empty());
+
+    // Two overlapping spheres make a closed mesh, which intersects itself.
+    let radius = sphere.bounding_box().diagonal().x / 2.0;
+    let mut shifted = sphere.clone();
+    shifted
+        .positions_mut()
+        .iter_mut()
+        .for_each(|p| p.x += radius);
+    let mut spheres = sphere.clone();
+    spheres.merge(shifted);
+    assert_eq!(spheres.shell_condition(), ShellCondition::Closed);
+    let intersections = self_intersections(&spheres);
+    println!("{} pairs of triangles intersect", intersections.len());
+    assert!(!intersections.is_empty());
+    // The segments are near the circle on the plane x = radius / 2.
+    let circle_radius = radius * f64::sqrt(3.0) / 2.0;
+    intersections.iter().for_each(|x| match x.intersection {
+        Intersection::Segment(p, q) => [p, q].iter().for_each(|p| {
+            assert!(f64::abs(p.x - radius / 2.0) < 0.1 * radius);
+            let r = f64::hypot(p.y, p.z);
+            assert!(f64::abs(r - circle_radius) < 0.1 * radius);
+        }),
+        Intersection::Coplanar => panic!("the spheres are not coplanar"),
+    });
+    let offending = offending_triangles(&spheres, &intersections);
+    println!("{} triangles are offending", offending.faces().len());
+    write_polygon(&offending, "offending-triangles.obj");
+}
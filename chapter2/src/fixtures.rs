@@ -0,0 +1,85 @@
+//! The meshes shared by the samples.
+
+use truck_meshalgo::prelude::*;
+
+/// the sphere created in section 2.4, with the positions put together.
+pub fn sphere() -> PolygonMesh {
+    let mut sphere = obj::read(include_bytes!("sphere.obj").as_slice()).unwrap();
+    sphere.put_together_same_attrs(1.0e-3);
+    sphere
+}
+
+/// the cube `[-1, 1]^3` whose faces are divided into `division * division` squares.
+pub fn subdivided_cube(division: usize) -> PolygonMesh {
+    // the origin and the two axes of each face, oriented outward
+    let frames = [
+        (
+            Vector3::new(-1.0, -1.0, -1.0),
+            Vector3::unit_y(),
+            Vector3::unit_x(),
+        ),
+        (
+            Vector3::new(-1.0, -1.0, -1.0),
+            Vector3::unit_x(),
+            Vector3::unit_z(),
+        ),
+        (
+            Vector3::new(-1.0, -1.0, -1.0),
+            Vector3::unit_z(),
+            Vector3::unit_y(),
+        ),
+        (
+            Vector3::new(1.0, 1.0, 1.0),
+            -Vector3::unit_x(),
+            -Vector3::unit_y(),
+        ),
+        (
+            Vector3::new(1.0, 1.0, 1.0),
+            -Vector3::unit_z(),
+            -Vector3::unit_x(),
+        ),
+        (
+            Vector3::new(1.0, 1.0, 1.0),
+            -Vector3::unit_y(),
+            -Vector3::unit_z(),
+        ),
+    ];
+    let n = division + 1;
+    let mut positions = Vec::new();
+    let mut faces = Faces::default();
+    frames.iter().for_each(|(origin, u, v)| {
+        let base = positions.len();
+        (0..n).for_each(|i| {
+            (0..n).for_each(|j| {
+                let (s, t) = (
+                    2.0 * i as f64 / division as f64,
+                    2.0 * j as f64 / division as f64,
+                );
+                positions.push(Point3::from_vec(origin + u * s + v * t));
+            })
+        });
+        (0..division).for_each(|i| {
+            (0..division).for_each(|j| {
+                let idx = |i: usize, j: usize| base + i * n + j;
+                faces.push([idx(i, j), idx(i + 1, j), idx(i + 1, j + 1), idx(i, j + 1)]);
+            })
+        });
+    });
+    let attrs = StandardAttributes {
+        positions,
+        ..Default::default()
+    };
+    let mut cube = PolygonMesh::new(attrs, faces);
+    cube.put_together_same_attrs(1.0e-6);
+    cube
+}
+
+/// the sphere made by projecting the cube whose faces are divided into `division * division` squares.
+pub fn cube_sphere(division: usize) -> PolygonMesh {
+    let mut sphere = subdivided_cube(division);
+    sphere
+        .positions_mut()
+        .iter_mut()
+        .for_each(|p| *p = Point3::from_vec(p.to_vec().normalize()));
+    sphere
+}
@@ -0,0 +1,127 @@
+//! glTF 2.0 output of meshes with tangent frames.
+//!
+//! The output is one `.gltf` file, whose binary buffer is embedded as a base64 data URI.
+
+use crate::tangent::TangentMesh;
+use std::io::Write;
+use truck_meshalgo::prelude::*;
+
+// `bufferView.target` for vertex attributes
+const ARRAY_BUFFER: u32 = 34962;
+// `bufferView.target` for vertex indices
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+// `accessor.componentType` of `f32`
+const FLOAT: u32 = 5126;
+// `accessor.componentType` of `u32`
+const UNSIGNED_INT: u32 = 5125;
+
+/// Writes `mesh` to `writer` as a glTF with the attributes `POSITION`, `NORMAL`, `TEXCOORD_0` and `TANGENT`.
+/// # Remarks
+/// The origin of texture coordinates of glTF is the upper left corner, while that of
+/// wavefront obj is the lower left corner. The `v` coordinates are flipped as `1 - v`,
+/// and hence the signs of bitangents are also flipped.
+pub fn write<W: Write>(mesh: &TangentMesh, mut writer: W) -> std::io::Result<()> {
+    let attrs = mesh.attributes();
+    let indices: Vec<u32> = mesh
+        .faces()
+        .triangle_iter()
+        .flatten()
+        .map(|i| i as u32)
+        .collect();
+
+    let mut buffer = Vec::<u8>::new();
+    let mut views = Vec::<(usize, usize, u32)>::new();
+    let mut push_view = |buffer: &mut Vec<u8>, data: Vec<f32>, target: u32| {
+        let offset = buffer.len();
+        data.iter().for_each(|x| buffer.extend(x.to_le_bytes()));
+        views.push((offset, buffer.len() - offset, target));
+    };
+    let positions = attrs.iter().flat_map(|a| {
+        let p = a.position.cast::<f32>().unwrap();
+        [p.x, p.y, p.z]
+    });
+    push_view(&mut buffer, positions.collect(), ARRAY_BUFFER);
+    let normals = attrs.iter().flat_map(|a| {
+        let n = a.normal.cast::<f32>().unwrap();
+        [n.x, n.y, n.z]
+    });
+    push_view(&mut buffer, normals.collect(), ARRAY_BUFFER);
+    let uv_coords = attrs
+        .iter()
+        .flat_map(|a| [a.uv_coord.x as f32, 1.0 - a.uv_coord.y as f32]);
+    push_view(&mut buffer, uv_coords.collect(), ARRAY_BUFFER);
+    let tangents = attrs.iter().flat_map(|a| {
+        let t = a.tangent.cast::<f32>().unwrap();
+        [t.x, t.y, t.z, -t.w]
+    });
+    push_view(&mut buffer, tangents.collect(), ARRAY_BUFFER);
+    let offset = buffer.len();
+    indices.iter().for_each(|i| buffer.extend(i.to_le_bytes()));
+    views.push((offset, buffer.len() - offset, ELEMENT_ARRAY_BUFFER));
+
+    let bdb: BoundingBox<Point3> = attrs.iter().map(|a| a.position).collect();
+    let (min, max) = (bdb.min(), bdb.max());
+    let n = attrs.len();
+    let accessors = [
+        format!(
+            r#"{{"bufferView":0,"componentType":{FLOAT},"count":{n},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}}"#,
+            min.x as f32, min.y as f32, min.z as f32, max.x as f32, max.y as f32, max.z as f32,
+        ),
+        format!(r#"{{"bufferView":1,"componentType":{FLOAT},"count":{n},"type":"VEC3"}}"#),
+        format!(r#"{{"bufferView":2,"componentType":{FLOAT},"count":{n},"type":"VEC2"}}"#),
+        format!(r#"{{"bufferView":3,"componentType":{FLOAT},"count":{n},"type":"VEC4"}}"#),
+        format!(
+            r#"{{"bufferView":4,"componentType":{UNSIGNED_INT},"count":{},"type":"SCALAR"}}"#,
+            indices.len(),
+        ),
+    ];
+    let views = views
+        .iter()
+        .map(|(offset, length, target)| {
+            format!(
+                r#"{{"buffer":0,"byteOffset":{offset},"byteLength":{length},"target":{target}}}"#
+            )
+        })
+        .collect::<Vec<_>>();
+
+    writeln!(writer, "{{")?;
+    writeln!(
+        writer,
+        r#"  "asset": {{ "version": "2.0", "generator": "truck-tutorial-code" }},"#
+    )?;
+    writeln!(writer, r#"  "scene": 0,"#)?;
+    writeln!(writer, r#"  "scenes": [{{ "nodes": [0] }}],"#)?;
+    writeln!(writer, r#"  "nodes": [{{ "mesh": 0 }}],"#)?;
+    writeln!(
+        writer,
+        r#"  "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2, "TANGENT": 3 }}, "indices": 4 }}] }}],"#
+    )?;
+    writeln!(writer, r#"  "accessors": [{}],"#, accessors.join(","))?;
+    writeln!(writer, r#"  "bufferViews": [{}],"#, views.join(","))?;
+    writeln!(
+        writer,
+        r#"  "buffers": [{{ "byteLength": {}, "uri": "data:application/octet-stream;base64,{}" }}]"#,
+        buffer.len(),
+        base64(&buffer),
+    )?;
+    writeln!(writer, "}}")
+}
+
+/// base64 encoding with padding
+fn base64(bytes: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut res = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    bytes.chunks(3).for_each(|chunk| {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        (0..4).for_each(|i| match i <= chunk.len() {
+            true => res.push(TABLE[(n >> (18 - 6 * i) & 63) as usize] as char),
+            false => res.push('='),
+        });
+    });
+    res
+}
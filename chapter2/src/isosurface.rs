@@ -0,0 +1,174 @@
+//! Scalar fields on regular grids and the extraction of their isosurfaces.
+
+use std::collections::HashMap;
+use truck_meshalgo::prelude::*;
+
+// Six tetrahedra around the diagonal from the corner 0 to the corner 7 of a cell.
+// The corner `c` of the cell is `(c & 1, (c >> 1) & 1, (c >> 2) & 1)`.
+// Since all cells are divided in the same way, the tetrahedra are conforming.
+const TETRAHEDRA: [[usize; 4]; 6] = [
+    [0, 1, 3, 7],
+    [0, 3, 2, 7],
+    [0, 2, 6, 7],
+    [0, 6, 4, 7],
+    [0, 4, 5, 7],
+    [0, 5, 1, 7],
+];
+
+/// Scalar field sampled at the vertices of a regular grid.
+#[derive(Clone, Debug)]
+pub struct ScalarGrid {
+    /// the position of the vertex `(0, 0, 0)`
+    pub origin: Point3,
+    /// the length of the edges of cells
+    pub cell_size: f64,
+    /// the numbers of the vertices along the axes
+    pub dims: [usize; 3],
+    /// the values at the vertices, the value at `(i, j, k)` is `values[i + dims[0] * (j + dims[1] * k)]`
+    pub values: Vec<f64>,
+}
+
+impl ScalarGrid {
+    /// Samples `function` at the vertices of the grid.
+    pub fn from_fn(
+        origin: Point3,
+        cell_size: f64,
+        dims: [usize; 3],
+        function: impl Fn(Point3) -> f64,
+    ) -> Self {
+        let mut grid = Self {
+            origin,
+            cell_size,
+            dims,
+            values: Vec::with_capacity(dims[0] * dims[1] * dims[2]),
+        };
+        for k in 0..dims[2] {
+            for j in 0..dims[1] {
+                for i in 0..dims[0] {
+                    let value = function(grid.point([i, j, k]));
+                    grid.values.push(value);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Creates the grid covering `bdb` with the margin of `padding` cells.
+    pub fn covering(
+        bdb: BoundingBox<Point3>,
+        cell_size: f64,
+        padding: usize,
+        function: impl Fn(Point3) -> f64,
+    ) -> Self {
+        let origin = bdb.min() - Vector3::new(1.0, 1.0, 1.0) * (padding as f64 * cell_size);
+        let diag = bdb.diagonal();
+        let dims = [0, 1, 2].map(|i| (diag[i] / cell_size).ceil() as usize + 2 * padding + 1);
+        Self::from_fn(origin, cell_size, dims, function)
+    }
+
+    /// Returns the position of the vertex `(i, j, k)`.
+    pub fn point(&self, [i, j, k]: [usize; 3]) -> Point3 {
+        self.origin + Vector3::new(i as f64, j as f64, k as f64) * self.cell_size
+    }
+
+    /// Returns the index of the vertex `(i, j, k)` in `values`.
+    pub fn index(&self, [i, j, k]: [usize; 3]) -> usize {
+        i + self.dims[0] * (j + self.dims[1] * k)
+    }
+
+    /// Extracts the isosurface `{ f = level }` by marching tetrahedra.
+    ///
+    /// The faces are oriented to the region `{ f > level }`, so the isosurface of
+    /// a signed distance function, negative inside, is oriented outward.
+    /// The isosurface is closed if the values on the boundary of the grid are greater than `level`.
+    pub fn isosurface(&self, level: f64) -> PolygonMesh {
+        let mut positions = Vec::new();
+        let mut vertex_map = HashMap::<(usize, usize), usize>::new();
+        let mut faces = Faces::<StandardVertex>::default();
+        let [nx, ny, nz] = self.dims;
+        for k in 0..nz.saturating_sub(1) {
+            for j in 0..ny.saturating_sub(1) {
+                for i in 0..nx.saturating_sub(1) {
+                    let corners: [usize; 8] = std::array::from_fn(|c| {
+                        self.index([i + (c & 1), j + ((c >> 1) & 1), k + ((c >> 2) & 1)])
+                    });
+                    for tetra in TETRAHEDRA {
+                        let tetra = tetra.map(|c| corners[c]);
+                        self.march_tetrahedron(
+                            tetra,
+                            level,
+                            &mut positions,
+                            &mut vertex_map,
+                            &mut faces,
+                        );
+                    }
+                }
+            }
+        }
+        let attrs = StandardAttributes {
+            positions,
+            ..Default::default()
+        };
+        PolygonMesh::new(attrs, faces)
+    }
+
+    fn grid_point(&self, index: usize) -> Point3 {
+        let (i, rest) = (index % self.dims[0], index / self.dims[0]);
+        let (j, k) = (rest % self.dims[1], rest / self.dims[1]);
+        self.point([i, j, k])
+    }
+
+    fn march_tetrahedron(
+        &self,
+        tetra: [usize; 4],
+        level: f64,
+        positions: &mut Vec<Point3>,
+        vertex_map: &mut HashMap<(usize, usize), usize>,
+        faces: &mut Faces,
+    ) {
+        let (inside, outside): (Vec<usize>, Vec<usize>) =
+            tetra.into_iter().partition(|idx| self.values[*idx] < level);
+        let mut edge_vertex = |a: usize, b: usize| {
+            let key = (usize::min(a, b), usize::max(a, b));
+            *vertex_map.entry(key).or_insert_with(|| {
+                let (fa, fb) = (self.values[a], self.values[b]);
+                let (pa, pb) = (self.grid_point(a), self.grid_point(b));
+                positions.push(pa + (pb - pa) * ((level - fa) / (fb - fa)));
+                positions.len() - 1
+            })
+        };
+        let polygon = match (inside.len(), outside.len()) {
+            (1, 3) => outside
+                .iter()
+                .map(|o| edge_vertex(inside[0], *o))
+                .collect::<Vec<_>>(),
+            (3, 1) => inside.iter().map(|i| edge_vertex(*i, outside[0])).collect(),
+            (2, 2) => vec![
+                edge_vertex(inside[0], outside[0]),
+                edge_vertex(inside[0], outside[1]),
+                edge_vertex(inside[1], outside[1]),
+                edge_vertex(inside[1], outside[0]),
+            ],
+            _ => return,
+        };
+        // The polygon is oriented from the inside to the outside if the tetrahedron
+        // (inside, outside) is positive. This does not depend on the interpolated
+        // positions, so it is robust even if the polygon is degenerate.
+        let p: Vec<Point3> = inside
+            .iter()
+            .chain(&outside)
+            .map(|i| self.grid_point(*i))
+            .collect();
+        let mut polygon = polygon;
+        if (p[1] - p[0]).cross(p[2] - p[0]).dot(p[3] - p[0]) < 0.0 {
+            polygon.reverse();
+        }
+        match polygon.len() {
+            3 => faces.push([polygon[0], polygon[1], polygon[2]]),
+            _ => {
+                faces.push([polygon[0], polygon[1], polygon[2]]);
+                faces.push([polygon[0], polygon[2], polygon[3]]);
+            }
+        }
+    }
+}
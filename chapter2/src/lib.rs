@@ -0,0 +1,21 @@
+//! The utility modules of chapter 2, shared by the samples.
+
+pub mod boolean;
+pub mod bvh;
+pub mod components;
+pub mod deformation;
+pub mod deviation;
+pub mod fixtures;
+pub mod gltf;
+pub mod isosurface;
+pub mod obj_writer;
+pub mod offset;
+pub mod polyhedra;
+pub mod reconstruction;
+pub mod sampling;
+pub mod self_intersection;
+pub mod slicing;
+pub mod tangent;
+pub mod triangulate;
+pub mod vertex_cache;
+pub mod voxel;
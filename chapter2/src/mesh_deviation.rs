@@ -0,0 +1,27 @@
+use chapter2::{deviation::*, fixtures::*};
+
+fn main() {
+    // The sphere is inside the sphere scaled by 1.01.
+    let sphere = sphere();
+    let mut scaled = sphere.clone();
+    scaled.positions_mut().iter_mut().for_each(|p| *p *= 1.01);
+    let dev = deviation(&sphere, &scaled, 4).unwrap();
+    println!(
+        "sphere -> scaled sphere: {dev:.6?}",
+        dev = (dev.max, dev.mean, dev.rms)
+    );
+
+    // The coarser spheres have the larger deviations.
+    let fine = cube_sphere(32);
+    let distances: Vec<f64> = [2, 4, 8, 16]
+        .into_iter()
+        .map(|division| hausdorff_distance(&cube_sphere(division), &fine, 4).unwrap())
+        .collect();
+    println!("Hausdorff distances from the fine sphere: {distances:.6?}");
+
+    // the distance map of the coarse sphere
+    let coarse = cube_sphere(4);
+    let dev = deviation(&coarse, &fine, 1).unwrap();
+    let file = std::fs::File::create("coarse-sphere-deviation.ply").unwrap();
+    write_ply(&coarse, &dev.vertex_distances, file).unwrap();
+}
@@ -0,0 +1,421 @@
+//! Command-line tool applying the filters of chapter 2 to mesh files.
+//!
+//! The meshes are read from the file given as the argument or from the standard input,
+//! and are written to the file given by `-o` or to the standard output. The formats are
+//! determined by the extensions of the files, or by `--from` and `--to` for the standard
+//! streams, where OBJ is the default.
+//!
+//! The exit code is 0 on success, 1 if the mesh cannot be read or written, and 2 if the
+//! arguments are wrong. The input without positions or faces, e.g. a file in the other format
+//! which the OBJ parser skips line by line, cannot be read.
+
+use chapter2::{
+    obj_writer::{self, FloatFormat, ObjOptions},
+    polyhedra,
+};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::process::ExitCode;
+use truck_meshalgo::prelude::*;
+
+const USAGE: &str = "usage: meshtool <command> [options]
+
+commands:
+    info [INPUT]                       print the shell condition, the counts and the bounding box
+    weld [INPUT] [--tol TOL]           put together the vertices closer than TOL (default: 1e-3)
+    normals [INPUT] [--smooth] [--angle DEG]
+                                       add the face normals, or the smooth normals which are
+                                       not blended over the angle DEG (default: 60)
+    convert [INPUT]                    convert the format
+    generate <SOLID> [--division N]    generate tetrahedron, hexahedron, octahedron,
+                                       dodecahedron, icosahedron or sphere (default N: 8)
+
+options:
+    -o, --output OUTPUT                the output file (default: the standard output)
+    --from FORMAT                      the format of the standard input: obj or stl
+    --to FORMAT                        the format of the standard output: obj, stl, stl-ascii or ply
+    --precision N                      the digits after the decimal point of OBJ (default: 10)
+    --float NOTATION                   the notation of the numbers of OBJ: scientific (default),
+                                       fixed or shortest
+    --dedup                            write the same normals and uv coordinates of OBJ once
+    -h, --help                         print this message
+
+The input `-` or no input means the standard input, and the output `-` the standard output.";
+
+/// The errors of the tool.
+#[derive(Debug)]
+enum ToolError {
+    /// The arguments are wrong.
+    Usage(String),
+    /// The mesh cannot be read or written.
+    Io(String),
+}
+
+impl std::fmt::Display for ToolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToolError::Usage(msg) => write!(f, "{msg}\n\n{USAGE}"),
+            ToolError::Io(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+impl From<io::Error> for ToolError {
+    fn from(error: io::Error) -> Self { ToolError::Io(error.to_string()) }
+}
+
+impl From<truck_meshalgo::prelude::errors::Error> for ToolError {
+    fn from(error: truck_meshalgo::prelude::errors::Error) -> Self {
+        ToolError::Io(error.to_string())
+    }
+}
+
+fn usage<T>(msg: impl Into<String>) -> Result<T, ToolError> { Err(ToolError::Usage(msg.into())) }
+
+/// The formats of the mesh files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Obj,
+    /// binary STL
+    Stl,
+    StlAscii,
+    /// ASCII PLY, only for the output
+    Ply,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Result<Self, ToolError> {
+        match name.to_ascii_lowercase().as_str() {
+            "obj" => Ok(Format::Obj),
+            "stl" => Ok(Format::Stl),
+            "stl-ascii" => Ok(Format::StlAscii),
+            "ply" => Ok(Format::Ply),
+            _ => usage(format!("unknown format: {name}")),
+        }
+    }
+
+    fn from_path(path: &str) -> Result<Self, ToolError> {
+        match std::path::Path::new(path).extension() {
+            Some(ext) => Format::from_name(&ext.to_string_lossy()),
+            None => usage(format!("cannot determine the format of {path}")),
+        }
+    }
+}
+
+/// The parsed arguments.
+#[derive(Clone, Debug, Default)]
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    output: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    tol: Option<String>,
+    angle: Option<String>,
+    division: Option<String>,
+    precision: Option<String>,
+    float: Option<String>,
+    dedup: bool,
+    smooth: bool,
+    help: bool,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, ToolError> {
+        let mut parsed = Args::default();
+        let mut iter = args.into_iter();
+        while let Some(arg) = iter.next() {
+            let mut value = |name: &str| match iter.next() {
+                Some(value) => Ok(Some(value)),
+                None => usage(format!("{name} needs a value")),
+            };
+            match arg.as_str() {
+                "-h" | "--help" => parsed.help = true,
+                "--smooth" => parsed.smooth = true,
+                "-o" | "--output" => parsed.output = value(&arg)?,
+                "--from" => parsed.from = value(&arg)?,
+                "--to" => parsed.to = value(&arg)?,
+                "--tol" => parsed.tol = value(&arg)?,
+                "--angle" => parsed.angle = value(&arg)?,
+                "--division" => parsed.division = value(&arg)?,
+                "--precision" => parsed.precision = value(&arg)?,
+                "--float" => parsed.float = value(&arg)?,
+                "--dedup" => parsed.dedup = true,
+                _ if arg.starts_with('-') && arg != "-" => {
+                    return usage(format!("unknown option: {arg}"))
+                }
+                _ if parsed.command.is_empty() => parsed.command = arg,
+                _ => parsed.positional.push(arg),
+            }
+        }
+        Ok(parsed)
+    }
+
+    // Fails if the options which the command does not take are given.
+    fn accept(&self, options: &[&str]) -> Result<(), ToolError> {
+        let given = [
+            ("--tol", self.tol.is_some()),
+            ("--angle", self.angle.is_some()),
+            ("--division", self.division.is_some()),
+            ("--smooth", self.smooth),
+            ("--precision", self.precision.is_some()),
+            ("--float", self.float.is_some()),
+            ("--dedup", self.dedup),
+        ];
+        match given
+            .iter()
+            .find(|(name, given)| *given && !options.contains(name))
+        {
+            Some((name, _)) => usage(format!("{} does not take {name}", self.command)),
+            None => Ok(()),
+        }
+    }
+
+    // the input path, `None` for the standard input
+    fn input(&self) -> Result<Option<&str>, ToolError> {
+        match self.positional.as_slice() {
+            [] => Ok(None),
+            [path] if path == "-" => Ok(None),
+            [path] => Ok(Some(path)),
+            _ => usage("too many inputs"),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(
+        value: &Option<String>,
+        name: &str,
+        default: T,
+    ) -> Result<T, ToolError> {
+        match value {
+            Some(value) => match value.parse() {
+                Ok(number) => Ok(number),
+                Err(_) => usage(format!("invalid value of {name}: {value}")),
+            },
+            None => Ok(default),
+        }
+    }
+}
+
+fn read_mesh(args: &Args) -> Result<PolygonMesh, ToolError> {
+    let input = args.input()?;
+    let (reader, format): (Box<dyn Read>, Format) = match input {
+        Some(path) => {
+            let format = Format::from_path(path)?;
+            let file = File::open(path).map_err(|e| ToolError::Io(format!("{path}: {e}")))?;
+            (Box::new(BufReader::new(file)), format)
+        }
+        None => {
+            let format = match &args.from {
+                Some(name) => Format::from_name(name)?,
+                None => Format::Obj,
+            };
+            (Box::new(BufReader::new(io::stdin().lock())), format)
+        }
+    };
+    let mesh = match format {
+        Format::Obj => obj::read(reader)?,
+        Format::Stl | Format::StlAscii => stl::read(reader, stl::StlType::Automatic)?,
+        Format::Ply => return usage("reading PLY is not supported"),
+    };
+    match mesh.positions().is_empty() || mesh.faces().is_empty() {
+        true => Err(ToolError::Io(format!(
+            "{}: no mesh is found",
+            input.unwrap_or("the standard input")
+        ))),
+        false => Ok(mesh),
+    }
+}
+
+// Opens the output file, or the standard output if `path` is `None` or `-`.
+fn open_output(path: Option<&str>) -> Result<Box<dyn Write>, ToolError> {
+    match path {
+        Some(path) if path != "-" => {
+            let file = File::create(path).map_err(|e| ToolError::Io(format!("{path}: {e}")))?;
+            Ok(Box::new(BufWriter::new(file)))
+        }
+        _ => Ok(Box::new(BufWriter::new(io::stdout().lock()))),
+    }
+}
+
+fn output_format(args: &Args) -> Result<Format, ToolError> {
+    match (&args.to, args.output.as_deref()) {
+        (Some(name), _) => Format::from_name(name),
+        (None, Some(path)) if path != "-" => Format::from_path(path),
+        (None, _) => Ok(Format::Obj),
+    }
+}
+
+fn write_mesh(mesh: &PolygonMesh, args: &Args) -> Result<(), ToolError> {
+    let format = output_format(args)?;
+    let mut writer = open_output(args.output.as_deref())?;
+    match format {
+        Format::Obj => obj_writer::write_obj(mesh, &obj_options(args)?, &mut writer)?,
+        Format::Stl => stl::write(mesh, &mut writer, stl::StlType::Binary)?,
+        Format::StlAscii => stl::write(mesh, &mut writer, stl::StlType::Ascii)?,
+        Format::Ply => write_ply(mesh, &mut writer)?,
+    }
+    Ok(writer.flush()?)
+}
+
+fn obj_options(args: &Args) -> Result<ObjOptions, ToolError> {
+    let format = match args.float.as_deref() {
+        None | Some("scientific") => FloatFormat::Scientific,
+        Some("fixed") => FloatFormat::Fixed,
+        Some("shortest") => FloatFormat::Shortest,
+        Some(name) => return usage(format!("unknown notation: {name}")),
+    };
+    Ok(ObjOptions {
+        precision: Args::number(&args.precision, "--precision", 10)?,
+        format,
+        deduplicate_normals: args.dedup,
+        deduplicate_uv_coords: args.dedup,
+        normalize_zeros: false,
+    })
+}
+
+/// Writes the positions and the faces as an ASCII PLY.
+fn write_ply<W: Write>(mesh: &PolygonMesh, mut writer: W) -> io::Result<()> {
+    writeln!(writer, "ply\nformat ascii 1.0")?;
+    writeln!(writer, "element vertex {}", mesh.positions().len())?;
+    writeln!(
+        writer,
+        "property double x\nproperty double y\nproperty double z"
+    )?;
+    writeln!(writer, "element face {}", mesh.faces().len())?;
+    writeln!(writer, "property list uchar int vertex_indices\nend_header")?;
+    mesh.positions()
+        .iter()
+        .try_for_each(|p| writeln!(writer, "{} {} {}", p.x, p.y, p.z))?;
+    mesh.face_iter().try_for_each(|face| {
+        write!(writer, "{}", face.len())?;
+        face.iter().try_for_each(|v| write!(writer, " {}", v.pos))?;
+        writeln!(writer)
+    })
+}
+
+fn info(args: &Args) -> Result<(), ToolError> {
+    args.accept(&[])?;
+    let mesh = read_mesh(args)?;
+    let faces = mesh.faces();
+    let condition = mesh.shell_condition();
+    let mut writer = open_output(args.output.as_deref())?;
+    writeln!(writer, "positions: {}", mesh.positions().len())?;
+    writeln!(writer, "uv coords: {}", mesh.uv_coords().len())?;
+    writeln!(writer, "normals: {}", mesh.normals().len())?;
+    writeln!(
+        writer,
+        "faces: {} (triangles: {}, quadrangles: {}, others: {})",
+        faces.len(),
+        faces.tri_faces().len(),
+        faces.quad_faces().len(),
+        faces.other_faces().len()
+    )?;
+    writeln!(writer, "shell condition: {condition:?}")?;
+    if !mesh.positions().is_empty() {
+        let bdb = mesh.bounding_box();
+        let (min, max) = (bdb.min(), bdb.max());
+        writeln!(
+            writer,
+            "bounding box: ({}, {}, {}) - ({}, {}, {})",
+            min.x, min.y, min.z, max.x, max.y, max.z
+        )?;
+    }
+    if condition == ShellCondition::Closed {
+        writeln!(writer, "volume: {}", mesh.volume())?;
+    }
+    Ok(writer.flush()?)
+}
+
+fn weld(args: &Args) -> Result<(), ToolError> {
+    args.accept(&["--tol", "--precision", "--float", "--dedup"])?;
+    let tol: f64 = Args::number(&args.tol, "--tol", 1.0e-3)?;
+    if tol.is_nan() || tol < 0.0 {
+        return usage(format!("--tol must not be negative: {tol}"));
+    }
+    let mut mesh = read_mesh(args)?;
+    mesh.put_together_same_attrs(tol).remove_unused_attrs();
+    write_mesh(&mesh, args)
+}
+
+fn normals(args: &Args) -> Result<(), ToolError> {
+    args.accept(&["--smooth", "--angle", "--precision", "--float", "--dedup"])?;
+    if args.angle.is_some() && !args.smooth {
+        return usage("--angle needs --smooth");
+    }
+    let angle: f64 = Args::number(&args.angle, "--angle", 60.0)?;
+    let mut mesh = read_mesh(args)?;
+    match args.smooth {
+        true => mesh
+            .add_naive_normals(true)
+            .add_smooth_normals(angle.to_radians(), true),
+        false => mesh.add_naive_normals(true),
+    };
+    write_mesh(&mesh, args)
+}
+
+fn convert(args: &Args) -> Result<(), ToolError> {
+    args.accept(&["--precision", "--float", "--dedup"])?;
+    let mesh = read_mesh(args)?;
+    write_mesh(&mesh, args)
+}
+
+fn generate(args: &Args) -> Result<(), ToolError> {
+    args.accept(&["--division", "--precision", "--float", "--dedup"])?;
+    let [solid] = args.positional.as_slice() else {
+        return usage("generate needs one solid");
+    };
+    if solid != "sphere" && args.division.is_some() {
+        return usage("--division is only for the sphere");
+    }
+    let mesh = match solid.as_str() {
+        "tetrahedron" => polyhedra::tetrahedron(),
+        "hexahedron" | "cube" => polyhedra::hexahedron(),
+        "octahedron" => polyhedra::octahedron(),
+        "dodecahedron" => polyhedra::dodecahedron(),
+        "icosahedron" => polyhedra::icosahedron(),
+        "sphere" => match Args::number(&args.division, "--division", 8)? {
+            0 => return usage("--division must be positive"),
+            division => polyhedra::sphere(division),
+        },
+        _ => return usage(format!("unknown solid: {solid}")),
+    };
+    write_mesh(&mesh, args)
+}
+
+fn run(args: &Args) -> Result<(), ToolError> {
+    match args.command.as_str() {
+        "info" => info(args),
+        "weld" => weld(args),
+        "normals" => normals(args),
+        "convert" => convert(args),
+        "generate" => generate(args),
+        "" => usage("no command"),
+        command => usage(format!("unknown command: {command}")),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("meshtool: {error}");
+            return ExitCode::from(2);
+        }
+    };
+    if args.help {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("meshtool: {error}");
+            match error {
+                ToolError::Usage(_) => ExitCode::from(2),
+                ToolError::Io(_) => ExitCode::from(1),
+            }
+        }
+    }
+}
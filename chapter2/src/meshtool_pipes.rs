@@ -0,0 +1,112 @@
+// Runs `meshtool` through the pipes and the files, and checks the outputs and the exit codes.
+//
+// The executable `meshtool` is looked up next to this executable, so build the package first.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use truck_meshalgo::prelude::*;
+
+/// The exit code, the standard output and the standard error of `meshtool` with `args`,
+/// whose standard input is `input`.
+fn meshtool(args: &[&str], input: &[u8]) -> (i32, Vec<u8>, String) {
+    let name = format!("meshtool{}", std::env::consts::EXE_SUFFIX);
+    let path = std::env::current_exe().unwrap().with_file_name(name);
+    let mut child = Command::new(&path)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    // The tool may exit without reading the input, which closes the pipe.
+    let _ = child.stdin.take().unwrap().write_all(input);
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    (output.status.code().unwrap(), output.stdout, stderr)
+}
+
+/// Runs `meshtool` which should succeed, and returns the standard output.
+fn success(args: &[&str], input: &[u8]) -> Vec<u8> {
+    let (code, stdout, stderr) = meshtool(args, input);
+    assert_eq!(code, 0, "meshtool {args:?}: {stderr}");
+    stdout
+}
+
+/// Runs `meshtool` which should fail with `code`.
+fn failure(args: &[&str], input: &[u8], code: i32) {
+    let (actual, _, stderr) = meshtool(args, input);
+    println!(
+        "meshtool {args:?}: exit {actual}, {}",
+        stderr.lines().next().unwrap()
+    );
+    assert_eq!(actual, code, "meshtool {args:?}");
+}
+
+fn main() {
+    // generate: the cube of section 2.3
+    let cube = success(&["generate", "cube"], b"");
+    let mesh = obj::read(cube.as_slice()).unwrap();
+    assert_eq!((mesh.positions().len(), mesh.faces().len()), (8, 6));
+    assert_eq!(mesh.shell_condition(), ShellCondition::Closed);
+
+    // info: the cube through the pipe
+    let info = String::from_utf8(success(&["info"], &cube)).unwrap();
+    assert!(info.contains("positions: 8\n"));
+    assert!(info.contains("faces: 6 (triangles: 0, quadrangles: 6, others: 0)\n"));
+    assert!(info.contains("shell condition: Closed\n"));
+    assert!(info.lines().any(|line| line.starts_with("volume: ")));
+
+    // weld: the sphere of section 2.4 is closed only after the vertices are put together
+    let sphere = success(&["generate", "sphere", "--division", "4"], b"");
+    let info = String::from_utf8(success(&["info", "-"], &sphere)).unwrap();
+    assert!(!info.contains("shell condition: Closed\n"));
+    let welded = success(&["weld", "--tol", "1e-6"], &sphere);
+    let mesh = obj::read(welded.as_slice()).unwrap();
+    assert_eq!(mesh.shell_condition(), ShellCondition::Closed);
+    assert_eq!(mesh.positions().len(), 6 * 4 * 4 + 2);
+
+    // normals: the face normals and the smooth ones
+    let flat = obj::read(success(&["normals"], &cube).as_slice()).unwrap();
+    assert_eq!(flat.normals().len(), 6);
+    let smooth = success(&["normals", "--smooth", "--angle", "180"], &welded);
+    let smooth = obj::read(smooth.as_slice()).unwrap();
+    assert!(smooth.normals().len() > 6);
+
+    // convert: OBJ to binary STL by the extension, and back through the pipe
+    let path = std::env::temp_dir().join("meshtool-cube.stl");
+    let path = path.to_str().unwrap();
+    assert!(success(&["convert", "-o", path], &cube).is_empty());
+    let stl = std::fs::read(path).unwrap();
+    let obj = success(&["convert", "--from", "stl", "--to", "obj"], &stl);
+    let mesh = obj::read(obj.as_slice()).unwrap();
+    assert_eq!(mesh.faces().len(), 12);
+    let info = String::from_utf8(success(&["info", path], b"")).unwrap();
+    assert!(info.contains("faces: 12 (triangles: 12, quadrangles: 0, others: 0)\n"));
+    let ply = success(&["convert", "--to", "ply"], &cube);
+    assert!(ply.starts_with(b"ply\nformat ascii 1.0\nelement vertex 8\n"));
+
+    // help
+    let help = String::from_utf8(success(&["--help"], b"")).unwrap();
+    assert!(help.starts_with("usage: meshtool"));
+
+    // The mesh which cannot be read or written exits with 1.
+    failure(&["info"], b"this is not a mesh\n", 1);
+    failure(&["info"], b"", 1);
+    failure(&["convert", "--from", "stl"], &cube, 1);
+    failure(&["info", "no-such-mesh.obj"], b"", 1);
+    failure(&["convert", "-o", "no-such-directory/cube.obj"], &cube, 1);
+
+    // The wrong arguments exit with 2.
+    failure(&[], b"", 2);
+    failure(&["render"], &cube, 2);
+    failure(&["info", "--verbose"], &cube, 2);
+    failure(&["info", "--tol", "0.1"], &cube, 2);
+    failure(&["weld", "--tol"], &cube, 2);
+    failure(&["weld", "--tol", "-1"], &cube, 2);
+    failure(&["normals", "--angle", "30"], &cube, 2);
+    failure(&["convert", "--to", "step"], &cube, 2);
+    failure(&["convert", "cube.step"], b"", 2);
+    failure(&["generate", "torus"], b"", 2);
+    failure(&["generate", "sphere", "--division", "0"], b"", 2);
+    failure(&["generate", "cube", "--division", "4"], b"", 2);
+}
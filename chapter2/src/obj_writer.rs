@@ -0,0 +1,119 @@
+//! OBJ output with the control of the format of the numbers.
+//!
+//! The default options reproduce `obj::write` byte for byte, including the negative zeros.
+//! The output depends only on the mesh and the options, so the files can be kept as the golden
+//! files of the tests, where the negative zeros can be written as the positive ones so that
+//! the signs of the values rounded to zero do not matter. The normals and the uv coordinates
+//! can be deduplicated by their written texts, so that the values which are equal in the given
+//! precision are written once.
+
+use std::collections::HashMap;
+use std::io::{self, BufWriter, Write};
+use truck_meshalgo::prelude::*;
+
+/// The notations of the numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// `-5.7735026919e-1`, with `precision` digits after the point
+    Scientific,
+    /// `-0.5773502692`, with `precision` digits after the point
+    Fixed,
+    /// `-0.5773502691896258`, the shortest text which is read as the same number.
+    /// `precision` is ignored.
+    Shortest,
+}
+
+/// The options of the OBJ writer.
+#[derive(Clone, Copy, Debug)]
+pub struct ObjOptions {
+    /// the number of the digits after the decimal point
+    pub precision: usize,
+    /// the notation of the numbers
+    pub format: FloatFormat,
+    /// If `true`, the normals with the same text are written once.
+    pub deduplicate_normals: bool,
+    /// If `true`, the uv coordinates with the same text are written once.
+    pub deduplicate_uv_coords: bool,
+    /// If `true`, the negative zeros, e.g. the negative numbers rounded to `-0.000`,
+    /// are written as the positive zeros.
+    pub normalize_zeros: bool,
+}
+
+impl Default for ObjOptions {
+    fn default() -> Self {
+        Self {
+            precision: 10,
+            format: FloatFormat::Scientific,
+            deduplicate_normals: false,
+            deduplicate_uv_coords: false,
+            normalize_zeros: false,
+        }
+    }
+}
+
+impl ObjOptions {
+    /// Returns the text of `x`.
+    pub fn format_float(&self, x: f64) -> String {
+        let text = |x: f64| match self.format {
+            FloatFormat::Scientific => format!("{x:.*e}", self.precision),
+            FloatFormat::Fixed => format!("{x:.*}", self.precision),
+            FloatFormat::Shortest => format!("{x}"),
+        };
+        let res = text(x);
+        // the numbers which are rounded to zero, e.g. "-0.000"
+        match self.normalize_zeros && res.starts_with('-') && res.parse::<f64>() == Ok(0.0) {
+            true => text(0.0),
+            false => res,
+        }
+    }
+}
+
+/// Writes `mesh` to `writer` in the OBJ format by `options`.
+pub fn write_obj<W: Write>(mesh: &PolygonMesh, options: &ObjOptions, writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    let mut write_vectors = |prefix: &str, vectors: Vec<Vec<f64>>, deduplicate: bool| {
+        // the new indices of the vectors
+        let mut indices = Vec::with_capacity(vectors.len());
+        let mut written = HashMap::<String, usize>::new();
+        let mut count = 0;
+        vectors.iter().try_for_each(|vector| {
+            let line = vector.iter().fold(prefix.to_string(), |line, x| {
+                line + " " + &options.format_float(*x)
+            });
+            match written.get(&line) {
+                Some(idx) if deduplicate => indices.push(*idx),
+                _ => {
+                    writeln!(writer, "{line}")?;
+                    indices.push(count);
+                    written.entry(line).or_insert(count);
+                    count += 1;
+                }
+            }
+            io::Result::Ok(())
+        })?;
+        io::Result::Ok(indices)
+    };
+    let positions = mesh.positions().iter().map(|p| vec![p.x, p.y, p.z]);
+    write_vectors("v", positions.collect(), false)?;
+    let uv_coords = mesh.uv_coords().iter().map(|uv| vec![uv.x, uv.y]);
+    let uv_indices = write_vectors("vt", uv_coords.collect(), options.deduplicate_uv_coords)?;
+    let normals = mesh.normals().iter().map(|n| vec![n.x, n.y, n.z]);
+    let normal_indices = write_vectors("vn", normals.collect(), options.deduplicate_normals)?;
+    mesh.face_iter().try_for_each(|face| {
+        writer.write_all(b"f")?;
+        face.iter().try_for_each(|v| {
+            let (uv, nor) = (
+                v.uv.map(|i| uv_indices[i]),
+                v.nor.map(|i| normal_indices[i]),
+            );
+            match (uv, nor) {
+                (None, None) => write!(writer, " {}", v.pos + 1),
+                (Some(uv), None) => write!(writer, " {}/{}", v.pos + 1, uv + 1),
+                (None, Some(nor)) => write!(writer, " {}//{}", v.pos + 1, nor + 1),
+                (Some(uv), Some(nor)) => write!(writer, " {}/{}/{}", v.pos + 1, uv + 1, nor + 1),
+            }
+        })?;
+        writer.write_all(b"\n")
+    })?;
+    writer.flush()
+}
@@ -0,0 +1,255 @@
+//! Offsetting polygon meshes, and thickening open surfaces into closed shells.
+
+use crate::bvh::SignedDistance;
+use crate::isosurface::ScalarGrid;
+use crate::self_intersection::self_intersections;
+use std::collections::BTreeSet;
+use truck_meshalgo::prelude::*;
+
+// the maximum number of the iterations of the relaxation of folded regions
+const RELAXATION_ITERATIONS: usize = 100;
+
+/// Errors of offsetting and thickening.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OffsetError {
+    /// The exact offset needs a closed mesh to determine the inside.
+    NotClosed,
+    /// The thickening needs an oriented manifold mesh to extract the boundary.
+    NotOriented,
+    /// The cells of the grid of the exact offset must have a positive size.
+    InvalidCellSize,
+    /// The offset along the normals intersects itself at the number of the pairs of triangles.
+    SelfIntersecting(usize),
+}
+
+impl std::fmt::Display for OffsetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OffsetError::NotClosed => f.write_str("the mesh is not closed"),
+            OffsetError::NotOriented => f.write_str("the mesh is not an oriented manifold"),
+            OffsetError::InvalidCellSize => f.write_str("the cell size is not positive"),
+            OffsetError::SelfIntersecting(pairs) => {
+                write!(f, "the offset intersects itself at {pairs} pairs of triangles")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OffsetError {}
+
+/// The folds, i.e. the local self-intersections, created by the offset along the normals.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FoldReport {
+    /// the number of the faces folded by the naive offset
+    pub folded: usize,
+    /// the number of the faces still folded after the relaxation
+    pub unresolved: usize,
+}
+
+/// Returns the angle-weighted normals at the positions of `mesh`.
+pub fn vertex_normals(mesh: &PolygonMesh) -> Vec<Vector3> {
+    let positions = mesh.positions();
+    let mut normals = vec![Vector3::zero(); positions.len()];
+    mesh.face_iter().for_each(|face| {
+        let normal = face_normal(positions, face.iter().map(|v| v.pos));
+        let len = face.len();
+        (0..len).for_each(|k| {
+            let p = positions[face[k].pos];
+            let u = (positions[face[(k + 1) % len].pos] - p).normalize();
+            let v = (positions[face[(k + len - 1) % len].pos] - p).normalize();
+            normals[face[k].pos] += normal * u.dot(v).clamp(-1.0, 1.0).acos();
+        });
+    });
+    normals.iter_mut().for_each(|n| {
+        if !n.so_small() {
+            *n = n.normalize()
+        }
+    });
+    normals
+}
+
+/// Returns the indices of the faces whose normals are flipped by moving the positions to `moved`.
+pub fn folded_faces(mesh: &PolygonMesh, moved: &[Point3]) -> Vec<usize> {
+    mesh.face_iter()
+        .enumerate()
+        .filter(|(_, face)| {
+            let n0 = face_normal(mesh.positions(), face.iter().map(|v| v.pos));
+            let n1 = face_normal(moved, face.iter().map(|v| v.pos));
+            !n0.so_small() && n0.dot(n1) <= 0.0
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Offsets `mesh` by `distance` along the angle-weighted vertex normals.
+///
+/// The self-intersections created by the offset are relaxed by the Laplacian smoothing:
+/// the folds, i.e. the faces whose normals are flipped, and the faces which cross the distant
+/// parts of the offset, found by `self_intersections`. The relaxed vertices move off the offset,
+/// so the distance is only approximate in the relaxed regions. If the offset still intersects
+/// itself after the relaxation, e.g. `mesh` itself intersects, `OffsetError::SelfIntersecting`
+/// is returned; use `offset_by_sdf` for such offsets.
+///
+/// The positions of `mesh` should be put together beforehand, otherwise the offset is torn
+/// at the seams. The normals and the texture coordinates are not changed.
+pub fn offset_by_normals(
+    mesh: &PolygonMesh,
+    distance: f64,
+) -> Result<(PolygonMesh, FoldReport), OffsetError> {
+    let normals = vertex_normals(mesh);
+    let mut moved: Vec<Point3> = mesh
+        .positions()
+        .iter()
+        .zip(&normals)
+        .map(|(p, n)| p + n * distance)
+        .collect();
+
+    let mut folded = folded_faces(mesh, &moved);
+    let mut report = FoldReport {
+        folded: folded.len(),
+        unresolved: 0,
+    };
+    let mut adjacency = vec![BTreeSet::<usize>::new(); moved.len()];
+    mesh.face_iter().for_each(|face| {
+        (0..face.len()).for_each(|k| {
+            let (i, j) = (face[k].pos, face[(k + 1) % face.len()].pos);
+            adjacency[i].insert(j);
+            adjacency[j].insert(i);
+        })
+    });
+    // the indices of the faces of the triangles in the order of `mesh.faces().triangle_iter()`
+    let triangle_faces: Vec<usize> = mesh
+        .face_iter()
+        .enumerate()
+        .flat_map(|(i, face)| std::iter::repeat_n(i, face.len() - 2))
+        .collect();
+    let mut offset = mesh.clone();
+    offset.positions_mut().copy_from_slice(&moved);
+    let mut crossing = self_intersections(&offset);
+    // The free vertices grow with the folded and the crossing faces, and are never fixed again.
+    let mut free = BTreeSet::<usize>::new();
+    for _ in 0..RELAXATION_ITERATIONS {
+        if folded.is_empty() && crossing.is_empty() {
+            break;
+        }
+        let faces = mesh.faces();
+        folded
+            .iter()
+            .copied()
+            .chain(crossing.iter().flat_map(|x| {
+                let (s, t) = x.triangles;
+                [triangle_faces[s], triangle_faces[t]]
+            }))
+            .for_each(|i| free.extend(faces[i].iter().map(|v| v.pos)));
+        // the Jacobi iteration, which does not depend on the order of the vertices
+        let previous = moved.clone();
+        free.iter().for_each(|i| {
+            let sum = adjacency[*i]
+                .iter()
+                .map(|j| previous[*j].to_vec())
+                .sum::<Vector3>();
+            moved[*i] = Point3::from_vec(sum / adjacency[*i].len() as f64);
+        });
+        folded = folded_faces(mesh, &moved);
+        offset.positions_mut().copy_from_slice(&moved);
+        crossing = self_intersections(&offset);
+    }
+    report.unresolved = folded.len();
+    match crossing.len() {
+        0 => Ok((offset, report)),
+        pairs => Err(OffsetError::SelfIntersecting(pairs)),
+    }
+}
+
+/// Offsets the closed `mesh` by `distance` exactly, as the level set of the signed distance function.
+///
+/// The level set is sampled by the grid with the cells of `cell_size`, so the result has no
+/// self-intersections but the sharp features are rounded off by the resolution of the grid.
+pub fn offset_by_sdf(
+    mesh: &PolygonMesh,
+    distance: f64,
+    cell_size: f64,
+) -> Result<PolygonMesh, OffsetError> {
+    if !(cell_size > 0.0 && cell_size.is_finite()) {
+        return Err(OffsetError::InvalidCellSize);
+    }
+    if mesh.shell_condition() != ShellCondition::Closed {
+        return Err(OffsetError::NotClosed);
+    }
+    let sdf = SignedDistance::new(mesh).ok_or(OffsetError::NotClosed)?;
+    let padding = f64::max(distance, 0.0) / cell_size;
+    let grid = ScalarGrid::covering(
+        sdf.bounding_box(),
+        cell_size,
+        padding.ceil() as usize + 2,
+        |p| sdf.distance(p),
+    );
+    Ok(grid.isosurface(distance))
+}
+
+/// Thickens the open surface `mesh` into a closed shell.
+///
+/// The offset by `thickness` along the normals is stitched to `mesh` at the boundaries.
+/// The shell is oriented outward, i.e. `mesh` is inverted if `thickness` is positive and
+/// the offset is inverted otherwise. The result has only the positions.
+/// The errors of `offset_by_normals` are returned as they are.
+pub fn thicken(
+    mesh: &PolygonMesh,
+    thickness: f64,
+) -> Result<(PolygonMesh, FoldReport), OffsetError> {
+    match mesh.shell_condition() {
+        ShellCondition::Oriented | ShellCondition::Closed => {}
+        _ => return Err(OffsetError::NotOriented),
+    }
+    let (offset, report) = offset_by_normals(mesh, thickness)?;
+    let len = mesh.positions().len();
+    let positions = mesh
+        .positions()
+        .iter()
+        .chain(offset.positions())
+        .copied()
+        .collect();
+
+    let mut faces = Faces::default();
+    mesh.face_iter().for_each(|face| {
+        let original: Vec<usize> = face.iter().map(|v| v.pos).collect();
+        let moved: Vec<usize> = original.iter().map(|i| i + len).collect();
+        match thickness > 0.0 {
+            true => {
+                faces.push(original.iter().rev().copied().collect::<Vec<_>>());
+                faces.push(moved);
+            }
+            false => {
+                faces.push(original);
+                faces.push(moved.iter().rev().copied().collect::<Vec<_>>());
+            }
+        }
+    });
+    mesh.extract_boundaries().iter().for_each(|boundary| {
+        (0..boundary.len()).for_each(|k| {
+            let (a, b) = (boundary[k], boundary[(k + 1) % boundary.len()]);
+            match thickness > 0.0 {
+                true => faces.push([a, b, b + len, a + len]),
+                false => faces.push([b, a, a + len, b + len]),
+            }
+        })
+    });
+    let attrs = StandardAttributes {
+        positions,
+        ..Default::default()
+    };
+    Ok((PolygonMesh::new(attrs, faces), report))
+}
+
+// the normal of the polygon by the Newell's method
+fn face_normal(positions: &[Point3], face: impl Iterator<Item = usize> + Clone) -> Vector3 {
+    let next = face.clone().cycle().skip(1);
+    let normal = face
+        .zip(next)
+        .map(|(i, j)| positions[i].to_vec().cross(positions[j].to_vec()))
+        .sum::<Vector3>();
+    match normal.so_small() {
+        true => normal,
+        false => normal.normalize(),
+    }
+}
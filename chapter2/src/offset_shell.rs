@@ -0,0 +1,94 @@
+use chapter2::{fixtures::*, offset::*};
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+
+/// Output the contents of `polygon` to the file specified by `path`.
+fn write_polygon(polygon: &PolygonMesh, path: &str) {
+    let mut obj = std::fs::File::create(path).unwrap();
+    obj::write(polygon, &mut obj).unwrap();
+}
+
+fn main() {
+    // thicken the upper hemisphere into a dome of the thickness 0.05
+    let sphere = sphere();
+    let upper: Vec<usize> = sphere
+        .face_iter()
+        .enumerate()
+        .filter(|(_, face)| {
+            face.iter()
+                .all(|v| sphere.positions()[v.pos].z > -TOLERANCE)
+        })
+        .map(|(i, _)| i)
+        .collect();
+    let hemisphere = sphere.create_mesh_by_face_indices(&upper);
+    assert_eq!(hemisphere.shell_condition(), ShellCondition::Oriented);
+    let (dome, report) = thicken(&hemisphere, 0.05).unwrap();
+    assert_eq!(report, FoldReport::default());
+    assert_eq!(dome.shell_condition(), ShellCondition::Closed);
+    let exact = 2.0 * PI / 3.0 * (1.05f64.powi(3) - 1.0);
+    println!("dome volume: {:.4} (exact: {exact:.4})", dome.volume());
+    assert!((dome.volume() - exact).abs() < 0.05 * exact);
+    write_polygon(&dome, "dome.obj");
+
+    // thickening inward makes the dome with the same outer surface
+    let (dome, _) = thicken(&hemisphere, -0.05).unwrap();
+    assert_eq!(dome.shell_condition(), ShellCondition::Closed);
+    assert!(dome.volume() > 0.0);
+
+    // The inward offset of the cube folds the faces near the edges, and the faces beside them
+    // cross each other, which are relaxed together.
+    let cube = subdivided_cube(10);
+    assert_eq!(cube.shell_condition(), ShellCondition::Closed);
+    let naive: Vec<Point3> = cube
+        .positions()
+        .iter()
+        .zip(vertex_normals(&cube))
+        .map(|(p, n)| p - n * 0.4)
+        .collect();
+    assert!(!folded_faces(&cube, &naive).is_empty());
+    let (inner, report) = offset_by_normals(&cube, -0.4).unwrap();
+    println!("inward offset of the cube: {report:?}");
+    assert!(report.folded > 0);
+    assert_eq!(report.unresolved, 0);
+    write_polygon(&inner, "cube-inward-offset.obj");
+
+    // The spheres which already intersect each other are not separated by the relaxation.
+    let mut spheres = sphere.clone();
+    let mut moved = sphere.clone();
+    moved.positions_mut().iter_mut().for_each(|p| p.x += 1.0);
+    spheres.merge(moved);
+    assert!(matches!(
+        offset_by_normals(&spheres, 0.05),
+        Err(OffsetError::SelfIntersecting(_))
+    ));
+
+    // The exact offset of the cube is the rounded box.
+    let (a, r) = (2.0, 0.25);
+    let rounded = offset_by_sdf(&cube, r, 0.05).unwrap();
+    assert_eq!(rounded.shell_condition(), ShellCondition::Closed);
+    let exact = a * a * a + 6.0 * a * a * r + 3.0 * PI * a * r * r + 4.0 / 3.0 * PI * r * r * r;
+    println!(
+        "rounded box volume: {:.4} (exact: {exact:.4})",
+        rounded.volume()
+    );
+    assert!((rounded.volume() - exact).abs() < 0.01 * exact);
+    write_polygon(&rounded, "rounded-box.obj");
+
+    // the open mesh has no inside
+    assert_eq!(
+        offset_by_sdf(&hemisphere, 0.1, 0.1),
+        Err(OffsetError::NotClosed)
+    );
+    // the empty mesh has no inside either
+    assert_eq!(
+        offset_by_sdf(&PolygonMesh::default(), 0.1, 0.1),
+        Err(OffsetError::NotClosed)
+    );
+    // the grid needs the positive cells
+    for cell_size in [0.0, -0.1, f64::NAN, f64::INFINITY] {
+        assert_eq!(
+            offset_by_sdf(&cube, r, cell_size),
+            Err(OffsetError::InvalidCellSize)
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use chapter2::{fixtures, vertex_cache::*};
+use std::collections::HashMap;
+use truck_meshalgo::prelude::*;
+
+/// Output the contents of `polygon` to the file specified by `path`.
+fn write_polygon(polygon: &PolygonMesh, path: &str) {
+    let mut obj = std::fs::File::create(path).unwrap();
+    obj::write(polygon, &mut obj).unwrap();
+}
+
+/// the numbers of the triangles, identified by the positions up to rotation
+fn triangle_counts(mesh: &PolygonMesh) -> HashMap<[[u64; 3]; 3], usize> {
+    let mut counts = HashMap::new();
+    mesh.faces().triangle_iter().for_each(|tri| {
+        let key = tri.map(|v| {
+            let p = mesh.positions()[v.pos];
+            [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]
+        });
+        let key = (0..3)
+            .map(|i| [key[i], key[(i + 1) % 3], key[(i + 2) % 3]])
+            .min()
+            .unwrap();
+        *counts.entry(key).or_insert(0) += 1;
+    });
+    counts
+}
+
+fn main() {
+    let options = CacheOptimization::default();
+
+    // The teapot is stored in order of the patches.
+    let teapot = obj::read(include_bytes!("../../chapter4/src/teapot.obj").as_slice()).unwrap();
+    let (optimized, report) = optimize_for_rendering(&teapot, options);
+    println!("teapot: ACMR {:.3} -> {:.3}", report.before, report.after);
+    assert!(report.after < report.before);
+    assert_eq!(triangle_counts(&optimized), triangle_counts(&teapot));
+    assert!(optimized.face_iter().all(|face| face.len() == 3));
+    write_polygon(&optimized, "teapot-optimized.obj");
+
+    // the sphere whose faces are shuffled, the worst case for the cache
+    let sphere = fixtures::sphere();
+    let mut faces: Vec<Vec<StandardVertex>> =
+        sphere.face_iter().map(|face| face.to_vec()).collect();
+    // shuffle by a linear congruential generator, to be reproducible
+    let mut seed = 12345_u64;
+    (1..faces.len()).rev().for_each(|i| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        faces.swap(i, (seed >> 33) as usize % (i + 1));
+    });
+    let shuffled = PolygonMesh::new(sphere.attributes().clone(), Faces::from_iter(faces));
+    let (optimized, report) = optimize_for_rendering(&shuffled, options);
+    println!(
+        "shuffled sphere: ACMR {:.3} -> {:.3}",
+        report.before, report.after
+    );
+    assert!(report.before > 1.5 && report.after < 1.0);
+    assert_eq!(triangle_counts(&optimized), triangle_counts(&shuffled));
+    // The attributes are in order of the first use.
+    let first_positions: Vec<usize> = optimized
+        .faces()
+        .triangle_iter()
+        .take(2)
+        .flatten()
+        .map(|v| v.pos)
+        .collect();
+    assert_eq!(first_positions[..3], [0, 1, 2]);
+
+    // Sorting the clusters for the overdraw costs a little of the cache efficiency.
+    let cache_only = CacheOptimization {
+        reduce_overdraw: false,
+        ..options
+    };
+    let (_, without_sorting) = optimize_for_rendering(&shuffled, cache_only);
+    println!(
+        "without sorting the clusters: ACMR {:.3}",
+        without_sorting.after
+    );
+    assert!(without_sorting.after <= report.after + 0.1);
+}
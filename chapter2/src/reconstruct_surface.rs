@@ -0,0 +1,81 @@
+use chapter2::{deviation::*, fixtures, reconstruction::*, sampling::*};
+use rand::{rngs::StdRng, SeedableRng};
+use truck_meshalgo::prelude::*;
+
+/// Output the contents of `polygon` to the file specified by `path`.
+fn write_polygon(polygon: &PolygonMesh, path: &str) {
+    let mut obj = std::fs::File::create(path).unwrap();
+    obj::write(polygon, &mut obj).unwrap();
+}
+
+fn main() {
+    // the oriented points sampled on the sphere of section 2.4
+    let sphere = fixtures::sphere();
+    let samples = MeshSampler::new(&sphere).uniform(20000, &mut StdRng::seed_from_u64(0));
+    let points: Vec<Point3> = samples.iter().map(|s| s.point).collect();
+    let normals: Vec<Vector3> = samples.iter().map(|s| s.normal).collect();
+
+    let options = PoissonOptions::default();
+    let reconstructed = poisson_reconstruction(&points, &normals, options).unwrap();
+    assert_eq!(reconstructed.shell_condition(), ShellCondition::Closed);
+    // The tolerance is the size of the cells of the grid.
+    let size = sphere.bounding_box().diagonal().x;
+    let tolerance = size / f64::powi(2.0, options.depth as i32);
+    let distance = hausdorff_distance(&reconstructed, &sphere, 1).unwrap();
+    println!("Hausdorff distance: {distance:.5} (tolerance: {tolerance:.5})");
+    assert!(distance < tolerance);
+    // The faces are oriented outward, so the volume is positive.
+    println!(
+        "volume: {:.5} (sphere: {:.5})",
+        reconstructed.volume(),
+        sphere.volume()
+    );
+    assert!(f64::abs(reconstructed.volume() / sphere.volume() - 1.0) < 0.05);
+    let center = sphere.bounding_box().center();
+    let positions = reconstructed.positions();
+    assert!(reconstructed.face_iter().all(|face| {
+        let [a, b, c] = [0, 1, 2].map(|k| positions[face[k].pos]);
+        (b - a).cross(c - a).dot(a - center) > 0.0
+    }));
+    write_polygon(&reconstructed, "reconstructed-sphere.obj");
+
+    // The finer grid reconstructs the sphere more accurately.
+    let fine = PoissonOptions {
+        depth: 7,
+        ..options
+    };
+    let reconstructed = poisson_reconstruction(&points, &normals, fine).unwrap();
+    let fine_distance = hausdorff_distance(&reconstructed, &sphere, 1).unwrap();
+    println!("Hausdorff distance of depth 7: {fine_distance:.5}");
+    assert!(fine_distance < distance);
+    // The deeper grids are rejected before allocating them.
+    let deep = PoissonOptions {
+        depth: MAX_DEPTH + 1,
+        ..options
+    };
+    let error = poisson_reconstruction(&points, &normals, deep).unwrap_err();
+    assert_eq!(error, ReconstructionError::TooDeep(MAX_DEPTH + 1));
+
+    // The upper hemisphere is closed by the extrapolation, which is trimmed by the density.
+    let (points, normals): (Vec<Point3>, Vec<Vector3>) = samples
+        .iter()
+        .filter(|s| s.point.z > center.z)
+        .map(|s| (s.point, s.normal))
+        .unzip();
+    let closed = poisson_reconstruction(&points, &normals, options).unwrap();
+    assert_eq!(closed.shell_condition(), ShellCondition::Closed);
+    let trim = PoissonOptions {
+        trim: 0.5,
+        ..options
+    };
+    let trimmed = poisson_reconstruction(&points, &normals, trim).unwrap();
+    let lowest = |mesh: &PolygonMesh| mesh.bounding_box().min().z - center.z;
+    println!(
+        "the lowest points of the hemisphere: {:.4} -> {:.4}",
+        lowest(&closed),
+        lowest(&trimmed)
+    );
+    assert!(lowest(&trimmed) > -2.0 * tolerance && lowest(&closed) < -2.0 * tolerance);
+    assert!(trimmed.faces().len() < closed.faces().len());
+    write_polygon(&trimmed, "reconstructed-hemisphere.obj");
+}
@@ -0,0 +1,265 @@
+//! Surface reconstruction from oriented point clouds by the screened Poisson equation.
+//!
+//! The method is the one of M. Kazhdan and H. Hoppe, "Screened Poisson Surface Reconstruction",
+//! solved on a regular grid instead of an octree. The normals are splatted to a vector field,
+//! the implicit function whose gradient fits the field and which vanishes at the points is
+//! solved by the conjugate gradient method, and its level set through the points is extracted
+//! as a closed mesh. The level is the average of the function at the points, as in the paper.
+//! The open point clouds are closed at the boundary of the grid, and the extrapolated
+//! faces can be trimmed by the density of the points.
+
+use crate::isosurface::ScalarGrid;
+use truck_meshalgo::prelude::*;
+
+/// The finest depth of the grid: `2^7` cells on the longest side, about two million cells
+/// in all, each of which has a few vectors of the system.
+pub const MAX_DEPTH: u32 = 7;
+
+/// Errors of the reconstruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReconstructionError {
+    /// There are no points to reconstruct from.
+    NoPoints,
+    /// The numbers of the points and the normals differ.
+    MismatchedNormals,
+    /// The depth is greater than `MAX_DEPTH`.
+    TooDeep(u32),
+}
+
+impl std::fmt::Display for ReconstructionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReconstructionError::NoPoints => f.write_str("there are no points"),
+            ReconstructionError::MismatchedNormals => {
+                f.write_str("the numbers of the points and the normals differ")
+            }
+            ReconstructionError::TooDeep(depth) => {
+                write!(f, "the depth {depth} is greater than {MAX_DEPTH}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReconstructionError {}
+
+/// The options of the reconstruction.
+#[derive(Clone, Copy, Debug)]
+pub struct PoissonOptions {
+    /// The longest side of the bounding box is divided into `2^depth` cells, at most `MAX_DEPTH`.
+    pub depth: u32,
+    /// the weight of the interpolation of the points, zero for the unscreened equation
+    pub screening: f64,
+    /// the number of the cells around the bounding box of the points
+    pub padding: usize,
+    /// The faces whose sample density is less than `trim` times the average one are removed.
+    /// The result is not closed if some faces are trimmed.
+    pub trim: f64,
+    /// the maximum number of the iterations of the conjugate gradient method
+    pub iterations: usize,
+}
+
+impl Default for PoissonOptions {
+    fn default() -> Self {
+        Self {
+            depth: 6,
+            screening: 4.0,
+            padding: 4,
+            trim: 0.0,
+            iterations: 1000,
+        }
+    }
+}
+
+// the relative residual at which the conjugate gradient method stops
+const CG_TOLERANCE: f64 = 1.0e-8;
+
+/// Reconstructs the surface through `points` whose outward normals are `normals`.
+///
+/// The normals do not have to be normalized, and their lengths are used as the confidences.
+/// The faces of the result are oriented outward, along the normals.
+pub fn poisson_reconstruction(
+    points: &[Point3],
+    normals: &[Vector3],
+    options: PoissonOptions,
+) -> Result<PolygonMesh, ReconstructionError> {
+    if points.is_empty() {
+        return Err(ReconstructionError::NoPoints);
+    } else if points.len() != normals.len() {
+        return Err(ReconstructionError::MismatchedNormals);
+    } else if options.depth > MAX_DEPTH {
+        return Err(ReconstructionError::TooDeep(options.depth));
+    }
+    let bdb: BoundingBox<Point3> = points.iter().collect();
+    let size = bdb.diagonal().x.max(bdb.diagonal().y).max(bdb.diagonal().z);
+    let cell_size = match size > 0.0 {
+        true => size / f64::powi(2.0, options.depth as i32),
+        false => 1.0,
+    };
+    let mut grid = ScalarGrid::covering(bdb, cell_size, options.padding.max(1), |_| 0.0);
+    let weights: Vec<[(usize, f64); 8]> = points.iter().map(|p| trilinear(&grid, *p)).collect();
+
+    // The samples are weighted by the inverse of the density, so that the sparse regions
+    // and the dense ones contribute evenly.
+    let len = grid.values.len();
+    let mut density = vec![0.0; len];
+    weights.iter().flatten().for_each(|(i, w)| density[*i] += w);
+    let sample_density: Vec<f64> = weights
+        .iter()
+        .map(|ws| ws.iter().map(|(i, w)| density[*i] * w).sum())
+        .collect();
+    let mut field = vec![Vector3::zero(); len];
+    weights
+        .iter()
+        .zip(normals)
+        .zip(&sample_density)
+        .for_each(|((ws, normal), d)| ws.iter().for_each(|(i, w)| field[*i] += *normal * (w / d)));
+
+    // the normal equation of the least squares of the gradients and the values at the points
+    let strides = [1, grid.dims[0], grid.dims[0] * grid.dims[1]];
+    let edges = |axis: usize| {
+        let dims = grid.dims;
+        (0..len).filter(move |i| {
+            let coord = [i % dims[0], i / dims[0] % dims[1], i / (dims[0] * dims[1])];
+            coord[axis] + 1 < dims[axis]
+        })
+    };
+    let mut rhs = vec![0.0; len];
+    let mut diagonal = vec![0.0; len];
+    for (axis, stride) in strides.iter().enumerate() {
+        edges(axis).for_each(|i| {
+            let j = i + stride;
+            let gradient = (field[i][axis] + field[j][axis]) / 2.0;
+            rhs[j] += gradient;
+            rhs[i] -= gradient;
+            diagonal[i] += 1.0;
+            diagonal[j] += 1.0;
+        });
+    }
+    let screening: Vec<f64> = sample_density
+        .iter()
+        .map(|d| options.screening / d)
+        .collect();
+    weights.iter().zip(&screening).for_each(|(ws, alpha)| {
+        ws.iter().for_each(|(i, w)| diagonal[*i] += alpha * w * w);
+    });
+    let apply = |x: &[f64], y: &mut [f64]| {
+        // the graph Laplacian, the product of the transposed difference and the difference
+        y.iter_mut().for_each(|y| *y = 0.0);
+        for (axis, stride) in strides.iter().enumerate() {
+            edges(axis).for_each(|i| {
+                let j = i + stride;
+                let d = x[j] - x[i];
+                y[j] += d;
+                y[i] -= d;
+            });
+        }
+        weights.iter().zip(&screening).for_each(|(ws, alpha)| {
+            let value: f64 = ws.iter().map(|(i, w)| x[*i] * w).sum();
+            ws.iter().for_each(|(i, w)| y[*i] += alpha * w * value);
+        });
+    };
+    grid.values = conjugate_gradient(apply, &rhs, &diagonal, options.iterations);
+    // the level through the points, on average
+    let level = weights
+        .iter()
+        .map(|ws| ws.iter().map(|(i, w)| grid.values[*i] * w).sum::<f64>())
+        .sum::<f64>()
+        / points.len() as f64;
+    // The open point clouds may have the level set reaching the boundary of the grid,
+    // so the outside value is set on the boundary to close the surface.
+    let outside = grid.values.iter().copied().fold(0.0, f64::max);
+    let dims = grid.dims;
+    grid.values.iter_mut().enumerate().for_each(|(i, value)| {
+        let coord = [i % dims[0], i / dims[0] % dims[1], i / (dims[0] * dims[1])];
+        if (0..3).any(|a| coord[a] == 0 || coord[a] + 1 == dims[a]) {
+            *value = f64::max(*value, outside);
+        }
+    });
+
+    let mut mesh = grid.isosurface(level);
+    if options.trim > 0.0 {
+        let average = sample_density.iter().sum::<f64>() / sample_density.len() as f64;
+        let threshold = options.trim * average;
+        let positions = mesh.positions();
+        let faces: Vec<Vec<StandardVertex>> = mesh
+            .face_iter()
+            .filter(|face| {
+                let sum: f64 = face
+                    .iter()
+                    .map(|v| {
+                        let ws = trilinear(&grid, positions[v.pos]);
+                        ws.iter().map(|(i, w)| density[*i] * w).sum::<f64>()
+                    })
+                    .sum();
+                sum / face.len() as f64 >= threshold
+            })
+            .map(<[_]>::to_vec)
+            .collect();
+        mesh = PolygonMesh::new(mesh.attributes().clone(), Faces::from_iter(faces));
+        mesh.remove_unused_attrs();
+    }
+    Ok(mesh)
+}
+
+// Returns the indices of the vertices of the cell containing `point` and their trilinear weights.
+// The points outside the grid are clamped.
+fn trilinear(grid: &ScalarGrid, point: Point3) -> [(usize, f64); 8] {
+    let local = (point - grid.origin) / grid.cell_size;
+    let (base, frac): (Vec<usize>, Vec<f64>) = (0..3)
+        .map(|a| {
+            let x = local[a].clamp(0.0, (grid.dims[a] - 1) as f64);
+            let i = usize::min(x.floor() as usize, grid.dims[a].saturating_sub(2));
+            (i, x - i as f64)
+        })
+        .unzip();
+    std::array::from_fn(|corner| {
+        let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+        let index = grid.index([0, 1, 2].map(|a| base[a] + offset[a]));
+        let weight = (0..3)
+            .map(|a| match offset[a] {
+                0 => 1.0 - frac[a],
+                _ => frac[a],
+            })
+            .product();
+        (index, weight)
+    })
+}
+
+// Solves the positive definite system `apply(x) = b` by the conjugate gradient method
+// preconditioned by the diagonal.
+fn conjugate_gradient(
+    apply: impl Fn(&[f64], &mut [f64]),
+    b: &[f64],
+    diagonal: &[f64],
+    iterations: usize,
+) -> Vec<f64> {
+    let dot = |x: &[f64], y: &[f64]| x.iter().zip(y).map(|(x, y)| x * y).sum::<f64>();
+    let precondition = |r: &[f64]| -> Vec<f64> {
+        r.iter()
+            .zip(diagonal)
+            .map(|(r, d)| if *d > 0.0 { r / d } else { *r })
+            .collect()
+    };
+    let mut x = vec![0.0; b.len()];
+    let mut r = b.to_vec();
+    let mut z = precondition(&r);
+    let mut p = z.clone();
+    let mut ap = vec![0.0; b.len()];
+    let mut rz = dot(&r, &z);
+    let threshold = CG_TOLERANCE * CG_TOLERANCE * dot(b, b);
+    for _ in 0..iterations {
+        if dot(&r, &r) <= threshold {
+            break;
+        }
+        apply(&p, &mut ap);
+        let alpha = rz / dot(&p, &ap);
+        x.iter_mut().zip(&p).for_each(|(x, p)| *x += alpha * p);
+        r.iter_mut().zip(&ap).for_each(|(r, ap)| *r -= alpha * ap);
+        z = precondition(&r);
+        let rz_next = dot(&r, &z);
+        let beta = rz_next / rz;
+        rz = rz_next;
+        p.iter_mut().zip(&z).for_each(|(p, z)| *p = z + beta * *p);
+    }
+    x
+}
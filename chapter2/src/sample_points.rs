@@ -0,0 +1,84 @@
+use chapter2::{bvh::TriangleTree, fixtures, sampling::*};
+use rand::{rngs::StdRng, SeedableRng};
+use truck_meshalgo::prelude::*;
+
+fn main() {
+    // the sphere of section 2.4, whose normals are the ones of the exact sphere
+    let sphere = fixtures::sphere();
+    let sampler = MeshSampler::new(&sphere);
+    let tree = TriangleTree::from_mesh(&sphere);
+    let center = sphere.bounding_box().center();
+
+    // The same seed reproduces the same samples.
+    let samples = sampler.uniform(10000, &mut StdRng::seed_from_u64(0));
+    let again = sampler.uniform(10000, &mut StdRng::seed_from_u64(0));
+    assert!(samples
+        .iter()
+        .zip(&again)
+        .all(|(s, t)| s.point == t.point && s.face == t.face));
+    // The samples are on the faces from which they come, with the normals outward.
+    let faces: Vec<Vec<StandardVertex>> = sphere.face_iter().map(<[_]>::to_vec).collect();
+    samples.iter().for_each(|s| {
+        assert!(tree.nearest(s.point).unwrap().distance < 1.0e-9);
+        let face = &faces[s.face];
+        let (a, b) = (
+            sphere.positions()[face[0].pos],
+            sphere.positions()[face[2].pos],
+        );
+        let diagonal = a.distance(b);
+        assert!(s.point.distance(a) <= diagonal && s.point.distance(b) <= diagonal);
+        assert!(s.normal.dot((s.point - center).normalize()) > 0.99);
+    });
+    // Half of the area is above the equator.
+    let upper = samples.iter().filter(|s| s.point.z > center.z).count();
+    println!("{upper} of {} uniform samples are upper", samples.len());
+    assert!(f64::abs(upper as f64 / samples.len() as f64 - 0.5) < 0.02);
+
+    // Poisson-disk samples are separated by the radius, and cover the surface.
+    let radius = 0.1;
+    let disks = sampler.poisson_disk(radius, &mut StdRng::seed_from_u64(1));
+    println!("{} Poisson-disk samples of radius {radius}", disks.len());
+    let min_distance = disks
+        .iter()
+        .enumerate()
+        .flat_map(|(i, s)| disks[i + 1..].iter().map(|t| s.point.distance(t.point)))
+        .fold(f64::INFINITY, f64::min);
+    assert!(min_distance >= radius);
+    let disk_tree = TriangleTree::new(disks.iter().map(|s| [s.point; 3]).collect());
+    let max_gap = samples
+        .iter()
+        .map(|s| disk_tree.nearest(s.point).unwrap().distance)
+        .fold(0.0, f64::max);
+    println!("the largest gap: {max_gap:.4}");
+    assert!(max_gap < 2.0 * radius);
+
+    // The zero normals of the vertices, e.g. the sums of the opposite normals of the faces,
+    // fall back to the normal of the face.
+    let attrs = StandardAttributes {
+        positions: vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ],
+        normals: vec![Vector3::zero()],
+        ..Default::default()
+    };
+    let vertex = |pos: usize| StandardVertex {
+        pos,
+        uv: None,
+        nor: Some(0),
+    };
+    let faces = Faces::from_iter([[vertex(0), vertex(1), vertex(2)]]);
+    let triangle = PolygonMesh::new(attrs, faces);
+    let samples = MeshSampler::new(&triangle).uniform(100, &mut StdRng::seed_from_u64(3));
+    assert!(samples.iter().all(|s| s.normal == Vector3::unit_z()));
+
+    // the teapot, whose normals are interpolated
+    let teapot = obj::read(include_bytes!("../../chapter4/src/teapot.obj").as_slice()).unwrap();
+    let samples = MeshSampler::new(&teapot).poisson_disk(0.05, &mut StdRng::seed_from_u64(2));
+    println!("{} samples on the teapot", samples.len());
+    let mut obj = std::fs::File::create("teapot-samples.obj").unwrap();
+    write_obj_points(&samples, &mut obj).unwrap();
+    let mut ply = std::fs::File::create("teapot-samples.ply").unwrap();
+    write_ply_points(&samples, &mut ply).unwrap();
+}
@@ -0,0 +1,172 @@
+//! Random point sampling on the surfaces of polygon meshes.
+//!
+//! The uniform sampling chooses the triangles in proportion to their areas, and the
+//! Poisson-disk sampling throws the uniform samples one by one as darts, and keeps the ones
+//! which are not closer than the given radius to the kept ones. Both take the random number
+//! generator as an argument, so a seeded generator reproduces the same samples.
+
+use rand::Rng;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use truck_meshalgo::prelude::*;
+
+// the number of the uniform candidates per disk of the Poisson-disk sampling
+const CANDIDATES_PER_DISK: f64 = 30.0;
+
+/// A point on the surface.
+#[derive(Clone, Copy, Debug)]
+pub struct SurfaceSample {
+    /// the position
+    pub point: Point3,
+    /// the unit normal, interpolated from the normals of the vertices if the face has them
+    pub normal: Vector3,
+    /// the index of the face in the order of `face_iter`
+    pub face: usize,
+}
+
+/// Sampler of the points on the surface of a polygon mesh.
+#[derive(Clone, Debug)]
+pub struct MeshSampler<'a> {
+    mesh: &'a PolygonMesh,
+    // the fan triangles of the faces and the indices of the faces
+    triangles: Vec<([StandardVertex; 3], usize)>,
+    // the cumulative sums of the areas of the triangles
+    cumulative_areas: Vec<f64>,
+}
+
+impl<'a> MeshSampler<'a> {
+    /// Creates the sampler of `mesh`. The polygons are divided into fans of triangles.
+    pub fn new(mesh: &'a PolygonMesh) -> Self {
+        let triangles: Vec<([StandardVertex; 3], usize)> = mesh
+            .face_iter()
+            .enumerate()
+            .flat_map(|(i, face)| {
+                (2..face.len()).map(move |k| ([face[0], face[k - 1], face[k]], i))
+            })
+            .collect();
+        let mut area = 0.0;
+        let cumulative_areas = triangles
+            .iter()
+            .map(|(tri, _)| {
+                let [a, b, c] = tri.map(|v| mesh.positions()[v.pos]);
+                area += (b - a).cross(c - a).magnitude() / 2.0;
+                area
+            })
+            .collect();
+        Self {
+            mesh,
+            triangles,
+            cumulative_areas,
+        }
+    }
+
+    /// Returns the area of the surface.
+    pub fn area(&self) -> f64 { self.cumulative_areas.last().copied().unwrap_or(0.0) }
+
+    /// Returns `count` points distributed uniformly with respect to the area.
+    /// Returns no points if the surface has no area.
+    pub fn uniform<R: Rng>(&self, count: usize, rng: &mut R) -> Vec<SurfaceSample> {
+        if self.area() <= 0.0 {
+            return Vec::new();
+        }
+        (0..count).map(|_| self.sample(rng)).collect()
+    }
+
+    /// Returns the samples no two of which are closer than `radius` in the space.
+    ///
+    /// The candidates are uniform samples, drawn one at a time and accepted if they are far
+    /// from the accepted ones, so only the accepted samples are stored. The result is not
+    /// strictly maximal, but the gaps are small enough for the blue noise. Returns no points
+    /// if `radius` is not positive or the surface has no area.
+    pub fn poisson_disk<R: Rng>(&self, radius: f64, rng: &mut R) -> Vec<SurfaceSample> {
+        if radius <= 0.0 || self.area() <= 0.0 {
+            return Vec::new();
+        }
+        let disk = std::f64::consts::PI * radius * radius / 4.0;
+        let count = (CANDIDATES_PER_DISK * self.area() / disk).ceil() as usize;
+        // the grid of the cells of `radius`, containing the accepted samples
+        let cell = |p: Point3| [p.x, p.y, p.z].map(|x| (x / radius).floor() as i64);
+        let mut grid = HashMap::<[i64; 3], Vec<usize>>::new();
+        let mut samples = Vec::<SurfaceSample>::new();
+        (0..count).for_each(|_| {
+            let sample = self.sample(rng);
+            let [i, j, k] = cell(sample.point);
+            let neighbors = (-1..=1).flat_map(|di| {
+                (-1..=1).flat_map(move |dj| (-1..=1).map(move |dk| [i + di, j + dj, k + dk]))
+            });
+            let too_close = neighbors
+                .filter_map(|key| grid.get(&key))
+                .flatten()
+                .any(|s| samples[*s].point.distance2(sample.point) < radius * radius);
+            if !too_close {
+                grid.entry([i, j, k]).or_default().push(samples.len());
+                samples.push(sample);
+            }
+        });
+        samples
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R) -> SurfaceSample {
+        let target = rng.gen_range(0.0..self.area());
+        let idx = self
+            .cumulative_areas
+            .partition_point(|area| *area <= target);
+        let (tri, face) = self.triangles[usize::min(idx, self.triangles.len() - 1)];
+        // the uniform barycentric coordinates by folding the unit square
+        let (mut s, mut t) = (rng.gen::<f64>(), rng.gen::<f64>());
+        if s + t > 1.0 {
+            (s, t) = (1.0 - s, 1.0 - t);
+        }
+        let weights = [1.0 - s - t, s, t];
+        let [a, b, c] = tri.map(|v| self.mesh.positions()[v.pos]);
+        let point = Point3::from_vec(a.to_vec() * weights[0] + b.to_vec() * s + c.to_vec() * t);
+        let normals = tri.map(|v| v.nor.map(|i| self.mesh.normals()[i]));
+        let face_normal = (b - a).cross(c - a).normalize();
+        // The opposite normals of the vertices may cancel out each other.
+        let normal = match normals {
+            [Some(n0), Some(n1), Some(n2)] => n0 * weights[0] + n1 * s + n2 * t,
+            _ => face_normal,
+        };
+        SurfaceSample {
+            point,
+            normal: match normal.so_small() {
+                true => face_normal,
+                false => normal.normalize(),
+            },
+            face,
+        }
+    }
+}
+
+/// Writes the samples as an OBJ point cloud of the vertices and the normals.
+pub fn write_obj_points<W: Write>(samples: &[SurfaceSample], mut writer: W) -> io::Result<()> {
+    samples.iter().try_for_each(|s| {
+        let (p, n) = (s.point, s.normal);
+        writeln!(writer, "v {} {} {}", p.x, p.y, p.z)?;
+        writeln!(writer, "vn {} {} {}", n.x, n.y, n.z)
+    })
+}
+
+/// Writes the samples as an ASCII PLY point cloud.
+/// The indices of the faces are written as the vertex property `face`.
+pub fn write_ply_points<W: Write>(samples: &[SurfaceSample], mut writer: W) -> io::Result<()> {
+    writeln!(writer, "ply\nformat ascii 1.0")?;
+    writeln!(writer, "element vertex {}", samples.len())?;
+    writeln!(
+        writer,
+        "property double x\nproperty double y\nproperty double z"
+    )?;
+    writeln!(
+        writer,
+        "property double nx\nproperty double ny\nproperty double nz"
+    )?;
+    writeln!(writer, "property int face\nend_header")?;
+    samples.iter().try_for_each(|s| {
+        let (p, n) = (s.point, s.normal);
+        writeln!(
+            writer,
+            "{} {} {} {} {} {} {}",
+            p.x, p.y, p.z, n.x, n.y, n.z, s.face
+        )
+    })
+}
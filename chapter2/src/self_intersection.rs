@@ -0,0 +1,205 @@
+//! Detection of the self-intersections of triangle meshes.
+//!
+//! The candidates are the pairs of the triangles whose bounding boxes overlap, and the sides
+//! of the planes are decided by the exact predicates of the crate `robust`.
+
+use crate::bvh::TriangleTree;
+use robust::{Coord, Coord3D};
+use std::collections::BTreeSet;
+use truck_meshalgo::prelude::*;
+
+/// The intersection of two triangles.
+#[derive(Clone, Copy, Debug)]
+pub enum Intersection {
+    /// The triangles cross each other along the segment, which may be a point if they touch.
+    Segment(Point3, Point3),
+    /// The triangles are on the same plane and overlap.
+    Coplanar,
+}
+
+/// A pair of the intersecting triangles.
+#[derive(Clone, Copy, Debug)]
+pub struct SelfIntersection {
+    /// the indices of the triangles in the order of `mesh.faces().triangle_iter()`, the smaller first
+    pub triangles: (usize, usize),
+    /// the intersection of the triangles
+    pub intersection: Intersection,
+}
+
+/// Returns all pairs of the intersecting triangles of `mesh` which do not share positions.
+///
+/// The polygons are divided into triangles. The adjacency is decided by the indices of the
+/// positions, so the shared positions should be put together in advance.
+pub fn self_intersections(mesh: &PolygonMesh) -> Vec<SelfIntersection> {
+    let tree = TriangleTree::from_mesh(mesh);
+    let indices: Vec<[usize; 3]> = mesh
+        .faces()
+        .triangle_iter()
+        .map(|tri| tri.map(|v| v.pos))
+        .collect();
+    let mut pairs: Vec<(usize, usize)> = tree
+        .overlapping_pairs(&tree)
+        .into_iter()
+        .filter(|(s, t)| s < t && indices[*s].iter().all(|i| !indices[*t].contains(i)))
+        .collect();
+    pairs.sort();
+    let triangles = tree.triangles();
+    pairs
+        .into_iter()
+        .filter_map(|(s, t)| {
+            let intersection = triangle_intersection(triangles[s], triangles[t])?;
+            Some(SelfIntersection {
+                triangles: (s, t),
+                intersection,
+            })
+        })
+        .collect()
+}
+
+/// Returns the mesh of the triangles in `intersections`, for the inspection.
+/// The unused attributes are removed.
+pub fn offending_triangles(mesh: &PolygonMesh, intersections: &[SelfIntersection]) -> PolygonMesh {
+    let offending: BTreeSet<usize> = intersections
+        .iter()
+        .flat_map(|x| [x.triangles.0, x.triangles.1])
+        .collect();
+    let faces = mesh
+        .faces()
+        .triangle_iter()
+        .enumerate()
+        .filter(|(i, _)| offending.contains(i))
+        .map(|(_, tri)| tri);
+    let mut extracted = PolygonMesh::new(mesh.attributes().clone(), Faces::from_iter(faces));
+    extracted.remove_unused_attrs();
+    extracted
+}
+
+fn orient3d(a: Point3, b: Point3, c: Point3, d: Point3) -> f64 {
+    let coord = |p: Point3| Coord3D {
+        x: p.x,
+        y: p.y,
+        z: p.z,
+    };
+    robust::orient3d(coord(a), coord(b), coord(c), coord(d))
+}
+
+fn orient2d(a: Point2, b: Point2, c: Point2) -> f64 {
+    let coord = |p: Point2| Coord { x: p.x, y: p.y };
+    robust::orient2d(coord(a), coord(b), coord(c))
+}
+
+/// Returns the intersection of the triangles `tri0` and `tri1`, or `None` if they are disjoint.
+///
+/// The intersection segment is the overlap of the segments in which each triangle
+/// meets the plane of the other, by the method of T. Möller.
+pub fn triangle_intersection(tri0: [Point3; 3], tri1: [Point3; 3]) -> Option<Intersection> {
+    let [a, b, c] = tri1;
+    let sides0 = tri0.map(|p| orient3d(a, b, c, p));
+    let [a, b, c] = tri0;
+    let sides1 = tri1.map(|p| orient3d(a, b, c, p));
+    let separated =
+        |sides: [f64; 3]| sides.iter().all(|s| *s > 0.0) || sides.iter().all(|s| *s < 0.0);
+    if separated(sides0) || separated(sides1) {
+        return None;
+    }
+    if sides0.iter().all(|s| *s == 0.0) {
+        return match coplanar_overlap(tri0, tri1) {
+            true => Some(Intersection::Coplanar),
+            false => None,
+        };
+    }
+    // Both segments are on the line of the planes, so they are compared by the parameter on it.
+    let direction = (tri0[1] - tri0[0])
+        .cross(tri0[2] - tri0[0])
+        .cross((tri1[1] - tri1[0]).cross(tri1[2] - tri1[0]));
+    let segment0 = plane_segment(tri0, sides0, direction)?;
+    let segment1 = plane_segment(tri1, sides1, direction)?;
+    // the later start and the earlier end
+    let start = match segment0.0 .0 >= segment1.0 .0 {
+        true => segment0.0,
+        false => segment1.0,
+    };
+    let end = match segment0.1 .0 <= segment1.1 .0 {
+        true => segment0.1,
+        false => segment1.1,
+    };
+    match start.0 <= end.0 {
+        true => Some(Intersection::Segment(start.1, end.1)),
+        false => None,
+    }
+}
+
+// whether the signs are strictly opposite, without the underflow of the product
+fn opposite(a: f64, b: f64) -> bool { (a > 0.0 && b < 0.0) || (a < 0.0 && b > 0.0) }
+
+// an endpoint of the segment with its parameter along the line of the planes
+type Endpoint = (f64, Point3);
+
+// The segment in which the triangle meets the plane, whose sides of the vertices are `sides`.
+// The endpoints are in ascending order of the parameters along `direction`.
+fn plane_segment(
+    tri: [Point3; 3],
+    sides: [f64; 3],
+    direction: Vector3,
+) -> Option<(Endpoint, Endpoint)> {
+    let points = (0..3).flat_map(|i| {
+        let j = (i + 1) % 3;
+        let (si, sj) = (sides[i], sides[j]);
+        let vertex = (si == 0.0).then_some(tri[i]);
+        // The sides are exact, but the crossing point is interpolated by the floating points.
+        let crossing = opposite(si, sj).then(|| tri[i] + (tri[j] - tri[i]) * (si / (si - sj)));
+        vertex.into_iter().chain(crossing)
+    });
+    let mut points = points.map(|p| (p.to_vec().dot(direction), p));
+    let first = points.next()?;
+    Some(points.fold((first, first), |(min, max), p| {
+        (
+            if p.0 < min.0 { p } else { min },
+            if p.0 > max.0 { p } else { max },
+        )
+    }))
+}
+
+// whether the coplanar triangles overlap
+fn coplanar_overlap(tri0: [Point3; 3], tri1: [Point3; 3]) -> bool {
+    // project onto the coordinate plane which is the most parallel to the triangles
+    let normal = (tri0[1] - tri0[0]).cross(tri0[2] - tri0[0]);
+    let axis = match (normal.x.abs(), normal.y.abs(), normal.z.abs()) {
+        (x, y, z) if x >= y && x >= z => 0,
+        (_, y, z) if y >= z => 1,
+        _ => 2,
+    };
+    let project = |p: Point3| match axis {
+        0 => Point2::new(p.y, p.z),
+        1 => Point2::new(p.z, p.x),
+        _ => Point2::new(p.x, p.y),
+    };
+    let (t0, t1) = (tri0.map(project), tri1.map(project));
+    let edges_cross = (0..3)
+        .any(|i| (0..3).any(|j| segments_cross(t0[i], t0[(i + 1) % 3], t1[j], t1[(j + 1) % 3])));
+    edges_cross || contains(t0, t1[0]) || contains(t1, t0[0])
+}
+
+// whether the closed segments `pq` and `rs` have a common point
+fn segments_cross(p: Point2, q: Point2, r: Point2, s: Point2) -> bool {
+    let (d0, d1) = (orient2d(p, q, r), orient2d(p, q, s));
+    let (d2, d3) = (orient2d(r, s, p), orient2d(r, s, q));
+    if opposite(d0, d1) && opposite(d2, d3) {
+        return true;
+    }
+    // the collinear cases: some endpoint is on the other segment
+    let on = |a: Point2, b: Point2, x: Point2| {
+        orient2d(a, b, x) == 0.0
+            && f64::min(a.x, b.x) <= x.x
+            && x.x <= f64::max(a.x, b.x)
+            && f64::min(a.y, b.y) <= x.y
+            && x.y <= f64::max(a.y, b.y)
+    };
+    on(p, q, r) || on(p, q, s) || on(r, s, p) || on(r, s, q)
+}
+
+// whether the closed triangle contains `p`, in either orientation
+fn contains([a, b, c]: [Point2; 3], p: Point2) -> bool {
+    let sides = [orient2d(a, b, p), orient2d(b, c, p), orient2d(c, a, p)];
+    sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0)
+}
@@ -0,0 +1,97 @@
+use chapter2::{fixtures::sphere, slicing::*};
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+
+fn main() {
+    let slicer = Slicer::new(Vector3::unit_z());
+
+    // The sections of the sphere are single disks.
+    let layers = slicer.slice_layers(&sphere(), 0.1).unwrap();
+    assert_eq!(layers.len(), 20);
+    layers.iter().for_each(|layer| {
+        assert!(layer.open_contours.is_empty());
+        assert_eq!(layer.contours.len(), 1);
+        let contour = &layer.contours[0];
+        assert_eq!(contour.kind, ContourKind::Outer);
+        // The inscribed polygon is a little smaller than the exact section.
+        let exact = PI * (1.0 - layer.height * layer.height);
+        let area = signed_area(&contour.points);
+        assert!(0.8 * exact < area && area < exact);
+        // The vertices of the section are on the sphere.
+        contour.points.iter().for_each(|pt| {
+            let radius = slicer.position(*pt, layer.height).to_vec().magnitude();
+            assert!(0.95 < radius && radius < 1.0 + TOLERANCE);
+        });
+    });
+
+    // A hollow ball: the inner sphere is inverted and has the half radius.
+    let mut hollow_ball = sphere();
+    let mut inner = sphere().inverse();
+    inner.positions_mut().iter_mut().for_each(|p| *p *= 0.5);
+    hollow_ball.merge(inner);
+    let layer = &slicer.slice(&hollow_ball, &[0.0]).unwrap()[0];
+    assert_eq!(layer.contours.len(), 2);
+    let (outer, hole) = match layer.contours[0].kind {
+        ContourKind::Outer => (0, 1),
+        ContourKind::Hole => (1, 0),
+    };
+    assert_eq!(layer.contours[hole].kind, ContourKind::Hole);
+    assert_eq!(layer.contours[hole].parent, Some(outer));
+    assert!(signed_area(&layer.contours[hole].points) < 0.0);
+    let file = std::fs::File::create("hollow-ball-section.svg").unwrap();
+    write_svg(layer, file).unwrap();
+
+    // The single layer is printed at its own thickness, not at the bottom.
+    let setting = GcodeSetting {
+        layer_height: 0.1,
+        ..Default::default()
+    };
+    let mut gcode = Vec::new();
+    write_gcode(std::slice::from_ref(layer), setting, &mut gcode).unwrap();
+    let gcode = String::from_utf8(gcode).unwrap();
+    assert!(gcode.lines().any(|line| line.starts_with("G0 Z0.1000 ")));
+    assert!(gcode.lines().all(|line| !line.starts_with("G0 Z0.0000 ")));
+
+    // Three triangles share the edge from the position 0 to 1, which crosses the plane.
+    let fin = PolygonMesh::new(
+        StandardAttributes {
+            positions: vec![
+                Point3::new(0.0, 0.0, -1.0),
+                Point3::new(0.0, 0.0, 1.0),
+                Point3::new(1.0, 0.0, 0.5),
+                Point3::new(0.0, 1.0, 0.5),
+                Point3::new(-1.0, 0.0, 0.5),
+            ],
+            ..Default::default()
+        },
+        Faces::from_iter([[0, 1, 2], [1, 0, 3], [0, 1, 4]]),
+    );
+    assert_eq!(
+        slicer.slice(&fin, &[0.0]).err(),
+        Some(SliceError::NonManifoldEdge {
+            height: 0.0,
+            edge: (0, 1)
+        })
+    );
+
+    // The teapot is y-up, and is not closed at the rim of the body.
+    let teapot = obj::read(include_bytes!("../../chapter4/src/teapot.obj").as_slice()).unwrap();
+    let layers = Slicer::new(Vector3::unit_y())
+        .slice_layers(&teapot, 0.1)
+        .unwrap();
+    layers
+        .iter()
+        .enumerate()
+        .filter(|(_, layer)| !layer.open_contours.is_empty())
+        .for_each(|(i, layer)| {
+            println!(
+                "layer {i}: {} open contour(s) at height {:.2}",
+                layer.open_contours.len(),
+                layer.height
+            )
+        });
+    let file = std::fs::File::create("teapot-section.svg").unwrap();
+    write_svg(&layers[layers.len() / 2], file).unwrap();
+    let file = std::fs::File::create("teapot.gcode").unwrap();
+    write_gcode(&layers, setting, file).unwrap();
+}
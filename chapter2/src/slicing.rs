@@ -0,0 +1,428 @@
+//! Planar slicing of polygon meshes into layered contours, for additive manufacturing.
+//!
+//! A mesh is cut by a family of parallel planes. The section of each plane is a set of
+//! closed loops, classified into outer loops and holes by their nesting, and open
+//! contours, which appear where the mesh is not closed. The sections through the
+//! non-manifold edges cannot be connected uniquely, so they are reported as errors.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use truck_meshalgo::prelude::*;
+
+/// The errors of slicing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SliceError {
+    /// The plane at `height` crosses the edge between the positions, which is shared by more
+    /// than two faces or by two faces of the opposite orientations.
+    NonManifoldEdge { height: f64, edge: (usize, usize) },
+}
+
+impl std::fmt::Display for SliceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SliceError::NonManifoldEdge { height, edge } => write!(
+                f,
+                "the plane at {height} crosses the non-manifold edge {edge:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SliceError {}
+
+/// The classification of closed loops by their nesting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContourKind {
+    /// A loop contained in an even number of loops. It is counterclockwise.
+    Outer,
+    /// A loop contained in an odd number of loops. It is clockwise.
+    Hole,
+}
+
+/// A closed loop of a section. The last point is connected to the first point.
+#[derive(Clone, Debug)]
+pub struct Contour {
+    /// the vertices of the loop in the coordinate of the plane
+    pub points: Vec<Point2>,
+    /// outer or hole
+    pub kind: ContourKind,
+    /// the index of the innermost loop containing this loop
+    pub parent: Option<usize>,
+}
+
+/// The section of a mesh by one plane.
+#[derive(Clone, Debug)]
+pub struct Layer {
+    /// the signed distance of the plane from the origin along the slicing direction
+    pub height: f64,
+    /// closed loops in the section
+    pub contours: Vec<Contour>,
+    /// contours which cannot be closed since the mesh has boundaries
+    pub open_contours: Vec<Vec<Point2>>,
+}
+
+/// Slices meshes by the planes perpendicular to `direction`.
+///
+/// The section of the plane at height `h` is described by the 2D coordinates `(x, y)`
+/// whose 3D position is `x * axes[0] + y * axes[1] + h * direction`.
+#[derive(Clone, Copy, Debug)]
+pub struct Slicer {
+    direction: Vector3,
+    axes: [Vector3; 2],
+}
+
+impl Slicer {
+    /// Creates the slicer whose planes are perpendicular to `direction`.
+    /// # Panics
+    /// Panics if `direction` is zero.
+    pub fn new(direction: Vector3) -> Self {
+        assert!(
+            !direction.so_small(),
+            "the slicing direction must not be zero."
+        );
+        let n = direction.normalize();
+        let e = match n.x.abs() < 0.9 {
+            true => Vector3::unit_x(),
+            false => Vector3::unit_y(),
+        };
+        let x = (e - n * n.dot(e)).normalize();
+        let y = n.cross(x);
+        Self {
+            direction: n,
+            axes: [x, y],
+        }
+    }
+
+    /// Returns the 3D position of the point `pt` on the plane at `height`.
+    pub fn position(&self, pt: Point2, height: f64) -> Point3 {
+        Point3::from_vec(self.axes[0] * pt.x + self.axes[1] * pt.y + self.direction * height)
+    }
+
+    /// Slices `mesh` by layers with thickness `layer_height`.
+    /// The planes pass through the middles of the layers, from the bottom of `mesh`.
+    pub fn slice_layers(
+        &self,
+        mesh: &PolygonMesh,
+        layer_height: f64,
+    ) -> Result<Vec<Layer>, SliceError> {
+        assert!(layer_height > 0.0, "the layer height must be positive.");
+        let heights = mesh
+            .positions()
+            .iter()
+            .map(|p| p.to_vec().dot(self.direction));
+        let (min, max) = heights.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), h| {
+            (f64::min(min, h), f64::max(max, h))
+        });
+        let heights: Vec<f64> = (0..)
+            .map(|i| min + (i as f64 + 0.5) * layer_height)
+            .take_while(|h| *h < max)
+            .collect();
+        self.slice(mesh, &heights)
+    }
+
+    /// Slices `mesh` by the planes at `heights`.
+    /// Returns an error if a plane crosses a non-manifold edge.
+    /// # Remarks
+    /// Contours are connected through the indices of positions, so apply
+    /// `put_together_same_attrs` beforehand to the meshes whose faces do not share positions.
+    pub fn slice(&self, mesh: &PolygonMesh, heights: &[f64]) -> Result<Vec<Layer>, SliceError> {
+        let positions = mesh.positions();
+        let triangles: Vec<[usize; 3]> = mesh
+            .faces()
+            .triangle_iter()
+            .map(|tri| tri.map(|v| v.pos))
+            .collect();
+        heights
+            .iter()
+            .map(|height| self.slice_one(positions, &triangles, *height))
+            .collect()
+    }
+
+    fn slice_one(
+        &self,
+        positions: &[Point3],
+        triangles: &[[usize; 3]],
+        height: f64,
+    ) -> Result<Layer, SliceError> {
+        // Vertices on the plane are regarded as above the plane, which keeps the topology consistent.
+        let dist = |i: usize| positions[i].to_vec().dot(self.direction) - height;
+        let crossing = |i: usize, j: usize| {
+            let (di, dj) = (dist(i), dist(j));
+            let p = positions[i] + (positions[j] - positions[i]) * (di / (di - dj));
+            Point2::new(p.to_vec().dot(self.axes[0]), p.to_vec().dot(self.axes[1]))
+        };
+        let edge_key = |i: usize, j: usize| (usize::min(i, j), usize::max(i, j));
+        // The segment runs from the edge going downward to the edge going upward,
+        // so that the inside of the mesh is on the left of the segment.
+        let mut segments = HashMap::<(usize, usize), ((usize, usize), Point2)>::new();
+        let mut points = HashMap::<(usize, usize), Point2>::new();
+        let mut ends = HashSet::<(usize, usize)>::new();
+        for tri in triangles {
+            let above = tri.map(|i| dist(i) >= 0.0);
+            let (mut down, mut up) = (None, None);
+            (0..3).for_each(|k| {
+                let (i, j) = (tri[k], tri[(k + 1) % 3]);
+                match (above[k], above[(k + 1) % 3]) {
+                    (true, false) => down = Some((i, j)),
+                    (false, true) => up = Some((i, j)),
+                    _ => {}
+                }
+            });
+            if let (Some((i0, j0)), Some((i1, j1))) = (down, up) {
+                let (start, end) = (edge_key(i0, j0), edge_key(i1, j1));
+                points.entry(start).or_insert_with(|| crossing(i0, j0));
+                let end_pt = *points.entry(end).or_insert_with(|| crossing(i1, j1));
+                // Each edge of a manifold is the start of one segment and the end of another.
+                let edge = match segments.insert(start, (end, end_pt)).is_some() {
+                    true => Some(start),
+                    false => Some(end).filter(|end| !ends.insert(*end)),
+                };
+                if let Some(edge) = edge {
+                    return Err(SliceError::NonManifoldEdge { height, edge });
+                }
+            }
+        }
+        let (loops, open_contours) = connect_segments(segments, &points);
+        Ok(Layer {
+            height,
+            contours: classify_loops(loops),
+            open_contours,
+        })
+    }
+}
+
+/// Connects segments into closed loops and open contours.
+fn connect_segments(
+    mut segments: HashMap<(usize, usize), ((usize, usize), Point2)>,
+    points: &HashMap<(usize, usize), Point2>,
+) -> (Vec<Vec<Point2>>, Vec<Vec<Point2>>) {
+    let ends: HashSet<(usize, usize)> = segments.values().map(|(end, _)| *end).collect();
+    // Open contours start from the points which are not the end of any segment.
+    let mut starts: Vec<(usize, usize)> = segments
+        .keys()
+        .filter(|key| !ends.contains(key))
+        .copied()
+        .collect();
+    starts.sort();
+    let mut open_contours = Vec::new();
+    starts.into_iter().for_each(|start| {
+        let mut contour = vec![points[&start]];
+        let mut key = start;
+        while let Some((next, pt)) = segments.remove(&key) {
+            push_point(&mut contour, pt);
+            key = next;
+        }
+        open_contours.push(contour);
+    });
+    // The remained segments form loops, since no two segments share the start or the end.
+    let mut keys: Vec<(usize, usize)> = segments.keys().copied().collect();
+    keys.sort();
+    let mut loops = Vec::new();
+    keys.into_iter().for_each(|start| {
+        let mut key = start;
+        let mut contour = Vec::new();
+        while let Some((next, pt)) = segments.remove(&key) {
+            push_point(&mut contour, pt);
+            key = next;
+        }
+        if contour.is_empty() {
+            return;
+        }
+        if contour.len() > 1 && contour[0].near(&contour[contour.len() - 1]) {
+            contour.pop();
+        }
+        if contour.len() > 2 {
+            loops.push(contour);
+        }
+    });
+    (loops, open_contours)
+}
+
+/// Pushes a point skipping the duplicated points, which occur at the vertices on the plane.
+fn push_point(contour: &mut Vec<Point2>, pt: Point2) {
+    if contour.last().is_none_or(|last| !last.near(&pt)) {
+        contour.push(pt);
+    }
+}
+
+/// Classifies loops into outer loops and holes, and orients them.
+fn classify_loops(loops: Vec<Vec<Point2>>) -> Vec<Contour> {
+    let areas: Vec<f64> = loops.iter().map(|pts| signed_area(pts).abs()).collect();
+    let containers: Vec<Vec<usize>> = loops
+        .iter()
+        .enumerate()
+        .map(|(i, pts)| {
+            (0..loops.len())
+                .filter(|j| *j != i && areas[*j] > areas[i] && contains(&loops[*j], pts[0]))
+                .collect()
+        })
+        .collect();
+    loops
+        .into_iter()
+        .zip(containers)
+        .map(|(mut points, containers)| {
+            let kind = match containers.len() % 2 {
+                0 => ContourKind::Outer,
+                _ => ContourKind::Hole,
+            };
+            let ccw = signed_area(&points) > 0.0;
+            if ccw != (kind == ContourKind::Outer) {
+                points.reverse();
+            }
+            let parent = containers
+                .into_iter()
+                .min_by(|i, j| areas[*i].total_cmp(&areas[*j]));
+            Contour {
+                points,
+                kind,
+                parent,
+            }
+        })
+        .collect()
+}
+
+/// Returns the signed area of the polygon, positive if counterclockwise.
+pub fn signed_area(points: &[Point2]) -> f64 {
+    let n = points.len();
+    (0..n).fold(0.0, |sum, i| {
+        let (p, q) = (points[i], points[(i + 1) % n]);
+        sum + (p.x * q.y - p.y * q.x) / 2.0
+    })
+}
+
+/// Determines whether `pt` is inside the polygon by the even-odd rule.
+fn contains(points: &[Point2], pt: Point2) -> bool {
+    let n = points.len();
+    (0..n).fold(false, |inside, i| {
+        let (p, q) = (points[i], points[(i + 1) % n]);
+        match (p.y > pt.y) != (q.y > pt.y) {
+            true => {
+                let x = p.x + (pt.y - p.y) / (q.y - p.y) * (q.x - p.x);
+                inside ^ (pt.x < x)
+            }
+            false => inside,
+        }
+    })
+}
+
+/// Writes the section of `layer` as an SVG image. The unit of the coordinates is millimeter.
+pub fn write_svg<W: Write>(layer: &Layer, mut writer: W) -> std::io::Result<()> {
+    let bdb: BoundingBox<Point2> = layer
+        .contours
+        .iter()
+        .flat_map(|c| &c.points)
+        .chain(layer.open_contours.iter().flatten())
+        .copied()
+        .collect();
+    let (min, max) = match bdb.is_empty() {
+        true => (Point2::origin(), Point2::origin()),
+        false => (bdb.min(), bdb.max()),
+    };
+    let margin = f64::max((max - min).magnitude() * 0.05, 1.0e-3);
+    let (width, height) = (max.x - min.x + 2.0 * margin, max.y - min.y + 2.0 * margin);
+    // SVG has the downward y-axis.
+    let to_svg = |p: &Point2| format!("{:.6},{:.6}", p.x - min.x + margin, max.y - p.y + margin);
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.6}mm" height="{height:.6}mm" viewBox="0 0 {width:.6} {height:.6}">"#,
+    )?;
+    let path = layer
+        .contours
+        .iter()
+        .map(|c| {
+            let pts: Vec<String> = c.points.iter().map(to_svg).collect();
+            format!("M {} Z", pts.join(" L "))
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    if !path.is_empty() {
+        writeln!(
+            writer,
+            r#"  <path d="{path}" fill="gray" fill-rule="evenodd" stroke="black" stroke-width="{:.6}"/>"#,
+            margin / 10.0,
+        )?;
+    }
+    for contour in &layer.open_contours {
+        let pts: Vec<String> = contour.iter().map(to_svg).collect();
+        writeln!(
+            writer,
+            r#"  <polyline points="{}" fill="none" stroke="red" stroke-width="{:.6}"/>"#,
+            pts.join(" "),
+            margin / 10.0,
+        )?;
+    }
+    writeln!(writer, "</svg>")
+}
+
+/// Settings of G-code output.
+#[derive(Clone, Copy, Debug)]
+pub struct GcodeSetting {
+    /// feed rate of extrusion moves, mm/min
+    pub feed_rate: f64,
+    /// feed rate of travel moves, mm/min
+    pub travel_rate: f64,
+    /// length of extruded filament per unit length of path
+    pub extrusion_ratio: f64,
+    /// thickness of the layers, which should be the layer height of the slicing, mm
+    pub layer_height: f64,
+}
+
+impl Default for GcodeSetting {
+    fn default() -> Self {
+        Self {
+            feed_rate: 1200.0,
+            travel_rate: 4800.0,
+            extrusion_ratio: 0.05,
+            layer_height: 0.2,
+        }
+    }
+}
+
+/// Writes the perimeter paths of all closed loops as G-code.
+///
+/// Assuming that all layers have the thickness `setting.layer_height`, the z-coordinate of each
+/// layer is the top of the layer measured from the bottom of the first layer. Open contours are
+/// not printed.
+pub fn write_gcode<W: Write>(
+    layers: &[Layer],
+    setting: GcodeSetting,
+    mut writer: W,
+) -> std::io::Result<()> {
+    let thickness = setting.layer_height;
+    let bottom = layers
+        .first()
+        .map_or(0.0, |first| first.height - thickness / 2.0);
+    writeln!(writer, "; perimeters generated by truck-tutorial-code")?;
+    writeln!(writer, "G21 ; millimeter")?;
+    writeln!(writer, "G90 ; absolute positioning")?;
+    writeln!(writer, "M82 ; absolute extrusion")?;
+    writeln!(writer, "G92 E0")?;
+    let mut extruded = 0.0;
+    for layer in layers {
+        writeln!(
+            writer,
+            "G0 Z{:.4} F{:.0}",
+            layer.height - bottom + thickness / 2.0,
+            setting.travel_rate
+        )?;
+        for contour in &layer.contours {
+            let first = contour.points[0];
+            writeln!(
+                writer,
+                "G0 X{:.4} Y{:.4} F{:.0}",
+                first.x, first.y, setting.travel_rate
+            )?;
+            let mut prev = first;
+            for pt in contour.points.iter().skip(1).chain([&first]) {
+                extruded += pt.distance(prev) * setting.extrusion_ratio;
+                writeln!(
+                    writer,
+                    "G1 X{:.4} Y{:.4} E{:.5} F{:.0}",
+                    pt.x, pt.y, extruded, setting.feed_rate
+                )?;
+                prev = *pt;
+            }
+        }
+    }
+    writeln!(writer, "M2")
+}
@@ -0,0 +1,84 @@
+use chapter2::components::*;
+use std::collections::HashSet;
+use truck_meshalgo::prelude::*;
+
+/// Output the contents of `polygon` to the file specified by `path`.
+fn write_polygon(polygon: &PolygonMesh, path: &str) {
+    let mut obj = std::fs::File::create(path).unwrap();
+    obj::write(polygon, &mut obj).unwrap();
+}
+
+/// the number of the positions referred by the faces
+fn used_positions(mesh: &PolygonMesh) -> usize {
+    mesh.face_iter()
+        .flatten()
+        .map(|v| v.pos)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// the number of the normals referred by the faces
+fn used_normals(mesh: &PolygonMesh) -> usize {
+    mesh.face_iter()
+        .flatten()
+        .filter_map(|v| v.nor)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+fn main() {
+    // The sphere of section 2.4 consists of the six patches before the positions are put together.
+    let sphere = obj::read(include_bytes!("sphere.obj").as_slice()).unwrap();
+    assert_eq!(split_components(&sphere).len(), 6);
+    let mut welded = sphere.clone();
+    welded.put_together_same_attrs(1.0e-3);
+    assert_eq!(split_components(&welded).len(), 1);
+
+    // a multi-object mesh: the welded sphere, the teapot, and the shifted sphere.
+    let teapot = obj::read(include_bytes!("../../chapter4/src/teapot.obj").as_slice()).unwrap();
+    let mut shifted = welded.clone();
+    shifted
+        .positions_mut()
+        .iter_mut()
+        .for_each(|p| *p += Vector3::new(3.0, 0.0, 0.0));
+    let scene = merge_meshes([welded.clone(), teapot.clone(), shifted]);
+    assert_eq!(
+        scene.faces().len(),
+        2 * welded.faces().len() + teapot.faces().len()
+    );
+
+    let teapot_components = split_components(&teapot).len();
+    println!("the teapot has {teapot_components} component(s)");
+    let components = split_components(&scene);
+    assert_eq!(components.len(), teapot_components + 2);
+    // Each component has only its own attributes.
+    components.iter().for_each(|component| {
+        assert_eq!(used_positions(component), component.positions().len());
+    });
+    // The spheres keep their faces and volumes.
+    let spheres: Vec<&PolygonMesh> = components
+        .iter()
+        .filter(|component| component.faces().len() == welded.faces().len())
+        .collect();
+    assert_eq!(spheres.len(), 2);
+    spheres.iter().for_each(|sphere| {
+        assert_eq!(sphere.normals().len(), used_normals(&welded));
+        assert!((sphere.volume() - welded.volume()).abs() < TOLERANCE);
+    });
+    assert!(spheres[1]
+        .bounding_box()
+        .center()
+        .near(&Point3::new(3.0, 0.0, 0.0)));
+
+    // Splitting and merging again restores the faces and the referred attributes.
+    let merged = merge_meshes(components.iter().cloned());
+    assert_eq!(merged.faces().len(), scene.faces().len());
+    assert_eq!(merged.positions().len(), used_positions(&scene));
+    assert_eq!(merged.normals().len(), used_normals(&scene));
+    assert!((merged.volume() - scene.volume()).abs() < TOLERANCE);
+
+    components
+        .iter()
+        .enumerate()
+        .for_each(|(i, component)| write_polygon(component, &format!("component-{i}.obj")));
+}
@@ -0,0 +1,256 @@
+//! Generation of MikkTSpace tangent frames for normal mapping.
+//!
+//! The tangents are computed by [`mikktspace`], the Rust port of the reference implementation,
+//! so that they coincide with the tangent frames baked by other tools.
+
+use mikktspace::Geometry;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use truck_meshalgo::prelude::*;
+
+/// The attribute of a vertex of [`TangentMesh`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TangentAttribute {
+    /// position
+    pub position: Point3,
+    /// texture uv coordinate
+    pub uv_coord: Vector2,
+    /// normal at vertex
+    pub normal: Vector3,
+    /// the unit tangent in `xyz` and the sign of the bitangent in `w`.
+    /// The bitangent is `w * normal.cross(tangent.truncate())`.
+    pub tangent: Vector4,
+}
+
+/// Polygon mesh whose vertices have tangent frames.
+///
+/// This is the same form as the output of `PolygonMesh::expands`:
+/// each vertex of a face is an index of the vector of [`TangentAttribute`].
+pub type TangentMesh = PolygonMesh<usize, Vec<TangentAttribute>>;
+
+/// The reasons why tangent frames cannot be generated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TangentError {
+    /// The face with the index has a vertex without texture uv coordinate.
+    NoUvCoord(usize),
+    /// The face with the index has a vertex without normal.
+    NoNormal(usize),
+    /// MikkTSpace rejected the mesh, e.g. the mesh has no faces.
+    GenerationFailed,
+}
+
+impl Display for TangentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TangentError::NoUvCoord(idx) => write!(f, "the {idx}th face has no uv coordinate."),
+            TangentError::NoNormal(idx) => write!(f, "the {idx}th face has no normal."),
+            TangentError::GenerationFailed => write!(f, "failed to generate tangent space."),
+        }
+    }
+}
+
+impl std::error::Error for TangentError {}
+
+/// Generates tangent frames by the MikkTSpace algorithm.
+pub trait TangentGeneration {
+    /// Returns the mesh with the tangent frames at all vertices.
+    /// # Details
+    /// - All vertices must have both texture uv coordinates and normals.
+    /// - MikkTSpace accepts only triangles and quadrangles,
+    ///   so the other polygons are divided into triangles in a fan shape.
+    /// - The vertices are separated if their tangents are different even if they have
+    ///   the same position, uv coordinate and normal.
+    fn tangent_mesh(&self) -> Result<TangentMesh, TangentError>;
+}
+
+/// The bridge between `PolygonMesh` and the interface of `mikktspace`.
+struct MikkTSpaceGeometry<'a> {
+    mesh: &'a PolygonMesh,
+    // triangles and quadrangles of the mesh
+    faces: Vec<Vec<StandardVertex>>,
+    // tangents of the vertices of `faces`
+    tangents: Vec<[[f32; 4]; 4]>,
+}
+
+impl<'a> MikkTSpaceGeometry<'a> {
+    fn try_new(mesh: &'a PolygonMesh) -> Result<Self, TangentError> {
+        let mut faces = Vec::new();
+        for (idx, face) in mesh.face_iter().enumerate() {
+            if face.iter().any(|v| v.uv.is_none()) {
+                return Err(TangentError::NoUvCoord(idx));
+            }
+            if face.iter().any(|v| v.nor.is_none()) {
+                return Err(TangentError::NoNormal(idx));
+            }
+            match face.len() {
+                3 | 4 => faces.push(face.to_vec()),
+                _ => faces.extend((2..face.len()).map(|i| vec![face[0], face[i - 1], face[i]])),
+            }
+        }
+        let tangents = vec![[[0.0; 4]; 4]; faces.len()];
+        Ok(Self {
+            mesh,
+            faces,
+            tangents,
+        })
+    }
+
+    fn vertex(&self, face: usize, vert: usize) -> StandardVertex { self.faces[face][vert] }
+}
+
+impl Geometry for MikkTSpaceGeometry<'_> {
+    fn num_faces(&self) -> usize { self.faces.len() }
+    fn num_vertices_of_face(&self, face: usize) -> usize { self.faces[face].len() }
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        let p = self.mesh.positions()[self.vertex(face, vert).pos];
+        [p.x as f32, p.y as f32, p.z as f32]
+    }
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        let n = self.mesh.normals()[self.vertex(face, vert).nor.unwrap()];
+        [n.x as f32, n.y as f32, n.z as f32]
+    }
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        let uv = self.mesh.uv_coords()[self.vertex(face, vert).uv.unwrap()];
+        [uv.x as f32, uv.y as f32]
+    }
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        self.tangents[face][vert] = tangent;
+    }
+}
+
+impl TangentGeneration for PolygonMesh {
+    fn tangent_mesh(&self) -> Result<TangentMesh, TangentError> {
+        let mut geometry = MikkTSpaceGeometry::try_new(self)?;
+        if !mikktspace::generate_tangents(&mut geometry) {
+            return Err(TangentError::GenerationFailed);
+        }
+        let mut attributes = Vec::<TangentAttribute>::new();
+        // Vertices are identified by the indices of attributes and the bit pattern of tangents.
+        let mut vertex_map = HashMap::<(StandardVertex, [u32; 4]), usize>::new();
+        let faces: Faces<usize> = geometry
+            .faces
+            .iter()
+            .zip(&geometry.tangents)
+            .map(|(face, tangents)| {
+                face.iter()
+                    .zip(tangents)
+                    .map(|(v, t)| {
+                        *vertex_map
+                            .entry((*v, t.map(f32::to_bits)))
+                            .or_insert_with(|| {
+                                attributes.push(TangentAttribute {
+                                    position: self.positions()[v.pos],
+                                    uv_coord: self.uv_coords()[v.uv.unwrap()],
+                                    normal: self.normals()[v.nor.unwrap()],
+                                    tangent: Vector4::new(
+                                        t[0] as f64,
+                                        t[1] as f64,
+                                        t[2] as f64,
+                                        t[3] as f64,
+                                    ),
+                                });
+                                attributes.len() - 1
+                            })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        Ok(PolygonMesh::new(attributes, faces))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The tangents of `patch()` as `(position index, uv index, tangent)`, computed by calling
+    /// `mikktspace::generate_tangents` directly on the quadrangle, the triangle and the fan of the pentagon.
+    const REFERENCE: [(usize, usize, [f64; 4]); 10] = [
+        (0, 0, [1.0, 0.0, 0.0, 1.0]),
+        (1, 1, [0.98115224, 0.02033570, 0.19216333, 1.0]),
+        (2, 2, [0.94354278, 0.02307369, 0.33044592, 1.0]),
+        (3, 3, [0.98058063, 0.0, -0.19611613, 1.0]),
+        (4, 4, [0.99980778, -0.00387933, -0.01922029, 1.0]),
+        (5, 5, [0.97933286, -0.01464439, 0.20172434, 1.0]),
+        (6, 6, [0.93324578, 0.02934785, 0.35803747, 1.0]),
+        (3, 8, [-0.98058069, 0.0, 0.19611612, -1.0]),
+        (4, 9, [-0.99961627, -0.01534919, 0.02306217, -1.0]),
+        (7, 10, [-0.98111814, 0.01851166, 0.19252129, -1.0]),
+    ];
+
+    /// A patch of the graph `z = x^2 / 10 - xy / 5` with a quadrangle, a pentagon and a triangle.
+    /// The uv coordinates of the triangle are mirrored, so its bitangents are flipped.
+    fn patch() -> PolygonMesh {
+        let xy = [
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.2),
+            (0.0, 1.0),
+            (1.0, 1.1),
+            (2.0, 1.0),
+            (2.6, 0.6),
+            (1.0, 2.0),
+        ];
+        let positions = xy
+            .iter()
+            .map(|&(x, y)| Point3::new(x, y, 0.1 * x * x - 0.2 * x * y))
+            .collect();
+        let normals = xy
+            .iter()
+            .map(|&(x, y)| Vector3::new(0.2 * (y - x), 0.2 * x, 1.0).normalize())
+            .collect();
+        let uv_coords = xy
+            .iter()
+            .map(|&(x, y)| Vector2::new(0.5 * x, 0.5 * y))
+            .chain([3, 4, 7].map(|i| Vector2::new(1.0 - 0.5 * xy[i].0, 0.5 * xy[i].1)))
+            .collect();
+        let v = |pos: usize, uv: usize| (pos, Some(uv), Some(pos));
+        let faces = Faces::from_iter([
+            &[v(0, 0), v(1, 1), v(4, 4), v(3, 3)][..],
+            &[v(1, 1), v(2, 2), v(6, 6), v(5, 5), v(4, 4)],
+            &[v(3, 8), v(4, 9), v(7, 10)],
+        ]);
+        let attrs = StandardAttributes {
+            positions,
+            uv_coords,
+            normals,
+        };
+        PolygonMesh::new(attrs, faces)
+    }
+
+    #[test]
+    fn reference_tangents() {
+        let patch = patch();
+        let mesh = patch.tangent_mesh().unwrap();
+        // The vertices with the same attributes are not separated since their tangents are the same.
+        assert_eq!(mesh.attributes().len(), REFERENCE.len());
+        REFERENCE.iter().for_each(|&(pos, uv, tangent)| {
+            let (position, uv_coord) = (patch.positions()[pos], patch.uv_coords()[uv]);
+            let attr = mesh
+                .attributes()
+                .iter()
+                .find(|attr| {
+                    // The 4th vertex has the same uv coordinate also in the mirrored triangle.
+                    attr.position == position && attr.uv_coord == uv_coord && attr.tangent.w == tangent[3]
+                })
+                .unwrap();
+            let diff = attr.tangent - Vector4::from(tangent);
+            assert!(diff.magnitude() < 1.0e-6, "{pos}th vertex: {:?}", attr.tangent);
+        });
+    }
+
+    #[test]
+    fn missing_attributes() {
+        let mut faces = Faces::from_iter([[(0, Some(0), Some(0)), (1, Some(1), None), (2, Some(2), Some(2))]]);
+        let attrs = StandardAttributes {
+            positions: vec![Point3::origin(), Point3::new(1.0, 0.0, 0.0), Point3::new(0.0, 1.0, 0.0)],
+            uv_coords: vec![Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0), Vector2::new(0.0, 1.0)],
+            normals: vec![Vector3::unit_z(); 3],
+        };
+        let mesh = PolygonMesh::new(attrs.clone(), faces.clone());
+        assert_eq!(mesh.tangent_mesh(), Err(TangentError::NoNormal(0)));
+        faces[0][0].uv = None;
+        let mesh = PolygonMesh::new(attrs, faces);
+        assert_eq!(mesh.tangent_mesh(), Err(TangentError::NoUvCoord(0)));
+    }
+}
@@ -0,0 +1,122 @@
+use chapter2::{gltf, tangent::*};
+use truck_meshalgo::prelude::*;
+
+// edge parts
+const DIVISION: usize = 8;
+
+/// Corners of the faces of the hexahedron, the same as `hexahedron()` in section 2.4.
+fn hexahedron_faces() -> [[Vector3; 4]; 6] {
+    let a = f64::sqrt(3.0) / 3.0;
+    let v = [
+        Vector3::new(-a, -a, -a),
+        Vector3::new(a, -a, -a),
+        Vector3::new(a, a, -a),
+        Vector3::new(-a, a, -a),
+        Vector3::new(-a, -a, a),
+        Vector3::new(a, -a, a),
+        Vector3::new(a, a, a),
+        Vector3::new(-a, a, a),
+    ];
+    [
+        [3, 2, 1, 0],
+        [0, 1, 5, 4],
+        [1, 2, 6, 5],
+        [2, 3, 7, 6],
+        [3, 0, 4, 7],
+        [4, 5, 6, 7],
+    ]
+    .map(|face| face.map(|i| v[i]))
+}
+
+/// the point on the square with parameter `(s, t)`, the same interpolation as section 2.4
+fn lattice_point(v: [Vector3; 4], s: f64, t: f64) -> Vector3 {
+    v[0] * (1.0 - s) * (1.0 - t) + v[1] * s * (1.0 - t) + v[3] * (1.0 - s) * t + v[2] * s * t
+}
+
+/// The sphere of section 2.4 with texture coordinates.
+/// The uv coordinate of a vertex is the lattice parameter `(s, t)` on the face of the hexahedron.
+fn sphere() -> PolygonMesh {
+    let (mut positions, mut uv_coords) = (Vec::new(), Vec::new());
+    hexahedron_faces().into_iter().for_each(|v| {
+        (0..=DIVISION)
+            .flat_map(|i| (0..=DIVISION).map(move |j| (i, j)))
+            .for_each(|(i, j)| {
+                let s = i as f64 / DIVISION as f64;
+                let t = j as f64 / DIVISION as f64;
+                positions.push(Point3::from_vec(lattice_point(v, s, t).normalize()));
+                uv_coords.push(Vector2::new(s, t));
+            })
+    });
+    let normals = positions.iter().copied().map(Point3::to_vec).collect();
+    let faces: Faces = (0..6)
+        .flat_map(|face_idx| {
+            let base = face_idx * (DIVISION + 1) * (DIVISION + 1);
+            let to_index = move |i: usize, j: usize| {
+                let idx = base + (DIVISION + 1) * i + j;
+                // this time, the texture is also registered
+                (idx, Some(idx), Some(idx))
+            };
+            (0..DIVISION)
+                .flat_map(move |i| (0..DIVISION).map(move |j| (i, j)))
+                .map(move |(i, j)| {
+                    [
+                        to_index(i, j),
+                        to_index(i + 1, j),
+                        to_index(i + 1, j + 1),
+                        to_index(i, j + 1),
+                    ]
+                })
+        })
+        .collect();
+    let attrs = StandardAttributes {
+        positions,
+        uv_coords,
+        normals,
+    };
+    PolygonMesh::new(attrs, faces)
+}
+
+/// The exact tangent frame of the sphere at `(s, t)` on the face `v`:
+/// the normalized derivatives of the projected lattice by `s` and `t`.
+fn exact_frame(v: [Vector3; 4], s: f64, t: f64) -> (Vector3, Vector3) {
+    let pt = lattice_point(v, s, t);
+    let der_s = (v[1] - v[0]) * (1.0 - t) + (v[2] - v[3]) * t;
+    let der_t = (v[3] - v[0]) * (1.0 - s) + (v[2] - v[1]) * s;
+    let n = pt.normalize();
+    let project = |der: Vector3| (der - n * n.dot(der)).normalize();
+    (project(der_s), project(der_t))
+}
+
+fn main() {
+    let sphere = sphere();
+    let mesh = sphere.tangent_mesh().unwrap();
+
+    // Compare the generated tangents with the derivatives of the parametrization.
+    let faces = hexahedron_faces();
+    let mut max_angle = 0.0_f64;
+    // All faces are quadrangles, and each `DIVISION * DIVISION` faces are on one face of the hexahedron.
+    mesh.face_iter().enumerate().for_each(|(face_idx, face)| {
+        let v = faces[face_idx / (DIVISION * DIVISION)];
+        face.iter().for_each(|idx| {
+            let attr = mesh.attributes()[*idx];
+            let (uv, n) = (attr.uv_coord, attr.normal);
+            let (exact_tangent, exact_bitangent) = exact_frame(v, uv.x, uv.y);
+            let tangent = attr.tangent.truncate();
+            let bitangent = n.cross(tangent) * attr.tangent.w;
+            assert!(f64::abs(tangent.magnitude() - 1.0) < 1.0e-6);
+            assert!(f64::abs(tangent.dot(n)) < 1.0e-6);
+            assert!(
+                bitangent.dot(exact_bitangent) > 0.0,
+                "the bitangent is flipped."
+            );
+            max_angle = max_angle.max(tangent.angle(exact_tangent).0);
+        })
+    });
+    println!("maximum angle from the exact tangents: {max_angle:.3e} rad");
+    // The error of the discretization with `DIVISION = 8`.
+    assert!(max_angle < 0.05);
+
+    // output glTF with the tangents
+    let file = std::fs::File::create("sphere-with-tangents.gltf").unwrap();
+    gltf::write(&mesh, file).unwrap();
+}
@@ -0,0 +1,235 @@
+//! Triangulation of non-convex polygons, with holes, by ear clipping.
+//!
+//! The orientations are decided by the exact predicates of the crate `robust`, so the
+//! triangles are never flipped. The polygons which cannot be triangulated are reported.
+
+use robust::Coord;
+use truck_meshalgo::prelude::*;
+
+fn orient(a: Point2, b: Point2, c: Point2) -> f64 {
+    let coord = |p: Point2| Coord { x: p.x, y: p.y };
+    robust::orient2d(coord(a), coord(b), coord(c))
+}
+
+// whether `p` is in the closed triangle `abc`, which is counter-clockwise
+fn in_triangle(p: Point2, [a, b, c]: [Point2; 3]) -> bool {
+    orient(a, b, p) >= 0.0 && orient(b, c, p) >= 0.0 && orient(c, a, p) >= 0.0
+}
+
+/// Returns the signed area of the polygon, positive if counter-clockwise.
+pub fn signed_area(points: &[Point2]) -> f64 {
+    let len = points.len();
+    (0..len)
+        .map(|i| {
+            let (p, q) = (points[i], points[(i + 1) % len]);
+            p.x * q.y - p.y * q.x
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Triangulates the simple polygon `outer` with `holes` by ear clipping.
+///
+/// The vertices are indexed in order of the concatenation of `outer` and `holes`,
+/// and the triangles are counter-clockwise. The holes are bridged to the outer boundary
+/// by the algorithm of D. Eberly. Returns `None` if the polygon is degenerate or
+/// is not simple, i.e. some vertex is left which cannot be clipped.
+pub fn triangulate_polygon(outer: &[Point2], holes: &[Vec<Point2>]) -> Option<Vec<[usize; 3]>> {
+    let points: Vec<Point2> = outer
+        .iter()
+        .chain(holes.iter().flatten())
+        .copied()
+        .collect();
+    // the boundary as a ring of the indices, the outer is counter-clockwise
+    let mut ring: Vec<usize> = (0..outer.len()).collect();
+    if signed_area(outer) < 0.0 {
+        ring.reverse();
+    }
+    // the holes are clockwise, and are bridged from the rightmost one
+    let mut offset = outer.len();
+    let mut hole_rings: Vec<Vec<usize>> = holes
+        .iter()
+        .map(|hole| {
+            let mut hole_ring: Vec<usize> = (offset..offset + hole.len()).collect();
+            offset += hole.len();
+            if signed_area(hole) > 0.0 {
+                hole_ring.reverse();
+            }
+            hole_ring
+        })
+        .filter(|hole_ring| hole_ring.len() >= 3)
+        .collect();
+    let max_x = |ring: &Vec<usize>| ring.iter().map(|i| points[*i].x).fold(f64::MIN, f64::max);
+    hole_rings.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+    for hole_ring in hole_rings {
+        ring = bridge_hole(&points, ring, hole_ring)?;
+    }
+    clip_ears(&points, ring)
+}
+
+// Bridges `hole` to `ring` from the rightmost vertex of the hole.
+fn bridge_hole(points: &[Point2], ring: Vec<usize>, hole: Vec<usize>) -> Option<Vec<usize>> {
+    let (k, m) = hole
+        .iter()
+        .enumerate()
+        .max_by(|(_, i), (_, j)| points[**i].x.total_cmp(&points[**j].x))?;
+    let pm = points[*m];
+    // the nearest intersection of the ray from `pm` to +x and the ring
+    let len = ring.len();
+    let mut nearest: Option<(f64, usize)> = None;
+    (0..len).for_each(|i| {
+        let (a, b) = (points[ring[i]], points[ring[(i + 1) % len]]);
+        if (a.y > pm.y) == (b.y > pm.y) && a.y != pm.y && b.y != pm.y {
+            return;
+        }
+        let x = match a.y == b.y {
+            true => f64::min(a.x, b.x),
+            false => a.x + (pm.y - a.y) * (b.x - a.x) / (b.y - a.y),
+        };
+        if x >= pm.x && nearest.is_none_or(|(x0, _)| x < x0) {
+            // the endpoint with the larger x is visible from `pm`, if no vertices are in the way
+            let j = match a.x > b.x {
+                true => i,
+                false => (i + 1) % len,
+            };
+            nearest = Some((x, j));
+        }
+    });
+    let (x, mut slot) = nearest?;
+    let (pi, pp) = (Point2::new(x, pm.y), points[ring[slot]]);
+    // the reflex vertices in the triangle (pm, pi, pp) may hide `pp`
+    let triangle = match orient(pm, pi, pp) >= 0.0 {
+        true => [pm, pi, pp],
+        false => [pm, pp, pi],
+    };
+    let mut best_angle = f64::INFINITY;
+    (0..len).for_each(|i| {
+        let (prev, cur, next) = (ring[(i + len - 1) % len], ring[i], ring[(i + 1) % len]);
+        let p = points[cur];
+        if i != slot
+            && p != pp
+            && orient(points[prev], p, points[next]) <= 0.0
+            && in_triangle(p, triangle)
+        {
+            let v = p - pm;
+            let angle = f64::atan2(v.y.abs(), v.x);
+            if angle < best_angle {
+                best_angle = angle;
+                slot = i;
+            }
+        }
+    });
+    // The bridged vertex may appear several times, so the bridge is connected to the
+    // occurrence whose interior angle contains the vertex of the hole.
+    let in_wedge = |i: usize| {
+        let (prev, cur, next) = (
+            points[ring[(i + len - 1) % len]],
+            points[ring[i]],
+            points[ring[(i + 1) % len]],
+        );
+        let (left_in, left_out) = (orient(prev, cur, pm) >= 0.0, orient(cur, next, pm) >= 0.0);
+        match orient(prev, cur, next) >= 0.0 {
+            true => left_in && left_out,
+            false => left_in || left_out,
+        }
+    };
+    if !in_wedge(slot) {
+        slot = (0..len).find(|i| ring[*i] == ring[slot] && in_wedge(*i))?;
+    }
+
+    let mut bridged = Vec::with_capacity(ring.len() + hole.len() + 2);
+    bridged.extend(&ring[..=slot]);
+    bridged.extend(hole[k..].iter().chain(&hole[..k]));
+    bridged.extend([*m, ring[slot]]);
+    bridged.extend(&ring[slot + 1..]);
+    Some(bridged)
+}
+
+fn clip_ears(points: &[Point2], mut ring: Vec<usize>) -> Option<Vec<[usize; 3]>> {
+    let mut triangles = Vec::with_capacity(ring.len().saturating_sub(2));
+    while ring.len() > 3 {
+        let len = ring.len();
+        let ear = (0..len).find(|i| {
+            let tri = [ring[(i + len - 1) % len], ring[*i], ring[(i + 1) % len]];
+            let pts = tri.map(|j| points[j]);
+            orient(pts[0], pts[1], pts[2]) > 0.0
+                && ring
+                    .iter()
+                    .filter(|j| !tri.contains(j))
+                    .all(|j| !in_triangle(points[*j], pts))
+        })?;
+        triangles.push([
+            ring[(ear + len - 1) % len],
+            ring[ear],
+            ring[(ear + 1) % len],
+        ]);
+        ring.remove(ear);
+    }
+    match ring.len() == 3 && orient(points[ring[0]], points[ring[1]], points[ring[2]]) > 0.0 {
+        true => triangles.push([ring[0], ring[1], ring[2]]),
+        false => return None,
+    }
+    Some(triangles)
+}
+
+/// Triangulation of the faces of polygon meshes.
+pub trait FaceTriangulation {
+    /// Triangulates all faces on their best-fit planes, keeping the normals and the texture coordinates.
+    ///
+    /// Returns the triangulated mesh and the indices, in order of `face_iter`, of the faces
+    /// which are too degenerate to triangulate. The degenerate faces are removed from the mesh.
+    fn triangulate_faces(&self) -> (PolygonMesh, Vec<usize>);
+}
+
+impl FaceTriangulation for PolygonMesh {
+    fn triangulate_faces(&self) -> (PolygonMesh, Vec<usize>) {
+        let positions = self.positions();
+        let mut triangles = Vec::<[StandardVertex; 3]>::new();
+        let mut degenerate = Vec::new();
+        self.face_iter().enumerate().for_each(|(i, face)| {
+            let pts: Vec<Point3> = face.iter().map(|v| positions[v.pos]).collect();
+            match triangulate_face(&pts) {
+                Some(tris) => triangles.extend(tris.into_iter().map(|tri| tri.map(|j| face[j]))),
+                None => degenerate.push(i),
+            }
+        });
+        let mesh = PolygonMesh::new(self.attributes().clone(), Faces::from_iter(triangles));
+        (mesh, degenerate)
+    }
+}
+
+// Triangulates the polygon in the space, and returns `None` if it is degenerate.
+fn triangulate_face(pts: &[Point3]) -> Option<Vec<[usize; 3]>> {
+    let len = pts.len();
+    // the normal of the best-fit plane by the Newell's method
+    let normal: Vector3 = (0..len)
+        .map(|i| pts[i].to_vec().cross(pts[(i + 1) % len].to_vec()))
+        .sum();
+    let perimeter: f64 = (0..len).map(|i| pts[i].distance(pts[(i + 1) % len])).sum();
+    if len < 3 || normal.magnitude() <= TOLERANCE * perimeter * perimeter {
+        return None;
+    }
+    let n = normal.normalize();
+    // the basis of the plane, in which the polygon is counter-clockwise
+    let u = match n.x.abs() < 0.5 {
+        true => Vector3::unit_x().cross(n).normalize(),
+        false => Vector3::unit_y().cross(n).normalize(),
+    };
+    let v = n.cross(u);
+    let projected: Vec<Point2> = pts
+        .iter()
+        .map(|p| Point2::new(p.to_vec().dot(u), p.to_vec().dot(v)))
+        .collect();
+    let triangles = match len {
+        3 => vec![[0, 1, 2]],
+        _ => triangulate_polygon(&projected, &[])?,
+    };
+    // The non-planar polygon may be flipped in the space even if it is not on the plane.
+    let flipped = triangles
+        .iter()
+        .any(|[a, b, c]| (pts[*b] - pts[*a]).cross(pts[*c] - pts[*a]).dot(n) <= 0.0);
+    match flipped {
+        true => None,
+        false => Some(triangles),
+    }
+}
@@ -0,0 +1,157 @@
+use chapter2::triangulate::*;
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+
+/// Output the contents of `polygon` to the file specified by `path`.
+fn write_polygon(polygon: &PolygonMesh, path: &str) {
+    let mut obj = std::fs::File::create(path).unwrap();
+    obj::write(polygon, &mut obj).unwrap();
+}
+
+/// the area of the triangle in the space
+fn area(mesh: &PolygonMesh, tri: [StandardVertex; 3]) -> f64 {
+    let [a, b, c] = tri.map(|v| mesh.positions()[v.pos]);
+    (b - a).cross(c - a).magnitude() / 2.0
+}
+
+/// the area of the counter-clockwise triangle on the plane, negative if the triangle is flipped.
+fn signed_triangle_area(points: &[Point2], [a, b, c]: [usize; 3]) -> f64 {
+    signed_area(&[points[a], points[b], points[c]])
+}
+
+fn main() {
+    // a star: a non-convex decagon, with the texture coordinates and the normals.
+    let star: Vec<Point3> = (0..10)
+        .map(|i| {
+            let r = if i % 2 == 0 { 1.0 } else { 0.4 };
+            let t = PI / 2.0 + PI * i as f64 / 5.0;
+            Point3::new(r * f64::cos(t), r * f64::sin(t), 0.0)
+        })
+        .collect();
+    // L-shaped hexagon on the plane z = 1
+    let l_shape = [
+        [0.0, 0.0],
+        [2.0, 0.0],
+        [2.0, 1.0],
+        [1.0, 1.0],
+        [1.0, 2.0],
+        [0.0, 2.0],
+    ]
+    .map(|[x, y]| Point3::new(x, y, 1.0));
+    // non-planar hexagon: the vertices go up and down alternately
+    let saddle: Vec<Point3> = (0..6)
+        .map(|i| {
+            let t = PI * i as f64 / 3.0;
+            let z = if i % 2 == 0 { 0.2 } else { -0.2 };
+            Point3::new(5.0 + f64::cos(t), f64::sin(t), z)
+        })
+        .collect();
+    // degenerate faces: collinear vertices and a bow tie
+    let collinear = (0..5).map(|i| Point3::new(i as f64, 5.0, 0.0));
+    let bow_tie =
+        [[0.0, 0.0], [1.0, 1.0], [1.0, 0.0], [0.0, 1.0]].map(|[x, y]| Point3::new(x, y, 3.0));
+
+    let positions: Vec<Point3> = star
+        .iter()
+        .chain(&l_shape)
+        .chain(&saddle)
+        .copied()
+        .chain(collinear)
+        .chain(bow_tie)
+        .collect();
+    let uv_coords = star.iter().map(|p| Vector2::new(p.x, p.y)).collect();
+    let normals = vec![Vector3::unit_z()];
+    let star_face: Vec<StandardVertex> = (0..10)
+        .map(|i| StandardVertex {
+            pos: i,
+            uv: Some(i),
+            nor: Some(0),
+        })
+        .collect();
+    let mut faces = Faces::from_iter([star_face]);
+    faces.push((10..16).collect::<Vec<_>>());
+    faces.push((16..22).collect::<Vec<_>>());
+    faces.push((22..27).collect::<Vec<_>>());
+    faces.push((27..31).collect::<Vec<_>>());
+    let attrs = StandardAttributes {
+        positions,
+        uv_coords,
+        normals,
+    };
+    let mesh = PolygonMesh::new(attrs, faces);
+
+    // The fan triangulation flips some triangles of the star.
+    let fan_flipped = mesh
+        .faces()
+        .triangle_iter()
+        .filter(|tri| tri.iter().all(|v| v.pos < 10))
+        .filter(|tri| {
+            let [a, b, c] = tri.map(|v| mesh.positions()[v.pos]);
+            (b - a).cross(c - a).z < 0.0
+        })
+        .count();
+    println!("the fan triangulation flips {fan_flipped} triangle(s) of the star");
+    assert!(fan_flipped > 0);
+
+    let (triangulated, degenerate) = mesh.triangulate_faces();
+    // The faces are in order of the triangles, the quadrangles, and the other polygons,
+    // so the bow tie is the first one and the collinear pentagon is the last one.
+    assert_eq!(degenerate, vec![0, 4]);
+    assert_eq!(triangulated.faces().len(), 8 + 4 + 4);
+    assert!(triangulated.face_iter().all(|face| face.len() == 3));
+    let face_area = |range: std::ops::Range<usize>| {
+        triangulated
+            .faces()
+            .triangle_iter()
+            .filter(|tri| tri.iter().all(|v| range.contains(&v.pos)))
+            .map(|tri| area(&triangulated, tri))
+            .sum::<f64>()
+    };
+    // the area of the star is ten times the area of the triangle of the center and two vertices.
+    let star_area = 10.0 * 0.5 * 1.0 * 0.4 * f64::sin(PI / 5.0);
+    assert!(f64::abs(face_area(0..10) - star_area) < TOLERANCE);
+    assert!(f64::abs(face_area(10..16) - 3.0) < TOLERANCE);
+    // The triangles keep the attributes and the orientations.
+    triangulated.faces().triangle_iter().for_each(|tri| {
+        let [a, b, c] = tri.map(|v| triangulated.positions()[v.pos]);
+        let normal = (b - a).cross(c - a);
+        if tri[0].pos < 10 {
+            assert!(tri.iter().all(|v| v.uv == Some(v.pos) && v.nor == Some(0)));
+            assert!(normal.z > 0.0);
+        } else if tri[0].pos >= 16 {
+            // the triangles of the saddle face upward
+            assert!(normal.z > 0.0);
+        }
+    });
+    write_polygon(&triangulated, "triangulated-faces.obj");
+
+    // a square with three holes, which are bridged to the outer boundary
+    let square = |x: f64, y: f64, w: f64, h: f64| {
+        vec![
+            Point2::new(x, y),
+            Point2::new(x + w, y),
+            Point2::new(x + w, y + h),
+            Point2::new(x, y + h),
+        ]
+    };
+    let outer = square(0.0, 0.0, 10.0, 10.0);
+    let holes = [
+        square(1.0, 1.0, 2.0, 2.0),
+        square(5.0, 1.0, 2.0, 2.0),
+        square(4.0, 5.0, 2.0, 3.0),
+    ];
+    let triangles = triangulate_polygon(&outer, &holes).unwrap();
+    let points: Vec<Point2> = outer
+        .iter()
+        .chain(holes.iter().flatten())
+        .copied()
+        .collect();
+    // n vertices and h holes make n + 2h - 2 triangles.
+    assert_eq!(triangles.len(), points.len() + 2 * holes.len() - 2);
+    let areas: Vec<f64> = triangles
+        .iter()
+        .map(|tri| signed_triangle_area(&points, *tri))
+        .collect();
+    assert!(areas.iter().all(|a| *a > 0.0));
+    assert!(f64::abs(areas.iter().sum::<f64>() - (100.0 - 4.0 - 4.0 - 6.0)) < TOLERANCE);
+}
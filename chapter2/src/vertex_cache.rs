@@ -0,0 +1,231 @@
+//! Reordering triangles and vertices of polygon meshes for rendering.
+//!
+//! The triangles are reordered by Tipsify of P. V. Sander, D. Nehab and J. Barczak,
+//! "Fast Triangle Reordering for Vertex Locality and Reduced Overdraw", which runs in linear time.
+//! A vertex of the GPU is a combination of the indices of the attributes, i.e. a `StandardVertex`.
+
+use std::collections::{HashMap, VecDeque};
+use truck_meshalgo::prelude::*;
+
+/// The options of the optimization.
+#[derive(Clone, Copy, Debug)]
+pub struct CacheOptimization {
+    /// the number of the vertices in the post-transform cache
+    pub cache_size: usize,
+    /// If `true`, the clusters of triangles are sorted so that the outer ones are drawn first.
+    pub reduce_overdraw: bool,
+}
+
+impl Default for CacheOptimization {
+    fn default() -> Self {
+        Self {
+            cache_size: 16,
+            reduce_overdraw: true,
+        }
+    }
+}
+
+/// The average cache miss ratios before and after the optimization.
+#[derive(Clone, Copy, Debug)]
+pub struct CacheReport {
+    /// ACMR of the original mesh
+    pub before: f64,
+    /// ACMR of the optimized mesh
+    pub after: f64,
+}
+
+/// Returns the average cache miss ratio, the number of the transformed vertices per triangle,
+/// simulating the FIFO cache of `cache_size` vertices. The polygons are divided into triangles.
+pub fn acmr(mesh: &PolygonMesh, cache_size: usize) -> f64 {
+    let mut cache = VecDeque::<StandardVertex>::with_capacity(cache_size + 1);
+    let (mut misses, mut triangles) = (0, 0);
+    mesh.faces().triangle_iter().for_each(|tri| {
+        triangles += 1;
+        tri.iter().for_each(|v| {
+            if !cache.contains(v) {
+                misses += 1;
+                cache.push_back(*v);
+                if cache.len() > cache_size {
+                    cache.pop_front();
+                }
+            }
+        });
+    });
+    match triangles {
+        0 => 0.0,
+        _ => misses as f64 / triangles as f64,
+    }
+}
+
+/// Reorders the triangles for the vertex cache and the overdraw, and the attributes in order of use.
+///
+/// The polygons are divided into triangles. Returns the optimized mesh and its ACMRs.
+pub fn optimize_for_rendering(
+    mesh: &PolygonMesh,
+    options: CacheOptimization,
+) -> (PolygonMesh, CacheReport) {
+    let before = acmr(mesh, options.cache_size);
+    // the vertices of the GPU and the triangles of their indices
+    let mut vertex_map = HashMap::<StandardVertex, usize>::new();
+    let mut vertices = Vec::<StandardVertex>::new();
+    let triangles: Vec<[usize; 3]> = mesh
+        .faces()
+        .triangle_iter()
+        .map(|tri| {
+            tri.map(|v| {
+                *vertex_map.entry(v).or_insert_with(|| {
+                    vertices.push(v);
+                    vertices.len() - 1
+                })
+            })
+        })
+        .collect();
+
+    let clusters = tipsify(&triangles, vertices.len(), options.cache_size);
+    let order: Vec<usize> = match options.reduce_overdraw {
+        true => sort_clusters(mesh, &vertices, &triangles, clusters),
+        false => clusters.into_iter().flatten().collect(),
+    };
+    let optimized = reorder_attributes(
+        mesh,
+        order.iter().map(|t| triangles[*t].map(|v| vertices[v])),
+    );
+    let after = acmr(&optimized, options.cache_size);
+    (optimized, CacheReport { before, after })
+}
+
+// Returns the clusters of the triangles in order of Tipsify.
+// A cluster ends when the fanning vertex is not taken from the cache.
+fn tipsify(triangles: &[[usize; 3]], vertex_count: usize, cache_size: usize) -> Vec<Vec<usize>> {
+    let mut adjacency = vec![Vec::<usize>::new(); vertex_count];
+    triangles
+        .iter()
+        .enumerate()
+        .for_each(|(t, tri)| tri.iter().for_each(|v| adjacency[*v].push(t)));
+    let mut live: Vec<usize> = adjacency.iter().map(Vec::len).collect();
+    let mut time_stamps = vec![0; vertex_count];
+    let mut emitted = vec![false; triangles.len()];
+    let mut dead_end = Vec::<usize>::new();
+    let (mut time, mut cursor) = (cache_size + 1, 0);
+
+    let mut clusters = vec![Vec::new()];
+    let mut fanning = match vertex_count {
+        0 => None,
+        _ => Some(0),
+    };
+    while let Some(f) = fanning {
+        let mut candidates = Vec::new();
+        for t in &adjacency[f] {
+            if emitted[*t] {
+                continue;
+            }
+            clusters.last_mut().unwrap().push(*t);
+            for v in triangles[*t] {
+                dead_end.push(v);
+                candidates.push(v);
+                live[v] -= 1;
+                if time - time_stamps[v] > cache_size {
+                    time_stamps[v] = time;
+                    time += 1;
+                }
+            }
+            emitted[*t] = true;
+        }
+        // the candidate which stays in the cache after fanning, and entered the cache earliest
+        let best = candidates
+            .iter()
+            .filter(|v| live[**v] > 0)
+            .filter_map(|v| {
+                let age = time - time_stamps[*v];
+                (age + 2 * live[*v] <= cache_size).then_some((age, *v))
+            })
+            .max_by_key(|(age, _)| *age)
+            .map(|(_, v)| v);
+        fanning = best.or_else(|| {
+            // a dead end: restart from the recent vertices, or from the next live vertex
+            clusters.push(Vec::new());
+            while let Some(v) = dead_end.pop() {
+                if live[v] > 0 {
+                    return Some(v);
+                }
+            }
+            while cursor < vertex_count {
+                if live[cursor] > 0 {
+                    return Some(cursor);
+                }
+                cursor += 1;
+            }
+            None
+        });
+    }
+    clusters.retain(|cluster| !cluster.is_empty());
+    clusters
+}
+
+// Sorts the clusters in descending order of the occlusion potential, the distance from
+// the center of the mesh along the normal of the cluster, so that the outer ones are drawn first.
+fn sort_clusters(
+    mesh: &PolygonMesh,
+    vertices: &[StandardVertex],
+    triangles: &[[usize; 3]],
+    clusters: Vec<Vec<usize>>,
+) -> Vec<usize> {
+    let point = |v: usize| mesh.positions()[vertices[v].pos];
+    let center = mesh.bounding_box().center();
+    let mut clusters: Vec<(f64, Vec<usize>)> = clusters
+        .into_iter()
+        .map(|cluster| {
+            let (mut normal, mut centroid, mut area) = (Vector3::zero(), Vector3::zero(), 0.0);
+            cluster.iter().for_each(|t| {
+                let [a, b, c] = triangles[*t].map(point);
+                let n = (b - a).cross(c - a);
+                let w = n.magnitude();
+                normal += n;
+                centroid += (a.to_vec() + b.to_vec() + c.to_vec()) / 3.0 * w;
+                area += w;
+            });
+            let potential = match area > 0.0 && !normal.so_small() {
+                true => (Point3::from_vec(centroid / area) - center).dot(normal.normalize()),
+                false => f64::NEG_INFINITY,
+            };
+            (potential, cluster)
+        })
+        .collect();
+    // the stable sort keeps the order of Tipsify for the equal potentials
+    clusters.sort_by(|(p, _), (q, _)| q.total_cmp(p));
+    clusters
+        .into_iter()
+        .flat_map(|(_, cluster)| cluster)
+        .collect()
+}
+
+// Creates the mesh of `triangles`, whose attributes are reindexed in order of the first use.
+fn reorder_attributes(
+    mesh: &PolygonMesh,
+    triangles: impl Iterator<Item = [StandardVertex; 3]>,
+) -> PolygonMesh {
+    fn reindex(map: &mut HashMap<usize, usize>, order: &mut Vec<usize>, idx: usize) -> usize {
+        *map.entry(idx).or_insert_with(|| {
+            order.push(idx);
+            order.len() - 1
+        })
+    }
+    let (mut pos_map, mut uv_map, mut nor_map) =
+        <(HashMap<_, _>, HashMap<_, _>, HashMap<_, _>)>::default();
+    let (mut pos_order, mut uv_order, mut nor_order) = <(Vec<_>, Vec<_>, Vec<_>)>::default();
+    let faces: Vec<[StandardVertex; 3]> = triangles
+        .map(|tri| {
+            tri.map(|v| StandardVertex {
+                pos: reindex(&mut pos_map, &mut pos_order, v.pos),
+                uv: v.uv.map(|uv| reindex(&mut uv_map, &mut uv_order, uv)),
+                nor: v.nor.map(|nor| reindex(&mut nor_map, &mut nor_order, nor)),
+            })
+        })
+        .collect();
+    let attrs = StandardAttributes {
+        positions: pos_order.into_iter().map(|i| mesh.positions()[i]).collect(),
+        uv_coords: uv_order.into_iter().map(|i| mesh.uv_coords()[i]).collect(),
+        normals: nor_order.into_iter().map(|i| mesh.normals()[i]).collect(),
+    };
+    PolygonMesh::new(attrs, Faces::from_iter(faces))
+}
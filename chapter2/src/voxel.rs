@@ -0,0 +1,405 @@
+//! Voxelization of polygon meshes and solids into occupancy grids.
+//!
+//! The surface voxels are the ones overlapping the triangles, by the separating axis test of
+//! T. Akenine-Möller. The solid voxels are the surface ones and the ones whose centers are inside,
+//! so they cover the mesh, while the interior voxels are only the ones whose centers are inside.
+//! The grids are stored densely as flags or sparsely as the indices of the occupied voxels.
+
+use crate::bvh::SignedDistance;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, Write};
+use truck_meshalgo::prelude::*;
+
+/// Which voxels are occupied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoxelMode {
+    /// the voxels overlapping the surface
+    Surface,
+    /// the voxels overlapping the surface or inside it, which needs a closed mesh
+    ///
+    /// The voxels cover the mesh, so their volume exceeds the one of the mesh by at most
+    /// the area of the mesh times the diagonal of the voxels.
+    Solid,
+    /// the voxels whose centers are inside the surface, which needs a closed mesh
+    ///
+    /// The voxels approximate the volume of the mesh, whose error is at most the area of
+    /// the mesh times the half diagonal of the voxels, but may not cover the thin parts.
+    Interior,
+}
+
+/// Errors of the voxelization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoxelError {
+    /// The solid voxelization needs a closed mesh to determine the inside.
+    NotClosed,
+    /// The mesh has no faces or the voxel size is not positive.
+    Empty,
+}
+
+impl std::fmt::Display for VoxelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoxelError::NotClosed => f.write_str("the mesh is not closed"),
+            VoxelError::Empty => {
+                f.write_str("the mesh has no faces or the voxel size is not positive")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VoxelError {}
+
+/// The storage of the occupancy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Occupancy {
+    /// the flags of all voxels, the voxel `(i, j, k)` is `i + dims[0] * (j + dims[1] * k)`
+    Dense(Vec<bool>),
+    /// the indices of the occupied voxels
+    Sparse(BTreeSet<[usize; 3]>),
+}
+
+/// Occupancy grid of the voxels of the same size.
+#[derive(Clone, Debug)]
+pub struct VoxelGrid {
+    /// the minimum corner of the voxel `(0, 0, 0)`
+    pub origin: Point3,
+    /// the length of the edges of voxels
+    pub voxel_size: f64,
+    /// the numbers of the voxels along the axes
+    pub dims: [usize; 3],
+    /// the occupied voxels
+    pub occupancy: Occupancy,
+}
+
+impl VoxelGrid {
+    /// Voxelizes `mesh` into the dense grid covering the bounding box of the mesh.
+    pub fn from_mesh(
+        mesh: &PolygonMesh,
+        voxel_size: f64,
+        mode: VoxelMode,
+    ) -> Result<Self, VoxelError> {
+        Self::voxelize(mesh, voxel_size, mode, false)
+    }
+
+    /// Voxelizes `mesh` into the sparse grid covering the bounding box of the mesh.
+    /// Only the occupied voxels are stored, without the dense flags in between.
+    pub fn from_mesh_sparse(
+        mesh: &PolygonMesh,
+        voxel_size: f64,
+        mode: VoxelMode,
+    ) -> Result<Self, VoxelError> {
+        Self::voxelize(mesh, voxel_size, mode, true)
+    }
+
+    fn voxelize(
+        mesh: &PolygonMesh,
+        voxel_size: f64,
+        mode: VoxelMode,
+        sparse: bool,
+    ) -> Result<Self, VoxelError> {
+        if mesh.faces().is_empty() || voxel_size <= 0.0 {
+            return Err(VoxelError::Empty);
+        }
+        let sdf = match mode {
+            VoxelMode::Solid | VoxelMode::Interior
+                if mesh.shell_condition() != ShellCondition::Closed =>
+            {
+                return Err(VoxelError::NotClosed)
+            }
+            VoxelMode::Solid | VoxelMode::Interior => SignedDistance::new(mesh),
+            VoxelMode::Surface => None,
+        };
+        let bdb = mesh.bounding_box();
+        let dims =
+            [0, 1, 2].map(|i| usize::max((bdb.diagonal()[i] / voxel_size).ceil() as usize, 1));
+        let occupancy = match sparse {
+            true => Occupancy::Sparse(BTreeSet::new()),
+            false => Occupancy::Dense(vec![false; dims[0] * dims[1] * dims[2]]),
+        };
+        let mut grid = Self {
+            origin: bdb.min(),
+            voxel_size,
+            dims,
+            occupancy,
+        };
+        let half = Vector3::new(1.0, 1.0, 1.0) * (voxel_size / 2.0);
+        // The interior voxels are classified only by their centers.
+        if mode != VoxelMode::Interior {
+            mesh.faces().triangle_iter().for_each(|tri| {
+                let tri = tri.map(|v| mesh.positions()[v.pos]);
+                let (min, max) = grid.index_range(tri.iter().collect());
+                for k in min[2]..=max[2] {
+                    for j in min[1]..=max[1] {
+                        for i in min[0]..=max[0] {
+                            let center = grid.center([i, j, k]);
+                            if !grid.get([i, j, k]) && triangle_box_overlap(tri, center, half) {
+                                grid.insert([i, j, k]);
+                            }
+                        }
+                    }
+                }
+            });
+        }
+        if let Some(sdf) = sdf {
+            for k in 0..dims[2] {
+                for j in 0..dims[1] {
+                    for i in 0..dims[0] {
+                        if !grid.get([i, j, k]) && sdf.distance(grid.center([i, j, k])) < 0.0 {
+                            grid.insert([i, j, k]);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    /// Voxelizes the triangulation of `shape`, e.g. a `Solid`, with the tolerance `tol`.
+    pub fn from_shape<S: MeshableShape>(
+        shape: &S,
+        tol: f64,
+        voxel_size: f64,
+        mode: VoxelMode,
+    ) -> Result<Self, VoxelError> {
+        let mut mesh = shape.triangulation(tol).to_polygon();
+        // The faces share the vertices of the polylines of the edges,
+        // and the vertices on the seams of the closed surfaces are apart a little.
+        mesh.put_together_same_attrs(10.0 * TOLERANCE);
+        Self::from_mesh(&mesh, voxel_size, mode)
+    }
+
+    /// Returns the center of the voxel `(i, j, k)`.
+    pub fn center(&self, [i, j, k]: [usize; 3]) -> Point3 {
+        self.origin + Vector3::new(i as f64 + 0.5, j as f64 + 0.5, k as f64 + 0.5) * self.voxel_size
+    }
+
+    /// Returns the box of the voxel `(i, j, k)`.
+    pub fn voxel_box(&self, [i, j, k]: [usize; 3]) -> BoundingBox<Point3> {
+        let min = self.origin + Vector3::new(i as f64, j as f64, k as f64) * self.voxel_size;
+        let max = min + Vector3::new(1.0, 1.0, 1.0) * self.voxel_size;
+        [min, max].iter().collect()
+    }
+
+    // the range of the voxels overlapping `bdb`, clamped into the grid
+    fn index_range(&self, bdb: BoundingBox<Point3>) -> ([usize; 3], [usize; 3]) {
+        let index = |p: Point3| {
+            [0, 1, 2].map(|a| {
+                let x = ((p[a] - self.origin[a]) / self.voxel_size).floor();
+                x.clamp(0.0, (self.dims[a] - 1) as f64) as usize
+            })
+        };
+        (index(bdb.min()), index(bdb.max()))
+    }
+
+    /// Returns whether the voxel `(i, j, k)` is occupied. The voxels outside the grid are empty.
+    pub fn get(&self, [i, j, k]: [usize; 3]) -> bool {
+        if i >= self.dims[0] || j >= self.dims[1] || k >= self.dims[2] {
+            return false;
+        }
+        match &self.occupancy {
+            Occupancy::Dense(flags) => flags[i + self.dims[0] * (j + self.dims[1] * k)],
+            Occupancy::Sparse(set) => set.contains(&[i, j, k]),
+        }
+    }
+
+    // marks the voxel `(i, j, k)` in the grid as occupied
+    fn insert(&mut self, [i, j, k]: [usize; 3]) {
+        match &mut self.occupancy {
+            Occupancy::Dense(flags) => flags[i + self.dims[0] * (j + self.dims[1] * k)] = true,
+            Occupancy::Sparse(set) => {
+                set.insert([i, j, k]);
+            }
+        }
+    }
+
+    /// Returns the indices of the occupied voxels, in order of `k`, `j` and `i`.
+    pub fn occupied(&self) -> Vec<[usize; 3]> {
+        let dims = self.dims;
+        match &self.occupancy {
+            Occupancy::Dense(flags) => flags
+                .iter()
+                .enumerate()
+                .filter(|(_, flag)| **flag)
+                .map(|(idx, _)| {
+                    [
+                        idx % dims[0],
+                        idx / dims[0] % dims[1],
+                        idx / (dims[0] * dims[1]),
+                    ]
+                })
+                .collect(),
+            Occupancy::Sparse(set) => {
+                let mut occupied: Vec<[usize; 3]> = set.iter().copied().collect();
+                occupied.sort_by_key(|[i, j, k]| [*k, *j, *i]);
+                occupied
+            }
+        }
+    }
+
+    /// Returns the number of the occupied voxels.
+    pub fn count(&self) -> usize {
+        match &self.occupancy {
+            Occupancy::Dense(flags) => flags.iter().filter(|flag| **flag).count(),
+            Occupancy::Sparse(set) => set.len(),
+        }
+    }
+
+    /// Converts the storage into the sparse one.
+    pub fn into_sparse(self) -> Self {
+        let occupancy = Occupancy::Sparse(self.occupied().into_iter().collect());
+        Self { occupancy, ..self }
+    }
+
+    /// Converts the storage into the dense one.
+    pub fn into_dense(self) -> Self {
+        let mut flags = vec![false; self.dims[0] * self.dims[1] * self.dims[2]];
+        self.occupied()
+            .into_iter()
+            .for_each(|[i, j, k]| flags[i + self.dims[0] * (j + self.dims[1] * k)] = true);
+        let occupancy = Occupancy::Dense(flags);
+        Self { occupancy, ..self }
+    }
+
+    /// Returns whether `point` is in an occupied voxel.
+    pub fn contains(&self, point: Point3) -> bool {
+        let local = (point - self.origin) / self.voxel_size;
+        if local.x < 0.0 || local.y < 0.0 || local.z < 0.0 {
+            return false;
+        }
+        self.get([local.x as usize, local.y as usize, local.z as usize])
+    }
+
+    /// Returns whether some occupied voxels of `self` and `other` overlap with positive volume.
+    /// The grids may have different origins and voxel sizes.
+    pub fn collides(&self, other: &VoxelGrid) -> bool {
+        self.occupied().into_iter().any(|idx| {
+            let bdb = self.voxel_box(idx);
+            let (min, max) = other.index_range(bdb);
+            (min[2]..=max[2]).any(|k| {
+                (min[1]..=max[1]).any(|j| {
+                    (min[0]..=max[0]).any(|i| {
+                        let other_box = other.voxel_box([i, j, k]);
+                        other.get([i, j, k])
+                            && (0..3).all(|a| {
+                                bdb.min()[a] < other_box.max()[a]
+                                    && other_box.min()[a] < bdb.max()[a]
+                            })
+                    })
+                })
+            })
+        })
+    }
+
+    /// Returns the blocky mesh of the faces between the occupied voxels and the empty ones.
+    /// The faces are quadrangles oriented outward, and the corners are shared.
+    pub fn to_mesh(&self) -> PolygonMesh {
+        let mut corner_map = HashMap::<[usize; 3], usize>::new();
+        let mut positions = Vec::new();
+        let mut corner = |c: [usize; 3]| {
+            *corner_map.entry(c).or_insert_with(|| {
+                let v = Vector3::new(c[0] as f64, c[1] as f64, c[2] as f64);
+                positions.push(self.origin + v * self.voxel_size);
+                positions.len() - 1
+            })
+        };
+        let mut faces = Faces::default();
+        self.occupied().into_iter().for_each(|idx| {
+            for axis in 0..3 {
+                let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+                for positive in [false, true] {
+                    let mut neighbor = idx;
+                    let empty = match positive {
+                        true => {
+                            neighbor[axis] += 1;
+                            !self.get(neighbor)
+                        }
+                        false => {
+                            idx[axis] == 0 || {
+                                neighbor[axis] -= 1;
+                                !self.get(neighbor)
+                            }
+                        }
+                    };
+                    if !empty {
+                        continue;
+                    }
+                    // the corners of the face counter-clockwise around the outward normal
+                    let mut base = idx;
+                    base[axis] += positive as usize;
+                    let shift = |du: usize, dv: usize| {
+                        let mut c = base;
+                        c[u] += du;
+                        c[v] += dv;
+                        c
+                    };
+                    let mut quad =
+                        [shift(0, 0), shift(1, 0), shift(1, 1), shift(0, 1)].map(&mut corner);
+                    if !positive {
+                        quad.reverse();
+                    }
+                    faces.push(quad);
+                }
+            }
+        });
+        let attrs = StandardAttributes {
+            positions,
+            ..Default::default()
+        };
+        PolygonMesh::new(attrs, faces)
+    }
+
+    /// Writes the grid as a legacy VTK file of structured points,
+    /// whose cell data `occupancy` is 1 for the occupied voxels.
+    pub fn write_vtk<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let [nx, ny, nz] = self.dims;
+        let o = self.origin;
+        writeln!(writer, "# vtk DataFile Version 3.0\noccupancy grid\nASCII")?;
+        writeln!(writer, "DATASET STRUCTURED_POINTS")?;
+        writeln!(writer, "DIMENSIONS {} {} {}", nx + 1, ny + 1, nz + 1)?;
+        writeln!(writer, "ORIGIN {} {} {}", o.x, o.y, o.z)?;
+        let s = self.voxel_size;
+        writeln!(writer, "SPACING {s} {s} {s}")?;
+        writeln!(writer, "CELL_DATA {}", nx * ny * nz)?;
+        writeln!(
+            writer,
+            "SCALARS occupancy unsigned_char 1\nLOOKUP_TABLE default"
+        )?;
+        self.write_flags(|flag| writeln!(writer, "{}", flag as u8))
+    }
+
+    /// Writes the occupancy as the raw bytes, 1 for the occupied voxels and 0 for the empty ones.
+    /// The index `i` runs fastest and `k` slowest.
+    pub fn write_raw<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.write_flags(|flag| writer.write_all(&[flag as u8]))
+    }
+
+    fn write_flags(&self, mut write: impl FnMut(bool) -> io::Result<()>) -> io::Result<()> {
+        for k in 0..self.dims[2] {
+            for j in 0..self.dims[1] {
+                for i in 0..self.dims[0] {
+                    write(self.get([i, j, k]))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether the triangle and the closed box of `center` and the half sizes `half` overlap.
+pub fn triangle_box_overlap(tri: [Point3; 3], center: Point3, half: Vector3) -> bool {
+    let v = tri.map(|p| p - center);
+    let edges = [v[1] - v[0], v[2] - v[1], v[0] - v[2]];
+    let units = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+    let normal = edges[0].cross(edges[1]);
+    let axes = units
+        .into_iter()
+        .chain([normal])
+        .chain(units.into_iter().flat_map(|e| edges.map(|f| e.cross(f))));
+    axes.filter(|axis| !axis.so_small()).all(|axis| {
+        let projections = v.map(|p| p.dot(axis));
+        let min = projections.into_iter().fold(f64::INFINITY, f64::min);
+        let max = projections.into_iter().fold(f64::NEG_INFINITY, f64::max);
+        let radius = half.x * axis.x.abs() + half.y * axis.y.abs() + half.z * axis.z.abs();
+        min <= radius && max >= -radius
+    })
+}
@@ -0,0 +1,71 @@
+use chapter2::{fixtures, voxel::*};
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+
+/// Output the contents of `polygon` to the file specified by `path`.
+fn write_polygon(polygon: &PolygonMesh, path: &str) {
+    let mut obj = std::fs::File::create(path).unwrap();
+    obj::write(polygon, &mut obj).unwrap();
+}
+
+fn main() {
+    // the sphere of section 2.4
+    let sphere = fixtures::sphere();
+    let radius = sphere.bounding_box().diagonal().x / 2.0;
+    let size = radius / 10.0;
+
+    let solid = VoxelGrid::from_mesh(&sphere, size, VoxelMode::Solid).unwrap();
+    let surface = VoxelGrid::from_mesh(&sphere, size, VoxelMode::Surface).unwrap();
+    let volume = solid.count() as f64 * size.powi(3);
+    println!(
+        "{} solid voxels, {} surface voxels, volume {volume:.4} (sphere: {:.4})",
+        solid.count(),
+        surface.count(),
+        4.0 * PI * radius.powi(3) / 3.0
+    );
+    // The surface voxels are a part of the solid ones, and the solid voxels enclose the sphere.
+    assert!(surface.occupied().iter().all(|idx| solid.get(*idx)));
+    assert!(volume > sphere.volume() && volume < 1.3 * sphere.volume());
+    // The hollow of the surface voxels is empty.
+    let center = sphere.bounding_box().center();
+    assert!(solid.contains(center) && !surface.contains(center));
+
+    // The sparse grid has the same voxels, and converts to the dense one and back.
+    let sparse = VoxelGrid::from_mesh_sparse(&sphere, size, VoxelMode::Solid).unwrap();
+    assert!(matches!(sparse.occupancy, Occupancy::Sparse(_)));
+    assert_eq!(sparse.occupied(), solid.occupied());
+    assert_eq!(sparse.clone().into_dense().occupancy, solid.occupancy);
+    assert_eq!(solid.clone().into_sparse().occupancy, sparse.occupancy);
+
+    // The solid voxelization needs a closed mesh.
+    let faces: Vec<Vec<StandardVertex>> = sphere.face_iter().skip(1).map(<[_]>::to_vec).collect();
+    let open = PolygonMesh::new(sphere.attributes().clone(), Faces::from_iter(faces));
+    assert!(VoxelGrid::from_mesh(&open, size, VoxelMode::Surface).is_ok());
+    assert_eq!(
+        VoxelGrid::from_mesh(&open, size, VoxelMode::Solid).err(),
+        Some(VoxelError::NotClosed)
+    );
+
+    // collision checks of the spheres
+    let shifted = |distance: f64| {
+        let mut shifted = sphere.clone();
+        shifted
+            .positions_mut()
+            .iter_mut()
+            .for_each(|p| p.x += distance);
+        VoxelGrid::from_mesh(&shifted, size * 0.7, VoxelMode::Solid).unwrap()
+    };
+    assert!(solid.collides(&shifted(1.5 * radius)));
+    assert!(!solid.collides(&shifted(2.5 * radius)));
+
+    // The blocky mesh has the volume of the voxels.
+    let blocks = solid.to_mesh();
+    assert!(f64::abs(blocks.volume() - volume) < 1.0e-9);
+    write_polygon(&blocks, "sphere-voxels.obj");
+    let mut vtk = std::fs::File::create("sphere-voxels.vtk").unwrap();
+    solid.write_vtk(&mut vtk).unwrap();
+    let mut raw = Vec::new();
+    solid.write_raw(&mut raw).unwrap();
+    assert_eq!(raw.len(), solid.dims.iter().product::<usize>());
+    std::fs::write("sphere-voxels.raw", raw).unwrap();
+}
@@ -0,0 +1,82 @@
+use chapter2::{obj_writer::*, polyhedra};
+use truck_meshalgo::prelude::*;
+
+fn write_to_string(mesh: &PolygonMesh, options: &ObjOptions) -> String {
+    let mut buf = Vec::new();
+    write_obj(mesh, options, &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+fn main() {
+    // the sphere of section 2.4, whose normals on the edges of the cube are written twice
+    let sphere = polyhedra::sphere(8);
+
+    // The default options are the ones of `obj::write`.
+    let mut truck = Vec::new();
+    obj::write(&sphere, &mut truck).unwrap();
+    let default = write_to_string(&sphere, &ObjOptions::default());
+    assert_eq!(default.as_bytes(), truck.as_slice());
+    // The output is the same every time.
+    assert_eq!(write_to_string(&sphere, &ObjOptions::default()), default);
+    // The negative zeros are kept by default, as `obj::write` does.
+    let mut flipped = sphere.clone();
+    flipped.positions_mut()[0] = Point3::new(-0.0, 0.0, -0.0);
+    let mut truck = Vec::new();
+    obj::write(&flipped, &mut truck).unwrap();
+    let default = write_to_string(&flipped, &ObjOptions::default());
+    assert_eq!(default.as_bytes(), truck.as_slice());
+    assert!(default.starts_with("v -0.0000000000e0 0.0000000000e0 -0.0000000000e0\n"));
+    let options = ObjOptions {
+        normalize_zeros: true,
+        ..Default::default()
+    };
+    let normalized = write_to_string(&flipped, &options);
+    assert!(normalized.starts_with("v 0.0000000000e0 0.0000000000e0 0.0000000000e0\n"));
+
+    // the smaller file with the fixed notation of the lower precision
+    let options = ObjOptions {
+        precision: 4,
+        format: FloatFormat::Fixed,
+        deduplicate_normals: true,
+        normalize_zeros: true,
+        ..Default::default()
+    };
+    let small = write_to_string(&sphere, &options);
+    println!("{} bytes -> {} bytes", default.len(), small.len());
+    assert!(3 * small.len() < 2 * default.len());
+    assert!(!small.contains("-0.0000 ") && !small.contains("-0.0000\n"));
+    let read = obj::read(small.as_bytes()).unwrap();
+    println!(
+        "{} normals -> {} normals",
+        sphere.normals().len(),
+        read.normals().len()
+    );
+    assert!(read.normals().len() < sphere.normals().len());
+    read.positions()
+        .iter()
+        .zip(sphere.positions())
+        .for_each(|(p, q)| assert!(p.distance(*q) < 1.0e-4));
+    // The deduplication does not change the normals of the vertices.
+    let (faces0, faces1) = (sphere.faces(), read.faces());
+    faces0
+        .face_iter()
+        .zip(faces1.face_iter())
+        .for_each(|(f0, f1)| {
+            f0.iter().zip(f1).for_each(|(v0, v1)| {
+                let n0 = sphere.normals()[v0.nor.unwrap()];
+                let n1 = read.normals()[v1.nor.unwrap()];
+                assert!(n0.distance(n1) < 1.0e-4);
+            })
+        });
+
+    // the shortest texts are read as the same numbers
+    let hexa = polyhedra::hexahedron();
+    let options = ObjOptions {
+        format: FloatFormat::Shortest,
+        ..Default::default()
+    };
+    let shortest = write_to_string(&hexa, &options);
+    let read = obj::read(shortest.as_bytes()).unwrap();
+    assert_eq!(read.positions(), hexa.positions());
+    std::fs::write("hexahedron-shortest.obj", shortest).unwrap();
+}
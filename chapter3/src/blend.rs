@@ -0,0 +1,922 @@
+//! Fillets and chamfers on the edges of polyhedral solids.
+//!
+//! This is not a general blending: only the polyhedral parts of the solids are supported.
+//! The blended edges have to be lines between planar faces, and the vertices at their ends
+//! have to be shared by three planar faces, e.g. the edges of the boxes, the wedges and the
+//! prisms. The edges on the curved faces, such as the edges of the cylinders and all the
+//! edges of the bottle of section 3.3, are skipped with `BlendError::NotPlanar`.
+//!
+//! A blend is bounded by the lines on the faces of the edge, and at each end by
+//! - the third face of the vertex, if only the edge is blended at the vertex,
+//! - the miter with the other blend, if two edges are blended at the vertex, or
+//! - the vertex blend, if all the three edges are blended at the vertex: the spherical
+//!   triangle of the fillets, or the triangle of the chamfers.
+//!
+//! The section of a fillet perpendicular to the edge is the circular arc of the radius,
+//! which changes linearly along the edge for the variable fillets. The edges which cannot
+//! be blended are skipped and reported with the reasons.
+
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
+use std::result::Result;
+use truck_modeling::*;
+
+/// The blends of the edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Blend {
+    /// the fillet of the radius
+    Fillet(f64),
+    /// the fillet whose radius changes linearly from the first one at the front vertex of
+    /// the edge to the second one at the back vertex
+    VariableFillet(f64, f64),
+    /// the chamfer of the distance from the edge on both faces
+    Chamfer(f64),
+    /// the chamfer of the distance from the edge on the left face, seen from the outside of
+    /// the solid, and of the angle from the left face
+    AngledChamfer(f64, Rad<f64>),
+}
+
+impl Blend {
+    fn is_valid(&self) -> bool {
+        let positive = |x: f64| x.is_finite() && x > 0.0;
+        match *self {
+            Blend::Fillet(radius) => positive(radius),
+            Blend::VariableFillet(radius0, radius1) => positive(radius0) && positive(radius1),
+            Blend::Chamfer(distance) => positive(distance),
+            Blend::AngledChamfer(distance, angle) => {
+                positive(distance) && positive(angle.0) && angle.0 < PI
+            }
+        }
+    }
+
+    fn is_fillet(&self) -> bool { matches!(self, Blend::Fillet(_) | Blend::VariableFillet(..)) }
+}
+
+/// The errors of the blends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendError {
+    /// The solid is not bounded by one closed shell.
+    InvalidSolid,
+    /// A radius or a distance is not positive and finite, or an angle is not in (0, π).
+    InvalidDimension,
+    /// The edge is not on the boundary of the solid.
+    UnknownEdge,
+    /// The edge is not a line between planar faces.
+    NotPlanar,
+    /// The faces at the edge make a reflex or straight angle.
+    ConcaveEdge,
+    /// A vertex of the edge is not shared by three planar faces and three lines.
+    UnsupportedVertex,
+    /// The blends do not meet at a vertex of the edge, e.g. the fillets of different radii.
+    MismatchedCorner,
+    /// The blend does not fit in the faces of the edge.
+    TooLarge,
+    /// The blended faces do not make a closed shell.
+    Failed,
+}
+
+impl std::fmt::Display for BlendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlendError::InvalidSolid => f.write_str("the solid is not bounded by a closed shell"),
+            BlendError::InvalidDimension => f.write_str("the dimension of a blend is invalid"),
+            BlendError::UnknownEdge => f.write_str("the edge is not in the solid"),
+            BlendError::NotPlanar => f.write_str("the edge is not a line between planar faces"),
+            BlendError::ConcaveEdge => f.write_str("the edge is not convex"),
+            BlendError::UnsupportedVertex => {
+                f.write_str("a vertex of the edge is not shared by three planar faces")
+            }
+            BlendError::MismatchedCorner => {
+                f.write_str("the blends do not meet at a vertex of the edge")
+            }
+            BlendError::TooLarge => f.write_str("the blend is too large for the faces"),
+            BlendError::Failed => f.write_str("the blended faces do not make a closed shell"),
+        }
+    }
+}
+
+impl std::error::Error for BlendError {}
+
+/// The blended solid and the skipped edges.
+#[derive(Clone, Debug)]
+pub struct Blended {
+    /// the solid whose edges are blended
+    pub solid: Solid,
+    /// the edges which could not be blended, with the reasons
+    pub skipped: Vec<(Edge, BlendError)>,
+}
+
+/// Rounds the edges of `solid` by the fillets of `radius`.
+pub fn fillet(solid: &Solid, edges: &[Edge], radius: f64) -> Result<Blended, BlendError> {
+    let blends: Vec<_> = edges
+        .iter()
+        .map(|edge| (edge.clone(), Blend::Fillet(radius)))
+        .collect();
+    blend(solid, &blends)
+}
+
+/// Bevels the edges of `solid` by the chamfers of `distance`.
+pub fn chamfer(solid: &Solid, edges: &[Edge], distance: f64) -> Result<Blended, BlendError> {
+    let blends: Vec<_> = edges
+        .iter()
+        .map(|edge| (edge.clone(), Blend::Chamfer(distance)))
+        .collect();
+    blend(solid, &blends)
+}
+
+/// Blends the edges of `solid`. The edges which cannot be blended are skipped, and the
+/// later blends of the same edge are ignored.
+pub fn blend(solid: &Solid, blends: &[(Edge, Blend)]) -> Result<Blended, BlendError> {
+    let shell = match solid.boundaries().as_slice() {
+        [shell] if shell.shell_condition() == ShellCondition::Closed => shell.clone(),
+        _ => return Err(BlendError::InvalidSolid),
+    };
+    if !blends.iter().all(|(_, blend)| blend.is_valid()) {
+        return Err(BlendError::InvalidDimension);
+    }
+    let topology = Topology::new(&shell);
+    let mut sections = Vec::<Section>::new();
+    let mut skipped = Vec::new();
+    for (edge, blend) in blends {
+        if sections.iter().any(|section| section.edge.is_same(edge)) {
+            continue;
+        }
+        match topology.section(edge, *blend) {
+            Ok(section) => sections.push(section),
+            Err(error) => skipped.push((edge.clone(), error)),
+        }
+    }
+    // Skips the edges one by one until all the blends meet.
+    let corners = loop {
+        match topology.corners(&sections) {
+            Ok(corners) => break corners,
+            Err((idx, error)) => skipped.push((sections.remove(idx).edge, error)),
+        }
+    };
+    let solid = topology.build(&sections, &corners)?;
+    Ok(Blended { solid, skipped })
+}
+
+// the faces of the shell and the adjacency of the edges
+struct Topology {
+    faces: Vec<Face>,
+    // the points and the outward normals of the planar faces
+    planes: Vec<Option<(Point3, Vector3)>>,
+    // the absolute edges and their left and right faces
+    edges: HashMap<EdgeID, (Edge, [usize; 2])>,
+    // the edges at the vertices
+    vertex_edges: HashMap<VertexID, Vec<EdgeID>>,
+}
+
+// a blended edge
+struct Section {
+    // the absolute edge
+    edge: Edge,
+    blend: Blend,
+    // the left and right faces
+    faces: [usize; 2],
+    // the front and back points
+    ends: [Point3; 2],
+    // the outward normals of the left and right faces
+    normals: [Vector3; 2],
+}
+
+// the keys of the new vertices at the blended vertices
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Corner {
+    // the point on the edge which is not blended
+    OnEdge(VertexID, EdgeID),
+    // the point in the face which has two blended edges at the vertex
+    InFace(VertexID, usize),
+}
+
+// the curve at the end of a blend, from the corner on the left face of the blend to the one
+// on the right face, in the homogeneous coordinates
+struct EndCurve {
+    points: Vec<Vector4>,
+    corners: [Corner; 2],
+}
+
+// the surfaces of the vertices where three edges are blended
+enum VertexBlend {
+    // the plane through the corners of the chamfers
+    Triangle,
+    // the sphere tangent to the three fillets
+    Sphere { center: Point3, radius: f64 },
+}
+
+// the new vertices and curves at the blended vertices
+#[derive(Default)]
+struct Corners {
+    vertices: HashSet<VertexID>,
+    points: HashMap<Corner, Point3>,
+    // the corners of the sections on their left and right faces at the vertices
+    sides: HashMap<(VertexID, EdgeID), [Corner; 2]>,
+    curves: Vec<EndCurve>,
+    // the indices of the end curves of the sections at the vertices
+    ends: HashMap<(VertexID, EdgeID), usize>,
+    // the indices of the end curves on the third faces of the vertices
+    third_faces: HashMap<(VertexID, usize), usize>,
+    vertex_blends: Vec<(VertexID, VertexBlend)>,
+    // the ranges of the parameters of the sections, which cover the blend faces
+    ranges: Vec<[f64; 2]>,
+}
+
+impl Topology {
+    fn new(shell: &Shell) -> Self {
+        let faces: Vec<Face> = shell.face_iter().cloned().collect();
+        let planes = faces
+            .iter()
+            .map(|face| match face.oriented_surface() {
+                Surface::Plane(plane) => Some((plane.origin(), plane.normal())),
+                _ => None,
+            })
+            .collect();
+        let mut edges = HashMap::<EdgeID, (Edge, [usize; 2])>::new();
+        for (idx, face) in faces.iter().enumerate() {
+            for edge in face.edge_iter() {
+                let entry = edges
+                    .entry(edge.id())
+                    .or_insert_with(|| (edge.absolute_clone(), [0; 2]));
+                entry.1[!edge.orientation() as usize] = idx;
+            }
+        }
+        let mut vertex_edges = HashMap::<VertexID, Vec<EdgeID>>::new();
+        for (id, (edge, _)) in &edges {
+            vertex_edges.entry(edge.front().id()).or_default().push(*id);
+            vertex_edges.entry(edge.back().id()).or_default().push(*id);
+        }
+        Self {
+            faces,
+            planes,
+            edges,
+            vertex_edges,
+        }
+    }
+
+    fn section(&self, edge: &Edge, blend: Blend) -> Result<Section, BlendError> {
+        let (edge, faces) = self.edges.get(&edge.id()).ok_or(BlendError::UnknownEdge)?;
+        let normals = match (edge.curve(), self.planes[faces[0]], self.planes[faces[1]]) {
+            (Curve::Line(_), Some((_, n0)), Some((_, n1))) => [n0, n1],
+            _ => return Err(BlendError::NotPlanar),
+        };
+        let section = Section {
+            edge: edge.clone(),
+            blend,
+            faces: *faces,
+            ends: [edge.front().point(), edge.back().point()],
+            normals,
+        };
+        // The left face goes below the right face at the convex edges.
+        if section.inwards()[0].dot(normals[1]) > -TOLERANCE {
+            return Err(BlendError::ConcaveEdge);
+        }
+        if let Blend::AngledChamfer(_, angle) = blend {
+            if angle.0 + section.angle() >= PI - TOLERANCE {
+                return Err(BlendError::TooLarge);
+            }
+        }
+        Ok(section)
+    }
+
+    // Returns the three faces at the vertex, if the vertex is shared by three planar faces
+    // and three lines.
+    fn vertex_faces(&self, edge_ids: &[EdgeID]) -> Option<[usize; 3]> {
+        let mut faces = Vec::new();
+        for id in edge_ids {
+            let (edge, edge_faces) = &self.edges[id];
+            if !matches!(edge.curve(), Curve::Line(_)) {
+                return None;
+            }
+            faces.extend(
+                edge_faces
+                    .iter()
+                    .filter(|face| self.planes[**face].is_some()),
+            );
+        }
+        faces.sort();
+        faces.dedup();
+        match (edge_ids.len(), faces.as_slice()) {
+            (3, [f0, f1, f2]) => Some([*f0, *f1, *f2]),
+            _ => None,
+        }
+    }
+
+    // Returns the new vertices and curves at the blended vertices, or the index of the
+    // section to be skipped.
+    fn corners(&self, sections: &[Section]) -> Result<Corners, (usize, BlendError)> {
+        let blended: HashMap<EdgeID, usize> = sections
+            .iter()
+            .enumerate()
+            .map(|(idx, section)| (section.edge.id(), idx))
+            .collect();
+        let mut corners = Corners::default();
+        let mut vertices = Vec::new();
+        for section in sections {
+            for vertex in [section.edge.front(), section.edge.back()] {
+                if corners.vertices.insert(vertex.id()) {
+                    vertices.push(vertex.id());
+                }
+            }
+        }
+        for vertex in &vertices {
+            let edge_ids = &self.vertex_edges[vertex];
+            let mut at_vertex: Vec<usize> = edge_ids
+                .iter()
+                .filter_map(|id| blended.get(id).copied())
+                .collect();
+            at_vertex.sort();
+            // the section to be skipped
+            let last = *at_vertex.last().unwrap();
+            let faces = self
+                .vertex_faces(edge_ids)
+                .ok_or((last, BlendError::UnsupportedVertex))?;
+            let plane = |face: usize| self.planes[face].unwrap();
+            let corner = |face: usize| {
+                let unblended = edge_ids
+                    .iter()
+                    .find(|id| !blended.contains_key(id) && self.edges[id].1.contains(&face));
+                match unblended {
+                    Some(id) => Corner::OnEdge(*vertex, *id),
+                    None => Corner::InFace(*vertex, face),
+                }
+            };
+            for idx in &at_vertex {
+                let section = &sections[*idx];
+                let sides = section.faces.map(corner);
+                corners.sides.insert((*vertex, section.edge.id()), sides);
+            }
+            let mismatched = (last, BlendError::MismatchedCorner);
+            match at_vertex.as_slice() {
+                [idx] => {
+                    let section = &sections[*idx];
+                    let third = *faces
+                        .iter()
+                        .find(|face| !section.faces.contains(face))
+                        .unwrap();
+                    for side in 0..2 {
+                        let point =
+                            intersect_plane(section.line(side), plane(third)).ok_or(mismatched)?;
+                        corners.points.insert(corner(section.faces[side]), point);
+                    }
+                    let points = project(&section.profile(0.0), section.apex(), plane(third))
+                        .ok_or(mismatched)?;
+                    corners
+                        .third_faces
+                        .insert((*vertex, third), corners.curves.len());
+                    corners.push_curve(*vertex, section, points);
+                }
+                [idx0, idx1] => {
+                    let (section0, section1) = (&sections[*idx0], &sections[*idx1]);
+                    let common = *section0
+                        .faces
+                        .iter()
+                        .find(|face| section1.faces.contains(face))
+                        .unwrap();
+                    let side = |section: &Section, face: usize| {
+                        section.faces.iter().position(|f| *f == face).unwrap()
+                    };
+                    let [face0, face1] = [section0, section1]
+                        .map(|section| section.faces[1 - side(section, common)]);
+                    let line0 = section0.line(side(section0, face0));
+                    let line1 = section1.line(side(section1, face1));
+                    let (point0, point1) = match (
+                        intersect_plane(line0, plane(face1)),
+                        intersect_plane(line1, plane(face0)),
+                    ) {
+                        (Some(point0), Some(point1)) if point0.near(&point1) => (point0, point1),
+                        _ => return Err(mismatched),
+                    };
+                    let inner = intersect_lines(
+                        section0.line(side(section0, common)),
+                        section1.line(side(section1, common)),
+                    )
+                    .ok_or(mismatched)?;
+                    // the plane of the miter, which contains the normal of the common face
+                    let normal = (point0 - inner).cross(plane(common).1);
+                    if normal.magnitude().so_small() {
+                        return Err(mismatched);
+                    }
+                    let miter = (inner, normal.normalize());
+                    let points0 = project(&section0.profile(0.0), section0.apex(), miter);
+                    let points1 = project(&section1.profile(0.0), section1.apex(), miter);
+                    let points = match (points0, points1) {
+                        (Some(points0), Some(points1)) if same_curve(&points0, &points1) => points0,
+                        _ => return Err(mismatched),
+                    };
+                    corners
+                        .points
+                        .insert(corner(face0), point0.midpoint(point1));
+                    corners.points.insert(corner(common), inner);
+                    corners
+                        .ends
+                        .insert((*vertex, section1.edge.id()), corners.curves.len());
+                    corners.push_curve(*vertex, section0, points);
+                }
+                _ => {
+                    let sections: Vec<&Section> =
+                        at_vertex.iter().map(|idx| &sections[*idx]).collect();
+                    // the corners in the faces, at the intersections of the lines of the blends
+                    let mut points = [Point3::origin(); 3];
+                    for (point, face) in points.iter_mut().zip(faces) {
+                        let lines: Vec<[Point3; 2]> = sections
+                            .iter()
+                            .filter_map(|section| {
+                                let side = section.faces.iter().position(|f| *f == face)?;
+                                Some(section.line(side))
+                            })
+                            .collect();
+                        *point = intersect_lines(lines[0], lines[1]).ok_or(mismatched)?;
+                        corners.points.insert(Corner::InFace(*vertex, face), *point);
+                    }
+                    let vertex_blend = if sections.iter().all(|section| !section.blend.is_fillet())
+                    {
+                        let normal = (points[1] - points[0]).cross(points[2] - points[0]);
+                        if normal.magnitude().so_small() {
+                            return Err(mismatched);
+                        }
+                        let triangle = (points[0], normal.normalize());
+                        for section in &sections {
+                            let points = project(&section.profile(0.0), section.apex(), triangle)
+                                .ok_or(mismatched)?;
+                            corners.push_curve(*vertex, section, points);
+                        }
+                        VertexBlend::Triangle
+                    } else {
+                        let radius = match sections[0].blend {
+                            Blend::Fillet(radius) => radius,
+                            _ => return Err(mismatched),
+                        };
+                        if sections
+                            .iter()
+                            .any(|section| section.blend != Blend::Fillet(radius))
+                        {
+                            return Err(mismatched);
+                        }
+                        // the center of the sphere is at the distance `radius` from the faces
+                        let [(o0, n0), (o1, n1), (o2, n2)] = faces.map(plane);
+                        let matrix = Matrix3::from_cols(n0, n1, n2).transpose();
+                        let offsets = Vector3::new(
+                            n0.dot(o0.to_vec()),
+                            n1.dot(o1.to_vec()),
+                            n2.dot(o2.to_vec()),
+                        );
+                        let center = Point3::from_vec(
+                            matrix.invert().ok_or(mismatched)?
+                                * (offsets - Vector3::new(radius, radius, radius)),
+                        );
+                        for section in &sections {
+                            let end = (center, section.direction());
+                            let points = project(&section.profile(0.0), section.apex(), end)
+                                .ok_or(mismatched)?;
+                            corners.push_curve(*vertex, section, points);
+                        }
+                        VertexBlend::Sphere { center, radius }
+                    };
+                    corners.vertex_blends.push((*vertex, vertex_blend));
+                }
+            }
+        }
+        corners.check_lengths(self, sections, &blended)?;
+        corners.ranges = sections
+            .iter()
+            .enumerate()
+            .map(|(idx, section)| corners.range(section).ok_or((idx, BlendError::TooLarge)))
+            .collect::<Result<_, _>>()?;
+        Ok(corners)
+    }
+
+    fn build(&self, sections: &[Section], corners: &Corners) -> Result<Solid, BlendError> {
+        let vertices: HashMap<Corner, Vertex> = corners
+            .points
+            .iter()
+            .map(|(corner, point)| (*corner, Vertex::new(*point)))
+            .collect();
+        // the lines of the blends on the left and right faces
+        let lines: HashMap<EdgeID, [Edge; 2]> = sections
+            .iter()
+            .map(|section| {
+                let id = section.edge.id();
+                let [front, back] = [section.edge.front(), section.edge.back()]
+                    .map(|vertex| &corners.sides[&(vertex.id(), id)]);
+                let lines = [0, 1].map(|side| {
+                    let (v0, v1) = (&vertices[&front[side]], &vertices[&back[side]]);
+                    Edge::new(v0, v1, Curve::Line(Line(v0.point(), v1.point())))
+                });
+                (id, lines)
+            })
+            .collect();
+        // the edges which are not blended but are trimmed at the blended vertices
+        let mut trimmed = HashMap::<EdgeID, Edge>::new();
+        for (id, (edge, _)) in &self.edges {
+            let ends = [edge.front(), edge.back()];
+            if lines.contains_key(id) || !ends.iter().any(|v| corners.vertices.contains(&v.id())) {
+                continue;
+            }
+            let [v0, v1] = ends.map(|vertex| match corners.vertices.contains(&vertex.id()) {
+                true => vertices[&Corner::OnEdge(vertex.id(), *id)].clone(),
+                false => vertex.clone(),
+            });
+            let line = Curve::Line(Line(v0.point(), v1.point()));
+            trimmed.insert(*id, Edge::new(&v0, &v1, line));
+        }
+        let end_edges: Vec<Edge> = corners
+            .curves
+            .iter()
+            .map(|curve| {
+                let [v0, v1] = curve.corners.map(|corner| &vertices[&corner]);
+                let curve = match curve.points.len() {
+                    2 => Curve::Line(Line(v0.point(), v1.point())),
+                    n => Curve::NurbsCurve(NurbsCurve::new(BSplineCurve::new(
+                        KnotVec::bezier_knot(n - 1),
+                        curve.points.clone(),
+                    ))),
+                };
+                Edge::new(v0, v1, curve)
+            })
+            .collect();
+
+        let mut faces = Vec::new();
+        for (idx, face) in self.faces.iter().enumerate() {
+            if !face
+                .vertex_iter()
+                .any(|vertex| corners.vertices.contains(&vertex.id()))
+            {
+                faces.push(face.clone());
+                continue;
+            }
+            let wires = face
+                .boundaries()
+                .iter()
+                .map(|wire| {
+                    let mut new_wire = Wire::new();
+                    for edge in wire.edge_iter() {
+                        let side = !edge.orientation() as usize;
+                        let new_edge = match (lines.get(&edge.id()), trimmed.get(&edge.id())) {
+                            (Some(lines), _) => lines[side].clone(),
+                            (None, Some(trimmed)) => trimmed.clone(),
+                            (None, None) => edge.absolute_clone(),
+                        };
+                        new_wire.push_back(match edge.orientation() {
+                            true => new_edge,
+                            false => new_edge.inverse(),
+                        });
+                        // the end of the blend on the third face of the vertex
+                        if let Some(curve) = corners.third_faces.get(&(edge.back().id(), idx)) {
+                            let front = new_wire.back_vertex().unwrap().clone();
+                            new_wire.push_back(starting_at(&end_edges[*curve], &front));
+                        }
+                    }
+                    new_wire
+                })
+                .collect();
+            let face = Face::try_new(wires, face.oriented_surface());
+            faces.push(face.map_err(|_| BlendError::Failed)?);
+        }
+        for (section, range) in sections.iter().zip(&corners.ranges) {
+            let id = section.edge.id();
+            let [left, right] = &lines[&id];
+            let [front, back] = [section.edge.front(), section.edge.back()]
+                .map(|vertex| &end_edges[corners.ends[&(vertex.id(), id)]]);
+            let wire: Wire = vec![
+                left.inverse(),
+                starting_at(front, left.front()),
+                right.clone(),
+                starting_at(back, right.back()),
+            ]
+            .into();
+            let face = Face::try_new(vec![wire], section.surface(*range));
+            faces.push(face.map_err(|_| BlendError::Failed)?);
+        }
+        for (vertex, vertex_blend) in &corners.vertex_blends {
+            // the end edges in the opposite direction to the blend faces
+            let mut edges: Vec<Edge> = sections
+                .iter()
+                .filter_map(|section| {
+                    let id = section.edge.id();
+                    let end = &end_edges[*corners.ends.get(&(*vertex, id))?];
+                    let [left, right] = &lines[&id];
+                    Some(match section.edge.front().id() == *vertex {
+                        true => starting_at(end, left.front()).inverse(),
+                        false => starting_at(end, right.back()).inverse(),
+                    })
+                })
+                .collect();
+            let mut wire = Wire::from(vec![edges.remove(0)]);
+            while !edges.is_empty() {
+                let back = wire.back_vertex().unwrap().id();
+                let next = edges.iter().position(|edge| edge.front().id() == back);
+                wire.push_back(edges.remove(next.ok_or(BlendError::Failed)?));
+            }
+            let points: Vec<Point3> = wire.vertex_iter().map(|vertex| vertex.point()).collect();
+            let surface = match vertex_blend {
+                VertexBlend::Triangle => {
+                    let mut plane = Plane::new(points[0], points[1], points[2]);
+                    let outward: Vector3 = self
+                        .vertex_faces(&self.vertex_edges[vertex])
+                        .unwrap()
+                        .iter()
+                        .map(|face| self.planes[*face].unwrap().1)
+                        .sum();
+                    if plane.normal().dot(outward) < 0.0 {
+                        plane.invert();
+                    }
+                    Surface::Plane(plane)
+                }
+                VertexBlend::Sphere { center, radius } => {
+                    let middle = points
+                        .iter()
+                        .map(|point| (point - center).normalize())
+                        .sum();
+                    let mut surface = sphere_patch(*center, *radius, middle);
+                    let outward = surface.subs(0.5, 0.5) - center;
+                    if surface.normal(0.5, 0.5).dot(outward) < 0.0 {
+                        surface.invert();
+                    }
+                    Surface::NurbsSurface(surface)
+                }
+            };
+            let face = Face::try_new(vec![wire], surface);
+            faces.push(face.map_err(|_| BlendError::Failed)?);
+        }
+        Solid::try_new(vec![faces.into()]).map_err(|_| BlendError::Failed)
+    }
+}
+
+impl Section {
+    fn direction(&self) -> Vector3 { (self.ends[1] - self.ends[0]).normalize() }
+
+    fn point(&self, s: f64) -> Point3 { self.ends[0] + (self.ends[1] - self.ends[0]) * s }
+
+    // the parameter of the projection of the point to the edge
+    fn parameter(&self, point: Point3) -> f64 {
+        let vector = self.ends[1] - self.ends[0];
+        (point - self.ends[0]).dot(vector) / vector.magnitude2()
+    }
+
+    // the directions perpendicular to the edge into the left and right faces
+    fn inwards(&self) -> [Vector3; 2] {
+        let direction = self.direction();
+        [
+            self.normals[0].cross(direction),
+            direction.cross(self.normals[1]),
+        ]
+    }
+
+    // the angle between the faces
+    fn angle(&self) -> f64 {
+        let [u0, u1] = self.inwards();
+        f64::acos(u0.dot(u1).clamp(-1.0, 1.0))
+    }
+
+    // the distances from the edge to the blend on the left and right faces
+    fn distances(&self, s: f64) -> [f64; 2] {
+        let angle = self.angle();
+        match self.blend {
+            Blend::Fillet(radius) => [radius / f64::tan(angle / 2.0); 2],
+            Blend::VariableFillet(radius0, radius1) => {
+                [(radius0 + (radius1 - radius0) * s) / f64::tan(angle / 2.0); 2]
+            }
+            Blend::Chamfer(distance) => [distance; 2],
+            Blend::AngledChamfer(distance, Rad(alpha)) => {
+                [distance, distance * alpha.sin() / f64::sin(angle + alpha)]
+            }
+        }
+    }
+
+    // the line of the blend on the left or right face, by the points at the parameters 0 and 1
+    fn line(&self, side: usize) -> [Point3; 2] {
+        let inward = self.inwards()[side];
+        [0.0, 1.0].map(|s| self.point(s) + inward * self.distances(s)[side])
+    }
+
+    // the homogeneous control points of the section perpendicular to the edge at `s`, from
+    // the left face to the right face
+    fn profile(&self, s: f64) -> Vec<Vector4> {
+        let point = self.point(s);
+        let [u0, u1] = self.inwards();
+        let [d0, d1] = self.distances(s);
+        let (p0, p1) = (point + u0 * d0, point + u1 * d1);
+        match self.blend.is_fillet() {
+            // the circular arc tangent to the faces
+            true => vec![
+                p0.to_homogeneous(),
+                point.to_homogeneous() * f64::sin(self.angle() / 2.0),
+                p1.to_homogeneous(),
+            ],
+            false => vec![p0.to_homogeneous(), p1.to_homogeneous()],
+        }
+    }
+
+    // the apex of the conical blend, or the direction of the cylindrical one, in the
+    // homogeneous coordinates
+    fn apex(&self) -> Vector4 {
+        match self.blend {
+            Blend::VariableFillet(radius0, radius1) if radius0 != radius1 => {
+                self.point(radius0 / (radius0 - radius1)).to_homogeneous()
+            }
+            _ => self.direction().extend(0.0),
+        }
+    }
+
+    // the surface of the blend between the sections at the parameters
+    fn surface(&self, [s0, s1]: [f64; 2]) -> Surface {
+        let outward = self.normals[0] + self.normals[1];
+        let (profile0, profile1) = (self.profile(s0), self.profile(s1));
+        match self.blend.is_fillet() {
+            true => {
+                let knot_vecs = (KnotVec::bezier_knot(1), KnotVec::bezier_knot(2));
+                let bspline = BSplineSurface::new(knot_vecs, vec![profile0, profile1]);
+                let mut surface = NurbsSurface::new(bspline);
+                if surface.normal(0.5, 0.5).dot(outward) < 0.0 {
+                    surface.invert();
+                }
+                Surface::NurbsSurface(surface)
+            }
+            false => {
+                let [p0, p1, q0] =
+                    [profile0[0], profile1[0], profile0[1]].map(Point3::from_homogeneous);
+                let mut plane = Plane::new(p0, p1, q0);
+                if plane.normal().dot(outward) < 0.0 {
+                    plane.invert();
+                }
+                Surface::Plane(plane)
+            }
+        }
+    }
+}
+
+impl Corners {
+    // Adds the end curve of the section at the vertex.
+    fn push_curve(&mut self, vertex: VertexID, section: &Section, points: Vec<Vector4>) {
+        let corners = self.sides[&(vertex, section.edge.id())];
+        self.ends
+            .insert((vertex, section.edge.id()), self.curves.len());
+        self.curves.push(EndCurve { points, corners });
+    }
+
+    // Checks that the trimmed lines keep their directions.
+    fn check_lengths(
+        &self,
+        topology: &Topology,
+        sections: &[Section],
+        blended: &HashMap<EdgeID, usize>,
+    ) -> Result<(), (usize, BlendError)> {
+        for (idx, section) in sections.iter().enumerate() {
+            let id = section.edge.id();
+            let [front, back] = [section.edge.front(), section.edge.back()]
+                .map(|vertex| self.sides[&(vertex.id(), id)]);
+            for side in 0..2 {
+                let vector = self.points[&back[side]] - self.points[&front[side]];
+                if vector.dot(section.direction()) < TOLERANCE {
+                    return Err((idx, BlendError::TooLarge));
+                }
+            }
+        }
+        for (id, (edge, _)) in &topology.edges {
+            let ends = [edge.front(), edge.back()];
+            if blended.contains_key(id) || !ends.iter().any(|v| self.vertices.contains(&v.id())) {
+                continue;
+            }
+            let [p0, p1] = ends.map(|vertex| match self.vertices.contains(&vertex.id()) {
+                true => self.points[&Corner::OnEdge(vertex.id(), *id)],
+                false => vertex.point(),
+            });
+            let vector = edge.back().point() - edge.front().point();
+            if (p1 - p0).dot(vector) < TOLERANCE * vector.magnitude() {
+                // the last blended edge at the ends
+                let last = ends
+                    .iter()
+                    .flat_map(|vertex| &topology.vertex_edges[&vertex.id()])
+                    .filter_map(|id| blended.get(id))
+                    .max();
+                return Err((*last.unwrap(), BlendError::TooLarge));
+            }
+        }
+        Ok(())
+    }
+
+    // Returns the range of the parameters of the section which covers its end curves, if
+    // the range does not contain the apex of the conical blend.
+    fn range(&self, section: &Section) -> Option<[f64; 2]> {
+        let id = section.edge.id();
+        let parameters: Vec<f64> = [section.edge.front(), section.edge.back()]
+            .iter()
+            .flat_map(|vertex| &self.curves[self.ends[&(vertex.id(), id)]].points)
+            .map(|point| section.parameter(Point3::from_homogeneous(*point)))
+            .collect();
+        let min = parameters.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = parameters.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let margin = 0.1 * (max - min);
+        let range = [min - margin, max + margin];
+        match section.blend {
+            Blend::VariableFillet(radius0, radius1) if radius0 != radius1 => {
+                let apex = radius0 / (radius0 - radius1);
+                (apex < range[0] || range[1] < apex).then_some(range)
+            }
+            _ => Some(range),
+        }
+    }
+}
+
+// Returns the edge or its inverse which starts at the vertex.
+fn starting_at(edge: &Edge, vertex: &Vertex) -> Edge {
+    match edge.front().id() == vertex.id() {
+        true => edge.clone(),
+        false => edge.inverse(),
+    }
+}
+
+// Returns the intersection of the line through the points and the plane.
+fn intersect_plane([p, q]: [Point3; 2], (origin, normal): (Point3, Vector3)) -> Option<Point3> {
+    let denom = (q - p).dot(normal);
+    match denom.abs() > TOLERANCE * (q - p).magnitude() {
+        true => Some(p + (q - p) * ((origin - p).dot(normal) / denom)),
+        false => None,
+    }
+}
+
+// Returns the intersection of the coplanar lines through the points.
+fn intersect_lines([p0, p1]: [Point3; 2], [q0, q1]: [Point3; 2]) -> Option<Point3> {
+    let (d, e) = (p1 - p0, q1 - q0);
+    let normal = d.cross(e);
+    match normal.magnitude() > TOLERANCE * d.magnitude() * e.magnitude() {
+        true => Some(p0 + d * ((q0 - p0).cross(e).dot(normal) / normal.magnitude2())),
+        false => None,
+    }
+}
+
+// Projects the rational curve from the apex to the plane in the homogeneous coordinates.
+// Returns `None` if the projected curve passes the infinity.
+fn project(
+    points: &[Vector4],
+    apex: Vector4,
+    (origin, normal): (Point3, Vector3),
+) -> Option<Vec<Vector4>> {
+    let plane = normal.extend(-normal.dot(origin.to_vec()));
+    let scale = plane.dot(apex);
+    let projected: Vec<Vector4> = points
+        .iter()
+        .map(|point| *point * scale - apex * plane.dot(*point))
+        .collect();
+    let weight = projected[0].w;
+    match projected.iter().all(|point| point.w / weight > TOLERANCE) {
+        true => Some(projected.into_iter().map(|point| point / weight).collect()),
+        false => None,
+    }
+}
+
+// Returns whether the rational Bézier curves are the same in either direction.
+fn same_curve(points0: &[Vector4], points1: &[Vector4]) -> bool {
+    // the ratio of the weights which is invariant under the reparametrizations
+    let shape = |points: &[Vector4]| match points.len() {
+        3 => points[1].w * points[1].w / (points[0].w * points[2].w),
+        _ => 1.0,
+    };
+    let euclidean = |points: &[Vector4]| -> Vec<Point3> {
+        points
+            .iter()
+            .map(|point| Point3::from_homogeneous(*point))
+            .collect()
+    };
+    let (p, mut q) = (euclidean(points0), euclidean(points1));
+    if p.len() != q.len() || !(shape(points0) - shape(points1)).so_small() {
+        return false;
+    }
+    if !p[0].near(&q[0]) {
+        q.reverse();
+    }
+    p.iter().zip(&q).all(|(p, q)| p.near(q))
+}
+
+// Returns the part of the sphere within the angle 70 degrees from the direction `middle` in
+// the latitude and the longitude around the axis perpendicular to `middle`. The part has no
+// singular points, and the vertex blends are trimmed from it.
+fn sphere_patch(center: Point3, radius: f64, middle: Vector3) -> NurbsSurface<Vector4> {
+    let e0 = middle.normalize();
+    let axis = match e0.x.abs() < 0.5 {
+        true => e0.cross(Vector3::unit_x()).normalize(),
+        false => e0.cross(Vector3::unit_y()).normalize(),
+    };
+    let e1 = axis.cross(e0);
+    let half = 70.0f64.to_radians();
+    let (cos, weight) = (half.cos(), half.cos());
+    // the control points of the arc from the angle -70 to 70 degrees in the unit circle
+    let arc = [(cos, -half.sin()), (1.0 / cos, 0.0), (cos, half.sin())];
+    let weights = [1.0, weight, 1.0];
+    // the meridian in the (distance from the axis, height) plane, revolved around the axis
+    let control_points = (0..3)
+        .map(|i| {
+            let (rho, height) = arc[i];
+            (0..3)
+                .map(|j| {
+                    let (x, y) = arc[j];
+                    let point = center + (e0 * x + e1 * y) * rho * radius + axis * height * radius;
+                    point.to_homogeneous() * (weights[i] * weights[j])
+                })
+                .collect()
+        })
+        .collect();
+    let knot_vecs = (KnotVec::bezier_knot(2), KnotVec::bezier_knot(2));
+    NurbsSurface::new(BSplineSurface::new(knot_vecs, control_points))
+}
@@ -0,0 +1,212 @@
+//! Deviation of triangulations from the surfaces of their source solids.
+
+use std::result::Result;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+
+/// the solid triangulated by `MeshableShape::triangulation`
+pub type MeshedSolid = <Solid as MeshableShape>::MeshedShape;
+
+/// The statistics of the distances from the samples on the meshes to the surfaces.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Deviation {
+    /// the maximum of the distances
+    pub max: f64,
+    /// the mean of the distances
+    pub mean: f64,
+    /// the root mean square of the distances
+    pub rms: f64,
+}
+
+/// The deviation of the whole triangulation and the ones of each face.
+#[derive(Clone, Debug)]
+pub struct SolidDeviation {
+    /// the deviation of all samples
+    pub total: Deviation,
+    /// the deviations of the faces, in order of `Solid::face_iter`
+    pub faces: Vec<Deviation>,
+}
+
+/// Errors of the measurement of the deviation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviationError {
+    /// The meshed solid does not have the same faces as the source.
+    TopologyMismatch,
+    /// The triangulation of the face failed.
+    NotTriangulated(usize),
+    /// The polygon of the face has no texture coordinates, i.e. the parameters of the surface.
+    NoParameters(usize),
+    /// The search of the nearest point on the surface of the face failed.
+    SearchFailed(usize),
+}
+
+impl std::fmt::Display for DeviationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeviationError::TopologyMismatch => {
+                f.write_str("the faces of the meshed solid do not match")
+            }
+            DeviationError::NotTriangulated(i) => write!(f, "the face {i} is not triangulated"),
+            DeviationError::NoParameters(i) => {
+                write!(f, "the polygon of the face {i} has no parameters")
+            }
+            DeviationError::SearchFailed(i) => {
+                write!(f, "failed to search the nearest point on the face {i}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeviationError {}
+
+/// Measures the deviation of the triangulation `meshed` from the surfaces of `solid`.
+///
+/// The samples are the points of the barycentric grid dividing each triangle into
+/// `division * division` triangles. The nearest points on the surfaces are searched
+/// from the parameters interpolated by the texture coordinates of the polygons.
+pub fn solid_deviation(
+    solid: &Solid,
+    meshed: &MeshedSolid,
+    division: usize,
+) -> Result<SolidDeviation, DeviationError> {
+    let division = usize::max(division, 1);
+    let (faces, meshed_faces): (Vec<_>, Vec<_>) =
+        (solid.face_iter().collect(), meshed.face_iter().collect());
+    if faces.len() != meshed_faces.len() {
+        return Err(DeviationError::TopologyMismatch);
+    }
+    let mut total = Vec::new();
+    let faces = faces
+        .iter()
+        .zip(meshed_faces)
+        .enumerate()
+        .map(|(i, (face, meshed_face))| {
+            let polygon = meshed_face
+                .surface()
+                .ok_or(DeviationError::NotTriangulated(i))?;
+            let surface = face.surface();
+            let distances = polygon
+                .faces()
+                .triangle_iter()
+                .flat_map(|tri| barycentric_grid(division).map(move |weights| (tri, weights)))
+                .map(|(tri, weights)| {
+                    let mut point = Vector3::zero();
+                    let mut uv = Vector2::zero();
+                    for (v, w) in tri.iter().zip(weights) {
+                        point += polygon.positions()[v.pos].to_vec() * w;
+                        let idx = v.uv.ok_or(DeviationError::NoParameters(i))?;
+                        uv += polygon.uv_coords()[idx] * w;
+                    }
+                    let point = Point3::from_vec(point);
+                    let (u, v) = surface
+                        .search_nearest_parameter(point, (uv.x, uv.y), 100)
+                        .or_else(|| surface.search_nearest_parameter(point, None, 100))
+                        .ok_or(DeviationError::SearchFailed(i))?;
+                    Ok(point.distance(surface.subs(u, v)))
+                })
+                .collect::<Result<Vec<f64>, _>>()?;
+            let deviation = statistics(&distances);
+            total.extend(distances);
+            Ok(deviation)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(SolidDeviation {
+        total: statistics(&total),
+        faces,
+    })
+}
+
+// the barycentric coordinates of the grid dividing a triangle into `division * division` triangles
+fn barycentric_grid(division: usize) -> impl Iterator<Item = [f64; 3]> + Clone {
+    (0..=division).flat_map(move |i| {
+        (0..=division - i).map(move |j| {
+            let (s, t) = (i as f64 / division as f64, j as f64 / division as f64);
+            [s, t, 1.0 - s - t]
+        })
+    })
+}
+
+fn statistics(distances: &[f64]) -> Deviation {
+    if distances.is_empty() {
+        return Deviation::default();
+    }
+    let len = distances.len() as f64;
+    Deviation {
+        max: distances.iter().copied().fold(0.0, f64::max),
+        mean: distances.iter().sum::<f64>() / len,
+        rms: f64::sqrt(distances.iter().map(|d| d * d).sum::<f64>() / len),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the cylinder of the radius 1 and the height 2, the same as `tessellation_error`
+    fn cylinder() -> Solid {
+        let vertex = builder::vertex(Point3::new(0.0, -1.0, 1.0));
+        let circle = builder::rsweep(&vertex, Point3::origin(), Vector3::unit_y(), Rad(7.0));
+        let disk = builder::try_attach_plane(&[circle]).unwrap();
+        builder::tsweep(&disk, Vector3::new(0.0, 2.0, 0.0))
+    }
+
+    // the torus of the major radius 1 and the minor radius 0.3, the same as `tessellation_error`
+    fn torus() -> Solid {
+        let vertex = builder::vertex(Point3::new(1.0, 0.0, 0.3));
+        let circle = builder::rsweep(
+            &vertex,
+            Point3::new(1.0, 0.0, 0.0),
+            Vector3::unit_y(),
+            Rad(7.0),
+        );
+        let shell = builder::rsweep(&circle, Point3::origin(), Vector3::unit_z(), Rad(7.0));
+        Solid::new(vec![shell])
+    }
+
+    #[test]
+    fn within_tolerance() {
+        for solid in [cylinder(), torus()] {
+            for tol in [0.1, 0.05, 0.01] {
+                let meshed = solid.triangulation(tol);
+                let deviation = solid_deviation(&solid, &meshed, 4).unwrap();
+                let Deviation { max, mean, rms } = deviation.total;
+                // The tolerance of the triangulation is a rough estimate, and may be exceeded a little.
+                assert!(max < 1.5 * tol);
+                assert!(mean <= rms && rms <= max);
+                assert_eq!(deviation.faces.len(), solid.face_iter().count());
+            }
+        }
+    }
+
+    #[test]
+    fn cylinder_sagitta() {
+        // The planar disks have no deviations, while the chords of the side have the sagittae.
+        let cylinder = cylinder();
+        let meshed = cylinder.triangulation(0.01);
+        let deviation = solid_deviation(&cylinder, &meshed, 2).unwrap();
+        let faces: Vec<_> = cylinder.face_iter().collect();
+        let side = deviation
+            .faces
+            .iter()
+            .zip(&faces)
+            .filter(|(_, face)| !matches!(face.surface(), Surface::Plane(_)))
+            .map(|(deviation, _)| deviation.max)
+            .fold(0.0, f64::max);
+        assert!(side > 0.0);
+        deviation
+            .faces
+            .iter()
+            .zip(&faces)
+            .filter(|(_, face)| matches!(face.surface(), Surface::Plane(_)))
+            .for_each(|(deviation, _)| assert!(deviation.max < TOLERANCE));
+    }
+
+    #[test]
+    fn topology_mismatch() {
+        let meshed = torus().triangulation(0.1);
+        assert_eq!(
+            solid_deviation(&cylinder(), &meshed, 1).err(),
+            Some(DeviationError::TopologyMismatch)
+        );
+    }
+}
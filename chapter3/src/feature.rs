@@ -0,0 +1,692 @@
+//! Extrusions and revolutions of the planar profiles.
+//!
+//! The profiles are the planar faces, or the closed planar wires attached to the planes. The
+//! extrusions go along the normals of the faces, so the solids are capped by the faces and
+//! their images, and the lateral faces are ruled between the boundaries of the caps.
+//!
+//! The drafts offset the boundaries on the plane of the caps, toward the inside of the
+//! faces, so the positive draft angles taper the solids, and the holes grow. The drafts are
+//! only for the boundaries of the lines and the circle arcs, e.g. the ones of the sketches,
+//! and are exact unless an arc meets the other edge at a corner: the lateral face of the arc
+//! is then ruled between the arcs of the different angles instead of a cone.
+//!
+//! The revolutions go counterclockwise around the axis in the plane of the profile. The
+//! profile may touch the axis, where the lateral faces are made by `cone` without the
+//! degenerate edges, and the caps of the partial revolutions meet at the edge on the axis.
+//!
+//! The profiles are checked before the construction, e.g. for the drafted boundaries which
+//! cross each other, and the results are triangulated and checked by `self_intersections`
+//! of chapter 2, so the solids whose faces cross each other are not returned.
+
+use chapter2::self_intersection::self_intersections;
+use std::f64::consts::PI;
+use std::result::Result;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+
+/// The extents of the extrusions.
+#[derive(Clone, Debug)]
+pub enum Extent {
+    /// the distance from the profile along its normal
+    Blind(f64),
+    /// the total distance, the half of which is on each side of the profile
+    Symmetric(f64),
+    /// up to the plane of the planar face, which may be inclined to the profile
+    UpToFace(Face),
+}
+
+/// The options of the extrusions.
+#[derive(Clone, Debug)]
+pub struct ExtrudeOptions {
+    /// the extent along the normal of the profile
+    pub extent: Extent,
+    /// the angle of the lateral faces from the normal, in radians, which tapers the solid if
+    /// it is positive
+    pub draft: f64,
+}
+
+/// The extents of the revolutions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RevolveExtent {
+    /// the angle from the profile, in radians, less than `2 * PI`
+    Angle(f64),
+    /// the full revolution
+    Full,
+    /// the total angle, the half of which is on each side of the profile
+    TwoSided(f64),
+}
+
+/// The errors of the extrusions and the revolutions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeatureError {
+    /// The dimension of the name is out of its range or not finite.
+    InvalidDimension(&'static str),
+    /// The profile or the face of the extent is not planar.
+    NotPlanar,
+    /// The wire of the profile is not closed.
+    NotClosed,
+    /// The face of the extent is not ahead of the whole profile.
+    Unreachable,
+    /// The draft has the curves other than the lines and the circle arcs, or goes up to the
+    /// inclined face.
+    UnsupportedDraft,
+    /// The axis of the revolution is not in the plane of the profile.
+    InvalidAxis,
+    /// The result intersects itself: the drafted boundaries collapse or cross each other, the
+    /// revolved profile crosses the axis or touches it at the separate parts, or the faces of
+    /// the triangulated result cross each other.
+    SelfIntersecting,
+    /// The faces of the result do not make closed and oriented shells.
+    InvalidTopology,
+}
+
+impl std::fmt::Display for FeatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeatureError::InvalidDimension(name) => write!(f, "invalid {name}"),
+            FeatureError::NotPlanar => f.write_str("the face is not planar"),
+            FeatureError::NotClosed => f.write_str("the wire is not closed"),
+            FeatureError::Unreachable => f.write_str("the face is not ahead of the profile"),
+            FeatureError::UnsupportedDraft => f.write_str("the draft is not supported"),
+            FeatureError::InvalidAxis => f.write_str("the axis is not in the plane of the profile"),
+            FeatureError::SelfIntersecting => f.write_str("the result intersects itself"),
+            FeatureError::InvalidTopology => f.write_str("the shells are not closed or oriented"),
+        }
+    }
+}
+
+impl std::error::Error for FeatureError {}
+
+// the number of the points on every edge for the checks of the intersections
+const SAMPLES: usize = 16;
+
+// the ratio of the tolerance of the triangulations for the checks of the intersections to
+// the diameter of the solid
+const MESH_RATIO: f64 = 1.0e-3;
+
+// the closed planar wire attached to the plane
+fn profile(wire: &Wire) -> Result<Face, FeatureError> {
+    if wire.is_empty() || !wire.is_closed() {
+        return Err(FeatureError::NotClosed);
+    }
+    builder::try_attach_plane(std::slice::from_ref(wire)).map_err(|_| FeatureError::NotPlanar)
+}
+
+fn planar(face: &Face) -> Result<Plane, FeatureError> {
+    match face.oriented_surface() {
+        Surface::Plane(plane) => Ok(plane),
+        _ => Err(FeatureError::NotPlanar),
+    }
+}
+
+// the points on the edge in its direction
+fn samples(edge: &Edge) -> Vec<Point3> {
+    let curve = edge.oriented_curve();
+    let (t0, t1) = curve.range_tuple();
+    (0..=SAMPLES)
+        .map(|i| curve.subs(t0 + (t1 - t0) * i as f64 / SAMPLES as f64))
+        .collect()
+}
+
+/// Extrudes the face along its normal.
+pub fn extrude(face: &Face, options: &ExtrudeOptions) -> Result<Solid, FeatureError> {
+    let plane = planar(face)?;
+    let draft = options.draft;
+    if !draft.is_finite() || draft.abs() >= PI / 2.0 {
+        return Err(FeatureError::InvalidDimension("draft"));
+    }
+    let normal = plane.normal();
+    let boundaries = face.boundaries();
+    let offset = |height: f64| height.abs() * draft.tan();
+    let levels = match &options.extent {
+        Extent::Blind(distance) => {
+            check("distance", *distance)?;
+            vec![Level::Profile, level(*distance, offset(*distance))]
+        }
+        Extent::Symmetric(distance) => {
+            check("distance", *distance)?;
+            let half = distance / 2.0;
+            match draft == 0.0 {
+                true => vec![level(-half, 0.0), level(half, 0.0)],
+                false => vec![
+                    level(-half, offset(half)),
+                    Level::Profile,
+                    level(half, offset(half)),
+                ],
+            }
+        }
+        Extent::UpToFace(target) => {
+            let target = planar(target)?;
+            let m = target.normal();
+            let k = normal.dot(m);
+            // the distance along the normal from the point to the target
+            let travel = |p: Point3| (target.origin() - p).dot(m) / k;
+            let points = boundaries
+                .iter()
+                .flat_map(|wire| wire.edge_iter().flat_map(samples));
+            if k.abs() < TOLERANCE || points.clone().any(|p| travel(p) < TOLERANCE) {
+                return Err(FeatureError::Unreachable);
+            }
+            match (normal.cross(m).magnitude() < TOLERANCE, draft == 0.0) {
+                (true, _) => {
+                    let distance = travel(plane.origin());
+                    vec![Level::Profile, level(distance, offset(distance))]
+                }
+                (false, true) => {
+                    // the projection onto the target along the normal
+                    let column = |i: usize| {
+                        let mut column = Vector3::zero();
+                        column[i] = 1.0;
+                        (column - normal * m[i] / k).extend(0.0)
+                    };
+                    let translation = normal * target.origin().to_vec().dot(m) / k;
+                    let matrix = Matrix4::from_cols(
+                        column(0),
+                        column(1),
+                        column(2),
+                        translation.extend(1.0),
+                    );
+                    vec![Level::Profile, Level::Projected(matrix)]
+                }
+                (false, false) => return Err(FeatureError::UnsupportedDraft),
+            }
+        }
+    };
+
+    let basis = Basis::new(&plane);
+    let geometries = match draft == 0.0 {
+        true => Vec::new(),
+        false => boundaries
+            .iter()
+            .map(|wire| wire.edge_iter().map(|edge| basis.geometry(edge)).collect())
+            .collect::<Option<Vec<Vec<_>>>>()
+            .ok_or(FeatureError::UnsupportedDraft)?,
+    };
+    let loops = levels
+        .iter()
+        .map(|level| match level {
+            Level::Profile => Ok(boundaries.clone()),
+            Level::Projected(matrix) => Ok(boundaries
+                .iter()
+                .map(|wire| builder::transformed(wire, *matrix))
+                .collect()),
+            Level::Offset(height, offset) if *offset == 0.0 => Ok(boundaries
+                .iter()
+                .map(|wire| builder::translated(wire, normal * *height))
+                .collect()),
+            Level::Offset(height, offset) => {
+                let loops = geometries
+                    .iter()
+                    .map(|geometry| basis.offset_loop(geometry, *height, *offset))
+                    .collect::<Option<Vec<Wire>>>()
+                    .ok_or(FeatureError::SelfIntersecting)?;
+                match intersecting(&loops) {
+                    true => Err(FeatureError::SelfIntersecting),
+                    false => Ok(loops),
+                }
+            }
+        })
+        .collect::<Result<Vec<Vec<Wire>>, _>>()?;
+
+    let cap = |loops: &[Wire]| builder::try_attach_plane(loops).unwrap();
+    let mut shell: Shell = match levels[0] {
+        Level::Profile => vec![face.inverse()].into(),
+        _ => vec![cap(&loops[0]).inverse()].into(),
+    };
+    for pair in loops.windows(2) {
+        for (lower, upper) in pair[0].iter().zip(&pair[1]) {
+            shell.append(&mut builder::try_wire_homotopy(lower, upper).unwrap());
+        }
+    }
+    shell.push(cap(loops.last().unwrap()));
+    solid(vec![shell])
+}
+
+/// Extrudes the closed planar wire, which is attached to the plane.
+pub fn extrude_wire(wire: &Wire, options: &ExtrudeOptions) -> Result<Solid, FeatureError> {
+    extrude(&profile(wire)?, options)
+}
+
+/// Revolves the face around the axis through `origin` in the plane of the face.
+pub fn revolve(
+    face: &Face,
+    origin: Point3,
+    axis: Vector3,
+    extent: RevolveExtent,
+) -> Result<Solid, FeatureError> {
+    let plane = planar(face)?;
+    let angle = match extent {
+        RevolveExtent::Angle(angle) | RevolveExtent::TwoSided(angle) => {
+            check("angle", angle)?;
+            if angle >= 2.0 * PI {
+                return Err(FeatureError::InvalidDimension("angle"));
+            }
+            angle
+        }
+        // `rsweep` closes the solid for the angles not less than `2 * PI`.
+        RevolveExtent::Full => 2.0 * PI,
+    };
+    let normal = plane.normal();
+    if axis.magnitude() < TOLERANCE
+        || normal.dot(axis.normalize()).abs() > TOLERANCE
+        || (origin - plane.origin()).dot(normal).abs() > TOLERANCE
+    {
+        return Err(FeatureError::InvalidAxis);
+    }
+    let axis = axis.normalize();
+    // the distance from the axis on the plane, which is positive on the left of the axis
+    let side = normal.cross(axis);
+    let distance = |p: Point3| (p - origin).dot(side);
+    let (min, max) = face
+        .boundaries()
+        .iter()
+        .flat_map(|wire| wire.edge_iter().flat_map(samples))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+            (f64::min(min, distance(p)), f64::max(max, distance(p)))
+        });
+    if min < -TOLERANCE && max > TOLERANCE {
+        return Err(FeatureError::SelfIntersecting);
+    }
+    // The face is swept along its normal, so the profile on the right is inverted.
+    let face = match max > TOLERANCE {
+        true => face.clone(),
+        false => face.inverse(),
+    };
+    let face = match extent {
+        RevolveExtent::TwoSided(angle) => builder::rotated(&face, origin, axis, Rad(-angle / 2.0)),
+        _ => face,
+    };
+    // the distance from the axis
+    let radial = |p: Point3| {
+        let v = p - origin;
+        (v - axis * v.dot(axis)).magnitude()
+    };
+    let on_axis = |p: Point3| radial(p) < TOLERANCE;
+    let mut boundaries = face.boundaries().into_iter();
+    let outer = boundaries.next().unwrap();
+    if !outer.vertex_iter().any(|v| on_axis(v.point())) {
+        return unless_intersecting(builder::rsweep(&face, origin, axis, Rad(angle)));
+    }
+    // The profile on the axis is revolved by `cone`, which does not make the degenerate edges.
+    let chain = open_chain(&outer, on_axis).ok_or(FeatureError::SelfIntersecting)?;
+    let to_origin = origin.to_vec();
+    let chain = builder::translated(&chain, -to_origin);
+    let cone = builder::translated(&builder::cone(&chain, axis, Rad(angle)), to_origin);
+    let holes = boundaries
+        .map(
+            |hole| match hole.vertex_iter().any(|v| on_axis(v.point())) {
+                true => Err(FeatureError::SelfIntersecting),
+                false => Ok(builder::rsweep(&hole, origin, axis, Rad(angle))),
+            },
+        )
+        .collect::<Result<Vec<Shell>, _>>()?;
+    if extent == RevolveExtent::Full {
+        // the holes of the profile are the voids of the solid
+        let shells = std::iter::once(cone).chain(holes).collect();
+        return solid(shells);
+    }
+
+    // The caps are bounded by the boundaries of the lateral faces, and the outer boundary is
+    // split by the edge on the axis between the caps.
+    let mut shell = cone;
+    holes
+        .into_iter()
+        .for_each(|mut hole| shell.append(&mut hole));
+    // The profile is on the left of the axis on the plane of the first cap.
+    let start = planar(&face)?.normal();
+    let on_start = |p: Point3| {
+        let v = p - origin;
+        v.dot(start).abs() < TOLERANCE && v.dot(start.cross(axis)) > TOLERANCE
+    };
+    let mut caps = [Vec::new(), Vec::new()];
+    for wire in shell.extract_boundaries() {
+        let edges: Vec<Edge> = wire.edge_iter().cloned().collect();
+        let first = |edge: &Edge| on_start(samples(edge)[SAMPLES / 2]);
+        if !wire.vertex_iter().any(|v| on_axis(v.point())) {
+            let k = if first(&edges[0]) { 0 } else { 1 };
+            caps[k].push(wire);
+            continue;
+        }
+        // the edges of the first cap are in a row
+        let n = edges.len();
+        let i = (0..n)
+            .find(|i| first(&edges[*i]) && !first(&edges[(i + n - 1) % n]))
+            .unwrap();
+        let (mut wire0, mut wire1) = (Wire::new(), Wire::new());
+        for k in 0..n {
+            let edge = edges[(i + k) % n].clone();
+            match first(&edge) {
+                true => wire0.push_back(edge),
+                false => wire1.push_back(edge),
+            }
+        }
+        let (x, y) = (wire0.front_vertex().unwrap(), wire0.back_vertex().unwrap());
+        if x != y {
+            let seam = builder::line(y, x);
+            wire1.push_back(seam.inverse());
+            wire0.push_back(seam);
+        }
+        caps[0].insert(0, wire0);
+        caps[1].insert(0, wire1);
+    }
+    for cap in caps {
+        let wires: Vec<Wire> = cap.iter().map(Wire::inverse).collect();
+        shell.push(builder::try_attach_plane(&wires).unwrap());
+    }
+    solid(vec![shell])
+}
+
+/// Revolves the closed planar wire, which is attached to the plane.
+pub fn revolve_wire(
+    wire: &Wire,
+    origin: Point3,
+    axis: Vector3,
+    extent: RevolveExtent,
+) -> Result<Solid, FeatureError> {
+    revolve(&profile(wire)?, origin, axis, extent)
+}
+
+fn check(name: &'static str, value: f64) -> Result<(), FeatureError> {
+    match value.is_finite() && value > 0.0 {
+        true => Ok(()),
+        false => Err(FeatureError::InvalidDimension(name)),
+    }
+}
+
+// the solid of the shells, which have to be closed and oriented, and must not intersect
+fn solid(shells: Vec<Shell>) -> Result<Solid, FeatureError> {
+    let solid = Solid::try_new(shells).map_err(|_| FeatureError::InvalidTopology)?;
+    unless_intersecting(solid)
+}
+
+// The solid, or the error if the faces of its triangulation cross each other. The positions
+// on the shared edges are put together and the degenerate triangles at the poles are removed,
+// so only the triangles apart from each other are checked.
+fn unless_intersecting(solid: Solid) -> Result<Solid, FeatureError> {
+    let bounding: BoundingBox<Point3> = solid
+        .edge_iter()
+        .flat_map(|edge| samples(&edge))
+        .collect();
+    let mut mesh = solid
+        .triangulation(bounding.diameter() * MESH_RATIO)
+        .to_polygon();
+    mesh.put_together_same_attrs(10.0 * TOLERANCE)
+        .remove_degenerate_faces();
+    match self_intersections(&mesh).is_empty() {
+        true => Ok(solid),
+        false => Err(FeatureError::SelfIntersecting),
+    }
+}
+
+// the boundaries of the profile on the levels of the extrusions
+enum Level {
+    // the profile itself
+    Profile,
+    // the height along the normal and the offset toward the inside
+    Offset(f64, f64),
+    // the projection onto the face of the extent
+    Projected(Matrix4),
+}
+
+fn level(height: f64, offset: f64) -> Level { Level::Offset(height, offset) }
+
+// The open chain of the edges off the axis from the vertex on the axis to the vertex on the
+// axis, or `None` if the wire touches the axis at the separate parts.
+fn open_chain(wire: &Wire, on_axis: impl Fn(Point3) -> bool) -> Option<Wire> {
+    let edges: Vec<&Edge> = wire.edge_iter().collect();
+    let on = |edge: &Edge| samples(edge).into_iter().all(&on_axis);
+    let n = edges.len();
+    // the chain starts at the vertex on the axis after the edges on the axis
+    let start = (0..n).find(|i| on_axis(edges[*i].front().point()) && !on(edges[*i]))?;
+    // The curves are in the directions of the edges, since `cone` orients the faces by them.
+    let chain: Vec<Edge> = (0..n)
+        .map(|i| edges[(start + i) % n])
+        .take_while(|edge| !on(edge))
+        .map(|edge| Edge::new(edge.front(), edge.back(), edge.oriented_curve()))
+        .collect();
+    let inner = chain[..chain.len() - 1]
+        .iter()
+        .any(|edge| on_axis(edge.back().point()));
+    let rest = (start + chain.len()..start + n).all(|i| on(edges[i % n]));
+    match !inner && rest && on_axis(chain.last().unwrap().back().point()) {
+        true => Some(chain.into()),
+        false => None,
+    }
+}
+
+// the geometry of an edge on the plane
+#[derive(Clone, Copy, Debug)]
+struct EdgeGeometry {
+    // the center of the arc, or `None` for the line
+    center: Option<Point2>,
+    start: Point2,
+    end: Point2,
+    middle: Point2,
+    // the unit tangents at the ends
+    tangents: [Vector2; 2],
+}
+
+// the orthonormal coordinates on the plane
+struct Basis {
+    origin: Point3,
+    axes: [Vector3; 3],
+}
+
+impl Basis {
+    fn new(plane: &Plane) -> Self {
+        let normal = plane.normal();
+        let u = plane.u_axis().normalize();
+        Basis {
+            origin: plane.origin(),
+            axes: [u, normal.cross(u), normal],
+        }
+    }
+
+    fn project(&self, point: Point3) -> Point2 {
+        let p = point - self.origin;
+        Point2::new(p.dot(self.axes[0]), p.dot(self.axes[1]))
+    }
+
+    fn lift(&self, point: Point2, height: f64) -> Point3 {
+        self.origin + self.axes[0] * point.x + self.axes[1] * point.y + self.axes[2] * height
+    }
+
+    // The geometry of the line or the circle arc, or `None` for the other curves.
+    fn geometry(&self, edge: &Edge) -> Option<EdgeGeometry> {
+        let curve = edge.oriented_curve();
+        let (t0, t1) = curve.range_tuple();
+        let points: Vec<Point2> = samples(edge).into_iter().map(|p| self.project(p)).collect();
+        let tangent = |t: f64| {
+            let d = curve.der(t);
+            Vector2::new(d.dot(self.axes[0]), d.dot(self.axes[1])).normalize()
+        };
+        let (start, end) = (points[0], points[SAMPLES]);
+        let middle = points[SAMPLES / 2];
+        let center = match cross(middle - start, points[SAMPLES / 3] - start).abs() < TOLERANCE {
+            true => None,
+            false => Some(circumcenter(
+                start,
+                points[SAMPLES / 3],
+                points[2 * SAMPLES / 3],
+            )?),
+        };
+        let on_curve = |p: &Point2| match center {
+            Some(c) => (c.distance(*p) - c.distance(start)).abs() < TOLERANCE,
+            None => cross(end - start, p - start).abs() < TOLERANCE * (end - start).magnitude(),
+        };
+        match points.iter().all(on_curve) {
+            true => Some(EdgeGeometry {
+                center,
+                start,
+                end,
+                middle,
+                tangents: [tangent(t0), tangent(t1)],
+            }),
+            false => None,
+        }
+    }
+
+    // The loop of the edges offset toward the left by `offset` at the height, or `None` if the
+    // edges collapse.
+    fn offset_loop(&self, edges: &[EdgeGeometry], height: f64, offset: f64) -> Option<Wire> {
+        let n = edges.len();
+        let left = |v: Vector2| Vector2::new(-v.y, v.x);
+        // the vertices at the starts of the edges
+        let points = (0..n)
+            .map(|i| {
+                let (previous, next) = (&edges[(i + n - 1) % n], &edges[i]);
+                let a = next.start + left(previous.tangents[1]) * offset;
+                let b = next.start + left(next.tangents[0]) * offset;
+                match a.near(&b) {
+                    true => Some(a),
+                    false => intersection(
+                        offset_curve(previous, offset)?,
+                        offset_curve(next, offset)?,
+                        next.start,
+                    ),
+                }
+            })
+            .collect::<Option<Vec<Point2>>>()?;
+        let vertices: Vec<Vertex> = points
+            .iter()
+            .map(|p| builder::vertex(self.lift(*p, height)))
+            .collect();
+        (0..n)
+            .map(|i| {
+                let edge = &edges[i];
+                let (p, q) = (points[i], points[(i + 1) % n]);
+                let (v0, v1) = (&vertices[i], &vertices[(i + 1) % n]);
+                match offset_curve(edge, offset)? {
+                    OffsetCurve::Line(_, direction) => match (q - p).dot(direction) > TOLERANCE {
+                        true => Some(builder::line(v0, v1)),
+                        false => None,
+                    },
+                    OffsetCurve::Circle(center, radius) => {
+                        let transit = center + (edge.middle - center).normalize() * radius;
+                        // the arc keeps its direction and does not pass over the ends
+                        let turn = |a: Point2, b: Point2| {
+                            let (u, v) = (a - center, b - center);
+                            f64::atan2(cross(u, v), u.dot(v))
+                        };
+                        let original = turn(edge.start, edge.middle) + turn(edge.middle, edge.end);
+                        let [t0, t1] = [turn(p, transit), turn(transit, q)];
+                        let valid = t0 * original > 0.0
+                            && t1 * original > 0.0
+                            && (t0 + t1).abs() < 2.0 * PI
+                            && p.distance(q) > TOLERANCE;
+                        match valid {
+                            true => Some(builder::circle_arc(v0, v1, self.lift(transit, height))),
+                            false => None,
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+// the line of the point and the unit direction, or the circle of the center and the radius
+#[derive(Clone, Copy, Debug)]
+enum OffsetCurve {
+    Line(Point2, Vector2),
+    Circle(Point2, f64),
+}
+
+// The curve of the edge offset toward the left, or `None` if the arc collapses.
+fn offset_curve(edge: &EdgeGeometry, offset: f64) -> Option<OffsetCurve> {
+    let left = Vector2::new(-edge.tangents[0].y, edge.tangents[0].x);
+    let start = edge.start + left * offset;
+    match edge.center {
+        None => Some(OffsetCurve::Line(start, edge.tangents[0])),
+        Some(center) => {
+            let radius = center.distance(start);
+            // The arc collapses if the offset passes over the center.
+            let same_side = (start - center).dot(edge.start - center) > 0.0;
+            match same_side && radius > TOLERANCE {
+                true => Some(OffsetCurve::Circle(center, radius)),
+                false => None,
+            }
+        }
+    }
+}
+
+// The intersection of the curves nearest to the point, or `None` if they do not intersect.
+fn intersection(curve0: OffsetCurve, curve1: OffsetCurve, near: Point2) -> Option<Point2> {
+    let candidates = match (curve0, curve1) {
+        (OffsetCurve::Line(p, u), OffsetCurve::Line(q, v)) => {
+            let det = cross(u, v);
+            match det.abs() < TOLERANCE {
+                true => Vec::new(),
+                false => vec![p + u * cross(q - p, v) / det],
+            }
+        }
+        (OffsetCurve::Line(p, u), OffsetCurve::Circle(c, r))
+        | (OffsetCurve::Circle(c, r), OffsetCurve::Line(p, u)) => {
+            // the foot of the perpendicular from the center
+            let foot = p + u * (c - p).dot(u);
+            let h2 = r * r - (foot - c).magnitude2();
+            match h2 < 0.0 {
+                true => Vec::new(),
+                false => vec![foot + u * h2.sqrt(), foot - u * h2.sqrt()],
+            }
+        }
+        (OffsetCurve::Circle(c0, r0), OffsetCurve::Circle(c1, r1)) => {
+            let d = c0.distance(c1);
+            let a = (r0 * r0 - r1 * r1 + d * d) / (2.0 * d);
+            let h2 = r0 * r0 - a * a;
+            match d < TOLERANCE || h2 < 0.0 {
+                true => Vec::new(),
+                false => {
+                    let u = (c1 - c0) / d;
+                    let foot = c0 + u * a;
+                    let v = Vector2::new(-u.y, u.x) * h2.sqrt();
+                    vec![foot + v, foot - v]
+                }
+            }
+        }
+    };
+    candidates
+        .into_iter()
+        .min_by(|a, b| a.distance2(near).total_cmp(&b.distance2(near)))
+}
+
+fn cross(u: Vector2, v: Vector2) -> f64 { u.x * v.y - u.y * v.x }
+
+fn circumcenter(a: Point2, b: Point2, c: Point2) -> Option<Point2> {
+    let (u, v) = (b - a, c - a);
+    let det = 2.0 * cross(u, v);
+    match det.abs() < TOLERANCE * TOLERANCE {
+        true => None,
+        false => {
+            let (u2, v2) = (u.magnitude2(), v.magnitude2());
+            Some(a + Vector2::new(v.y * u2 - u.y * v2, u.x * v2 - v.x * u2) / det)
+        }
+    }
+}
+
+// Whether the polylines of the loops cross each other, except at the shared vertices of the
+// adjacent edges.
+fn intersecting(loops: &[Wire]) -> bool {
+    let segments: Vec<(Point3, Point3)> = loops
+        .iter()
+        .flat_map(|wire| {
+            wire.edge_iter().flat_map(|edge| {
+                let points = samples(edge);
+                (0..SAMPLES)
+                    .map(move |i| (points[i], points[i + 1]))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    let crossing = |(p0, p1): (Point3, Point3), (q0, q1): (Point3, Point3)| {
+        let shared = [p0, p1].iter().any(|p| p.near(&q0) || p.near(&q1));
+        // the segments are coplanar, so they cross if each separates the ends of the other
+        let separates = |(a, b): (Point3, Point3), (c, d): (Point3, Point3)| {
+            let normal = (b - a).cross(c - a);
+            let other = (b - a).cross(d - a);
+            normal.dot(other) < 0.0
+        };
+        !shared && separates((p0, p1), (q0, q1)) && separates((q0, q1), (p0, p1))
+    };
+    (0..segments.len()).any(|i| (i + 1..segments.len()).any(|j| crossing(segments[i], segments[j])))
+}
@@ -0,0 +1,272 @@
+use chapter3::{
+    feature::*,
+    primitive::{self, *},
+    sketch::*,
+};
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+use truck_stepio::out::*;
+
+fn save_shape(solid: &Solid, filename: &str) {
+    // output to polygonmesh
+    let mesh_with_topology = solid.triangulation(0.01);
+    let mesh = mesh_with_topology.to_polygon();
+    let obj_path = filename.to_string() + ".obj";
+    let mut obj = std::fs::File::create(&obj_path).unwrap();
+    obj::write(&mesh, &mut obj).unwrap();
+
+    // compress solid data.
+    let compressed = solid.compress();
+
+    // step format display
+    let display = CompleteStepDisplay::new(StepModel::from(&compressed), Default::default());
+    // content of step file
+    let step_string: String = display.to_string();
+    let step_path = filename.to_string() + ".step";
+    std::fs::write(&step_path, &step_string).unwrap();
+}
+
+// Checks that the solid is closed and has the volume.
+fn check(name: &str, solid: &Solid, volume: f64) {
+    for shell in solid.boundaries() {
+        assert_eq!(shell.shell_condition(), ShellCondition::Closed, "{name}");
+    }
+    let mut mesh = solid.triangulation(0.001).to_polygon();
+    mesh.put_together_same_attrs(10.0 * TOLERANCE)
+        .remove_degenerate_faces();
+    assert_eq!(mesh.shell_condition(), ShellCondition::Closed, "{name}");
+    println!("{name}: volume {:.5} (exact: {volume:.5})", mesh.volume());
+    assert!(f64::abs(mesh.volume() - volume) < 0.005 * volume, "{name}");
+}
+
+// the closed polygon of the points
+fn polygon(points: &[Point3]) -> Wire {
+    let vertices: Vec<Vertex> = points.iter().map(|p| builder::vertex(*p)).collect();
+    (0..vertices.len())
+        .map(|i| builder::line(&vertices[i], &vertices[(i + 1) % vertices.len()]))
+        .collect()
+}
+
+// the square of the side on the xy-plane centered at the point, counterclockwise
+fn square(center: Point3, side: f64) -> Wire {
+    let h = side / 2.0;
+    let points =
+        [(-h, -h), (h, -h), (h, h), (-h, h)].map(|(x, y)| center + Vector3::new(x, y, 0.0));
+    polygon(&points)
+}
+
+// the circle of the radius around the z-axis on the xy-plane
+fn circle(radius: f64) -> Wire {
+    builder::rsweep(
+        &builder::vertex(Point3::new(radius, 0.0, 0.0)),
+        Point3::origin(),
+        Vector3::unit_z(),
+        Rad(7.0),
+    )
+}
+
+// the frustum of the height between the similar sections of the areas
+fn frustum(bottom: f64, top: f64, height: f64) -> f64 {
+    height / 3.0 * (bottom + top + f64::sqrt(bottom * top))
+}
+
+fn main() {
+    let blind = |distance: f64, draft: f64| ExtrudeOptions {
+        extent: Extent::Blind(distance),
+        draft,
+    };
+
+    // the cube, and the frustum of the pyramid by the draft
+    let unit = square(Point3::origin(), 1.0);
+    let cube = extrude_wire(&unit, &blind(1.0, 0.0)).unwrap();
+    check("cube", &cube, 1.0);
+    let draft = 10.0_f64.to_radians();
+    let tapered = extrude_wire(&unit, &blind(1.0, draft)).unwrap();
+    let top = 1.0 - 2.0 * draft.tan();
+    check("tapered box", &tapered, frustum(1.0, top * top, 1.0));
+    // the negative draft widens the solid
+    let widened = extrude_wire(&unit, &blind(1.0, -draft)).unwrap();
+    let top = 1.0 + 2.0 * draft.tan();
+    check("widened box", &widened, frustum(1.0, top * top, 1.0));
+
+    // the frustum of the cone, both ways from the disk
+    let disk = circle(1.0);
+    let symmetric = ExtrudeOptions {
+        extent: Extent::Symmetric(2.0),
+        draft,
+    };
+    let cones = extrude_wire(&disk, &symmetric).unwrap();
+    let r = 1.0 - draft.tan();
+    check("two cones", &cones, 2.0 * frustum(PI, PI * r * r, 1.0));
+    let z = cones.vertex_iter().map(|v| v.point().z);
+    let (min, max) = z.fold((0.0, 0.0), |(min, max), z| {
+        (f64::min(min, z), f64::max(max, z))
+    });
+    assert!(min.near(&-1.0) && max.near(&1.0));
+
+    // the washer, whose hole grows by the draft
+    let washer = builder::try_attach_plane(&[circle(1.0), circle(0.5).inverse()]).unwrap();
+    let options = blind(0.5, draft);
+    let drafted = extrude(&washer, &options).unwrap();
+    let (outer, inner) = (1.0 - 0.5 * draft.tan(), 0.5 + 0.5 * draft.tan());
+    let volume = frustum(PI, PI * outer * outer, 0.5) - frustum(PI / 4.0, PI * inner * inner, 0.5);
+    check("drafted washer", &drafted, volume);
+
+    // up to the inclined face, whose height is 1 + x / 2
+    let target = builder::try_attach_plane(&[polygon(&[
+        Point3::new(0.0, 0.0, 1.0),
+        Point3::new(1.0, 0.0, 1.5),
+        Point3::new(1.0, 1.0, 1.5),
+        Point3::new(0.0, 1.0, 1.0),
+    ])])
+    .unwrap();
+    let up_to = ExtrudeOptions {
+        extent: Extent::UpToFace(target.clone()),
+        draft: 0.0,
+    };
+    let cornered = square(Point3::new(0.5, 0.5, 0.0), 1.0);
+    let wedge = extrude_wire(&cornered, &up_to).unwrap();
+    check("up to the face", &wedge, 1.25);
+
+    // The slot of the sketch with the draft, whose lines and arcs are tangent.
+    let (length, radius) = (2.0, 0.5);
+    let mut sketch = Sketch::new(Frame::default());
+    let c1 = sketch.point(0.0, 0.0);
+    let c2 = sketch.point(length, 0.0);
+    let p = [
+        (0.0, -radius),
+        (length, -radius),
+        (length, radius),
+        (0.0, radius),
+    ]
+    .map(|(x, y)| sketch.point(x, y));
+    let bottom = sketch.line(p[0], p[1]);
+    let right = sketch.arc(c2, p[1], p[2]);
+    let top = sketch.line(p[2], p[3]);
+    let left = sketch.arc(c1, p[3], p[0]);
+    let wire = sketch.wire(&[bottom, right, top, left]).unwrap();
+    let slot = extrude_wire(&wire, &blind(1.0, draft)).unwrap();
+    let area = |r: f64| 2.0 * r * length + PI * r * r;
+    let r = radius - draft.tan();
+    // the volume of the drafted slot by the integral of the areas of the sections
+    let volume = (area(radius) + 4.0 * area((radius + r) / 2.0) + area(r)) / 6.0;
+    check("drafted slot", &slot, volume);
+    save_shape(&slot, "drafted-slot");
+
+    // the revolutions of the rectangle off the axis, which make the tubes
+    let rectangle = polygon(&[
+        Point3::new(0.5, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 1.0),
+        Point3::new(0.5, 0.0, 1.0),
+    ]);
+    let (origin, axis) = (Point3::origin(), Vector3::unit_z());
+    let tube = revolve_wire(&rectangle, origin, axis, RevolveExtent::Full).unwrap();
+    let volume = PI * (1.0 - 0.25);
+    check("tube", &tube, volume);
+    let quarter = revolve_wire(&rectangle, origin, axis, RevolveExtent::Angle(PI / 2.0)).unwrap();
+    check("quarter of the tube", &quarter, volume / 4.0);
+    // the half tube on both sides of the profile, and the one on the other side of the axis
+    let two_sided = RevolveExtent::TwoSided(PI);
+    let half = revolve_wire(&rectangle, origin, axis, two_sided).unwrap();
+    check("half of the tube", &half, volume / 2.0);
+    assert!(half.vertex_iter().all(|v| v.point().x > -TOLERANCE));
+    let mirrored = builder::rotated(&rectangle, origin, axis, Rad(PI));
+    let half = revolve_wire(&mirrored, origin, axis, two_sided).unwrap();
+    check("mirrored half", &half, volume / 2.0);
+    assert!(half.vertex_iter().all(|v| v.point().x < TOLERANCE));
+
+    // The half disk on the axis makes the sphere, and the hole makes the void of the torus.
+    let [north, south] = [1.0, -1.0].map(|z| builder::vertex(Point3::new(0.0, 0.0, z)));
+    let half_disk: Wire = vec![
+        builder::circle_arc(&south, &north, Point3::new(1.0, 0.0, 0.0)),
+        builder::line(&north, &south),
+    ]
+    .into();
+    let sphere = revolve_wire(&half_disk, origin, axis, RevolveExtent::Full).unwrap();
+    check("sphere", &sphere, 4.0 / 3.0 * PI);
+    let hole = builder::rsweep(
+        &builder::vertex(Point3::new(0.6, 0.0, 0.0)),
+        Point3::new(0.5, 0.0, 0.0),
+        Vector3::unit_y(),
+        Rad(7.0),
+    );
+    let profile = builder::try_attach_plane(&[half_disk.clone(), hole]).unwrap();
+    let hollow = revolve(&profile, origin, axis, RevolveExtent::Full).unwrap();
+    assert_eq!(hollow.boundaries().len(), 2);
+    let torus = 2.0 * PI * PI * 0.5 * 0.1 * 0.1;
+    check("hollow sphere", &hollow, 4.0 / 3.0 * PI - torus);
+    let bowl = revolve_wire(&half_disk, origin, axis, RevolveExtent::Angle(PI)).unwrap();
+    check("half sphere", &bowl, 2.0 / 3.0 * PI);
+    let slice = revolve(&profile, origin, axis, RevolveExtent::TwoSided(PI / 2.0)).unwrap();
+    check(
+        "slice of the hollow sphere",
+        &slice,
+        (4.0 / 3.0 * PI - torus) / 4.0,
+    );
+    save_shape(&slice, "revolved-slice");
+
+    // errors
+    assert_eq!(
+        extrude_wire(&unit, &blind(0.0, 0.0)).err(),
+        Some(FeatureError::InvalidDimension("distance"))
+    );
+    assert_eq!(
+        extrude_wire(&unit, &blind(1.0, PI / 2.0)).err(),
+        Some(FeatureError::InvalidDimension("draft"))
+    );
+    let open: Wire = unit.edge_iter().take(3).cloned().collect();
+    assert_eq!(
+        extrude_wire(&open, &blind(1.0, 0.0)).err(),
+        Some(FeatureError::NotClosed)
+    );
+    let rod = primitive::cylinder(&Frame::default(), 1.0, 1.0).unwrap();
+    let lateral = rod
+        .face_iter()
+        .find(|face| !matches!(face.surface(), Surface::Plane(_)))
+        .unwrap();
+    assert_eq!(
+        extrude(lateral, &blind(1.0, 0.0)).err(),
+        Some(FeatureError::NotPlanar)
+    );
+    let behind = builder::translated(&target, Vector3::new(0.0, 0.0, -1.2));
+    let unreachable = ExtrudeOptions {
+        extent: Extent::UpToFace(behind),
+        draft: 0.0,
+    };
+    assert_eq!(
+        extrude_wire(&cornered, &unreachable).err(),
+        Some(FeatureError::Unreachable)
+    );
+    let inclined = ExtrudeOptions { draft, ..up_to };
+    assert_eq!(
+        extrude_wire(&cornered, &inclined).err(),
+        Some(FeatureError::UnsupportedDraft)
+    );
+    // the draft collapses the square
+    assert_eq!(
+        extrude_wire(&unit, &blind(1.0, 0.5)).err(),
+        Some(FeatureError::SelfIntersecting)
+    );
+    // the hole crosses the outer boundary, which is found in the triangulation
+    let hole = square(Point3::new(0.5, 0.0, 0.0), 0.4).inverse();
+    let profile = builder::try_attach_plane(&[unit.clone(), hole]).unwrap();
+    assert_eq!(
+        extrude(&profile, &blind(1.0, 0.0)).err(),
+        Some(FeatureError::SelfIntersecting)
+    );
+    let crossing = builder::translated(&rectangle, Vector3::new(-0.75, 0.0, 0.0));
+    assert_eq!(
+        revolve_wire(&crossing, origin, axis, RevolveExtent::Full).err(),
+        Some(FeatureError::SelfIntersecting)
+    );
+    assert_eq!(
+        revolve_wire(&rectangle, origin, Vector3::unit_y(), RevolveExtent::Full).err(),
+        Some(FeatureError::InvalidAxis)
+    );
+    assert_eq!(
+        revolve_wire(&rectangle, origin, axis, RevolveExtent::Angle(2.0 * PI)).err(),
+        Some(FeatureError::InvalidDimension("angle"))
+    );
+}
@@ -0,0 +1,173 @@
+use chapter3::{
+    blend::*,
+    primitive::{self, *},
+};
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+use truck_stepio::out::*;
+
+fn save_shape(solid: &Solid, filename: &str) {
+    // output to polygonmesh
+    let mesh_with_topology = solid.triangulation(0.01);
+    let mesh = mesh_with_topology.to_polygon();
+    let obj_path = filename.to_string() + ".obj";
+    let mut obj = std::fs::File::create(&obj_path).unwrap();
+    obj::write(&mesh, &mut obj).unwrap();
+
+    // compress solid data.
+    let compressed = solid.compress();
+
+    // step format display
+    let display = CompleteStepDisplay::new(StepModel::from(&compressed), Default::default());
+    // content of step file
+    let step_string: String = display.to_string();
+    let step_path = filename.to_string() + ".step";
+    std::fs::write(&step_path, &step_string).unwrap();
+}
+
+// the edges of the solid whose end points satisfy the condition
+fn edges_where(solid: &Solid, condition: impl Fn(Point3) -> bool) -> Vec<Edge> {
+    solid.boundaries()[0]
+        .edge_iter()
+        .filter(|edge| condition(edge.front().point()) && condition(edge.back().point()))
+        .fold(Vec::new(), |mut edges: Vec<Edge>, edge| {
+            if !edges.iter().any(|e| e.is_same(&edge)) {
+                edges.push(edge);
+            }
+            edges
+        })
+}
+
+// Checks that the blended solid is valid and has the volume.
+fn check(name: &str, blended: &Blended, volume: f64) {
+    assert!(blended.skipped.is_empty(), "{name}: {:?}", blended.skipped);
+    let shell = &blended.solid.boundaries()[0];
+    assert_eq!(shell.shell_condition(), ShellCondition::Closed, "{name}");
+    assert!(shell.singular_vertices().is_empty(), "{name}");
+    let mut mesh = blended.solid.triangulation(0.001).to_polygon();
+    // The triangles at the poles of the vertex blends are degenerate.
+    mesh.put_together_same_attrs(10.0 * TOLERANCE)
+        .remove_degenerate_faces();
+    assert_eq!(mesh.shell_condition(), ShellCondition::Closed, "{name}");
+    println!("{name}: volume {:.5} (exact: {volume:.5})", mesh.volume());
+    assert!(f64::abs(mesh.volume() - volume) < 0.002 * volume, "{name}");
+}
+
+fn main() {
+    let a = 2.0;
+    let cube = cuboid(&Frame::default(), a, a, a).unwrap();
+    let all = edges_where(&cube, |_| true);
+    let top = edges_where(&cube, |p| p.z == a);
+    let vertical = edges_where(&cube, |p| p.x == a / 2.0 && p.y == a / 2.0);
+    assert_eq!((all.len(), top.len(), vertical.len()), (12, 4, 1));
+
+    // rounded cube: the vertex blends are the spherical triangles
+    let r = 0.3;
+    let blended = fillet(&cube, &all, r).unwrap();
+    let b = a - 2.0 * r;
+    let volume = b * b * b + 6.0 * b * b * r + 3.0 * PI * r * r * b + 4.0 * PI * r * r * r / 3.0;
+    check("rounded cube", &blended, volume);
+    save_shape(&blended.solid, "rounded-cube");
+
+    // the chain of the top edges: the fillets meet at the miters
+    let blended = fillet(&cube, &top, r).unwrap();
+    let removed = (1.0 - PI / 4.0) * r * r * a - 2.0 * r * r * r * (5.0 / 6.0 - PI / 4.0);
+    check("rounded top", &blended, a * a * a - 4.0 * removed);
+    save_shape(&blended.solid, "rounded-top");
+
+    // the variable fillet from the radius 0.2 at the bottom to 0.5 at the top
+    let edge = &vertical[0];
+    let (r0, r1) = match edge.front().point().z == 0.0 {
+        true => (0.2, 0.5),
+        false => (0.5, 0.2),
+    };
+    let blended = blend(&cube, &[(edge.clone(), Blend::VariableFillet(r0, r1))]).unwrap();
+    let removed = (1.0 - PI / 4.0) * a * (r0 * r0 + r0 * r1 + r1 * r1) / 3.0;
+    check("variable fillet", &blended, a * a * a - removed);
+    save_shape(&blended.solid, "variable-fillet");
+
+    // chamfered cube: the vertex blends are the triangles
+    let d = 0.3;
+    let blended = chamfer(&cube, &all, d).unwrap();
+    let volume = a * a * a - 8.0 * 5.0 * d * d * d / 6.0 - 6.0 * d * d * (a - 2.0 * d);
+    check("chamfered cube", &blended, volume);
+    save_shape(&blended.solid, "chamfered-cube");
+
+    let blended = chamfer(&cube, &top, d).unwrap();
+    let removed = d * d / 2.0 * (a - 2.0 * d / 3.0);
+    check("chamfered top", &blended, a * a * a - 4.0 * removed);
+
+    // the chamfer of the distance 0.3 and the angle 30 degrees
+    let blend_angle = Blend::AngledChamfer(d, Rad(PI / 6.0));
+    let blended = blend(&cube, &[(edge.clone(), blend_angle)]).unwrap();
+    let other = d * f64::sin(PI / 6.0) / f64::sin(PI / 2.0 + PI / 6.0);
+    check("angled chamfer", &blended, a * a * a - d * other / 2.0 * a);
+
+    // the hexagonal prism with the rounded edges, which is the inner prism offset by `r`
+    let (r, h) = (0.1, 1.0);
+    let hexagon = prism(&Frame::default(), 6, 1.0, h).unwrap();
+    let all = edges_where(&hexagon, |_| true);
+    let blended = fillet(&hexagon, &all, r).unwrap();
+    let side = 2.0 * (f64::sqrt(3.0) / 2.0 - r) / f64::sqrt(3.0);
+    let (area, height) = (3.0 * f64::sqrt(3.0) / 2.0 * side * side, h - 2.0 * r);
+    let surface = 2.0 * area + 6.0 * side * height;
+    let edges = 12.0 * side * PI / 2.0 + 6.0 * height * PI / 3.0;
+    let volume = area * height + surface * r + edges * r * r / 2.0 + 4.0 * PI * r * r * r / 3.0;
+    check("rounded prism", &blended, volume);
+    save_shape(&blended.solid, "rounded-prism");
+
+    // the edges which cannot be blended are reported
+    let front = edges_where(&cube, |p| p.z == a && p.y == -a / 2.0);
+    let right = edges_where(&cube, |p| p.z == a && p.x == a / 2.0);
+    let bottom = edges_where(&cube, |p| p.z == 0.0);
+    let cylinder = primitive::cylinder(&Frame::default(), 1.0, 1.0).unwrap();
+    let circle = cylinder.boundaries()[0].edge_iter().next().unwrap();
+    // The fillets of the different radii meet at the corner (a / 2, -a / 2, a).
+    let blends = [
+        (front[0].clone(), Blend::Fillet(0.3)),
+        (right[0].clone(), Blend::Fillet(0.2)),
+        (bottom[0].clone(), Blend::Fillet(2.5)),
+        (circle.clone(), Blend::Fillet(0.1)),
+    ];
+    let blended = blend(&cube, &blends).unwrap();
+    let errors: Vec<BlendError> = blended.skipped.iter().map(|(_, error)| *error).collect();
+    println!("skipped: {errors:?}");
+    assert_eq!(
+        errors,
+        [
+            BlendError::UnknownEdge,
+            BlendError::MismatchedCorner,
+            BlendError::TooLarge
+        ]
+    );
+    // The edges on the curved faces are not supported.
+    let blended = fillet(&cylinder, &[circle], 0.1).unwrap();
+    assert_eq!(blended.skipped[0].1, BlendError::NotPlanar);
+
+    // the concave edge of the L-shaped prism
+    let points = [
+        (0.0, 0.0),
+        (2.0, 0.0),
+        (2.0, 1.0),
+        (1.0, 1.0),
+        (1.0, 2.0),
+        (0.0, 2.0),
+    ];
+    let vertices: Vec<Vertex> = points
+        .iter()
+        .map(|(x, y)| builder::vertex(Point3::new(*x, *y, 0.0)))
+        .collect();
+    let wire: Wire = (0..6)
+        .map(|i| builder::line(&vertices[i], &vertices[(i + 1) % 6]))
+        .collect();
+    let face = builder::try_attach_plane(&[wire]).unwrap();
+    let l_shape = builder::tsweep(&face, Vector3::unit_z());
+    let inner = edges_where(&l_shape, |p| p.x == 1.0 && p.y == 1.0);
+    let blended = fillet(&l_shape, &inner, 0.1).unwrap();
+    assert_eq!(blended.skipped[0].1, BlendError::ConcaveEdge);
+    assert_eq!(
+        fillet(&cube, &top, -1.0).err(),
+        Some(BlendError::InvalidDimension)
+    );
+}
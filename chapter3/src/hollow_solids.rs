@@ -0,0 +1,152 @@
+use chapter3::{
+    primitive::{self, *},
+    shelling::*,
+};
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+use truck_stepio::out::*;
+
+fn save_shape(solid: &Solid, filename: &str) {
+    // output to polygonmesh
+    let mesh_with_topology = solid.triangulation(0.01);
+    let mesh = mesh_with_topology.to_polygon();
+    let obj_path = filename.to_string() + ".obj";
+    let mut obj = std::fs::File::create(&obj_path).unwrap();
+    obj::write(&mesh, &mut obj).unwrap();
+
+    // compress solid data.
+    let compressed = solid.compress();
+
+    // step format display
+    let display = CompleteStepDisplay::new(StepModel::from(&compressed), Default::default());
+    // content of step file
+    let step_string: String = display.to_string();
+    let step_path = filename.to_string() + ".step";
+    std::fs::write(&step_path, &step_string).unwrap();
+}
+
+// the face of the solid whose vertices satisfy the condition
+fn face_where(solid: &Solid, condition: impl Fn(Point3) -> bool) -> Face {
+    solid
+        .face_iter()
+        .find(|face| face.vertex_iter().all(|vertex| condition(vertex.point())))
+        .unwrap()
+        .clone()
+}
+
+// Checks that the hollowed solid has the closed boundaries and the volume.
+fn check(name: &str, solid: &Solid, shells: usize, volume: f64) {
+    assert_eq!(solid.boundaries().len(), shells, "{name}");
+    for shell in solid.boundaries() {
+        assert_eq!(shell.shell_condition(), ShellCondition::Closed, "{name}");
+        assert!(shell.singular_vertices().is_empty(), "{name}");
+    }
+    let mut mesh = solid.triangulation(0.001).to_polygon();
+    // The triangles at the poles of the spheres are degenerate.
+    mesh.put_together_same_attrs(10.0 * TOLERANCE)
+        .remove_degenerate_faces();
+    assert_eq!(mesh.shell_condition(), ShellCondition::Closed, "{name}");
+    println!("{name}: volume {:.5} (exact: {volume:.5})", mesh.volume());
+    assert!(f64::abs(mesh.volume() - volume) < 0.005 * volume, "{name}");
+}
+
+fn main() {
+    // the cube with the closed cavity
+    let (a, t) = (2.0, 0.2);
+    let cube = cuboid(&Frame::default(), a, a, a).unwrap();
+    let hollowed = shell(&cube, t, &[]).unwrap();
+    let b = a - 2.0 * t;
+    check("hollow cube", &hollowed, 2, a * a * a - b * b * b);
+
+    // the open box
+    let top = face_where(&cube, |p| p.z == a);
+    let open_box = shell(&cube, t, std::slice::from_ref(&top)).unwrap();
+    check("open box", &open_box, 1, a * a * a - b * b * (a - t));
+    save_shape(&open_box, "open-box");
+
+    // the cup from the cylinder
+    let (r, h) = (1.0, 2.0);
+    let cylinder = primitive::cylinder(&Frame::default(), r, h).unwrap();
+    let rim = face_where(&cylinder, |p| p.z == h);
+    let cup = shell(&cylinder, t, &[rim]).unwrap();
+    let volume = PI * (r * r * h - (r - t) * (r - t) * (h - t));
+    check("cup", &cup, 1, volume);
+    save_shape(&cup, "cup");
+
+    // the hollow sphere
+    let sphere = primitive::sphere(&Frame::default(), 1.5).unwrap();
+    let hollowed = shell(&sphere, t, &[]).unwrap();
+    let volume = 4.0 * PI * (1.5f64.powi(3) - 1.3f64.powi(3)) / 3.0;
+    check("hollow sphere", &hollowed, 2, volume);
+
+    // the dome: the inner edges are the circles on the inner sphere
+    let radius = 1.5;
+    let north = builder::vertex(Point3::new(0.0, 0.0, radius));
+    let equator = builder::vertex(Point3::new(radius, 0.0, 0.0));
+    let center = builder::vertex(Point3::origin());
+    let transit = Point3::new(radius, 0.0, radius) / f64::sqrt(2.0);
+    let wire: Wire = vec![
+        builder::circle_arc(&north, &equator, transit),
+        builder::line(&equator, &center),
+    ]
+    .into();
+    let dome = Solid::new(vec![builder::cone(&wire, Vector3::unit_z(), Rad(7.0))]);
+    let hollowed = shell(&dome, t, &[]).unwrap();
+    let (inner, cap) = (radius - t, radius - 2.0 * t);
+    let volume = 2.0 * PI * radius.powi(3) / 3.0 - PI * cap * cap * (3.0 * inner - cap) / 3.0;
+    check("hollow dome", &hollowed, 2, volume);
+
+    // The sheared cylinder has the slanted ends. The inner edges of the top ellipse are the
+    // projections of the circles on the inner cylinder along the axis.
+    let angle = PI / 6.0;
+    let shear = Matrix4::from_cols(
+        Vector4::unit_x(),
+        Vector4::new(0.0, 1.0, angle.tan(), 0.0),
+        Vector4::unit_z(),
+        Vector4::unit_w(),
+    );
+    let sheared = builder::transformed(&cylinder, shear);
+    let slope = face_where(&sheared, |p| p.z > h / 2.0);
+    let opened = shell(&sheared, t, &[slope]).unwrap();
+    let volume = PI * (r * r * h - (r - t) * (r - t) * (h - t / angle.cos()));
+    check("oblique cup", &opened, 1, volume);
+    save_shape(&opened, "oblique-cup");
+
+    // errors
+    assert_eq!(
+        shell(&cube, 0.0, &[]).err(),
+        Some(ShellError::InvalidThickness)
+    );
+    assert_eq!(
+        shell(&cube, f64::NAN, &[]).err(),
+        Some(ShellError::InvalidThickness)
+    );
+    let other = face_where(&cylinder, |p| p.z == 0.0);
+    assert_eq!(
+        shell(&cube, t, &[other]).err(),
+        Some(ShellError::UnknownFace)
+    );
+    let side = face_where(&cube, |p| p.x == a / 2.0);
+    assert_eq!(
+        shell(&cube, t, &[top, side]).err(),
+        Some(ShellError::UnsupportedOpening)
+    );
+    // The base of the dome consists of the adjacent faces.
+    let bases: Vec<Face> = dome
+        .face_iter()
+        .filter(|face| face.vertex_iter().all(|vertex| vertex.point().z == 0.0))
+        .cloned()
+        .collect();
+    assert_eq!(
+        shell(&dome, t, &bases).err(),
+        Some(ShellError::UnsupportedOpening)
+    );
+    assert_eq!(shell(&cube, 1.2, &[]).err(), Some(ShellError::TooThick));
+    assert_eq!(shell(&cylinder, 1.5, &[]).err(), Some(ShellError::TooThick));
+    let torus = torus(&Frame::default(), 1.0, 0.3).unwrap();
+    assert_eq!(
+        shell(&torus, 0.1, &[]).err(),
+        Some(ShellError::UnsupportedSurface)
+    );
+}
@@ -0,0 +1,13 @@
+//! The utility modules of chapter 3, shared by the samples.
+
+pub mod blend;
+pub mod deviation;
+pub mod feature;
+pub mod loft;
+pub mod pattern;
+pub mod primitive;
+pub mod shapeops;
+pub mod shelling;
+pub mod sketch;
+pub mod sweep;
+pub mod thread;
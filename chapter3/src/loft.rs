@@ -0,0 +1,633 @@
+//! Lofts through the sections.
+//!
+//! The lofted surfaces pass through the wires of the sections in order, which have to be all
+//! closed or all open. The start points and the directions of the sections are aligned to the
+//! previous sections, and the edges are split so that the sections have the edges at the same
+//! ratios of the lengths, which are joined by the lofted edges.
+//!
+//! The guides fix the points of the sections joined by the lofted edges: each guide has to
+//! pass through every section once, and the sections are split at the guides. The lofted
+//! edges are interpolated through the points, so the guides are followed only at the
+//! sections.
+//!
+//! The lofted surfaces are the NURBS surfaces interpolating the sections by the cubic
+//! B-splines, or by the lines for the ruled lofts. The rational curves are split into their
+//! spans, which are reparametrized to have the unit weights at their ends so that the
+//! adjacent surfaces share the lofted edges.
+
+use crate::sweep::{interpolate, interpolation, nurbs};
+use std::result::Result;
+use truck_modeling::*;
+
+/// The options of the lofts.
+#[derive(Clone, Debug, Default)]
+pub struct LoftOptions {
+    /// whether the surfaces are ruled between the consecutive sections, instead of the cubic
+    /// interpolation of all the sections
+    pub ruled: bool,
+    /// whether the closed sections at the ends are capped by the planar faces
+    pub caps: bool,
+    /// the wires through the sections, which fix the points joined by the lofted edges
+    pub guides: Vec<Wire>,
+}
+
+/// The errors of the lofts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoftError {
+    /// There are less than two sections.
+    TooFewSections,
+    /// A section is empty or not continuous, or the sections are not all closed or all open.
+    InvalidSection,
+    /// A section or a guide has an intersection curve or an unclamped curve.
+    UnsupportedCurve,
+    /// A guide does not pass through every section once, or the guides are in the different
+    /// orders on the sections.
+    InvalidGuide,
+    /// A section at the ends is not planar, and cannot be capped.
+    NonPlanarCap,
+    /// The sections are not closed for the solid.
+    NotClosed,
+}
+
+impl std::fmt::Display for LoftError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoftError::TooFewSections => f.write_str("the loft needs two sections at least"),
+            LoftError::InvalidSection => f.write_str("the sections are not valid wires"),
+            LoftError::UnsupportedCurve => f.write_str("the curve is not supported"),
+            LoftError::InvalidGuide => f.write_str("the guide does not meet the sections"),
+            LoftError::NonPlanarCap => f.write_str("the section at the end is not planar"),
+            LoftError::NotClosed => f.write_str("the sections are not closed"),
+        }
+    }
+}
+
+impl std::error::Error for LoftError {}
+
+type Nurbs = NurbsCurve<Vector4>;
+
+// a section as the curves in order
+struct Section {
+    curves: Vec<Nurbs>,
+    closed: bool,
+}
+
+/// Returns the shell lofted through the sections.
+pub fn loft(sections: &[Wire], options: &LoftOptions) -> Result<Shell, LoftError> {
+    if sections.len() < 2 {
+        return Err(LoftError::TooFewSections);
+    }
+    let closed = sections[0].is_closed();
+    let mut curves = Vec::new();
+    for wire in sections {
+        if wire.is_empty() || !wire.is_continuous() || wire.is_closed() != closed {
+            return Err(LoftError::InvalidSection);
+        }
+        let section = wire
+            .edge_iter()
+            .map(|edge| nurbs(&edge.oriented_curve()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(LoftError::UnsupportedCurve)?;
+        curves.push(section.into_iter().flat_map(spans).collect::<Vec<_>>());
+    }
+    let guides = options
+        .guides
+        .iter()
+        .map(|wire| {
+            wire.edge_iter()
+                .map(|edge| nurbs(&edge.oriented_curve()))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(LoftError::UnsupportedCurve)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut sections: Vec<Section> = curves
+        .into_iter()
+        .map(|curves| Section { curves, closed })
+        .collect();
+    // the indices of the curves starting at the guides and at the ends of the open sections,
+    // and the order of the guides on the sections
+    let mut breaks = Vec::new();
+    let mut orders = Vec::new();
+    for idx in 0..sections.len() {
+        let points = guides
+            .iter()
+            .map(|guide| sections[idx].split_at_guide(guide))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(LoftError::InvalidGuide)?;
+        match idx {
+            0 => {
+                let section = &mut sections[0];
+                if let (true, Some(point)) = (closed, points.first()) {
+                    let start = section.index_of(*point).ok_or(LoftError::InvalidGuide)?;
+                    section.curves.rotate_left(start);
+                }
+            }
+            _ => {
+                let (previous, section) = sections.split_at_mut(idx);
+                section[0].align(&previous[idx - 1], points.first());
+            }
+        }
+        let section = &sections[idx];
+        let indices = points
+            .iter()
+            .map(|point| section.index_of(*point))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(LoftError::InvalidGuide)?;
+        let mut order: Vec<usize> = (0..indices.len()).collect();
+        order.sort_by_key(|i| indices[*i]);
+        orders.push(order);
+        let mut indices = indices;
+        indices.extend([0, section.curves.len()]);
+        indices.sort();
+        indices.dedup();
+        breaks.push(indices);
+    }
+    if orders.iter().any(|order| *order != orders[0])
+        || breaks
+            .iter()
+            .any(|indices| indices.len() != breaks[0].len())
+    {
+        return Err(LoftError::InvalidGuide);
+    }
+    // The curves between the breaks are split at the same ratios of the lengths.
+    let mut split: Vec<Vec<Nurbs>> = vec![Vec::new(); sections.len()];
+    for segment in 0..breaks[0].len() - 1 {
+        let ranges: Vec<&[Nurbs]> = sections
+            .iter()
+            .zip(&breaks)
+            .map(|(section, indices)| &section.curves[indices[segment]..indices[segment + 1]])
+            .collect();
+        let mut ratios: Vec<f64> = ranges
+            .iter()
+            .flat_map(|curves| inner_ratios(curves))
+            .collect();
+        ratios.sort_by(f64::total_cmp);
+        ratios.dedup_by(|a, b| (*a - *b).so_small());
+        for (curves, split) in ranges.iter().zip(&mut split) {
+            split.extend(split_at_ratios(curves, &ratios));
+        }
+    }
+    let sections: Vec<Vec<Nurbs>> = split
+        .into_iter()
+        .map(|curves| {
+            curves
+                .into_iter()
+                .map(unit_ends)
+                .collect::<Option<Vec<_>>>()
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or(LoftError::UnsupportedCurve)?;
+    build(&sections, closed, options)
+}
+
+/// Returns the solid lofted through the closed sections, capped at the ends.
+pub fn loft_solid(sections: &[Wire], options: &LoftOptions) -> Result<Solid, LoftError> {
+    if sections.iter().any(|wire| !wire.is_closed()) {
+        return Err(LoftError::NotClosed);
+    }
+    let options = LoftOptions {
+        caps: true,
+        ..options.clone()
+    };
+    let shell = loft(sections, &options)?;
+    Solid::try_new(vec![shell]).map_err(|_| LoftError::NotClosed)
+}
+
+impl Section {
+    fn point(&self, ratio: f64) -> Point3 {
+        let lengths: Vec<f64> = self.curves.iter().map(length).collect();
+        let mut rest = ratio * lengths.iter().sum::<f64>();
+        for (curve, length) in self.curves.iter().zip(&lengths) {
+            if rest <= *length {
+                return curve.subs(parameter_at(curve, rest));
+            }
+            rest -= length;
+        }
+        let curve = self.curves.last().unwrap();
+        curve.subs(curve.range_tuple().1)
+    }
+
+    // Returns the index of the curve starting at the point.
+    fn index_of(&self, point: Point3) -> Option<usize> {
+        let ends = self
+            .curves
+            .iter()
+            .map(|curve| curve.subs(curve.range_tuple().0));
+        let mut ends: Vec<Point3> = ends.collect();
+        if !self.closed {
+            let last = self.curves.last().unwrap();
+            ends.push(last.subs(last.range_tuple().1));
+        }
+        ends.iter().position(|end| end.near(&point))
+    }
+
+    // Splits the curves at the point on the guide, and returns the point.
+    fn split_at_guide(&mut self, guide: &[Nurbs]) -> Option<Point3> {
+        // the nearest pair of the points on the curves and on the guide
+        let mut nearest: Option<(f64, usize, f64)> = None;
+        for (idx, curve) in self.curves.iter().enumerate() {
+            for edge in guide {
+                let (t0, t1) = edge.range_tuple();
+                for i in 0..=SAMPLES {
+                    let mut s = t0 + (t1 - t0) * i as f64 / SAMPLES as f64;
+                    let mut t = curve.search_nearest_parameter(edge.subs(s), None, 100)?;
+                    for _ in 0..10 {
+                        s = edge.search_nearest_parameter(curve.subs(t), Some(s), 100)?;
+                        t = curve.search_nearest_parameter(edge.subs(s), Some(t), 100)?;
+                    }
+                    let distance = curve.subs(t).distance(edge.subs(s));
+                    if nearest.is_none_or(|(d, _, _)| distance < d) {
+                        nearest = Some((distance, idx, t));
+                    }
+                }
+            }
+        }
+        let (distance, idx, t) = nearest?;
+        if !distance.so_small() {
+            return None;
+        }
+        let point = self.curves[idx].subs(t);
+        let (t0, t1) = self.curves[idx].range_tuple();
+        if !point.near(&self.curves[idx].subs(t0)) && !point.near(&self.curves[idx].subs(t1)) {
+            let tail = self.curves[idx].cut(t);
+            self.curves.insert(idx + 1, tail);
+        }
+        Some(point)
+    }
+
+    // Aligns the start point and the direction to the previous section. The closed section
+    // starts at the first guide if any.
+    fn align(&mut self, previous: &Section, guide: Option<&Point3>) {
+        let ratios = (0..SAMPLES).map(|i| i as f64 / SAMPLES as f64);
+        let points: Vec<Point3> = ratios.clone().map(|r| previous.point(r)).collect();
+        let center = centroid(&points);
+        let cost = |section: &Section| {
+            let others: Vec<Point3> = ratios.clone().map(|r| section.point(r)).collect();
+            let other_center = centroid(&others);
+            let pairs = points.iter().zip(&others);
+            pairs.fold(0.0, |sum, (p, q)| {
+                sum + ((p - center) - (q - other_center)).magnitude2()
+            })
+        };
+        // the candidates of the curves starting at the vertices, and at the point nearest to
+        // the start of the previous section
+        let rotated = |curves: &[Nurbs], start: usize| {
+            let mut curves = curves.to_vec();
+            curves.rotate_left(start);
+            curves
+        };
+        let mut candidates: Vec<Vec<Nurbs>> = match (self.closed, guide) {
+            (false, _) => vec![self.curves.clone()],
+            (true, Some(point)) => {
+                let start = self.index_of(*point);
+                start
+                    .map(|start| rotated(&self.curves, start))
+                    .into_iter()
+                    .collect()
+            }
+            (true, None) => (0..self.curves.len())
+                .map(|start| rotated(&self.curves, start))
+                .collect(),
+        };
+        if let (true, None) = (self.closed, guide) {
+            let ends: Vec<Point3> = ratios.clone().map(|r| self.point(r)).collect();
+            let target = centroid(&ends) + (points[0] - center);
+            let mut section = Section {
+                curves: self.curves.clone(),
+                closed: true,
+            };
+            if let Some(start) = section.split_nearest(target) {
+                candidates.push(rotated(&section.curves, start));
+            }
+        }
+        let mut best: Option<(f64, Vec<Nurbs>)> = None;
+        for curves in candidates {
+            for reversed in [false, true] {
+                let mut section = Section {
+                    curves: curves.clone(),
+                    closed: self.closed,
+                };
+                if reversed {
+                    section.reverse();
+                }
+                let cost = cost(&section);
+                if best.as_ref().is_none_or(|(c, _)| cost < *c) {
+                    best = Some((cost, section.curves));
+                }
+            }
+        }
+        if let Some((_, curves)) = best {
+            self.curves = curves;
+        }
+    }
+
+    // Splits the curves at the point nearest to the target, and returns the index of the
+    // curve starting at the point.
+    fn split_nearest(&mut self, target: Point3) -> Option<usize> {
+        let mut nearest: Option<(f64, usize, f64)> = None;
+        for (idx, curve) in self.curves.iter().enumerate() {
+            let (t0, t1) = curve.range_tuple();
+            for i in 0..=SAMPLES {
+                let hint = t0 + (t1 - t0) * i as f64 / SAMPLES as f64;
+                if let Some(t) = curve.search_nearest_parameter(target, Some(hint), 100) {
+                    let t = f64::clamp(t, t0, t1);
+                    let distance = curve.subs(t).distance(target);
+                    if nearest.is_none_or(|(d, _, _)| distance < d) {
+                        nearest = Some((distance, idx, t));
+                    }
+                }
+            }
+        }
+        let (_, idx, t) = nearest?;
+        let point = self.curves[idx].subs(t);
+        let (t0, t1) = self.curves[idx].range_tuple();
+        if point.near(&self.curves[idx].subs(t0)) {
+            return Some(idx);
+        }
+        if point.near(&self.curves[idx].subs(t1)) {
+            return Some((idx + 1) % self.curves.len());
+        }
+        let tail = self.curves[idx].cut(t);
+        self.curves.insert(idx + 1, tail);
+        Some(idx + 1)
+    }
+
+    // Reverses the direction, which keeps the start point of the closed section.
+    fn reverse(&mut self) {
+        self.curves.reverse();
+        self.curves.iter_mut().for_each(|curve| {
+            curve.invert();
+        });
+    }
+}
+
+// the number of the samples on the curves
+const SAMPLES: usize = 32;
+
+// the lengths from the start of the curve at the even parameters
+fn lengths(curve: &Nurbs) -> Vec<f64> {
+    let (t0, t1) = curve.range_tuple();
+    let points: Vec<Point3> = (0..=SAMPLES)
+        .map(|i| curve.subs(t0 + (t1 - t0) * i as f64 / SAMPLES as f64))
+        .collect();
+    let mut lengths = vec![0.0];
+    for pair in points.windows(2) {
+        lengths.push(lengths.last().unwrap() + pair[0].distance(pair[1]));
+    }
+    lengths
+}
+
+fn length(curve: &Nurbs) -> f64 { *lengths(curve).last().unwrap() }
+
+// the parameter of the curve at the length from the start
+fn parameter_at(curve: &Nurbs, length: f64) -> f64 {
+    let (t0, t1) = curve.range_tuple();
+    let lengths = lengths(curve);
+    let i = lengths.iter().rposition(|l| *l <= length).unwrap_or(0);
+    let i = usize::min(i, SAMPLES - 1);
+    let ratio = match lengths[i + 1] - lengths[i] {
+        step if step > 0.0 => (length - lengths[i]) / step,
+        _ => 0.0,
+    };
+    t0 + (t1 - t0) * (i as f64 + f64::clamp(ratio, 0.0, 1.0)) / SAMPLES as f64
+}
+
+fn centroid(points: &[Point3]) -> Point3 {
+    let sum = points
+        .iter()
+        .fold(Vector3::zero(), |sum, p| sum + p.to_vec());
+    Point3::from_vec(sum / points.len() as f64)
+}
+
+// the ratios of the lengths at the inner vertices of the curves
+fn inner_ratios(curves: &[Nurbs]) -> Vec<f64> {
+    let lengths: Vec<f64> = curves.iter().map(length).collect();
+    let total: f64 = lengths.iter().sum();
+    let mut sum = 0.0;
+    let mut ratios = Vec::new();
+    for length in &lengths[..lengths.len() - 1] {
+        sum += length;
+        ratios.push(sum / total);
+    }
+    ratios
+}
+
+// Splits the curves at the ratios of the lengths, except the ratios at the vertices.
+fn split_at_ratios(curves: &[Nurbs], ratios: &[f64]) -> Vec<Nurbs> {
+    let lengths: Vec<f64> = curves.iter().map(length).collect();
+    let total: f64 = lengths.iter().sum();
+    let mut start = 0.0;
+    let mut split = Vec::new();
+    for (curve, length) in curves.iter().zip(&lengths) {
+        let end = start + length / total;
+        let params: Vec<f64> = ratios
+            .iter()
+            .filter(|ratio| **ratio > start && **ratio < end)
+            .filter(|ratio| !(**ratio - start).so_small() && !(**ratio - end).so_small())
+            .map(|ratio| parameter_at(curve, (ratio - start) * total))
+            .collect();
+        let mut head = curve.clone();
+        let mut tails: Vec<Nurbs> = params.iter().rev().map(|t| head.cut(*t)).collect();
+        tails.push(head);
+        split.extend(tails.into_iter().rev());
+        start = end;
+    }
+    split
+}
+
+// Splits the rational curve into the curves of one span.
+fn spans(curve: Nurbs) -> Vec<Nurbs> {
+    if curve
+        .control_points()
+        .iter()
+        .all(|point| (point.w - 1.0).so_small())
+    {
+        return vec![curve];
+    }
+    let (knots, _) = curve.knot_vec().to_single_multi();
+    let mut head = curve;
+    let inner = &knots[1..knots.len() - 1];
+    let mut tails: Vec<Nurbs> = inner.iter().rev().map(|t| head.cut(*t)).collect();
+    tails.push(head);
+    tails.reverse();
+    tails
+}
+
+// Reparametrizes the rational curve of one span to have the unit weights at the ends, which
+// keeps the curve. Returns `None` for the curve of more spans without the unit weights.
+fn unit_ends(mut curve: Nurbs) -> Option<Nurbs> {
+    let points = curve.control_points();
+    let (w0, w1) = (points[0].w, points.last().unwrap().w);
+    if (w0 - 1.0).so_small() && (w1 - 1.0).so_small() {
+        return Some(curve);
+    }
+    let degree = curve.degree();
+    if curve.knot_vec().len() != 2 * (degree + 1) || w0 <= 0.0 || w1 <= 0.0 {
+        return None;
+    }
+    let alpha = f64::powf(w0 / w1, 1.0 / degree as f64);
+    let mut factor = 1.0 / w0;
+    curve.control_points_mut().for_each(|point| {
+        *point *= factor;
+        factor *= alpha;
+    });
+    Some(curve)
+}
+
+// Returns the lofted shell of the sections of the corresponding curves.
+fn build(sections: &[Vec<Nurbs>], closed: bool, options: &LoftOptions) -> Result<Shell, LoftError> {
+    let n = sections.len();
+    let m = sections[0].len();
+    // the parameters of the sections by the mean distances of the corresponding points
+    let mut params = vec![0.0];
+    for pair in sections.windows(2) {
+        let [section0, section1] = [&pair[0], &pair[1]].map(|curves| Section {
+            curves: curves.clone(),
+            closed,
+        });
+        let distance = (0..SAMPLES).fold(0.0, |sum, i| {
+            let ratio = i as f64 / SAMPLES as f64;
+            sum + section0.point(ratio).distance(section1.point(ratio))
+        });
+        params.push(params.last().unwrap() + distance);
+    }
+    let total = *params.last().unwrap();
+    params.iter_mut().enumerate().for_each(|(i, param)| {
+        *param = match total.so_small() {
+            true => i as f64 / (n - 1) as f64,
+            false => *param / total,
+        }
+    });
+    let degree = match options.ruled {
+        true => 1,
+        false => usize::min(3, n - 1),
+    };
+    let (knot_vec, inverse) = interpolation(&params, degree);
+
+    // the surfaces of the corresponding curves, and the section curves on them
+    let mut surfaces = Vec::new();
+    let mut curves: Vec<Vec<Nurbs>> = vec![Vec::new(); n];
+    for j in 0..m {
+        let mut column: Vec<Nurbs> = sections.iter().map(|curves| curves[j].clone()).collect();
+        let degree = column.iter().map(|curve| curve.degree()).max().unwrap();
+        for curve in &mut column {
+            curve.knot_normalize();
+            while curve.degree() < degree {
+                curve.elevate_degree();
+            }
+        }
+        for _ in 0..2 {
+            let (first, rest) = column.split_at_mut(1);
+            rest.iter_mut()
+                .for_each(|curve| first[0].syncro_knots(curve));
+        }
+        let control_points = (0..column[0].control_points().len())
+            .map(|k| {
+                let points: Vec<Vector4> =
+                    column.iter().map(|curve| *curve.control_point(k)).collect();
+                interpolate(&inverse, &points)
+            })
+            .collect();
+        let knot_vecs = (column[0].knot_vec().clone(), knot_vec.clone());
+        surfaces.push(NurbsSurface::new(BSplineSurface::new(
+            knot_vecs,
+            control_points,
+        )));
+        column
+            .into_iter()
+            .zip(&mut curves)
+            .for_each(|(curve, curves)| curves.push(curve));
+    }
+
+    let count = if closed { m } else { m + 1 };
+    let vertices: Vec<Vec<Vertex>> = curves
+        .iter()
+        .map(|curves| {
+            let mut points: Vec<Point3> = curves
+                .iter()
+                .map(|curve| curve.subs(curve.range_tuple().0))
+                .collect();
+            if !closed {
+                let last = curves.last().unwrap();
+                points.push(last.subs(last.range_tuple().1));
+            }
+            points.into_iter().map(Vertex::new).collect()
+        })
+        .collect();
+    let section_edges: Vec<Vec<Edge>> = curves
+        .iter()
+        .zip(&vertices)
+        .map(|(curves, vertices)| {
+            let edges = curves.iter().enumerate().map(|(j, curve)| {
+                let (v0, v1) = (&vertices[j], &vertices[(j + 1) % count]);
+                Edge::new(v0, v1, Curve::NurbsCurve(curve.clone()))
+            });
+            edges.collect()
+        })
+        .collect();
+    // the lofted edges between the sections at the vertices
+    let lofted: Vec<Vec<Edge>> = (0..count)
+        .map(|k| {
+            let control_points = match k < m {
+                true => surfaces[k].control_points()[0].clone(),
+                false => surfaces[m - 1].control_points().last().unwrap().clone(),
+            };
+            let curve = NurbsCurve::new(BSplineCurve::new(knot_vec.clone(), control_points));
+            (0..n - 1)
+                .map(|i| {
+                    let mut curve = curve.clone();
+                    if i > 0 {
+                        curve = curve.cut(params[i]);
+                    }
+                    if i + 2 < n {
+                        curve.cut(params[i + 1]);
+                    }
+                    let (v0, v1) = (&vertices[i][k], &vertices[i + 1][k]);
+                    Edge::new(v0, v1, Curve::NurbsCurve(curve))
+                })
+                .collect()
+        })
+        .collect();
+    let mut shell = Shell::new();
+    for i in 0..n - 1 {
+        for (j, surface) in surfaces.iter().enumerate() {
+            let wire: Wire = vec![
+                section_edges[i][j].clone(),
+                lofted[(j + 1) % count][i].clone(),
+                section_edges[i + 1][j].inverse(),
+                lofted[j][i].inverse(),
+            ]
+            .into();
+            shell.push(Face::new(
+                vec![wire],
+                Surface::NurbsSurface(surface.clone()),
+            ));
+        }
+    }
+    if options.caps && closed {
+        let [first, last] = [&section_edges[0], &section_edges[n - 1]]
+            .map(|edges| builder::try_attach_plane(&[edges.iter().cloned().collect()]));
+        let first = first.map_err(|_| LoftError::NonPlanarCap)?;
+        let last = last.map_err(|_| LoftError::NonPlanarCap)?;
+        // The lofted faces are outward if the first section is counterclockwise seen from
+        // the next one.
+        let surface = first.oriented_surface();
+        let point = vertices[0][0].point();
+        let (u, v) = surface
+            .search_parameter(point, None, 100)
+            .ok_or(LoftError::NonPlanarCap)?;
+        let ends = [&vertices[0], &vertices[1]].map(|vertices| {
+            let points: Vec<Point3> = vertices.iter().map(Vertex::point).collect();
+            centroid(&points)
+        });
+        let forward = surface.normal(u, v).dot(ends[1] - ends[0]) > 0.0;
+        shell.push(first.inverse());
+        shell.push(last);
+        if !forward {
+            shell.face_iter_mut().for_each(|face| {
+                face.invert();
+            });
+        }
+    }
+    Ok(shell)
+}
@@ -0,0 +1,214 @@
+use chapter3::loft::*;
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+use truck_stepio::out::*;
+
+fn save_shape(solid: &Solid, filename: &str) {
+    // output to polygonmesh
+    let mesh_with_topology = solid.triangulation(0.01);
+    let mesh = mesh_with_topology.to_polygon();
+    let obj_path = filename.to_string() + ".obj";
+    let mut obj = std::fs::File::create(&obj_path).unwrap();
+    obj::write(&mesh, &mut obj).unwrap();
+
+    // compress solid data.
+    let compressed = solid.compress();
+
+    // step format display
+    let display = CompleteStepDisplay::new(StepModel::from(&compressed), Default::default());
+    // content of step file
+    let step_string: String = display.to_string();
+    let step_path = filename.to_string() + ".step";
+    std::fs::write(&step_path, &step_string).unwrap();
+}
+
+// the closed polygon of the points
+fn polygon(points: &[Point3]) -> Wire {
+    let vertices: Vec<Vertex> = points.iter().map(|p| builder::vertex(*p)).collect();
+    (0..vertices.len())
+        .map(|i| builder::line(&vertices[i], &vertices[(i + 1) % vertices.len()]))
+        .collect()
+}
+
+// the square of the side centered on the z-axis at the height, rotated by the angle
+fn square(side: f64, height: f64, angle: f64) -> Wire {
+    let points: Vec<Point3> = (0..4)
+        .map(|i| {
+            let theta = angle + PI / 4.0 + PI / 2.0 * i as f64;
+            let radius = side / f64::sqrt(2.0);
+            Point3::new(radius * theta.cos(), radius * theta.sin(), height)
+        })
+        .collect();
+    polygon(&points)
+}
+
+// the circle of the radius around the z-axis at the height, starting at the angle
+fn circle(radius: f64, height: f64, angle: f64) -> Wire {
+    let point = Point3::new(radius * angle.cos(), radius * angle.sin(), height);
+    builder::rsweep(
+        &builder::vertex(point),
+        Point3::origin(),
+        Vector3::unit_z(),
+        Rad(7.0),
+    )
+}
+
+// Checks that the lofted solid is closed and has the volume.
+fn check(name: &str, solid: &Solid, volume: f64) {
+    let shell = &solid.boundaries()[0];
+    assert_eq!(shell.shell_condition(), ShellCondition::Closed, "{name}");
+    assert!(shell.singular_vertices().is_empty(), "{name}");
+    let mut mesh = solid.triangulation(0.001).to_polygon();
+    mesh.put_together_same_attrs(10.0 * TOLERANCE)
+        .remove_degenerate_faces();
+    assert_eq!(mesh.shell_condition(), ShellCondition::Closed, "{name}");
+    println!("{name}: volume {:.5} (exact: {volume:.5})", mesh.volume());
+    assert!(f64::abs(mesh.volume() - volume) < 0.005 * volume, "{name}");
+}
+
+fn main() {
+    let ruled = LoftOptions {
+        ruled: true,
+        ..Default::default()
+    };
+
+    // The frustum from the square to the double square. The second square starts at the other
+    // corner in the reverse direction, which is aligned to the first one.
+    let sections = [square(1.0, 0.0, 0.0), square(2.0, 1.0, PI / 2.0).inverse()];
+    let frustum = loft_solid(&sections, &ruled).unwrap();
+    check("frustum", &frustum, (1.0 + 4.0 + 2.0) / 3.0);
+
+    // the cylinder through the circles starting at the different angles
+    let sections = [
+        circle(1.0, 0.0, 0.0),
+        circle(1.0, 1.0, 1.0).inverse(),
+        circle(1.0, 2.0, 2.5),
+    ];
+    let cylinder = loft_solid(&sections, &Default::default()).unwrap();
+    check("cylinder", &cylinder, 2.0 * PI);
+
+    // The vase through the circles of the radii, which is smooth.
+    let radii = [0.5, 0.8, 0.4, 0.6];
+    let sections: Vec<Wire> = radii
+        .iter()
+        .enumerate()
+        .map(|(i, r)| circle(*r, i as f64, 0.0))
+        .collect();
+    let vase = loft_solid(&sections, &Default::default()).unwrap();
+    let shell = &vase.boundaries()[0];
+    assert_eq!(shell.shell_condition(), ShellCondition::Closed);
+    save_shape(&vase, "vase");
+
+    // The twisted prism: the guide joins the corners rotated by 45 degrees, and the sections
+    // are the squares at every height.
+    let angle = PI / 4.0;
+    let sections = [square(1.0, 0.0, 0.0), square(1.0, 1.0, angle)];
+    let corner = |p: Point3| {
+        let radius = 1.0 / f64::sqrt(2.0);
+        p + Vector3::new(
+            radius * f64::cos(PI / 4.0),
+            radius * f64::sin(PI / 4.0),
+            0.0,
+        )
+    };
+    let rotated = Point3::new(
+        f64::cos(PI / 4.0 + angle) / f64::sqrt(2.0),
+        f64::sin(PI / 4.0 + angle) / f64::sqrt(2.0),
+        1.0,
+    );
+    let guide = polygon(&[corner(Point3::origin()), rotated]);
+    let guide: Wire = vec![guide[0].clone()].into();
+    let guided = LoftOptions {
+        ruled: true,
+        guides: vec![guide],
+        ..Default::default()
+    };
+    let twisted = loft_solid(&sections, &guided).unwrap();
+    let shell = &twisted.boundaries()[0];
+    assert_eq!(shell.shell_condition(), ShellCondition::Closed);
+    // The lofted edges join the corners along the guide. The mesh of the twisted ruled faces
+    // is not refined since they are bilinear, so the volume is not checked.
+    let start = corner(Point3::origin());
+    let lofted = twisted
+        .edge_iter()
+        .find(|edge| edge.front().point().near(&start) && edge.back().point().z == 1.0)
+        .unwrap();
+    assert!(lofted.back().point().near(&rotated));
+    save_shape(&twisted, "twisted-prism");
+
+    // The shoulder of the bottle from the section of the body to the neck. The section of the
+    // body has two arcs and two lines, and the neck is the circle of three arcs.
+    let (height, width, thickness) = (1.4, 1.0, 0.6);
+    let y = height / 2.0;
+    let point = |x: f64, z: f64| builder::vertex(Point3::new(x, y, z));
+    let [v0, v1] = [
+        point(-width / 2.0, thickness / 4.0),
+        point(width / 2.0, thickness / 4.0),
+    ];
+    let [v2, v3] = [
+        point(width / 2.0, -thickness / 4.0),
+        point(-width / 2.0, -thickness / 4.0),
+    ];
+    let body: Wire = vec![
+        builder::circle_arc(&v0, &v1, Point3::new(0.0, y, thickness / 2.0)),
+        builder::line(&v1, &v2),
+        builder::circle_arc(&v2, &v3, Point3::new(0.0, y, -thickness / 2.0)),
+        builder::line(&v3, &v0),
+    ]
+    .into();
+    let neck = builder::rsweep(
+        &builder::vertex(Point3::new(0.0, y + height / 10.0, thickness / 4.0)),
+        Point3::origin(),
+        Vector3::unit_y(),
+        Rad(7.0),
+    );
+    let shoulder = loft(&[body.clone(), neck.clone()], &Default::default()).unwrap();
+    assert_eq!(shoulder.shell_condition(), ShellCondition::Oriented);
+    assert_eq!(shoulder.extract_boundaries().len(), 2);
+    let shoulder = loft_solid(&[body, neck], &Default::default()).unwrap();
+    let shell = &shoulder.boundaries()[0];
+    assert_eq!(shell.shell_condition(), ShellCondition::Closed);
+    save_shape(&shoulder, "shoulder");
+
+    // the open sections make the shell
+    let [p0, p1, p2] = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)].map(|(x, z)| Point3::new(x, 0.0, z));
+    let open0: Wire = vec![builder::line(&builder::vertex(p0), &builder::vertex(p1))].into();
+    let arc = builder::circle_arc(
+        &builder::vertex(p2 + Vector3::unit_y()),
+        &builder::vertex(p0 + Vector3::unit_y()),
+        Point3::new(0.8, 1.0, 0.2),
+    );
+    let sheet = loft(&[open0.clone(), vec![arc].into()], &ruled).unwrap();
+    assert_eq!(sheet.extract_boundaries().len(), 1);
+    assert_eq!(sheet.shell_condition(), ShellCondition::Oriented);
+
+    // errors
+    let one = [square(1.0, 0.0, 0.0)];
+    assert_eq!(loft(&one, &ruled).err(), Some(LoftError::TooFewSections));
+    let mixed = [square(1.0, 0.0, 0.0), open0.clone()];
+    assert_eq!(loft(&mixed, &ruled).err(), Some(LoftError::InvalidSection));
+    let sections = [square(1.0, 0.0, 0.0), square(1.0, 1.0, 0.0)];
+    let far = polygon(&[Point3::new(5.0, 0.0, 0.0), Point3::new(5.0, 0.0, 1.0)]);
+    let missing = LoftOptions {
+        guides: vec![vec![far[0].clone()].into()],
+        ..Default::default()
+    };
+    assert_eq!(
+        loft(&sections, &missing).err(),
+        Some(LoftError::InvalidGuide)
+    );
+    let bent = polygon(&[
+        Point3::new(0.0, 0.0, 1.0),
+        Point3::new(1.0, 0.0, 1.0),
+        Point3::new(1.0, 1.0, 1.5),
+        Point3::new(0.0, 1.0, 1.0),
+    ]);
+    let sections = [square(1.0, 0.0, 0.0), bent];
+    assert_eq!(
+        loft_solid(&sections, &ruled).err(),
+        Some(LoftError::NonPlanarCap)
+    );
+    let open = [open0.clone(), open0];
+    assert_eq!(loft_solid(&open, &ruled).err(), Some(LoftError::NotClosed));
+}
@@ -0,0 +1,237 @@
+//! Linear, circular and mirror patterns of solids and faces.
+//!
+//! A pattern is the list of the instances: the instance `0` is the original, and the others
+//! are its images by the translations, the rotations or the reflection. The mirrored solids
+//! and faces are inverted after the reflections, so that they face outward as the originals.
+//!
+//! The patterned faces are united with the base as the solid which they bound, so they have
+//! to make a closed shell, e.g. the faces of a boss or a rib. The instances are united with,
+//! or subtracted from, the base solid one by one by
+//! `truck-shapeops`, so the limits of `shapeops` apply: the instances should cross the base
+//! instead of touching it on the faces, and should not have the faces touching the axes of
+//! revolution, e.g. of the spheres.
+
+use crate::shapeops::{boolean, BooleanError, BooleanOperation};
+use std::f64::consts::PI;
+use std::result::Result;
+use truck_modeling::*;
+
+/// The patterns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pattern {
+    /// `count` instances along the direction, at the intervals of `spacing`
+    Linear {
+        direction: Vector3,
+        spacing: f64,
+        count: usize,
+    },
+    /// `count` instances counterclockwise around the axis through `origin`, at the intervals
+    /// of `angle` in radians, e.g. `2 * PI / count` for the full circle
+    Circular {
+        origin: Point3,
+        axis: Vector3,
+        angle: f64,
+        count: usize,
+    },
+    /// the original and its mirror image across the plane through `origin`
+    Mirror { origin: Point3, normal: Vector3 },
+}
+
+/// The options of the patterns.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatternOptions {
+    /// the pattern of the instances
+    pub pattern: Pattern,
+    /// the indices of the instances to skip, where the instance `0` is the original
+    pub skip: Vec<usize>,
+}
+
+/// The errors of the patterns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PatternError {
+    /// The dimension of the name is not positive or not finite, or the circular instances
+    /// overlap beyond the full circle.
+    InvalidDimension(&'static str),
+    /// The direction, the axis or the normal is zero or not finite.
+    InvalidDirection,
+    /// The pattern has no instances.
+    NoInstances,
+    /// The index of the skipped instance is out of the pattern.
+    InvalidSkip(usize),
+    /// The patterned faces do not make a closed and oriented shell.
+    NotClosed,
+    /// The boolean operation with an instance failed.
+    Boolean(BooleanError),
+}
+
+impl std::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternError::InvalidDimension(name) => write!(f, "invalid {name}"),
+            PatternError::InvalidDirection => f.write_str("the direction is not valid"),
+            PatternError::NoInstances => f.write_str("the pattern has no instances"),
+            PatternError::InvalidSkip(idx) => write!(f, "the instance {idx} is not in the pattern"),
+            PatternError::NotClosed => f.write_str("the faces do not make a closed shell"),
+            PatternError::Boolean(error) => write!(f, "the boolean operation failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+fn check(name: &'static str, value: f64) -> Result<(), PatternError> {
+    match value.is_finite() && value > 0.0 {
+        true => Ok(()),
+        false => Err(PatternError::InvalidDimension(name)),
+    }
+}
+
+fn unit(vector: Vector3) -> Result<Vector3, PatternError> {
+    let magnitude = vector.magnitude();
+    match magnitude.is_finite() && magnitude > TOLERANCE {
+        true => Ok(vector / magnitude),
+        false => Err(PatternError::InvalidDirection),
+    }
+}
+
+/// Returns the matrices of the instances which are not skipped, with their indices.
+pub fn transforms(options: &PatternOptions) -> Result<Vec<(usize, Matrix4)>, PatternError> {
+    let matrices: Vec<Matrix4> = match options.pattern {
+        Pattern::Linear {
+            direction,
+            spacing,
+            count,
+        } => {
+            let direction = unit(direction)?;
+            check("spacing", spacing)?;
+            (0..count)
+                .map(|i| Matrix4::from_translation(direction * spacing * i as f64))
+                .collect()
+        }
+        Pattern::Circular {
+            origin,
+            axis,
+            angle,
+            count,
+        } => {
+            let axis = unit(axis)?;
+            check("angle", angle)?;
+            if angle * count.saturating_sub(1) as f64 >= 2.0 * PI - TOLERANCE {
+                return Err(PatternError::InvalidDimension("angle"));
+            }
+            let to_origin = origin.to_vec();
+            (0..count)
+                .map(|i| {
+                    Matrix4::from_translation(to_origin)
+                        * Matrix4::from_axis_angle(axis, Rad(angle * i as f64))
+                        * Matrix4::from_translation(-to_origin)
+                })
+                .collect()
+        }
+        Pattern::Mirror { origin, normal } => {
+            let normal = unit(normal)?;
+            // the reflection p - 2 ((p - origin) . normal) normal
+            let column = |i: usize| {
+                let mut column = Vector3::zero();
+                column[i] = 1.0;
+                (column - normal * 2.0 * normal[i]).extend(0.0)
+            };
+            let translation = normal * 2.0 * origin.to_vec().dot(normal);
+            let reflection =
+                Matrix4::from_cols(column(0), column(1), column(2), translation.extend(1.0));
+            vec![Matrix4::identity(), reflection]
+        }
+    };
+    if let Some(idx) = options.skip.iter().find(|idx| **idx >= matrices.len()) {
+        return Err(PatternError::InvalidSkip(*idx));
+    }
+    let instances: Vec<(usize, Matrix4)> = matrices
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| !options.skip.contains(idx))
+        .collect();
+    match instances.is_empty() {
+        true => Err(PatternError::NoInstances),
+        false => Ok(instances),
+    }
+}
+
+/// Returns the instances of the solid which are not skipped.
+pub fn pattern_solids(solid: &Solid, options: &PatternOptions) -> Result<Vec<Solid>, PatternError> {
+    let instances = transforms(options)?
+        .into_iter()
+        .map(|(_, matrix)| {
+            let mut instance = builder::transformed(solid, matrix);
+            if matrix.determinant() < 0.0 {
+                instance.not();
+            }
+            instance
+        })
+        .collect();
+    Ok(instances)
+}
+
+/// Returns the instances of the faces which are not skipped, in the order of the instances.
+pub fn pattern_faces(faces: &[Face], options: &PatternOptions) -> Result<Vec<Face>, PatternError> {
+    let instances = transforms(options)?
+        .into_iter()
+        .flat_map(|(_, matrix)| {
+            faces.iter().map(move |face| {
+                let instance = builder::transformed(face, matrix);
+                match matrix.determinant() < 0.0 {
+                    true => instance.inverse(),
+                    false => instance,
+                }
+            })
+        })
+        .collect();
+    Ok(instances)
+}
+
+/// Returns the base united with the instances of the feature.
+pub fn pattern_union(
+    base: &Solid,
+    feature: &Solid,
+    options: &PatternOptions,
+    tol: f64,
+) -> Result<Solid, PatternError> {
+    pattern_boolean(base, feature, options, BooleanOperation::Union, tol)
+}
+
+/// Returns the base united with the instances of the faces, which bound the feature.
+pub fn pattern_faces_union(
+    base: &Solid,
+    faces: &[Face],
+    options: &PatternOptions,
+    tol: f64,
+) -> Result<Solid, PatternError> {
+    let shell: Shell = faces.iter().cloned().collect();
+    let feature = Solid::try_new(vec![shell]).map_err(|_| PatternError::NotClosed)?;
+    pattern_union(base, &feature, options, tol)
+}
+
+/// Returns the base minus the instances of the feature, e.g. the holes.
+pub fn pattern_difference(
+    base: &Solid,
+    feature: &Solid,
+    options: &PatternOptions,
+    tol: f64,
+) -> Result<Solid, PatternError> {
+    pattern_boolean(base, feature, options, BooleanOperation::Difference, tol)
+}
+
+/// Returns the result of the operation `op` of the base with the instances of the feature in
+/// order.
+pub fn pattern_boolean(
+    base: &Solid,
+    feature: &Solid,
+    options: &PatternOptions,
+    op: BooleanOperation,
+    tol: f64,
+) -> Result<Solid, PatternError> {
+    pattern_solids(feature, options)?
+        .iter()
+        .try_fold(base.clone(), |solid, instance| {
+            boolean(&solid, instance, op, tol).map_err(PatternError::Boolean)
+        })
+}
@@ -0,0 +1,175 @@
+use chapter3::{
+    pattern::*,
+    primitive::{self, *},
+    shapeops::*,
+};
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+use truck_stepio::out::*;
+
+fn save_shape(solid: &Solid, filename: &str) {
+    // output to polygonmesh
+    let mesh_with_topology = solid.triangulation(0.01);
+    let mesh = mesh_with_topology.to_polygon();
+    let obj_path = filename.to_string() + ".obj";
+    let mut obj = std::fs::File::create(&obj_path).unwrap();
+    obj::write(&mesh, &mut obj).unwrap();
+
+    // compress solid data.
+    let compressed = solid.compress();
+
+    // step format display
+    let display = CompleteStepDisplay::new(StepModel::from(&compressed), Default::default());
+    // content of step file
+    let step_string: String = display.to_string();
+    let step_path = filename.to_string() + ".step";
+    std::fs::write(&step_path, &step_string).unwrap();
+}
+
+// Checks that the solid is closed and has the volume.
+fn check(name: &str, solid: &Solid, volume: f64) {
+    for shell in solid.boundaries() {
+        assert_eq!(shell.shell_condition(), ShellCondition::Closed, "{name}");
+    }
+    let mut mesh = solid.triangulation(0.002).to_polygon();
+    mesh.put_together_same_attrs(10.0 * TOLERANCE)
+        .remove_degenerate_faces();
+    assert_eq!(mesh.shell_condition(), ShellCondition::Closed, "{name}");
+    println!("{name}: volume {:.4} (exact: {volume:.4})", mesh.volume());
+    assert!(f64::abs(mesh.volume() - volume) < 0.01 * volume, "{name}");
+}
+
+fn main() {
+    let tol = 0.05;
+
+    // The flange with the bolt holes around the axis, one of which is skipped.
+    let flange = primitive::cylinder(&Frame::default(), 2.0, 0.5).unwrap();
+    let frame = Frame::at(Point3::new(1.4, 0.0, -0.5));
+    let bolt = primitive::cylinder(&frame, 0.2, 1.5).unwrap();
+    let bolt_holes = PatternOptions {
+        pattern: Pattern::Circular {
+            origin: Point3::origin(),
+            axis: Vector3::unit_z(),
+            angle: PI / 3.0,
+            count: 6,
+        },
+        skip: vec![3],
+    };
+    let bolts = pattern_solids(&bolt, &bolt_holes).unwrap();
+    assert_eq!(bolts.len(), 5);
+    let holed = pattern_difference(&flange, &bolt, &bolt_holes, tol).unwrap();
+    let volume = PI * 4.0 * 0.5 - 5.0 * PI * 0.04 * 0.5;
+    check("flange", &holed, volume);
+    save_shape(&holed, "flange");
+
+    // The ribs on the plate, which sink into the plate so that they cross its top face.
+    let plate = cuboid(&Frame::default(), 4.0, 1.0, 0.2).unwrap();
+    let rib = cuboid(&Frame::at(Point3::new(-1.5, 0.0, 0.1)), 0.1, 0.6, 0.4).unwrap();
+    let ribs = PatternOptions {
+        pattern: Pattern::Linear {
+            direction: Vector3::unit_x(),
+            spacing: 0.5,
+            count: 7,
+        },
+        skip: vec![0, 6],
+    };
+    let ribbed = pattern_union(&plate, &rib, &ribs, tol).unwrap();
+    let volume = 0.8 + 5.0 * 0.1 * 0.6 * 0.3;
+    check("ribbed plate", &ribbed, volume);
+    save_shape(&ribbed, "ribbed-plate");
+    // the same ribs patterned by their faces
+    let rib_faces: Vec<Face> = rib.face_iter().cloned().collect();
+    let ribbed = pattern_faces_union(&plate, &rib_faces, &ribs, tol).unwrap();
+    check("plate ribbed by the faces", &ribbed, volume);
+
+    // The boss on the side of the cube and its mirror image. The seam of the boss is turned
+    // off the directions of the edges of the cube, along which the union is not triangulated
+    // into the closed mesh.
+    let cube = cuboid(&Frame::default(), 1.0, 1.0, 1.0).unwrap();
+    let x_axis = Frame::new(
+        Point3::new(0.3, 0.0, 0.5),
+        Vector3::unit_x(),
+        Vector3::new(0.0, 1.0, 1.0),
+    )
+    .unwrap();
+    let boss = primitive::cylinder(&x_axis, 0.2, 0.5).unwrap();
+    let mirror = PatternOptions {
+        pattern: Pattern::Mirror {
+            origin: Point3::origin(),
+            normal: Vector3::unit_x(),
+        },
+        skip: Vec::new(),
+    };
+    let bossed = pattern_union(&cube, &boss, &mirror, tol).unwrap();
+    check("bossed cube", &bossed, 1.0 + 2.0 * PI * 0.04 * 0.3);
+    let mirrored = pattern_solids(&boss, &mirror).unwrap();
+    let x = mirrored[1].vertex_iter().map(|v| v.point().x);
+    assert!(x.fold(f64::NEG_INFINITY, f64::max).near(&-0.3));
+    check("mirrored boss", &mirrored[1], PI * 0.04 * 0.5);
+
+    // The mirrored faces still face outward: the top face keeps its normal.
+    let top = cube.face_iter().last().unwrap().clone();
+    let normal = |face: &Face| match face.oriented_surface() {
+        Surface::Plane(plane) => plane.normal(),
+        _ => unreachable!(),
+    };
+    assert!(normal(&top).near(&Vector3::unit_z()));
+    let faces = pattern_faces(std::slice::from_ref(&top), &mirror).unwrap();
+    assert_eq!(faces.len(), 2);
+    assert!(normal(&faces[1]).near(&Vector3::unit_z()));
+    // The single face bounds no feature to unite with the base.
+    assert_eq!(
+        pattern_faces_union(&cube, &[top], &mirror, tol).err(),
+        Some(PatternError::NotClosed)
+    );
+
+    // errors
+    let zero = PatternOptions {
+        pattern: Pattern::Linear {
+            direction: Vector3::zero(),
+            spacing: 0.5,
+            count: 3,
+        },
+        skip: Vec::new(),
+    };
+    assert_eq!(
+        pattern_solids(&rib, &zero).err(),
+        Some(PatternError::InvalidDirection)
+    );
+    let overlapping = PatternOptions {
+        pattern: Pattern::Circular {
+            origin: Point3::origin(),
+            axis: Vector3::unit_z(),
+            angle: PI / 3.0,
+            count: 7,
+        },
+        skip: Vec::new(),
+    };
+    assert_eq!(
+        pattern_solids(&bolt, &overlapping).err(),
+        Some(PatternError::InvalidDimension("angle"))
+    );
+    let out_of_range = PatternOptions {
+        skip: vec![6],
+        ..bolt_holes
+    };
+    assert_eq!(
+        pattern_solids(&bolt, &out_of_range).err(),
+        Some(PatternError::InvalidSkip(6))
+    );
+    let all = PatternOptions {
+        skip: vec![0, 1],
+        ..mirror.clone()
+    };
+    assert_eq!(
+        pattern_solids(&boss, &all).err(),
+        Some(PatternError::NoInstances)
+    );
+    // The spheres are not supported by `shapeops`.
+    let ball = sphere(&Frame::at(Point3::new(0.5, 0.0, 0.5)), 0.3).unwrap();
+    assert_eq!(
+        pattern_union(&cube, &ball, &mirror, tol).err(),
+        Some(PatternError::Boolean(BooleanError::UnsupportedSurface(1)))
+    );
+}
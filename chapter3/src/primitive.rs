@@ -0,0 +1,379 @@
+//! Primitive solids with dimensions and local coordinate frames.
+//!
+//! Each primitive is modeled in its local coordinates, where the z-axis is the axis of the
+//! solid and the base is on the xy-plane, and is placed by the frame. The boxes, the wedges
+//! and the prisms are centered on the z-axis, and the spheres and the tori are centered at
+//! the origin.
+
+use std::f64::consts::PI;
+use std::result::Result;
+use truck_modeling::*;
+
+/// The errors of the primitives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrimitiveError {
+    /// The dimension of the name is not positive or not finite.
+    InvalidDimension(&'static str),
+    /// The minor radius of the torus is not less than the major one.
+    SelfIntersectingTorus,
+    /// The prism has less than three sides.
+    TooFewSides(usize),
+    /// The axes of the frame are zero or parallel.
+    DegenerateFrame,
+}
+
+impl std::fmt::Display for PrimitiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrimitiveError::InvalidDimension(name) => write!(f, "invalid {name}"),
+            PrimitiveError::SelfIntersectingTorus => {
+                f.write_str("the minor radius of the torus is not less than the major one")
+            }
+            PrimitiveError::TooFewSides(sides) => write!(f, "a prism with {sides} sides"),
+            PrimitiveError::DegenerateFrame => f.write_str("the axes of the frame are degenerate"),
+        }
+    }
+}
+
+impl std::error::Error for PrimitiveError {}
+
+/// A right-handed orthonormal coordinate frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    origin: Point3,
+    axes: [Vector3; 3],
+}
+
+impl Default for Frame {
+    fn default() -> Self { Self::at(Point3::origin()) }
+}
+
+impl Frame {
+    /// Creates the frame at `origin` whose z-axis is `z_axis`. The x-axis is `x_axis`
+    /// made orthogonal to the z-axis, and the y-axis completes the right-handed frame.
+    pub fn new(origin: Point3, z_axis: Vector3, x_axis: Vector3) -> Result<Self, PrimitiveError> {
+        let z = z_axis.normalize();
+        let x = x_axis - z * x_axis.dot(z);
+        if z_axis.magnitude().so_small() || x.magnitude() <= TOLERANCE * x_axis.magnitude() {
+            return Err(PrimitiveError::DegenerateFrame);
+        }
+        let x = x.normalize();
+        Ok(Self {
+            origin,
+            axes: [x, z.cross(x), z],
+        })
+    }
+
+    /// Returns the frame at `origin` parallel to the global one.
+    pub fn at(origin: Point3) -> Self {
+        Self {
+            origin,
+            axes: [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()],
+        }
+    }
+
+    /// Returns the origin.
+    pub fn origin(&self) -> Point3 { self.origin }
+
+    /// Returns the x-, y- and z-axes.
+    pub fn axes(&self) -> [Vector3; 3] { self.axes }
+
+    /// Returns the matrix from the local coordinates to the global ones.
+    pub fn matrix(&self) -> Matrix4 {
+        let [x, y, z] = self.axes;
+        Matrix4::from_cols(
+            x.extend(0.0),
+            y.extend(0.0),
+            z.extend(0.0),
+            self.origin.to_homogeneous(),
+        )
+    }
+
+    /// Returns the global coordinates of the local point.
+    pub fn transform_point(&self, point: Point3) -> Point3 { self.matrix().transform_point(point) }
+}
+
+fn check(name: &'static str, value: f64) -> Result<(), PrimitiveError> {
+    match value.is_finite() && value > 0.0 {
+        true => Ok(()),
+        false => Err(PrimitiveError::InvalidDimension(name)),
+    }
+}
+
+fn place(solid: Solid, frame: &Frame) -> Solid { builder::transformed(&solid, frame.matrix()) }
+
+/// Returns the box of `width` along the x-axis, `depth` along the y-axis and `height` along
+/// the z-axis.
+pub fn cuboid(frame: &Frame, width: f64, depth: f64, height: f64) -> Result<Solid, PrimitiveError> {
+    check("width", width)?;
+    check("depth", depth)?;
+    check("height", height)?;
+    let vertex = builder::vertex(Point3::new(-width / 2.0, -depth / 2.0, 0.0));
+    let edge = builder::tsweep(&vertex, width * Vector3::unit_x());
+    let face = builder::tsweep(&edge, depth * Vector3::unit_y());
+    Ok(place(
+        builder::tsweep(&face, height * Vector3::unit_z()),
+        frame,
+    ))
+}
+
+/// Returns the cylinder whose bottom disk of `radius` is on the xy-plane.
+pub fn cylinder(frame: &Frame, radius: f64, height: f64) -> Result<Solid, PrimitiveError> {
+    check("radius", radius)?;
+    check("height", height)?;
+    let vertex = builder::vertex(Point3::new(radius, 0.0, 0.0));
+    let circle = builder::rsweep(&vertex, Point3::origin(), Vector3::unit_z(), Rad(7.0));
+    let disk = builder::try_attach_plane(&[circle]).unwrap();
+    Ok(place(
+        builder::tsweep(&disk, height * Vector3::unit_z()),
+        frame,
+    ))
+}
+
+/// Returns the cone, or the frustum if both radii are positive, whose bottom disk is on the
+/// xy-plane. One of the radii may be zero.
+pub fn cone(
+    frame: &Frame,
+    bottom_radius: f64,
+    top_radius: f64,
+    height: f64,
+) -> Result<Solid, PrimitiveError> {
+    check("height", height)?;
+    if bottom_radius == 0.0 && top_radius == 0.0 {
+        return Err(PrimitiveError::InvalidDimension("radius"));
+    }
+    if bottom_radius != 0.0 {
+        check("bottom radius", bottom_radius)?;
+    }
+    if top_radius != 0.0 {
+        check("top radius", top_radius)?;
+    }
+    // the profile from the top to the bottom, without the edges of zero length
+    let points = [
+        Point3::new(0.0, 0.0, height),
+        Point3::new(top_radius, 0.0, height),
+        Point3::new(bottom_radius, 0.0, 0.0),
+        Point3::new(0.0, 0.0, 0.0),
+    ];
+    let vertices: Vec<Vertex> = points
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !(*i == 1 && top_radius == 0.0 || *i == 2 && bottom_radius == 0.0))
+        .map(|(_, p)| builder::vertex(*p))
+        .collect();
+    let wire: Wire = vertices
+        .windows(2)
+        .map(|v| builder::line(&v[0], &v[1]))
+        .collect();
+    let shell = builder::cone(&wire, Vector3::unit_z(), Rad(7.0));
+    Ok(place(Solid::new(vec![shell]), frame))
+}
+
+/// Returns the sphere centered at the origin.
+pub fn sphere(frame: &Frame, radius: f64) -> Result<Solid, PrimitiveError> {
+    check("radius", radius)?;
+    let north = builder::vertex(Point3::new(0.0, 0.0, radius));
+    let south = builder::vertex(Point3::new(0.0, 0.0, -radius));
+    let arc = builder::circle_arc(&north, &south, Point3::new(radius, 0.0, 0.0));
+    let shell = builder::cone(&vec![arc].into(), Vector3::unit_z(), Rad(7.0));
+    Ok(place(Solid::new(vec![shell]), frame))
+}
+
+/// Returns the torus centered at the origin around the z-axis.
+pub fn torus(frame: &Frame, major_radius: f64, minor_radius: f64) -> Result<Solid, PrimitiveError> {
+    check("major radius", major_radius)?;
+    check("minor radius", minor_radius)?;
+    if minor_radius >= major_radius {
+        return Err(PrimitiveError::SelfIntersectingTorus);
+    }
+    let vertex = builder::vertex(Point3::new(major_radius, 0.0, minor_radius));
+    let circle = builder::rsweep(
+        &vertex,
+        Point3::new(major_radius, 0.0, 0.0),
+        Vector3::unit_y(),
+        Rad(7.0),
+    );
+    let shell = builder::rsweep(&circle, Point3::origin(), Vector3::unit_z(), Rad(7.0));
+    Ok(place(Solid::new(vec![shell]), frame))
+}
+
+/// Returns the wedge on the base of the box, whose top edge is on the side of the negative
+/// x-axis. The section perpendicular to the y-axis is a right triangle.
+pub fn wedge(frame: &Frame, width: f64, depth: f64, height: f64) -> Result<Solid, PrimitiveError> {
+    check("width", width)?;
+    check("depth", depth)?;
+    check("height", height)?;
+    let y = -depth / 2.0;
+    let v = [
+        builder::vertex(Point3::new(-width / 2.0, y, 0.0)),
+        builder::vertex(Point3::new(-width / 2.0, y, height)),
+        builder::vertex(Point3::new(width / 2.0, y, 0.0)),
+    ];
+    let wire: Wire = (0..3)
+        .map(|i| builder::line(&v[i], &v[(i + 1) % 3]))
+        .collect();
+    let face = builder::try_attach_plane(&[wire]).unwrap();
+    Ok(place(
+        builder::tsweep(&face, depth * Vector3::unit_y()),
+        frame,
+    ))
+}
+
+/// Returns the right prism whose base is the regular polygon inscribed in the circle of
+/// `radius`. The first vertex of the base is on the x-axis.
+pub fn prism(
+    frame: &Frame,
+    sides: usize,
+    radius: f64,
+    height: f64,
+) -> Result<Solid, PrimitiveError> {
+    if sides < 3 {
+        return Err(PrimitiveError::TooFewSides(sides));
+    }
+    check("radius", radius)?;
+    check("height", height)?;
+    let vertices: Vec<Vertex> = (0..sides)
+        .map(|i| {
+            let angle = 2.0 * PI * i as f64 / sides as f64;
+            builder::vertex(Point3::new(radius * angle.cos(), radius * angle.sin(), 0.0))
+        })
+        .collect();
+    let wire: Wire = (0..sides)
+        .map(|i| builder::line(&vertices[i], &vertices[(i + 1) % sides]))
+        .collect();
+    let face = builder::try_attach_plane(&[wire]).unwrap();
+    Ok(place(
+        builder::tsweep(&face, height * Vector3::unit_z()),
+        frame,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use truck_meshalgo::prelude::*;
+
+    // the frame tilted and moved
+    fn tilted() -> Frame {
+        let z_axis = Vector3::new(1.0, 1.0, 1.0);
+        Frame::new(Point3::new(1.0, 2.0, 3.0), z_axis, Vector3::unit_x()).unwrap()
+    }
+
+    // Checks that `solid` is closed, has the volume `volume`, and has the bounding box
+    // `[min, max]` in the coordinates of `frame`.
+    fn assert_solid(solid: &Solid, frame: &Frame, volume: f64, min: Point3, max: Point3) {
+        // `is_geometric_consistent` is not used, since it does not return at the apices of cones.
+        let shell = &solid.boundaries()[0];
+        assert_eq!(shell.shell_condition(), ShellCondition::Closed);
+        assert!(shell.singular_vertices().is_empty());
+        let mut mesh = solid.triangulation(0.001).to_polygon();
+        // The triangles at the apices of cones are degenerate.
+        mesh.put_together_same_attrs(10.0 * TOLERANCE)
+            .remove_degenerate_faces();
+        assert_eq!(mesh.shell_condition(), ShellCondition::Closed);
+        assert!(f64::abs(mesh.volume() - volume) < 0.01 * volume);
+        let inverse = frame.matrix().invert().unwrap();
+        let local: BoundingBox<Point3> = mesh
+            .positions()
+            .iter()
+            .map(|p| inverse.transform_point(*p))
+            .collect();
+        assert!(local.min().distance(min) < 0.01 && local.max().distance(max) < 0.01);
+    }
+
+    #[test]
+    fn frame_axes() {
+        let [x, y, z] = tilted().axes();
+        assert!(x.cross(y).near(&z) && x.dot(z).so_small());
+        assert_eq!(
+            Frame::new(Point3::origin(), Vector3::unit_z(), Vector3::unit_z() * 2.0).err(),
+            Some(PrimitiveError::DegenerateFrame)
+        );
+    }
+
+    #[test]
+    fn cuboid_volume() {
+        let frame = tilted();
+        let solid = cuboid(&frame, 1.0, 2.0, 3.0).unwrap();
+        let (min, max) = (Point3::new(-0.5, -1.0, 0.0), Point3::new(0.5, 1.0, 3.0));
+        assert_solid(&solid, &frame, 6.0, min, max);
+    }
+
+    #[test]
+    fn cylinder_volume() {
+        let frame = tilted();
+        let solid = cylinder(&frame, 1.0, 2.0).unwrap();
+        let (min, max) = (Point3::new(-1.0, -1.0, 0.0), Point3::new(1.0, 1.0, 2.0));
+        assert_solid(&solid, &frame, 2.0 * PI, min, max);
+    }
+
+    #[test]
+    fn cone_volumes() {
+        let frame = tilted();
+        let (min, max) = (Point3::new(-1.0, -1.0, 0.0), Point3::new(1.0, 1.0, 3.0));
+        let solid = cone(&frame, 1.0, 0.0, 3.0).unwrap();
+        assert_solid(&solid, &frame, PI, min, max);
+        let solid = cone(&frame, 0.0, 1.0, 3.0).unwrap();
+        assert_solid(&solid, &frame, PI, min, max);
+        // the frustum
+        let solid = cone(&frame, 1.0, 0.5, 3.0).unwrap();
+        assert_solid(&solid, &frame, PI * (1.0 + 0.5 + 0.25), min, max);
+    }
+
+    #[test]
+    fn sphere_volume() {
+        let frame = tilted();
+        let solid = sphere(&frame, 1.5).unwrap();
+        let (min, max) = (Point3::new(-1.5, -1.5, -1.5), Point3::new(1.5, 1.5, 1.5));
+        assert_solid(&solid, &frame, 4.5 * PI, min, max);
+    }
+
+    #[test]
+    fn torus_volume() {
+        let frame = tilted();
+        let solid = torus(&frame, 1.0, 0.3).unwrap();
+        let (min, max) = (Point3::new(-1.3, -1.3, -0.3), Point3::new(1.3, 1.3, 0.3));
+        assert_solid(&solid, &frame, 2.0 * PI * PI * 0.09, min, max);
+    }
+
+    #[test]
+    fn wedge_volume() {
+        let frame = tilted();
+        let solid = wedge(&frame, 1.0, 2.0, 3.0).unwrap();
+        let (min, max) = (Point3::new(-0.5, -1.0, 0.0), Point3::new(0.5, 1.0, 3.0));
+        assert_solid(&solid, &frame, 3.0, min, max);
+    }
+
+    #[test]
+    fn prism_volume() {
+        let frame = tilted();
+        let solid = prism(&frame, 6, 1.0, 2.0).unwrap();
+        let half = f64::sqrt(3.0) / 2.0;
+        let (min, max) = (Point3::new(-1.0, -half, 0.0), Point3::new(1.0, half, 2.0));
+        assert_solid(&solid, &frame, 3.0 * f64::sqrt(3.0), min, max);
+    }
+
+    #[test]
+    fn invalid_dimensions() {
+        let world = Frame::default();
+        let invalid = |name| Some(PrimitiveError::InvalidDimension(name));
+        assert_eq!(cuboid(&world, 1.0, -2.0, 3.0).err(), invalid("depth"));
+        assert_eq!(cylinder(&world, f64::NAN, 1.0).err(), invalid("radius"));
+        assert_eq!(cylinder(&world, 1.0, 0.0).err(), invalid("height"));
+        assert_eq!(cone(&world, 0.0, 0.0, 1.0).err(), invalid("radius"));
+        assert_eq!(cone(&world, 1.0, -1.0, 1.0).err(), invalid("top radius"));
+        assert_eq!(cone(&world, -1.0, 1.0, 1.0).err(), invalid("bottom radius"));
+        assert_eq!(sphere(&world, 0.0).err(), invalid("radius"));
+        assert_eq!(torus(&world, -1.0, 0.5).err(), invalid("major radius"));
+        assert_eq!(
+            torus(&world, 1.0, 1.0).err(),
+            Some(PrimitiveError::SelfIntersectingTorus)
+        );
+        assert_eq!(wedge(&world, 1.0, 1.0, f64::INFINITY).err(), invalid("height"));
+        assert_eq!(prism(&world, 6, 1.0, -1.0).err(), invalid("height"));
+        assert_eq!(
+            prism(&world, 2, 1.0, 1.0).err(),
+            Some(PrimitiveError::TooFewSides(2))
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use chapter3::primitive::*;
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+
+/// Output the triangulation of `solid` to the file specified by `path`.
+fn write_solid(solid: &Solid, path: &str) -> PolygonMesh {
+    let mut mesh = solid.triangulation(0.001).to_polygon();
+    // The triangles at the apices of cones are degenerate.
+    mesh.put_together_same_attrs(10.0 * TOLERANCE)
+        .remove_degenerate_faces();
+    let mut obj = std::fs::File::create(path).unwrap();
+    obj::write(&mesh, &mut obj).unwrap();
+    mesh
+}
+
+fn main() {
+    // the frame tilted and moved
+    let frame = Frame::new(
+        Point3::new(1.0, 2.0, 3.0),
+        Vector3::new(1.0, 1.0, 1.0),
+        Vector3::unit_x(),
+    )
+    .unwrap();
+    let primitives = [
+        ("box", cuboid(&frame, 1.0, 2.0, 3.0), 6.0),
+        ("cylinder", cylinder(&frame, 1.0, 2.0), 2.0 * PI),
+        ("cone", cone(&frame, 1.0, 0.0, 3.0), PI),
+        ("frustum", cone(&frame, 1.0, 0.5, 3.0), PI * 1.75),
+        ("sphere", sphere(&frame, 1.5), 4.5 * PI),
+        ("torus", torus(&frame, 1.0, 0.3), 2.0 * PI * PI * 0.09),
+        ("wedge", wedge(&frame, 1.0, 2.0, 3.0), 3.0),
+        ("prism", prism(&frame, 6, 1.0, 2.0), 3.0 * f64::sqrt(3.0)),
+    ];
+    for (name, solid, exact) in primitives {
+        let mesh = write_solid(&solid.unwrap(), &format!("primitive-{name}.obj"));
+        println!("{name}: volume {:.5} (exact: {exact:.5})", mesh.volume());
+    }
+
+    // The invalid dimensions are reported with their names.
+    let error = cone(&Frame::default(), 1.0, -1.0, 1.0).unwrap_err();
+    println!("cone of the negative radius: {error}");
+}
@@ -47,7 +47,7 @@ fn cylinder() -> Solid {
         Rad(7.0),
     );
     // make a disk by attaching a plane to the circle
-    let face: Face = builder::try_attach_plane(&vec![wire]).expect("cannot attach plane");
+    let face: Face = builder::try_attach_plane(&[wire]).expect("cannot attach plane");
     // sweep the face along the z-axis
     builder::tsweep(
         // the reference to the disk
@@ -1,3 +1,4 @@
+use chapter3::shelling;
 use std::f64::consts::PI;
 use truck_meshalgo::prelude::*;
 use truck_modeling::*;
@@ -53,7 +54,7 @@ fn cylinder(bottom: f64, height: f64, radius: f64) -> Shell {
     // make a solid cylinder
     let vertex = builder::vertex(Point3::new(0.0, bottom, radius));
     let circle = builder::rsweep(&vertex, Point3::origin(), Vector3::unit_y(), Rad(7.0));
-    let disk = builder::try_attach_plane(&vec![circle]).unwrap();
+    let disk = builder::try_attach_plane(&[circle]).unwrap();
     let solid = builder::tsweep(&disk, Vector3::new(0.0, height, 0.0));
     // Return the solid as a boundary shell for easier processing later.
     solid.into_boundaries().pop().unwrap()
@@ -80,40 +81,13 @@ fn bottle(height: f64, width: f64, thickness: f64) -> Solid {
     // sew the body and the neck
     glue_body_neck(&mut body, neck);
 
+    // the top of the neck, which is the last face of the neck
+    let neck_top = body.last().unwrap().clone();
+
     // distance between outer and inner surface, i.e. the thickness of the faces.
     let eps = height / 50.0;
-    // inner body. Make it small enough to account for thickness.
-    let mut inner_body = body_shell(
-        -height / 2.0 + eps,
-        height - 2.0 * eps,
-        width - 2.0 * eps,
-        thickness - 2.0 * eps,
-    );
-    // inner neck. Make it long and narrow to account for thickness.
-    let inner_neck = cylinder(
-        height / 2.0 - eps,
-        height / 10.0 + eps,
-        thickness / 4.0 - eps,
-    );
-    // sew the inner body and the inner neck
-    glue_body_neck(&mut inner_body, inner_neck);
-
-    // invert all faces of the inner body
-    inner_body.face_iter_mut().for_each(|face| {
-        face.invert();
-    });
-    // pop the ceiling of the inner body
-    let inner_ceiling = inner_body.pop().unwrap();
-    // make the inner ceiling the boundary wire
-    let wire = inner_ceiling.into_boundaries().pop().unwrap();
-    // the mutable reference to the outer ceiling
-    let ceiling = body.last_mut().unwrap();
-    // drill a hole in the outer ceiling using the boundary of inner ceiling
-    ceiling.add_boundary(wire);
-    // add the faces of the neck to the body
-    body.extend(inner_body.into_iter());
-    // returns the solid
-    Solid::new(vec![body])
+    // hollow the solid bottle, opening the top of the neck
+    shelling::shell(&Solid::new(vec![body]), eps, &[neck_top]).unwrap()
 }
 
 fn main() { save_shape(&bottle(1.4, 1.0, 0.6), "bottle"); }
@@ -0,0 +1,224 @@
+//! Boolean operations of solids by `truck-shapeops`.
+//!
+//! The difference is the intersection with the inverted solid. The intersection curves are
+//! computed from the triangulations of the boundaries with the tolerance, so the tolerance
+//! has to be small enough to separate the features of the solids, and large enough for the
+//! triangulations to be valid.
+//!
+//! `truck-shapeops` 0.4 handles the planes, the cylinders made by `tsweep` and the NURBS
+//! surfaces, but not the surfaces of revolution made by `rsweep` or `builder::cone`. So the
+//! faces of revolution, e.g. of the tori, are replaced with the NURBS surfaces of the same
+//! shapes beforehand. The NURBS surfaces are degenerate on the axis, so the faces touching
+//! the axis, e.g. of the spheres and the cones, are not supported: the operations with them
+//! return `BooleanError::UnsupportedSurface`.
+
+use std::f64::consts::PI;
+use std::result::Result;
+use truck_modeling::*;
+
+/// The boolean operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BooleanOperation {
+    /// the points in either of the solids
+    Union,
+    /// the points in both of the solids
+    Intersection,
+    /// the points in the first solid but not in the second one
+    Difference,
+}
+
+impl std::fmt::Display for BooleanOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BooleanOperation::Union => f.write_str("union"),
+            BooleanOperation::Intersection => f.write_str("intersection"),
+            BooleanOperation::Difference => f.write_str("difference"),
+        }
+    }
+}
+
+/// The errors of the boolean operations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BooleanError {
+    /// The tolerance is not finite or less than `TOLERANCE`.
+    InvalidTolerance(f64),
+    /// A boundary of the operand of the index is not a closed shell.
+    NotClosed(usize),
+    /// A face of revolution of the operand of the index cannot be converted into a NURBS
+    /// surface, i.e. it touches the axis, as the spheres and the cones do, or it is not less
+    /// than the half turn.
+    UnsupportedSurface(usize),
+    /// The intersection curves of the boundaries or the division of the faces failed. A smaller
+    /// tolerance may resolve it.
+    Failed(BooleanOperation),
+    /// The result has no volume, e.g. the intersection of disjoint solids.
+    Empty(BooleanOperation),
+}
+
+impl std::fmt::Display for BooleanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BooleanError::InvalidTolerance(tol) => {
+                write!(f, "the tolerance {tol} must be no less than {TOLERANCE}")
+            }
+            BooleanError::NotClosed(idx) => write!(f, "the solid {idx} is not closed"),
+            BooleanError::UnsupportedSurface(idx) => {
+                write!(f, "the solid {idx} has an unsupported surface of revolution")
+            }
+            BooleanError::Failed(op) => write!(f, "truck-shapeops failed to compute the {op}"),
+            BooleanError::Empty(op) => write!(f, "the {op} is empty"),
+        }
+    }
+}
+
+impl std::error::Error for BooleanError {}
+
+/// Returns the union of the solids.
+pub fn union(solid0: &Solid, solid1: &Solid, tol: f64) -> Result<Solid, BooleanError> {
+    boolean(solid0, solid1, BooleanOperation::Union, tol)
+}
+
+/// Returns the intersection of the solids.
+pub fn intersection(solid0: &Solid, solid1: &Solid, tol: f64) -> Result<Solid, BooleanError> {
+    boolean(solid0, solid1, BooleanOperation::Intersection, tol)
+}
+
+/// Returns `solid0` minus `solid1`.
+pub fn difference(solid0: &Solid, solid1: &Solid, tol: f64) -> Result<Solid, BooleanError> {
+    boolean(solid0, solid1, BooleanOperation::Difference, tol)
+}
+
+/// Returns the result of the operation `op` of the solids.
+pub fn boolean(
+    solid0: &Solid,
+    solid1: &Solid,
+    op: BooleanOperation,
+    tol: f64,
+) -> Result<Solid, BooleanError> {
+    if !tol.is_finite() || tol < TOLERANCE {
+        return Err(BooleanError::InvalidTolerance(tol));
+    }
+    for (idx, solid) in [solid0, solid1].into_iter().enumerate() {
+        let closed = solid
+            .boundaries()
+            .iter()
+            .all(|shell| shell.shell_condition() == ShellCondition::Closed);
+        if solid.boundaries().is_empty() || !closed {
+            return Err(BooleanError::NotClosed(idx));
+        }
+    }
+    let solid0 = nurbs_revolutions(solid0).ok_or(BooleanError::UnsupportedSurface(0))?;
+    let solid1 = nurbs_revolutions(solid1).ok_or(BooleanError::UnsupportedSurface(1))?;
+    let result = match op {
+        BooleanOperation::Union => truck_shapeops::or(&solid0, &solid1, tol),
+        BooleanOperation::Intersection => truck_shapeops::and(&solid0, &solid1, tol),
+        BooleanOperation::Difference => {
+            let mut solid1 = solid1;
+            solid1.not();
+            truck_shapeops::and(&solid0, &solid1, tol)
+        }
+    };
+    match result {
+        Some(solid) if solid.boundaries().is_empty() => Err(BooleanError::Empty(op)),
+        Some(solid) => Ok(solid),
+        None => Err(BooleanError::Failed(op)),
+    }
+}
+
+/// Returns the copy of `solid` whose surfaces of revolution are replaced with the NURBS surfaces
+/// of the same shapes, or `None` if some of them cannot be converted.
+pub fn nurbs_revolutions(solid: &Solid) -> Option<Solid> {
+    let solid = solid.mapped(Point3::clone, Curve::clone, Surface::clone);
+    for face in solid.face_iter() {
+        if let Surface::RevolutedCurve(surface) = face.surface() {
+            let points = face.edge_iter().flat_map(|edge| {
+                let curve = edge.curve();
+                let (t0, t1) = curve.range_tuple();
+                (0..=BOUNDARY_SAMPLES)
+                    .map(move |i| curve.subs(t0 + (t1 - t0) * i as f64 / BOUNDARY_SAMPLES as f64))
+            });
+            let sector = revolution_sector(&surface, points)?;
+            face.set_surface(Surface::NurbsSurface(nurbs_revolution(&surface, sector)?));
+        }
+    }
+    Some(solid)
+}
+
+// the number of the divisions of each edge to find the angles of the face of revolution
+const BOUNDARY_SAMPLES: usize = 8;
+
+/// Returns the first angle and the angle of the sector covered by `points` on `surface`.
+/// The sectors must be less than the half turn, as the faces of `rsweep`, and must not touch
+/// the axis.
+fn revolution_sector(
+    surface: &Processor<RevolutedCurve<Curve>, Matrix4>,
+    points: impl Iterator<Item = Point3>,
+) -> Option<(f64, f64)> {
+    let revolution = surface.entity();
+    let (origin, axis) = (revolution.origin(), revolution.axis());
+    let inverse = surface.transform().invert()?;
+    // The NURBS surfaces are degenerate on the axis, where the parameters cannot be searched.
+    let mut angles = points
+        .map(|p| inverse.transform_point(p))
+        .map(|p| match (p - origin).cross(axis).so_small() {
+            true => None,
+            false => revolution.search_parameter(p, None, 100).map(|(_, v)| v),
+        })
+        .collect::<Option<Vec<f64>>>()?;
+    angles.sort_by(f64::total_cmp);
+    // The sector is the complement of the largest gap between the angles.
+    let (first, last) = (*angles.first()?, *angles.last()?);
+    let (gap, start) = angles
+        .windows(2)
+        .map(|w| (w[1] - w[0], w[1]))
+        .chain([(first + 2.0 * PI - last, first)])
+        .max_by(|a, b| a.0.total_cmp(&b.0))?;
+    let angle = 2.0 * PI - gap;
+    match angle > 0.0 && angle < PI {
+        true => Some((start, angle)),
+        false => None,
+    }
+}
+
+/// Returns the NURBS surface which coincides with `surface` on the sector `(start, angle)`.
+fn nurbs_revolution(
+    surface: &Processor<RevolutedCurve<Curve>, Matrix4>,
+    (start, angle): (f64, f64),
+) -> Option<NurbsSurface<Vector4>> {
+    let revolution = surface.entity();
+    let (origin, axis) = (revolution.origin(), revolution.axis().normalize());
+    let curve = match revolution.entity_curve() {
+        Curve::Line(line) => {
+            NurbsCurve::from(BSplineCurve::new(KnotVec::bezier_knot(1), vec![line.0, line.1]))
+        }
+        Curve::BSplineCurve(curve) => NurbsCurve::from(curve.clone()),
+        Curve::NurbsCurve(curve) => curve.clone(),
+        Curve::IntersectionCurve(_) => return None,
+    };
+    // the quadratic rational arcs of the quarter turn at most
+    let division = f64::ceil(angle / (PI / 2.0)) as usize;
+    let step = angle / division as f64;
+    let cos = f64::cos(step / 2.0);
+    let arc_points = |pt: &Vector4| {
+        let (p, w) = (Point3::from_homogeneous(*pt), pt.w);
+        let center = origin + axis * axis.dot(p - origin);
+        let radius = p - center;
+        let rotated = |t: f64| radius * f64::cos(t) + axis.cross(radius) * f64::sin(t);
+        (0..=2 * division)
+            .map(|j| {
+                let t = start + step * j as f64 / 2.0;
+                match j % 2 {
+                    0 => (center + rotated(t)).to_homogeneous() * w,
+                    _ => (center + rotated(t) / cos).to_homogeneous() * (w * cos),
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    let control_points = curve.control_points().iter().map(arc_points).collect();
+    let mut knots = vec![0.0; 3];
+    (1..division).for_each(|i| knots.extend([i as f64; 2]));
+    knots.extend([division as f64; 3]);
+    let knot_vecs = (curve.knot_vec().clone(), KnotVec::from(knots));
+    let entity = NurbsSurface::new(BSplineSurface::new(knot_vecs, control_points));
+    Some(surface.map_ref(|_| entity).constract())
+}
@@ -0,0 +1,561 @@
+//! Shelling, or hollowing, of solids.
+//!
+//! Every face is offset inward by the thickness, and the inner faces are trimmed by the
+//! intersections of the offset surfaces, so the inner shell has the same topology as the
+//! outer one. The removed faces are not offset: the inner faces are trimmed by their
+//! surfaces, and the removed faces become the rims around the openings.
+//!
+//! The offsets are exact for the planes, the cylinders and the spheres, whatever types the
+//! surfaces have, e.g. the NURBS surfaces swept from the lines and the circle arcs. The
+//! removed faces may have any surfaces, but must neither be adjacent to each other nor have
+//! holes.
+//!
+//! The inner edges are the images of the outer edges by the affine maps: the offsets of the
+//! faces, the projections to the offset planes along the axes of the cylinders, and the
+//! scalings of the circles on the spheres. So the intersections of the planes with the
+//! cylinders or the spheres are supported, but not the ones of the crossing cylinders.
+//!
+//! The thickness has to be small enough to keep the topology: no inner face vanishes, and
+//! the offset surfaces at each vertex meet at one point.
+
+use std::collections::{HashMap, HashSet};
+use std::result::Result;
+use truck_modeling::*;
+
+/// The errors of the shelling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShellError {
+    /// The thickness is not positive or not finite.
+    InvalidThickness,
+    /// A boundary of the solid is not a closed shell.
+    InvalidSolid,
+    /// A face to be removed is not a face of the solid.
+    UnknownFace,
+    /// The faces to be removed are adjacent to each other or have holes.
+    UnsupportedOpening,
+    /// The surface of a face is not a plane, a cylinder or a sphere.
+    UnsupportedSurface,
+    /// The offset surfaces at a vertex do not meet at one point.
+    UnsupportedVertex,
+    /// The inner curve of an edge is not an affine image of the edge, e.g. the intersection
+    /// of the offsets of the crossing cylinders.
+    UnsupportedEdge,
+    /// The thickness is not less than the radius of a convex face, or an inner edge is
+    /// reversed.
+    TooThick,
+    /// The inner faces do not make closed shells.
+    Failed,
+}
+
+impl std::fmt::Display for ShellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShellError::InvalidThickness => f.write_str("invalid thickness"),
+            ShellError::InvalidSolid => f.write_str("the boundary of the solid is not closed"),
+            ShellError::UnknownFace => f.write_str("the face is not in the solid"),
+            ShellError::UnsupportedOpening => {
+                f.write_str("the removed faces are adjacent to each other or have holes")
+            }
+            ShellError::UnsupportedSurface => {
+                f.write_str("the surface is not a plane, a cylinder or a sphere")
+            }
+            ShellError::UnsupportedVertex => {
+                f.write_str("the offset surfaces do not meet at the vertex")
+            }
+            ShellError::UnsupportedEdge => {
+                f.write_str("the inner curve of the edge is not supported")
+            }
+            ShellError::TooThick => f.write_str("the thickness is too large"),
+            ShellError::Failed => f.write_str("failed to make the inner shell"),
+        }
+    }
+}
+
+impl std::error::Error for ShellError {}
+
+/// Hollows `solid` into the walls of `thickness`, opening `faces_to_remove`. If no face is
+/// removed from a boundary, the boundary gets the closed inner shell of the cavity.
+pub fn shell(solid: &Solid, thickness: f64, faces_to_remove: &[Face]) -> Result<Solid, ShellError> {
+    if !thickness.is_finite() || thickness <= 0.0 {
+        return Err(ShellError::InvalidThickness);
+    }
+    let removed: HashSet<FaceID> = faces_to_remove.iter().map(|face| face.id()).collect();
+    let faces: HashSet<FaceID> = solid.face_iter().map(|face| face.id()).collect();
+    if !removed.is_subset(&faces) {
+        return Err(ShellError::UnknownFace);
+    }
+    let mut shells = Vec::new();
+    for shell in solid.boundaries() {
+        if shell.shell_condition() != ShellCondition::Closed {
+            return Err(ShellError::InvalidSolid);
+        }
+        shells.extend(hollow(shell, thickness, &removed)?);
+    }
+    Solid::try_new(shells).map_err(|_| ShellError::Failed)
+}
+
+// The offset of a face.
+struct Offset {
+    // the offset surface, oriented as the face
+    surface: Surface,
+    // the affine map from the surface of the face to the offset surface
+    matrix: Matrix4,
+    // the distance from the face
+    distance: f64,
+    // the quadric of the offset surface, `None` if the surface of a removed face is not a
+    // quadric
+    quadric: Option<Quadric>,
+}
+
+/// The surfaces whose offsets are exact.
+#[derive(Clone, Copy, Debug)]
+pub enum Quadric {
+    /// the origin and the unit normal
+    Plane(Point3, Vector3),
+    /// the center and the radius, which is negative if the surface is concave
+    Sphere(Point3, f64),
+    /// a point on the axis, the unit direction of the axis and the radius, which is negative
+    /// if the surface is concave
+    Cylinder(Point3, Vector3, f64),
+}
+
+// Returns the shell with the openings, or the outer shell and the inner one.
+fn hollow(
+    shell: &Shell,
+    thickness: f64,
+    removed: &HashSet<FaceID>,
+) -> Result<Vec<Shell>, ShellError> {
+    let faces: Vec<&Face> = shell.face_iter().collect();
+    let is_removed: Vec<bool> = faces
+        .iter()
+        .map(|face| removed.contains(&face.id()))
+        .collect();
+    // the absolute edges and their left and right faces
+    let mut edges = HashMap::<EdgeID, (Edge, [usize; 2])>::new();
+    for (idx, face) in faces.iter().enumerate() {
+        if is_removed[idx] && face.boundaries().len() > 1 {
+            return Err(ShellError::UnsupportedOpening);
+        }
+        for edge in face.edge_iter() {
+            let entry = edges
+                .entry(edge.id())
+                .or_insert_with(|| (edge.absolute_clone(), [0; 2]));
+            entry.1[!edge.orientation() as usize] = idx;
+        }
+    }
+    if edges
+        .values()
+        .any(|(_, [face0, face1])| is_removed[*face0] && is_removed[*face1])
+    {
+        return Err(ShellError::UnsupportedOpening);
+    }
+    let surfaces: Vec<Surface> = faces.iter().map(|face| face.oriented_surface()).collect();
+    let offsets = surfaces
+        .iter()
+        .zip(&is_removed)
+        .map(|(surface, removed)| offset(surface, if *removed { 0.0 } else { thickness }))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // the inner vertices on the offset surfaces of the faces at the vertices
+    let mut vertex_edges = HashMap::<VertexID, (Point3, Vec<EdgeID>)>::new();
+    for (id, (edge, _)) in &edges {
+        for vertex in [edge.front(), edge.back()] {
+            let entry = vertex_edges
+                .entry(vertex.id())
+                .or_insert_with(|| (vertex.point(), Vec::new()));
+            entry.1.push(*id);
+        }
+    }
+    let vertices = vertex_edges
+        .into_iter()
+        .map(|(id, (point, edge_ids))| {
+            let mut vertex_faces: Vec<usize> = Vec::new();
+            // The inner vertex is on the offsets of the edges between the tangent faces.
+            let mut planes = Vec::new();
+            for edge_id in &edge_ids {
+                let (edge, edge_faces) = &edges[edge_id];
+                vertex_faces.extend(edge_faces);
+                let normal = match edge_faces.map(|idx| normal_at(&surfaces[idx], point)) {
+                    [Some(n0), Some(n1)] => (n0 - n1).so_small().then_some(n0),
+                    _ => None,
+                };
+                if let Some(normal) = normal {
+                    let (t0, t1) = edge.curve().range_tuple();
+                    let t = if edge.front().id() == id { t0 } else { t1 };
+                    let origin = point - offsets[edge_faces[0]].distance * normal;
+                    planes.push((origin, edge.curve().der(t).cross(normal)));
+                }
+            }
+            vertex_faces.sort();
+            vertex_faces.dedup();
+            let surfaces: Vec<&Surface> = vertex_faces
+                .iter()
+                .map(|idx| &offsets[*idx].surface)
+                .collect();
+            let point =
+                intersect(&surfaces, point, &planes).ok_or(ShellError::UnsupportedVertex)?;
+            Ok((id, Vertex::new(point)))
+        })
+        .collect::<Result<HashMap<VertexID, Vertex>, _>>()?;
+    let inner_edges = edges
+        .iter()
+        .map(|(id, (edge, [face0, face1]))| {
+            let [v0, v1] = [edge.front(), edge.back()].map(|vertex| &vertices[&vertex.id()]);
+            let offsets = [&offsets[*face0], &offsets[*face1]];
+            let curve = inner_curve(edge, offsets, [v0.point(), v1.point()])
+                .ok_or(ShellError::UnsupportedEdge)?;
+            // The faces between the reversed edges are turned inside out.
+            let (t0, t1) = curve.range_tuple();
+            let (s0, s1) = edge.curve().range_tuple();
+            let tangent = edge.curve().der((s0 + s1) / 2.0);
+            if curve.der((t0 + t1) / 2.0).dot(tangent) <= 0.0 {
+                return Err(ShellError::TooThick);
+            }
+            Ok((*id, Edge::new(v0, v1, curve)))
+        })
+        .collect::<Result<HashMap<EdgeID, Edge>, _>>()?;
+
+    let inner_wire = |wire: &Wire| -> Wire {
+        wire.edge_iter()
+            .map(|edge| {
+                let inner = &inner_edges[&edge.id()];
+                match edge.orientation() {
+                    true => inner.clone(),
+                    false => inner.inverse(),
+                }
+            })
+            .collect()
+    };
+    let mut outer_faces = Vec::new();
+    let mut inner_faces = Vec::new();
+    for (idx, face) in faces.iter().enumerate() {
+        let wires: Vec<Wire> = face.boundaries().iter().map(inner_wire).collect();
+        if is_removed[idx] {
+            // the rim around the opening
+            let mut boundaries = face.boundaries();
+            boundaries.extend(wires.iter().map(Wire::inverse));
+            let rim = Face::try_new(boundaries, face.oriented_surface());
+            outer_faces.push(rim.map_err(|_| ShellError::Failed)?);
+        } else {
+            outer_faces.push((*face).clone());
+            let inner = Face::try_new(wires, offsets[idx].surface.clone());
+            inner_faces.push(inner.map_err(|_| ShellError::Failed)?.inverse());
+        }
+    }
+    match is_removed.contains(&true) {
+        true => {
+            outer_faces.extend(inner_faces);
+            Ok(vec![outer_faces.into()])
+        }
+        false => Ok(vec![outer_faces.into(), inner_faces.into()]),
+    }
+}
+
+// The points and the normals at the centers of the cells of the 4x4 grid on the surface.
+fn samples(surface: &Surface) -> Option<Vec<(Point3, Vector3)>> {
+    let ((u0, u1), (v0, v1)) = match surface.try_range_tuple() {
+        (Some(urange), Some(vrange)) => (urange, vrange),
+        _ => return None,
+    };
+    let samples = (0..16)
+        .map(|i| {
+            let u = u0 + (u1 - u0) * ((i % 4) as f64 + 0.5) / 4.0;
+            let v = v0 + (v1 - v0) * ((i / 4) as f64 + 0.5) / 4.0;
+            (surface.subs(u, v), surface.normal(u, v))
+        })
+        .collect();
+    Some(samples)
+}
+
+/// Returns the quadric of the surface, or `None` if it is not a plane, a cylinder or a sphere.
+pub fn classify(surface: &Surface) -> Option<Quadric> {
+    if let Surface::Plane(plane) = surface {
+        return Some(Quadric::Plane(plane.origin(), plane.normal()));
+    }
+    let samples = samples(surface)?;
+    let (p0, n0) = samples[0];
+    // the sample whose normal is the farthest from the first one
+    let (p1, n1) = samples.iter().fold(samples[0], |farthest, sample| {
+        match sample.1.dot(n0) < farthest.1.dot(n0) {
+            true => *sample,
+            false => farthest,
+        }
+    });
+    if (n1 - n0).so_small() {
+        let planar = samples.iter().all(|(p, _)| (p - p0).dot(n0).so_small());
+        return planar.then_some(Quadric::Plane(p0, n0));
+    }
+    // `point - radius * normal` is the center, and the radius is negative if the surface
+    // is concave.
+    let radius = (p1 - p0).dot(n1 - n0) / (n1 - n0).magnitude2();
+    let center = p0 - radius * n0;
+    if samples.iter().all(|(p, n)| (p - radius * n).near(&center)) {
+        return Some(Quadric::Sphere(center, radius));
+    }
+    let axis = n0.cross(n1).normalize();
+    let radial = |vector: Vector3| vector - axis * vector.dot(axis);
+    samples
+        .iter()
+        .all(|(p, n)| n.dot(axis).so_small() && radial(p - radius * n - center).so_small())
+        .then_some(Quadric::Cylinder(center, axis, radius))
+}
+
+// Returns the offset of the oriented surface of a face by `distance` along the inward
+// normals.
+fn offset(surface: &Surface, distance: f64) -> Result<Offset, ShellError> {
+    let translation = |vector: Vector3| Matrix4::from_translation(vector);
+    let quadric = classify(surface);
+    if distance == 0.0 {
+        return Ok(Offset {
+            surface: surface.clone(),
+            matrix: Matrix4::identity(),
+            distance,
+            quadric,
+        });
+    }
+    let (center, radius) = match quadric {
+        Some(Quadric::Plane(origin, normal)) => {
+            let matrix = translation(-distance * normal);
+            let origin = origin - distance * normal;
+            // The planar surface is replaced by the plane, which extends beyond the face.
+            let surface = match surface {
+                Surface::Plane(_) => surface.transformed(matrix),
+                _ => {
+                    let u = normal.cross(perpendicular(normal)).normalize();
+                    Surface::Plane(Plane::new(origin, origin + u, origin + normal.cross(u)))
+                }
+            };
+            return Ok(Offset {
+                surface,
+                matrix,
+                distance,
+                quadric: Some(Quadric::Plane(origin, normal)),
+            });
+        }
+        Some(Quadric::Sphere(center, radius)) | Some(Quadric::Cylinder(center, _, radius)) => {
+            (center, radius)
+        }
+        None => return Err(ShellError::UnsupportedSurface),
+    };
+    let scale = (radius - distance) / radius;
+    if scale <= 0.0 {
+        return Err(ShellError::TooThick);
+    }
+    let axis = match quadric {
+        Some(Quadric::Cylinder(_, axis, _)) => axis,
+        _ => {
+            let matrix = translation(center.to_vec())
+                * Matrix4::from_scale(scale)
+                * translation(-center.to_vec());
+            return Ok(Offset {
+                surface: surface.transformed(matrix),
+                matrix,
+                distance,
+                quadric: Some(Quadric::Sphere(center, radius - distance)),
+            });
+        }
+    };
+    // the scaling perpendicular to the axis
+    let matrix = translation(center.to_vec())
+        * Matrix4::from(Matrix3::identity() * scale + outer(axis, axis) * (1.0 - scale))
+        * translation(-center.to_vec());
+    // The offset cylinder is longer than the face, since the inner face extends beyond the
+    // face at the concave edges, and its seam is on the opposite side of the face.
+    let samples = samples(surface).ok_or(ShellError::UnsupportedSurface)?;
+    let heights = samples.iter().map(|(p, _)| (p - center).dot(axis));
+    let (min, max) = heights.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), h| {
+        (f64::min(min, h), f64::max(max, h))
+    });
+    let margin = max - min + 10.0 * distance;
+    let ((u0, u1), (v0, v1)) = match surface.try_range_tuple() {
+        (Some(urange), Some(vrange)) => (urange, vrange),
+        _ => return Err(ShellError::UnsupportedSurface),
+    };
+    let (u, v) = ((u0 + u1) / 2.0, (v0 + v1) / 2.0);
+    let middle = surface.subs(u, v);
+    let radial = center - middle - axis * (center - middle).dot(axis);
+    let opposite = radial.normalize() * f64::abs(radius - distance);
+    let line = Line(
+        center + opposite + axis * (min - margin),
+        center + opposite + axis * (max + margin),
+    );
+    let revolution = RevolutedCurve::by_revolution(Curve::Line(line), center, axis);
+    let mut cylinder = Surface::RevolutedCurve(Processor::new(revolution));
+    let point = matrix.transform_point(middle);
+    let (s, t) = cylinder
+        .search_nearest_parameter(point, None, 100)
+        .ok_or(ShellError::UnsupportedSurface)?;
+    if cylinder.normal(s, t).dot(surface.normal(u, v)) < 0.0 {
+        cylinder.invert();
+    }
+    Ok(Offset {
+        surface: cylinder,
+        matrix,
+        distance,
+        quadric: Some(Quadric::Cylinder(center, axis, radius - distance)),
+    })
+}
+
+// Returns the matrix of `vector -> a * b.dot(vector)`.
+fn outer(a: Vector3, b: Vector3) -> Matrix3 { Matrix3::from_cols(a * b.x, a * b.y, a * b.z) }
+
+/// Returns a vector which is not parallel to the unit vector.
+pub fn perpendicular(vector: Vector3) -> Vector3 {
+    match vector.x.abs() < 0.5 {
+        true => Vector3::unit_x(),
+        false => Vector3::unit_y(),
+    }
+}
+
+// Returns the point on all the surfaces and on the planes of the origins and the normals
+// near `point` by Newton's method. The point moves only along the normals of the surfaces
+// and the planes, e.g. only along the common normal of the tangent surfaces.
+fn intersect(
+    surfaces: &[&Surface],
+    mut point: Point3,
+    planes: &[(Point3, Vector3)],
+) -> Option<Point3> {
+    let mut hints = vec![None; surfaces.len()];
+    for _ in 0..100 {
+        // the planes tangent to the surfaces at the nearest points
+        let mut tangents = Vec::new();
+        let mut converged = true;
+        for (surface, hint) in surfaces.iter().zip(&mut hints) {
+            let (u, v) = surface.search_nearest_parameter(point, *hint, 100)?;
+            *hint = Some((u, v));
+            let nearest = surface.subs(u, v);
+            converged &= nearest.near(&point);
+            let normal = surface.normal(u, v);
+            tangents.push((normal, normal.dot(nearest.to_vec())));
+        }
+        if converged {
+            return Some(point);
+        }
+        let fixed = planes
+            .iter()
+            .map(|(origin, normal)| (*normal, normal.dot(origin.to_vec())));
+        // the independent planes, by the Gram-Schmidt orthogonalization
+        let mut basis: Vec<Vector3> = Vec::new();
+        let mut independent = Vec::new();
+        for (normal, value) in tangents.into_iter().chain(fixed) {
+            let residual = basis.iter().fold(normal, |r, b| r - b * r.dot(*b));
+            if residual.magnitude() > 1.0e-3 * normal.magnitude() {
+                basis.push(residual.normalize());
+                independent.push((normal, value));
+            }
+        }
+        // The point does not move along the other directions.
+        while independent.len() < 3 {
+            let normal = match basis.as_slice() {
+                [b0, b1] => b0.cross(*b1),
+                [b0] => b0.cross(perpendicular(*b0)),
+                _ => return None,
+            }
+            .normalize();
+            basis.push(normal);
+            independent.push((normal, normal.dot(point.to_vec())));
+        }
+        let [(n0, c0), (n1, c1), (n2, c2)] = [independent[0], independent[1], independent[2]];
+        let matrix = Matrix3::from_cols(n0, n1, n2).transpose();
+        point = Point3::from_vec(matrix.invert()? * Vector3::new(c0, c1, c2));
+    }
+    None
+}
+
+// Returns the unit normal of the surface at the point on it.
+fn normal_at(surface: &Surface, point: Point3) -> Option<Vector3> {
+    let (u, v) = surface.search_nearest_parameter(point, None, 100)?;
+    Some(surface.normal(u, v))
+}
+
+// Returns the inner curve of the edge from `ends[0]` to `ends[1]` on the offset surfaces of
+// the faces of the edge. The inner curve is the image of the edge by an affine map, so it is
+// exact.
+fn inner_curve(edge: &Edge, offsets: [&Offset; 2], ends: [Point3; 2]) -> Option<Curve> {
+    let curve = edge.curve();
+    let translation = |vector: Vector3| Matrix4::from_translation(vector);
+    let mut matrices = match curve {
+        Curve::Line(_) => return Some(Curve::Line(Line(ends[0], ends[1]))),
+        // The leaders of the moved intersection curves do not follow the surfaces.
+        Curve::IntersectionCurve(_) => return None,
+        _ => {
+            let [m0, m1] = offsets.map(|offset| offset.matrix);
+            vec![m0, m1, m0 * m1, m1 * m0]
+        }
+    };
+    for (plane, other) in [(offsets[0], offsets[1]), (offsets[1], offsets[0])] {
+        let (origin, normal) = match plane.quadric {
+            Some(Quadric::Plane(origin, normal)) => (origin, normal),
+            _ => continue,
+        };
+        match other.quadric {
+            // the projection of the moved edge to the plane along the axis, which keeps the
+            // points on the cylinder
+            Some(Quadric::Cylinder(_, axis, _)) if !axis.dot(normal).so_small() => {
+                let projection = Matrix3::identity() - outer(axis, normal) / axis.dot(normal);
+                let vector = axis * normal.dot(origin.to_vec()) / axis.dot(normal);
+                let matrix = translation(vector) * Matrix4::from(projection);
+                matrices.push(matrix * other.matrix);
+            }
+            // the scaling of the circle on the face plane to the circle on the offset plane
+            Some(Quadric::Sphere(center, radius)) => {
+                let (t0, t1) = curve.range_tuple();
+                let point = curve.subs((t0 + t1) / 2.0);
+                let circle_center = center + normal * (point - center).dot(normal);
+                let height = (origin - center).dot(normal);
+                let (old, new) = (
+                    point.distance2(circle_center),
+                    radius * radius - height * height,
+                );
+                if old.so_small() || new <= 0.0 {
+                    continue;
+                }
+                let scale = f64::sqrt(new / old);
+                let linear = Matrix3::identity() * scale + outer(normal, normal) * (1.0 - scale);
+                matrices.push(
+                    translation(center.to_vec() + normal * height)
+                        * Matrix4::from(linear)
+                        * translation(-circle_center.to_vec()),
+                );
+            }
+            _ => {}
+        }
+    }
+    let surfaces = offsets.map(|offset| &offset.surface);
+    let on_surfaces = |curve: &Curve| {
+        let (t0, t1) = curve.range_tuple();
+        let on_surface = |surface: &&Surface, point: Point3| match surface
+            .search_nearest_parameter(point, None, 100)
+        {
+            Some((u, v)) => surface.subs(u, v).near(&point),
+            None => false,
+        };
+        curve.subs(t0).near(&ends[0])
+            && curve.subs(t1).near(&ends[1])
+            && (1..8).all(|i| {
+                let point = curve.subs(t0 + (t1 - t0) * i as f64 / 8.0);
+                surfaces.iter().all(|surface| on_surface(surface, point))
+            })
+    };
+    // The inner edge is a part of the image if the inner faces are smaller than the faces,
+    // e.g. the meridians of the dome.
+    let trim = |mut curve: Curve| {
+        let [s0, s1] = ends.map(|end| curve.search_parameter(end, None, 100));
+        let (s0, s1) = (s0?, s1?);
+        let (t0, t1) = curve.range_tuple();
+        if s0 >= s1 {
+            return None;
+        }
+        if !(t1 - s1).so_small() {
+            curve.cut(s1);
+        }
+        match (s0 - t0).so_small() {
+            true => Some(curve),
+            false => Some(curve.cut(s0)),
+        }
+    };
+    matrices
+        .into_iter()
+        .filter_map(|matrix| trim(curve.transformed(matrix)))
+        .find(on_surfaces)
+}
@@ -0,0 +1,660 @@
+//! Sketches of the profiles on the planes, by the geometric constraints.
+//!
+//! A sketch has the points, and the lines, the circle arcs, the circles and the splines
+//! through the points. The positions of the points and the radii of the circles are the
+//! unknowns, which are solved from the constraints by the Levenberg-Marquardt method starting
+//! at the given positions, so the positions should be roughly drawn: the solution near them
+//! is chosen among the others, e.g. the flipped ones.
+//!
+//! The sketch is well constrained if the Jacobian matrix of the equations at the solution is
+//! square and regular. The rank of the matrix tells the degrees of freedom left in the
+//! under-constrained sketches and the redundant equations in the over-constrained ones.
+//!
+//! The arcs are counterclockwise from the start to the end around the center, and have the
+//! implicit equation that the start and the end are at the same distance from the center.
+//! The splines are the clamped uniform B-splines of degree three at most, whose control points
+//! are the points.
+
+use crate::primitive::Frame;
+use std::f64::consts::PI;
+use std::result::Result;
+use truck_modeling::*;
+
+/// The point of a sketch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PointId(usize);
+
+/// The curve of a sketch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CurveId(usize);
+
+/// The geometric constraints.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Constraint {
+    /// The point is at the position.
+    Fix(PointId, Point2),
+    /// The points are at the same position.
+    Coincident(PointId, PointId),
+    /// The line is parallel to the x-axis.
+    Horizontal(CurveId),
+    /// The line is parallel to the y-axis.
+    Vertical(CurveId),
+    /// The lines are parallel.
+    Parallel(CurveId, CurveId),
+    /// The line and the arc or the circle, or the arcs and the circles, are tangent. The curves
+    /// sharing an end touch at the end, and the others touch anywhere, where the arcs and the
+    /// circles touch inside or outside as they are drawn.
+    Tangent(CurveId, CurveId),
+    /// The points are at the distance.
+    Distance(PointId, PointId, f64),
+    /// The arc or the circle has the radius.
+    Radius(CurveId, f64),
+    /// The second line is at the angle from the first one counterclockwise, in radians. The
+    /// lines have no direction, so the angle is modulo `PI`.
+    Angle(CurveId, CurveId, f64),
+    /// The point is on the line, which is extended, or on the arc, which is completed to the
+    /// circle, or on the circle.
+    OnCurve(PointId, CurveId),
+    /// The points are symmetric with respect to the line.
+    Symmetric(PointId, PointId, CurveId),
+}
+
+/// The errors of the sketches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SketchError {
+    /// The constraint has the curves of the wrong kinds.
+    InvalidConstraint,
+    /// The spline has less than two points.
+    InvalidCurve,
+    /// The sketch has the degrees of freedom left.
+    UnderConstrained(usize),
+    /// The sketch has the redundant or conflicting equations.
+    OverConstrained(usize),
+    /// The solver did not converge, although the equations are independent.
+    NotConverged,
+    /// The curves do not make a closed wire.
+    NotClosed,
+}
+
+impl std::fmt::Display for SketchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SketchError::InvalidConstraint => f.write_str("the constraint has invalid curves"),
+            SketchError::InvalidCurve => f.write_str("the spline needs two points at least"),
+            SketchError::UnderConstrained(dof) => {
+                write!(f, "under-constrained by {dof} degrees of freedom")
+            }
+            SketchError::OverConstrained(count) => {
+                write!(f, "over-constrained by {count} equations")
+            }
+            SketchError::NotConverged => f.write_str("the solver did not converge"),
+            SketchError::NotClosed => f.write_str("the curves do not make a closed wire"),
+        }
+    }
+}
+
+impl std::error::Error for SketchError {}
+
+#[derive(Clone, Debug)]
+enum SketchCurve {
+    Line(PointId, PointId),
+    // the center, the start and the end
+    Arc(PointId, PointId, PointId),
+    // the center and the index of the radius
+    Circle(PointId, usize),
+    Spline(Vec<PointId>),
+}
+
+/// A sketch on the xy-plane of the frame.
+#[derive(Clone, Debug)]
+pub struct Sketch {
+    frame: Frame,
+    points: Vec<Point2>,
+    radii: Vec<f64>,
+    curves: Vec<SketchCurve>,
+    constraints: Vec<Constraint>,
+}
+
+// the unknowns: the coordinates of the points, and the radii of the circles
+struct Unknowns<'a>(&'a [f64]);
+
+impl Unknowns<'_> {
+    fn point(&self, point: PointId) -> Vector2 {
+        Vector2::new(self.0[2 * point.0], self.0[2 * point.0 + 1])
+    }
+}
+
+impl Sketch {
+    /// Creates the empty sketch on the xy-plane of the frame.
+    pub fn new(frame: Frame) -> Self {
+        Sketch {
+            frame,
+            points: Vec::new(),
+            radii: Vec::new(),
+            curves: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Adds the point at the position, which is the initial guess of the solver.
+    pub fn point(&mut self, x: f64, y: f64) -> PointId {
+        self.points.push(Point2::new(x, y));
+        PointId(self.points.len() - 1)
+    }
+
+    /// Adds the line from `start` to `end`.
+    pub fn line(&mut self, start: PointId, end: PointId) -> CurveId {
+        self.push(SketchCurve::Line(start, end))
+    }
+
+    /// Adds the arc around `center` counterclockwise from `start` to `end`.
+    pub fn arc(&mut self, center: PointId, start: PointId, end: PointId) -> CurveId {
+        self.push(SketchCurve::Arc(center, start, end))
+    }
+
+    /// Adds the circle around `center`, whose radius is the initial guess of the solver.
+    pub fn circle(&mut self, center: PointId, radius: f64) -> CurveId {
+        self.radii.push(radius);
+        self.push(SketchCurve::Circle(center, self.radii.len() - 1))
+    }
+
+    /// Adds the spline whose control points are the points.
+    pub fn spline(&mut self, points: &[PointId]) -> Result<CurveId, SketchError> {
+        match points.len() < 2 {
+            true => Err(SketchError::InvalidCurve),
+            false => Ok(self.push(SketchCurve::Spline(points.to_vec()))),
+        }
+    }
+
+    fn push(&mut self, curve: SketchCurve) -> CurveId {
+        self.curves.push(curve);
+        CurveId(self.curves.len() - 1)
+    }
+
+    /// Adds the constraint.
+    pub fn constrain(&mut self, constraint: Constraint) -> Result<(), SketchError> {
+        let line = |curve: CurveId| matches!(self.curves[curve.0], SketchCurve::Line(..));
+        let round = |curve: CurveId| {
+            matches!(
+                self.curves[curve.0],
+                SketchCurve::Arc(..) | SketchCurve::Circle(..)
+            )
+        };
+        let valid = match constraint {
+            Constraint::Fix(..) | Constraint::Coincident(..) | Constraint::Distance(..) => true,
+            Constraint::Horizontal(curve) | Constraint::Vertical(curve) => line(curve),
+            Constraint::Parallel(c0, c1) | Constraint::Angle(c0, c1, _) => line(c0) && line(c1),
+            Constraint::Tangent(c0, c1) => {
+                (line(c0) || round(c0)) && (line(c1) || round(c1)) && !(line(c0) && line(c1))
+            }
+            Constraint::Radius(curve, _) => round(curve),
+            Constraint::OnCurve(_, curve) => line(curve) || round(curve),
+            Constraint::Symmetric(_, _, curve) => line(curve),
+        };
+        match valid {
+            true => {
+                self.constraints.push(constraint);
+                Ok(())
+            }
+            false => Err(SketchError::InvalidConstraint),
+        }
+    }
+
+    /// Returns the position of the point.
+    pub fn position(&self, point: PointId) -> Point2 { self.points[point.0] }
+
+    /// Returns the radius of the arc or the circle.
+    pub fn radius(&self, curve: CurveId) -> Option<f64> {
+        match self.curves[curve.0] {
+            SketchCurve::Arc(center, start, _) => {
+                Some(self.position(center).distance(self.position(start)))
+            }
+            SketchCurve::Circle(_, idx) => Some(self.radii[idx]),
+            _ => None,
+        }
+    }
+
+    /// Solves the constraints, and moves the points to the solution. The points are moved to
+    /// the nearest solution even if the sketch is under-constrained, and to the least-squares
+    /// one if it is over-constrained, which are reported as the errors.
+    pub fn solve(&mut self) -> Result<(), SketchError> {
+        let mut unknowns: Vec<f64> = self.points.iter().flat_map(|p| [p.x, p.y]).collect();
+        unknowns.extend(&self.radii);
+        // The arcs and the circles touch inside if the distance of the centers is nearer to
+        // the difference of the radii than to the sum.
+        let insides: Vec<bool> = self
+            .constraints
+            .iter()
+            .map(|constraint| match constraint {
+                Constraint::Tangent(c0, c1)
+                    if !self.is_line(*c0)
+                        && !self.is_line(*c1)
+                        && self.shared_end(*c0, *c1).is_none() =>
+                {
+                    let x = Unknowns(&unknowns);
+                    let [(p, r), (q, s)] = [c0, c1].map(|curve| self.round(&x, *curve));
+                    let distance = p.distance(q);
+                    f64::abs(distance - f64::abs(r - s)) < f64::abs(distance - (r + s))
+                }
+                _ => false,
+            })
+            .collect();
+        let equations = |x: &[f64]| self.equations(&Unknowns(x), &insides);
+
+        let mut residual = norm2(&equations(&unknowns));
+        let mut lambda = 1.0e-3;
+        for _ in 0..MAX_ITERATIONS {
+            if residual < RESIDUAL * RESIDUAL {
+                break;
+            }
+            let values = equations(&unknowns);
+            let jacobian = jacobian(&equations, &unknowns);
+            let n = unknowns.len();
+            // the normal equations of the damped least squares
+            let mut matrix = vec![vec![0.0; n]; n];
+            let mut vector = vec![0.0; n];
+            for (row, value) in jacobian.iter().zip(&values) {
+                for i in 0..n {
+                    vector[i] -= row[i] * value;
+                    for j in 0..n {
+                        matrix[i][j] += row[i] * row[j];
+                    }
+                }
+            }
+            loop {
+                let mut damped = matrix.clone();
+                (0..n).for_each(|i| damped[i][i] += lambda * f64::max(matrix[i][i], 1.0));
+                let step = solve_linear(damped, vector.clone());
+                let next: Vec<f64> = unknowns.iter().zip(&step).map(|(x, d)| x + d).collect();
+                let next_residual = norm2(&equations(&next));
+                if next_residual < residual {
+                    (unknowns, residual) = (next, next_residual);
+                    lambda = f64::max(lambda / 10.0, 1.0e-12);
+                    break;
+                }
+                lambda *= 10.0;
+                if lambda > 1.0e12 {
+                    break;
+                }
+            }
+            if lambda > 1.0e12 {
+                break;
+            }
+        }
+        let jacobian = jacobian(&equations, &unknowns);
+        let (n, m) = (unknowns.len(), jacobian.len());
+        let rank = rank(jacobian);
+        let count = self.points.len();
+        self.points = (0..count)
+            .map(|i| Point2::new(unknowns[2 * i], unknowns[2 * i + 1]))
+            .collect();
+        self.radii = unknowns[2 * count..].to_vec();
+        match (residual < RESIDUAL * RESIDUAL, m > rank, n > rank) {
+            (_, true, _) => Err(SketchError::OverConstrained(m - rank)),
+            (false, false, _) => Err(SketchError::NotConverged),
+            (true, false, true) => Err(SketchError::UnderConstrained(n - rank)),
+            (true, false, false) => Ok(()),
+        }
+    }
+
+    fn is_line(&self, curve: CurveId) -> bool {
+        matches!(self.curves[curve.0], SketchCurve::Line(..))
+    }
+
+    // the end shared by the lines and the arcs
+    fn shared_end(&self, c0: CurveId, c1: CurveId) -> Option<PointId> {
+        let ends = |curve: CurveId| match self.curves[curve.0] {
+            SketchCurve::Line(p, q) | SketchCurve::Arc(_, p, q) => vec![p, q],
+            _ => Vec::new(),
+        };
+        let ends1 = ends(c1);
+        ends(c0).into_iter().find(|end| ends1.contains(end))
+    }
+
+    // the ends of the line
+    fn line_ends(&self, x: &Unknowns, curve: CurveId) -> (Vector2, Vector2) {
+        match self.curves[curve.0] {
+            SketchCurve::Line(p, q) => (x.point(p), x.point(q)),
+            _ => unreachable!(),
+        }
+    }
+
+    // the center and the radius of the arc or the circle
+    fn round(&self, x: &Unknowns, curve: CurveId) -> (Vector2, f64) {
+        match self.curves[curve.0] {
+            SketchCurve::Arc(center, start, _) => {
+                let center = x.point(center);
+                (center, center.distance(x.point(start)))
+            }
+            SketchCurve::Circle(center, idx) => (x.point(center), x.0[2 * self.points.len() + idx]),
+            _ => unreachable!(),
+        }
+    }
+
+    // Returns the values of the equations, which vanish at the solution.
+    fn equations(&self, x: &Unknowns, insides: &[bool]) -> Vec<f64> {
+        let mut values = Vec::new();
+        // the signed distance from the line
+        let from_line = |curve: CurveId, point: Vector2| {
+            let (p, q) = self.line_ends(x, curve);
+            cross(q - p, point - p) / (q - p).magnitude()
+        };
+        for (constraint, inside) in self.constraints.iter().zip(insides) {
+            match *constraint {
+                Constraint::Fix(point, position) => {
+                    let d = x.point(point) - position.to_vec();
+                    values.extend([d.x, d.y]);
+                }
+                Constraint::Coincident(p, q) => {
+                    let d = x.point(p) - x.point(q);
+                    values.extend([d.x, d.y]);
+                }
+                Constraint::Horizontal(curve) => {
+                    let (p, q) = self.line_ends(x, curve);
+                    values.push(q.y - p.y);
+                }
+                Constraint::Vertical(curve) => {
+                    let (p, q) = self.line_ends(x, curve);
+                    values.push(q.x - p.x);
+                }
+                Constraint::Parallel(c0, c1) | Constraint::Angle(c0, c1, _) => {
+                    let angle = match *constraint {
+                        Constraint::Angle(_, _, angle) => angle,
+                        _ => 0.0,
+                    };
+                    let [(p0, q0), (p1, q1)] = [c0, c1].map(|curve| self.line_ends(x, curve));
+                    let (u, v) = ((q0 - p0).normalize(), (q1 - p1).normalize());
+                    // the sine of the difference of the angles
+                    values.push(cross(u, v) * angle.cos() - u.dot(v) * angle.sin());
+                }
+                Constraint::Tangent(c0, c1) => match self.shared_end(c0, c1) {
+                    // The radius of the arc at the end is perpendicular to the line, or
+                    // parallel to the radius of the other arc.
+                    Some(end) => {
+                        let end = x.point(end);
+                        let direction = |curve: CurveId| match self.is_line(curve) {
+                            true => {
+                                let (p, q) = self.line_ends(x, curve);
+                                let u = (q - p).normalize();
+                                Vector2::new(-u.y, u.x)
+                            }
+                            false => (end - self.round(x, curve).0).normalize(),
+                        };
+                        values.push(cross(direction(c0), direction(c1)));
+                    }
+                    None if self.is_line(c0) || self.is_line(c1) => {
+                        let (line, round) = if self.is_line(c0) { (c0, c1) } else { (c1, c0) };
+                        let (center, radius) = self.round(x, round);
+                        values.push(f64::abs(from_line(line, center)) - radius);
+                    }
+                    None => {
+                        let [(p, r), (q, s)] = [c0, c1].map(|curve| self.round(x, curve));
+                        let distance = p.distance(q);
+                        match inside {
+                            true => values.push(distance - f64::abs(r - s)),
+                            false => values.push(distance - (r + s)),
+                        }
+                    }
+                },
+                Constraint::Distance(p, q, distance) => {
+                    values.push(x.point(p).distance(x.point(q)) - distance);
+                }
+                Constraint::Radius(curve, radius) => values.push(self.round(x, curve).1 - radius),
+                Constraint::OnCurve(point, curve) => match self.is_line(curve) {
+                    true => values.push(from_line(curve, x.point(point))),
+                    false => {
+                        let (center, radius) = self.round(x, curve);
+                        values.push(center.distance(x.point(point)) - radius);
+                    }
+                },
+                Constraint::Symmetric(p, q, line) => {
+                    let (p, q) = (x.point(p), x.point(q));
+                    let (l0, l1) = self.line_ends(x, line);
+                    values.push(from_line(line, (p + q) / 2.0));
+                    values.push((q - p).dot((l1 - l0).normalize()));
+                }
+            }
+        }
+        // The ends of the arcs are at the same distance from the centers.
+        for curve in &self.curves {
+            if let SketchCurve::Arc(center, start, end) = *curve {
+                let center = x.point(center);
+                values.push(center.distance(x.point(end)) - center.distance(x.point(start)));
+            }
+        }
+        values
+    }
+
+    // the point on the plane of the sketch
+    fn point3(&self, point: Point2) -> Point3 {
+        self.frame
+            .transform_point(Point3::new(point.x, point.y, 0.0))
+    }
+
+    // the ends of the curve
+    fn ends(&self, curve: CurveId) -> Result<(Point2, Point2), SketchError> {
+        let (start, end) = match &self.curves[curve.0] {
+            SketchCurve::Line(p, q) | SketchCurve::Arc(_, p, q) => (*p, *q),
+            SketchCurve::Spline(points) => (points[0], *points.last().unwrap()),
+            SketchCurve::Circle(..) => return Err(SketchError::NotClosed),
+        };
+        Ok((self.position(start), self.position(end)))
+    }
+
+    /// Returns the closed wire of the curves in the order, whose directions are adjusted to
+    /// join the curves. A circle makes the wire by itself. The wire is counterclockwise on the
+    /// sketch, so that its face is along the z-axis of the frame.
+    pub fn wire(&self, curves: &[CurveId]) -> Result<Wire, SketchError> {
+        if let [curve] = curves {
+            if let SketchCurve::Circle(center, idx) = self.curves[curve.0] {
+                let center = self.position(center);
+                let start = self.point3(center + Vector2::new(self.radii[idx], 0.0));
+                let axis = self.frame.axes()[2];
+                let vertex = builder::vertex(start);
+                return Ok(builder::rsweep(
+                    &vertex,
+                    self.point3(center),
+                    axis,
+                    Rad(7.0),
+                ));
+            }
+        }
+        let ends = curves
+            .iter()
+            .map(|curve| self.ends(*curve))
+            .collect::<Result<Vec<_>, _>>()?;
+        if ends.is_empty() {
+            return Err(SketchError::NotClosed);
+        }
+        // The first curve is reversed if its start joins the next one.
+        let joins =
+            |point: Point2, (start, end): (Point2, Point2)| point.near(&start) || point.near(&end);
+        let next = ends[1 % ends.len()];
+        let mut reversed = vec![!joins(ends[0].1, next) && joins(ends[0].0, next)];
+        let mut last = if reversed[0] { ends[0].0 } else { ends[0].1 };
+        for (start, end) in &ends[1..] {
+            let reverse = match (last.near(start), last.near(end)) {
+                (true, _) => false,
+                (_, true) => true,
+                _ => return Err(SketchError::NotClosed),
+            };
+            reversed.push(reverse);
+            last = if reverse { *start } else { *end };
+        }
+        let first = if reversed[0] { ends[0].1 } else { ends[0].0 };
+        if !last.near(&first) {
+            return Err(SketchError::NotClosed);
+        }
+        let first = builder::vertex(self.point3(first));
+        let mut vertex = first.clone();
+        let mut wire = Wire::new();
+        for (i, (curve, reversed)) in curves.iter().zip(reversed).enumerate() {
+            let (start, end) = ends[i];
+            let end = if reversed { start } else { end };
+            let next = match i + 1 == curves.len() {
+                true => first.clone(),
+                false => builder::vertex(self.point3(end)),
+            };
+            let edge = match reversed {
+                false => self.edge(*curve, &vertex, &next),
+                true => self.edge(*curve, &next, &vertex).inverse(),
+            };
+            wire.push_back(edge);
+            vertex = next;
+        }
+        match self.area(&wire) < 0.0 {
+            true => Ok(wire.inverse()),
+            false => Ok(wire),
+        }
+    }
+
+    // the signed area of the polygon of the points on the wire
+    fn area(&self, wire: &Wire) -> f64 {
+        let [x, y, _] = self.frame.axes();
+        let points: Vec<Vector2> = wire
+            .edge_iter()
+            .flat_map(|edge| {
+                let curve = edge.oriented_curve();
+                let (t0, t1) = curve.range_tuple();
+                (0..AREA_SAMPLES).map(move |i| {
+                    let p = curve.subs(t0 + (t1 - t0) * i as f64 / AREA_SAMPLES as f64);
+                    let p = p - self.frame.origin();
+                    Vector2::new(p.dot(x), p.dot(y))
+                })
+            })
+            .collect();
+        let n = points.len();
+        (0..n)
+            .map(|i| cross(points[i], points[(i + 1) % n]))
+            .sum::<f64>()
+            / 2.0
+    }
+
+    // the edge of the curve between the vertices at its start and its end
+    fn edge(&self, curve: CurveId, start: &Vertex, end: &Vertex) -> Edge {
+        match &self.curves[curve.0] {
+            SketchCurve::Line(..) => builder::line(start, end),
+            SketchCurve::Arc(center, p, q) => {
+                let center = self.position(*center);
+                let [a0, a1] = [p, q].map(|point| {
+                    let d = self.position(*point) - center;
+                    f64::atan2(d.y, d.x)
+                });
+                let angle = a0 + (a1 - a0).rem_euclid(2.0 * PI) / 2.0;
+                let radius = center.distance(self.position(*p));
+                let transit = center + radius * Vector2::new(angle.cos(), angle.sin());
+                builder::circle_arc(start, end, self.point3(transit))
+            }
+            SketchCurve::Spline(points) => {
+                let degree = usize::min(3, points.len() - 1);
+                let knot_vec = KnotVec::uniform_knot(degree, points.len() - degree);
+                let control_points = points
+                    .iter()
+                    .map(|point| self.point3(self.position(*point)))
+                    .collect();
+                let curve = BSplineCurve::new(knot_vec, control_points);
+                Edge::new(start, end, Curve::BSplineCurve(curve))
+            }
+            SketchCurve::Circle(..) => unreachable!(),
+        }
+    }
+}
+
+// the tolerance of the residual of the solver
+const RESIDUAL: f64 = 1.0e-9;
+
+const MAX_ITERATIONS: usize = 200;
+
+// the number of the points on every edge for the orientation of the wires
+const AREA_SAMPLES: usize = 8;
+
+fn cross(u: Vector2, v: Vector2) -> f64 { u.x * v.y - u.y * v.x }
+
+fn norm2(values: &[f64]) -> f64 { values.iter().map(|v| v * v).sum() }
+
+// the Jacobian matrix by the central differences
+fn jacobian(equations: &impl Fn(&[f64]) -> Vec<f64>, x: &[f64]) -> Vec<Vec<f64>> {
+    let m = equations(x).len();
+    let mut jacobian = vec![vec![0.0; x.len()]; m];
+    let mut x = x.to_vec();
+    for j in 0..x.len() {
+        let h = 1.0e-7 * f64::max(1.0, x[j].abs());
+        let original = x[j];
+        x[j] = original + h;
+        let plus = equations(&x);
+        x[j] = original - h;
+        let minus = equations(&x);
+        x[j] = original;
+        for i in 0..m {
+            jacobian[i][j] = (plus[i] - minus[i]) / (2.0 * h);
+        }
+    }
+    jacobian
+}
+
+// Subtracts the pivot row times the factor from the row below it, so that the entry of the
+// row in the column vanishes, and returns the factor.
+fn eliminate(matrix: &mut [Vec<f64>], pivot: usize, row: usize, col: usize) -> f64 {
+    let (upper, lower) = matrix.split_at_mut(row);
+    let (pivot, row) = (&upper[pivot], &mut lower[0]);
+    let factor = row[col] / pivot[col];
+    row.iter_mut()
+        .zip(pivot)
+        .for_each(|(a, b)| *a -= factor * b);
+    factor
+}
+
+// Solves the regular linear equations by the Gaussian elimination.
+fn solve_linear(mut matrix: Vec<Vec<f64>>, mut vector: Vec<f64>) -> Vec<f64> {
+    let n = vector.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|a, b| matrix[*a][col].abs().total_cmp(&matrix[*b][col].abs()))
+            .unwrap();
+        matrix.swap(col, pivot);
+        vector.swap(col, pivot);
+        for row in col + 1..n {
+            let factor = eliminate(&mut matrix, col, row, col);
+            vector[row] -= factor * vector[col];
+        }
+    }
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|j| matrix[row][j] * solution[j]).sum();
+        solution[row] = (vector[row] - sum) / matrix[row][row];
+    }
+    solution
+}
+
+// the rank of the matrix by the Gaussian elimination with the full pivoting
+fn rank(mut matrix: Vec<Vec<f64>>) -> usize {
+    let (m, n) = (matrix.len(), matrix.first().map_or(0, Vec::len));
+    let scale = matrix
+        .iter()
+        .flatten()
+        .fold(0.0, |max: f64, a| f64::max(max, a.abs()));
+    let tolerance = 1.0e-6 * f64::max(scale, 1.0);
+    let mut rank = 0;
+    let mut columns: Vec<usize> = (0..n).collect();
+    while rank < m && !columns.is_empty() {
+        // the largest entry in the rest of the matrix
+        let (row, k) = (rank..m)
+            .flat_map(|row| (0..columns.len()).map(move |k| (row, k)))
+            .max_by(|(r0, k0), (r1, k1)| {
+                let [a, b] = [(r0, k0), (r1, k1)].map(|(r, k)| matrix[*r][columns[*k]].abs());
+                a.total_cmp(&b)
+            })
+            .unwrap();
+        let col = columns[k];
+        if matrix[row][col].abs() < tolerance {
+            break;
+        }
+        matrix.swap(rank, row);
+        for r in rank + 1..m {
+            eliminate(&mut matrix, rank, r, col);
+        }
+        columns.remove(k);
+        rank += 1;
+    }
+    rank
+}
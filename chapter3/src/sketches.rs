@@ -0,0 +1,255 @@
+use chapter3::{primitive::*, sketch::*};
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+use truck_stepio::out::*;
+
+fn save_shape(solid: &Solid, filename: &str) {
+    // output to polygonmesh
+    let mesh_with_topology = solid.triangulation(0.01);
+    let mesh = mesh_with_topology.to_polygon();
+    let obj_path = filename.to_string() + ".obj";
+    let mut obj = std::fs::File::create(&obj_path).unwrap();
+    obj::write(&mesh, &mut obj).unwrap();
+
+    // compress solid data.
+    let compressed = solid.compress();
+
+    // step format display
+    let display = CompleteStepDisplay::new(StepModel::from(&compressed), Default::default());
+    // content of step file
+    let step_string: String = display.to_string();
+    let step_path = filename.to_string() + ".step";
+    std::fs::write(&step_path, &step_string).unwrap();
+}
+
+// Checks that the solid is closed and has the volume.
+fn check(name: &str, solid: &Solid, volume: f64) {
+    let shell = &solid.boundaries()[0];
+    assert_eq!(shell.shell_condition(), ShellCondition::Closed, "{name}");
+    let mut mesh = solid.triangulation(0.001).to_polygon();
+    mesh.put_together_same_attrs(10.0 * TOLERANCE)
+        .remove_degenerate_faces();
+    assert_eq!(mesh.shell_condition(), ShellCondition::Closed, "{name}");
+    println!("{name}: volume {:.5} (exact: {volume:.5})", mesh.volume());
+    assert!(f64::abs(mesh.volume() - volume) < 0.005 * volume, "{name}");
+}
+
+// the construction axes of the sketch, fixed at the origin
+fn axes(sketch: &mut Sketch) -> (CurveId, CurveId) {
+    let [o, ox, oy] = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)].map(|(x, y)| {
+        let point = sketch.point(x, y);
+        sketch
+            .constrain(Constraint::Fix(point, Point2::new(x, y)))
+            .unwrap();
+        point
+    });
+    (sketch.line(o, ox), sketch.line(o, oy))
+}
+
+fn main() {
+    // The section of the body of the bottle in section 3.3, whose points are solved from the
+    // width and the thickness instead of the coordinates derived by hand. The sketch is on the
+    // bottom facing upward, so that its y-axis is the negative z-axis of the world.
+    let (height, width, thickness) = (1.4, 1.0, 0.6);
+    let frame = Frame::new(
+        Point3::new(0.0, -height / 2.0, 0.0),
+        Vector3::unit_y(),
+        Vector3::unit_x(),
+    )
+    .unwrap();
+    let mut sketch = Sketch::new(frame);
+    let (xaxis, yaxis) = axes(&mut sketch);
+    // the rough positions of the corners, the tops of the arcs and their centers
+    let a = sketch.point(-0.4, 0.2);
+    let b = sketch.point(0.4, 0.2);
+    let c = sketch.point(0.4, -0.2);
+    let d = sketch.point(-0.4, -0.2);
+    let t1 = sketch.point(0.1, 0.4);
+    let t2 = sketch.point(0.1, -0.4);
+    let o1 = sketch.point(0.0, -0.5);
+    let o2 = sketch.point(0.0, 0.5);
+    let arc1 = sketch.arc(o1, b, a);
+    let side1 = sketch.line(a, d);
+    let arc2 = sketch.arc(o2, d, c);
+    let side2 = sketch.line(c, b);
+    let constraints = [
+        Constraint::OnCurve(t1, arc1),
+        Constraint::OnCurve(t2, arc2),
+        Constraint::Symmetric(a, b, yaxis),
+        Constraint::Symmetric(b, c, xaxis),
+        Constraint::Symmetric(a, d, xaxis),
+        Constraint::OnCurve(t1, yaxis),
+        Constraint::Symmetric(t1, t2, xaxis),
+        Constraint::Distance(a, b, width),
+        Constraint::Distance(b, c, thickness / 2.0),
+        Constraint::Distance(t1, t2, thickness),
+    ];
+    for constraint in constraints {
+        sketch.constrain(constraint).unwrap();
+    }
+    sketch.solve().unwrap();
+    let corners = [
+        (a, -width / 2.0, thickness / 4.0),
+        (b, width / 2.0, thickness / 4.0),
+        (c, width / 2.0, -thickness / 4.0),
+        (d, -width / 2.0, -thickness / 4.0),
+        (t1, 0.0, thickness / 2.0),
+    ];
+    for (point, x, y) in corners {
+        assert!(sketch.position(point).near(&Point2::new(x, y)));
+    }
+    let wire = sketch.wire(&[arc1, side1, arc2, side2]).unwrap();
+    let face = builder::try_attach_plane(&[wire]).unwrap();
+    let body = builder::tsweep(&face, Vector3::new(0.0, height, 0.0));
+    // The section is the rectangle and the two segments of the circles.
+    let radius = sketch.radius(arc1).unwrap();
+    let angle = 2.0 * f64::asin(width / 2.0 / radius);
+    let segment = radius * radius / 2.0 * (angle - angle.sin());
+    let area = width * thickness / 2.0 + 2.0 * segment;
+    check("bottle body", &body, area * height);
+    save_shape(&body, "sketched-body");
+
+    // The slot: the two semicircles joined by the tangent lines.
+    let (length, radius) = (2.0, 0.5);
+    let mut sketch = Sketch::new(Frame::default());
+    let c1 = sketch.point(0.0, 0.0);
+    let c2 = sketch.point(1.8, 0.1);
+    let p1 = sketch.point(0.1, -0.6);
+    let p2 = sketch.point(1.9, -0.4);
+    let p3 = sketch.point(2.1, 0.6);
+    let p4 = sketch.point(-0.1, 0.4);
+    let centers = sketch.line(c1, c2);
+    let bottom = sketch.line(p1, p2);
+    let right = sketch.arc(c2, p2, p3);
+    let top = sketch.line(p3, p4);
+    let left = sketch.arc(c1, p4, p1);
+    let constraints = [
+        Constraint::Fix(c1, Point2::origin()),
+        Constraint::Horizontal(centers),
+        Constraint::Distance(c1, c2, length),
+        Constraint::Radius(right, radius),
+        Constraint::Radius(left, radius),
+        Constraint::Tangent(bottom, right),
+        Constraint::Tangent(right, top),
+        Constraint::Tangent(top, left),
+        Constraint::Tangent(left, bottom),
+    ];
+    for constraint in constraints {
+        sketch.constrain(constraint).unwrap();
+    }
+    sketch.solve().unwrap();
+    assert!(sketch.position(p3).near(&Point2::new(length, radius)));
+    // The curves are joined in any direction.
+    let wire = sketch.wire(&[bottom, top, left, right]).err();
+    assert_eq!(wire, Some(SketchError::NotClosed));
+    let wire = sketch.wire(&[top, right, bottom, left]).unwrap();
+    let face = builder::try_attach_plane(&[wire]).unwrap();
+    let slot = builder::tsweep(&face, Vector3::unit_z());
+    check("slot", &slot, 2.0 * radius * length + PI * radius * radius);
+
+    // the equilateral triangle by the angles
+    let mut sketch = Sketch::new(Frame::default());
+    let p = sketch.point(0.0, 0.0);
+    let q = sketch.point(1.2, 0.1);
+    let r = sketch.point(0.4, 0.7);
+    let [l0, l1, l2] = [(p, q), (q, r), (r, p)].map(|(p, q)| sketch.line(p, q));
+    let constraints = [
+        Constraint::Fix(p, Point2::origin()),
+        Constraint::Horizontal(l0),
+        Constraint::Distance(p, q, 1.0),
+        Constraint::Angle(l0, l1, 2.0 * PI / 3.0),
+        Constraint::Angle(l0, l2, PI / 3.0),
+    ];
+    for constraint in constraints {
+        sketch.constrain(constraint).unwrap();
+    }
+    sketch.solve().unwrap();
+    let apex = Point2::new(0.5, f64::sqrt(3.0) / 2.0);
+    assert!(sketch.position(r).near(&apex));
+    let wire = sketch.wire(&[l0, l1, l2]).unwrap();
+    let face = builder::try_attach_plane(&[wire]).unwrap();
+    let prism = builder::tsweep(&face, Vector3::unit_z());
+    check("triangular prism", &prism, f64::sqrt(3.0) / 4.0);
+
+    // the circle, whose radius is solved
+    let mut sketch = Sketch::new(Frame::default());
+    let center = sketch.point(0.1, 0.0);
+    let circle = sketch.circle(center, 0.8);
+    let constraints = [
+        Constraint::Fix(center, Point2::origin()),
+        Constraint::Radius(circle, 1.0),
+    ];
+    for constraint in constraints {
+        sketch.constrain(constraint).unwrap();
+    }
+    sketch.solve().unwrap();
+    assert!(sketch.radius(circle).unwrap().near(&1.0));
+    let wire = sketch.wire(&[circle]).unwrap();
+    let face = builder::try_attach_plane(&[wire]).unwrap();
+    let cylinder = builder::tsweep(&face, Vector3::new(0.0, 0.0, 2.0));
+    check("cylinder", &cylinder, 2.0 * PI);
+
+    // the spline closed by the line
+    let mut sketch = Sketch::new(Frame::default());
+    let points = [(0.0, 0.0), (0.5, 1.0), (1.5, 1.0), (2.0, 0.0)].map(|(x, y)| {
+        let point = sketch.point(x, y);
+        sketch
+            .constrain(Constraint::Fix(point, Point2::new(x, y)))
+            .unwrap();
+        point
+    });
+    let spline = sketch
+        .spline(&[points[3], points[2], points[1], points[0]])
+        .unwrap();
+    let chord = sketch.line(points[0], points[3]);
+    sketch.solve().unwrap();
+    let wire = sketch.wire(&[spline, chord]).unwrap();
+    let face = builder::try_attach_plane(&[wire]).unwrap();
+    let arch = builder::tsweep(&face, Vector3::unit_z());
+    // the area under the cubic Bezier curve
+    check("arch", &arch, 1.05);
+
+    // The rectangle is under-constrained until it is fixed, and over-constrained by the
+    // redundant diagonal.
+    let mut sketch = Sketch::new(Frame::default());
+    let v = [(0.0, 0.0), (1.1, 0.1), (0.9, 0.6), (0.1, 0.4)].map(|(x, y)| sketch.point(x, y));
+    let e = [0, 1, 2, 3].map(|i| sketch.line(v[i], v[(i + 1) % 4]));
+    let constraints = [
+        Constraint::Horizontal(e[0]),
+        Constraint::Vertical(e[1]),
+        Constraint::Horizontal(e[2]),
+        Constraint::Vertical(e[3]),
+    ];
+    for constraint in constraints {
+        sketch.constrain(constraint).unwrap();
+    }
+    assert_eq!(sketch.solve(), Err(SketchError::UnderConstrained(4)));
+    sketch
+        .constrain(Constraint::Distance(v[0], v[1], 1.0))
+        .unwrap();
+    sketch
+        .constrain(Constraint::Distance(v[1], v[2], 0.5))
+        .unwrap();
+    assert_eq!(sketch.solve(), Err(SketchError::UnderConstrained(2)));
+    sketch
+        .constrain(Constraint::Fix(v[0], Point2::origin()))
+        .unwrap();
+    assert_eq!(sketch.solve(), Ok(()));
+    assert!(sketch.position(v[2]).near(&Point2::new(1.0, 0.5)));
+    sketch
+        .constrain(Constraint::Distance(v[0], v[2], f64::hypot(1.0, 0.5)))
+        .unwrap();
+    assert_eq!(sketch.solve(), Err(SketchError::OverConstrained(1)));
+
+    // errors
+    assert_eq!(
+        sketch.constrain(Constraint::Radius(e[0], 1.0)),
+        Err(SketchError::InvalidConstraint)
+    );
+    assert_eq!(sketch.spline(&[v[0]]), Err(SketchError::InvalidCurve));
+    assert_eq!(
+        sketch.wire(&[e[0], e[1], e[2]]).err(),
+        Some(SketchError::NotClosed)
+    );
+}
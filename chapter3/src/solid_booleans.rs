@@ -0,0 +1,126 @@
+use chapter3::{
+    primitive::{self, *},
+    shapeops::*,
+};
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+use truck_stepio::out::*;
+
+fn save_shape(solid: &Solid, filename: &str) {
+    // output to polygonmesh
+    let mesh_with_topology = solid.triangulation(0.01);
+    let mesh = mesh_with_topology.to_polygon();
+    let obj_path = filename.to_string() + ".obj";
+    let mut obj = std::fs::File::create(&obj_path).unwrap();
+    obj::write(&mesh, &mut obj).unwrap();
+
+    // compress solid data.
+    let compressed = solid.compress();
+
+    // step format display
+    let display = CompleteStepDisplay::new(StepModel::from(&compressed), Default::default());
+    // content of step file
+    let step_string: String = display.to_string();
+    let step_path = filename.to_string() + ".step";
+    std::fs::write(&step_path, &step_string).unwrap();
+}
+
+// the volume of the closed triangulation
+fn volume(solid: &Solid) -> f64 {
+    let mut mesh = solid.triangulation(0.005).to_polygon();
+    mesh.put_together_same_attrs(10.0 * TOLERANCE)
+        .remove_degenerate_faces();
+    assert_eq!(mesh.shell_condition(), ShellCondition::Closed);
+    mesh.volume()
+}
+
+fn main() {
+    let tol = 0.05;
+    let world = Frame::default();
+
+    // the cube punched by the cylinder
+    let cube = cuboid(&Frame::at(Point3::new(0.0, 0.0, -1.0)), 2.0, 2.0, 2.0).unwrap();
+    let cylinder = primitive::cylinder(&Frame::at(Point3::new(0.0, 0.0, -2.0)), 0.5, 4.0).unwrap();
+    let punched = difference(&cube, &cylinder, tol).unwrap();
+    let exact = 8.0 - 2.0 * PI * 0.25;
+    println!("punched cube: {:.4} (exact: {exact:.4})", volume(&punched));
+    assert!(f64::abs(volume(&punched) - exact) < 0.01 * exact);
+    save_shape(&punched, "punched-cube");
+
+    // the half of the cylinder
+    let half_space = cuboid(&Frame::at(Point3::new(0.5, 0.0, -2.0)), 1.0, 4.0, 4.0).unwrap();
+    let column = primitive::cylinder(&Frame::at(Point3::new(0.0, 0.0, -1.0)), 0.8, 2.0).unwrap();
+    let half = intersection(&column, &half_space, tol).unwrap();
+    let exact = PI * 0.64;
+    println!("half cylinder: {:.4} (exact: {exact:.4})", volume(&half));
+    assert!(f64::abs(volume(&half) - exact) < 0.01 * exact);
+    save_shape(&half, "half-cylinder");
+
+    // the hexagonal nut
+    let hexagon = prism(&Frame::at(Point3::new(0.0, 0.0, -0.5)), 6, 1.0, 1.0).unwrap();
+    let nut = difference(&hexagon, &cylinder, tol).unwrap();
+    let exact = 3.0 * f64::sqrt(3.0) / 2.0 - PI * 0.25;
+    println!("hexagonal nut: {:.4} (exact: {exact:.4})", volume(&nut));
+    assert!(f64::abs(volume(&nut) - exact) < 0.01 * exact);
+    save_shape(&nut, "hexagonal-nut");
+
+    // the cross of the cylinders
+    let x_axis = Frame::new(
+        Point3::new(-2.0, 0.0, 0.0),
+        Vector3::unit_x(),
+        Vector3::unit_y(),
+    )
+    .unwrap();
+    let bar = primitive::cylinder(&x_axis, 0.4, 4.0).unwrap();
+    let cross = union(&column, &bar, tol).unwrap();
+    let (column_volume, bar_volume) = (PI * 0.64 * 2.0, PI * 0.16 * 4.0);
+    println!(
+        "cross: {:.4} (cylinders: {column_volume:.4}, {bar_volume:.4})",
+        volume(&cross)
+    );
+    assert!(volume(&cross) > column_volume && volume(&cross) < column_volume + bar_volume);
+    save_shape(&cross, "cross");
+
+    // the tori linked as a chain, whose surfaces of revolution are converted into NURBS
+    let torus0 = torus(&world, 1.0, 0.3).unwrap();
+    let frame = Frame::new(
+        Point3::new(1.0, 0.0, 0.0),
+        Vector3::unit_x(),
+        Vector3::unit_y(),
+    );
+    let torus1 = torus(&frame.unwrap(), 1.0, 0.3).unwrap();
+    let united = union(&torus0, &torus1, 0.02).unwrap();
+    let (single, linked) = (volume(&torus0), volume(&united));
+    println!("linked tori: {linked:.4} (torus: {single:.4})");
+    assert!(linked > single && linked < 2.0 * single);
+    save_shape(&united, "linked-tori");
+    // The tori cross at the two regions.
+    let shells = |solid: &Solid| {
+        let closed = solid
+            .boundaries()
+            .iter()
+            .all(|shell| shell.shell_condition() == ShellCondition::Closed);
+        closed.then(|| solid.boundaries().len())
+    };
+    let common = intersection(&torus0, &torus1, 0.02).unwrap();
+    assert_eq!(shells(&common), Some(2));
+    let rest = difference(&torus0, &torus1, 0.02).unwrap();
+    assert_eq!(shells(&rest), Some(1));
+
+    // errors
+    let far = cuboid(&Frame::at(Point3::new(10.0, 0.0, 0.0)), 1.0, 1.0, 1.0).unwrap();
+    assert_eq!(
+        intersection(&cube, &far, tol).err(),
+        Some(BooleanError::Empty(BooleanOperation::Intersection))
+    );
+    assert_eq!(
+        union(&cube, &cylinder, 0.0).err(),
+        Some(BooleanError::InvalidTolerance(0.0))
+    );
+    // The faces of revolution which touch the axis are not supported.
+    let sphere = sphere(&world, 1.0).unwrap();
+    let result = intersection(&sphere, &half_space, tol);
+    println!("sphere: {}", result.as_ref().unwrap_err());
+    assert_eq!(result.err(), Some(BooleanError::UnsupportedSurface(0)));
+}
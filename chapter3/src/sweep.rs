@@ -0,0 +1,597 @@
+//! Sweeps of the shapes along the paths.
+//!
+//! The vertices, the edges, the wires and the faces are swept along the wires, which have to
+//! be continuous and tangent-continuous at their vertices. The profile is moved by the frames
+//! along the path relative to the frame at the start, so the profile is usually placed at the
+//! start of the path, across the path. The twist rotates the profile around the tangent, and
+//! the scale scales it perpendicular to the tangent, both by the laws of the ratio of the
+//! length along the path.
+//!
+//! The sections at `division + 1` points of each edge of the path are exact, and are
+//! interpolated by the cubic B-splines, so the swept surfaces are the NURBS surfaces which
+//! approximate the sweep between the sections.
+
+use std::collections::HashMap;
+use std::result::Result;
+use truck_modeling::*;
+
+/// The frames moving along the paths.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrameMode {
+    /// the tangent, the principal normal and the binormal, which are undefined on the straight
+    /// parts of the path
+    Frenet,
+    /// the frame which rotates around the tangent as little as possible, by the double
+    /// reflection method
+    RotationMinimizing,
+    /// the frame whose binormal is the vector made perpendicular to the tangent; the vector
+    /// must not be parallel to the tangent
+    FixedBinormal(Vector3),
+}
+
+/// The values along the paths, as the functions of the ratio of the length from the start.
+#[derive(Clone, Copy, Debug)]
+pub enum Law {
+    /// the constant value
+    Constant(f64),
+    /// the value changing linearly from the first one to the second one
+    Linear(f64, f64),
+    /// the value of the function on `[0, 1]`
+    Function(fn(f64) -> f64),
+}
+
+impl Law {
+    /// Returns the value at the ratio `s` of the length.
+    pub fn value(&self, s: f64) -> f64 {
+        match self {
+            Law::Constant(value) => *value,
+            Law::Linear(start, end) => start + (end - start) * s,
+            Law::Function(function) => function(s),
+        }
+    }
+}
+
+/// The options of the sweeps.
+#[derive(Clone, Copy, Debug)]
+pub struct SweepOptions {
+    /// the frames along the path
+    pub frame: FrameMode,
+    /// the angle of the rotation of the profile around the tangent in radians
+    pub twist: Law,
+    /// the scale of the profile perpendicular to the tangent
+    pub scale: Law,
+    /// the number of the intervals between the exact sections on each edge of the path
+    pub division: usize,
+}
+
+impl Default for SweepOptions {
+    fn default() -> Self {
+        SweepOptions {
+            frame: FrameMode::RotationMinimizing,
+            twist: Law::Constant(0.0),
+            scale: Law::Constant(1.0),
+            division: 16,
+        }
+    }
+}
+
+/// The errors of the sweeps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SweepError {
+    /// The path is empty, not continuous, or not tangent-continuous at a vertex.
+    InvalidPath,
+    /// The division is zero.
+    InvalidDivision,
+    /// The frame is undefined at a point of the path: the Frenet frame on a straight part, or
+    /// the fixed binormal parallel to the tangent.
+    UndefinedFrame,
+    /// The scale is not positive at a point of the path.
+    InvalidScale,
+    /// The path or the profile has an intersection curve or an unclamped curve.
+    UnsupportedCurve,
+    /// The path is closed, but the sections at the ends do not coincide, e.g. by the twist or
+    /// the scale.
+    MismatchedEnds,
+}
+
+impl std::fmt::Display for SweepError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SweepError::InvalidPath => f.write_str("the path is not a smooth continuous wire"),
+            SweepError::InvalidDivision => f.write_str("the division must be positive"),
+            SweepError::UndefinedFrame => f.write_str("the frame is undefined on the path"),
+            SweepError::InvalidScale => f.write_str("the scale must be positive"),
+            SweepError::UnsupportedCurve => f.write_str("the curve is not supported"),
+            SweepError::MismatchedEnds => {
+                f.write_str("the sections at the ends of the closed path do not coincide")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SweepError {}
+
+/// The shapes which are swept along the paths.
+pub trait PathSweep {
+    /// the swept shape
+    type Swept;
+    /// Sweeps the shape along the path.
+    fn sweep(&self, path: &Wire, options: &SweepOptions) -> Result<Self::Swept, SweepError>;
+}
+
+/// Sweeps the shape along the path: the vertex into the wire, the edge and the wire into the
+/// shell, and the face into the solid.
+pub fn sweep<T: PathSweep>(
+    shape: &T,
+    path: &Wire,
+    options: &SweepOptions,
+) -> Result<T::Swept, SweepError> {
+    shape.sweep(path, options)
+}
+
+impl PathSweep for Vertex {
+    type Swept = Wire;
+    fn sweep(&self, path: &Wire, options: &SweepOptions) -> Result<Wire, SweepError> {
+        let mut sweeper = Sweeper::new(path, options)?;
+        Ok(sweeper.trajectories(self).into())
+    }
+}
+
+impl PathSweep for Edge {
+    type Swept = Shell;
+    fn sweep(&self, path: &Wire, options: &SweepOptions) -> Result<Shell, SweepError> {
+        let mut sweeper = Sweeper::new(path, options)?;
+        Ok(sweeper.faces(self)?.into())
+    }
+}
+
+impl PathSweep for Wire {
+    type Swept = Shell;
+    fn sweep(&self, path: &Wire, options: &SweepOptions) -> Result<Shell, SweepError> {
+        let mut sweeper = Sweeper::new(path, options)?;
+        let mut faces = Vec::new();
+        for edge in self.edge_iter() {
+            faces.extend(sweeper.faces(edge)?);
+        }
+        Ok(faces.into())
+    }
+}
+
+impl PathSweep for Face {
+    type Swept = Solid;
+    fn sweep(&self, path: &Wire, options: &SweepOptions) -> Result<Solid, SweepError> {
+        let mut sweeper = Sweeper::new(path, options)?;
+        let mut shell = Shell::new();
+        for edge in self.boundaries().iter().flat_map(Wire::edge_iter) {
+            shell.extend(sweeper.faces(edge)?);
+        }
+        if !sweeper.closed {
+            let last = sweeper.matrices.len();
+            shell.push(sweeper.cap(self, 0).inverse());
+            shell.push(sweeper.cap(self, last));
+        }
+        // The faces are outward if the profile faces forward.
+        let surface = self.oriented_surface();
+        let point = self.boundaries()[0].front_vertex().unwrap().point();
+        let (u, v) = surface
+            .search_parameter(point, None, 100)
+            .ok_or(SweepError::UnsupportedCurve)?;
+        if surface.normal(u, v).dot(sweeper.tangent) < 0.0 {
+            shell.face_iter_mut().for_each(|face| {
+                face.invert();
+            });
+        }
+        Ok(Solid::new(vec![shell]))
+    }
+}
+
+// The sweep along the path, with the swept topology of the profile.
+struct Sweeper {
+    // the affine maps of the sections on each edge of the path
+    matrices: Vec<Vec<Matrix4>>,
+    // the tangent at the start of the path
+    tangent: Vector3,
+    closed: bool,
+    // the knot vector of the interpolation along the path
+    knot_vec: KnotVec,
+    // the inverse of the matrix of the interpolation
+    interpolation: Vec<Vec<f64>>,
+    // the swept vertices at the vertices of the path
+    vertices: HashMap<VertexID, Vec<Vertex>>,
+    // the swept edges along the edges of the path
+    trajectories: HashMap<VertexID, Vec<Edge>>,
+    // the edges at the vertices of the path
+    sections: HashMap<EdgeID, Vec<Edge>>,
+    // the faces along the edges of the path
+    faces: HashMap<EdgeID, Vec<Face>>,
+}
+
+impl Sweeper {
+    fn new(path: &Wire, options: &SweepOptions) -> Result<Self, SweepError> {
+        if options.division == 0 {
+            return Err(SweepError::InvalidDivision);
+        }
+        if path.is_empty() || !path.is_continuous() {
+            return Err(SweepError::InvalidPath);
+        }
+        let curves: Vec<Curve> = path.edge_iter().map(|edge| edge.oriented_curve()).collect();
+        if curves
+            .iter()
+            .any(|curve| matches!(curve, Curve::IntersectionCurve(_)))
+        {
+            return Err(SweepError::UnsupportedCurve);
+        }
+        let closed = path.is_closed();
+        let frames = frames(&curves, options, closed)?;
+        // the length from the start at the samples
+        let mut lengths = vec![0.0];
+        frames.windows(2).for_each(|pair| {
+            let length = lengths.last().unwrap() + pair[0].0.distance(pair[1].0);
+            lengths.push(length);
+        });
+        let total = *lengths.last().unwrap();
+        let (point0, rotation0) = (frames[0].0, frames[0].1);
+        let matrix = |(point, rotation): (Point3, Matrix3), length: f64| {
+            let s = length / total;
+            let (angle, scale) = (options.twist.value(s), options.scale.value(s));
+            if !scale.is_finite() || scale <= 0.0 {
+                return Err(SweepError::InvalidScale);
+            }
+            let local = Matrix3::from_angle_z(Rad(angle))
+                * Matrix3::from_diagonal(Vector3::new(scale, scale, 1.0));
+            let linear = rotation * local * rotation0.transpose();
+            Ok(Matrix4::from_translation(point.to_vec())
+                * Matrix4::from(linear)
+                * Matrix4::from_translation(-point0.to_vec()))
+        };
+        let matrices = (0..curves.len())
+            .map(|k| {
+                (0..=options.division)
+                    .map(|i| {
+                        let idx = (k * options.division + i) * SUBDIVISION;
+                        matrix(frames[idx], lengths[idx])
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if closed {
+            let (first, last) = (matrices[0][0], *matrices.last().unwrap().last().unwrap());
+            if (0..4).any(|i| !(first[i] - last[i]).so_small()) {
+                return Err(SweepError::MismatchedEnds);
+            }
+        }
+        let n = options.division;
+        let params: Vec<f64> = (0..=n).map(|i| i as f64 / n as f64).collect();
+        let (knot_vec, interpolation) = interpolation(&params, usize::min(3, n));
+        Ok(Sweeper {
+            matrices,
+            tangent: frames[0].1.z,
+            closed,
+            knot_vec,
+            interpolation,
+            vertices: HashMap::new(),
+            trajectories: HashMap::new(),
+            sections: HashMap::new(),
+            faces: HashMap::new(),
+        })
+    }
+
+    // the affine map of the section at the `k`th vertex of the path
+    fn matrix(&self, k: usize) -> Matrix4 {
+        match k < self.matrices.len() {
+            true => self.matrices[k][0],
+            false => *self.matrices[k - 1].last().unwrap(),
+        }
+    }
+
+    fn vertices(&mut self, vertex: &Vertex) -> Vec<Vertex> {
+        if let Some(vertices) = self.vertices.get(&vertex.id()) {
+            return vertices.clone();
+        }
+        let mut vertices: Vec<Vertex> = (0..self.matrices.len())
+            .map(|k| Vertex::new(self.matrix(k).transform_point(vertex.point())))
+            .collect();
+        let last = match self.closed {
+            true => vertices[0].clone(),
+            false => Vertex::new(self.matrix(vertices.len()).transform_point(vertex.point())),
+        };
+        vertices.push(last);
+        self.vertices.insert(vertex.id(), vertices.clone());
+        vertices
+    }
+
+    fn trajectories(&mut self, vertex: &Vertex) -> Vec<Edge> {
+        if let Some(edges) = self.trajectories.get(&vertex.id()) {
+            return edges.clone();
+        }
+        let vertices = self.vertices(vertex);
+        let point = vertex.point().to_homogeneous();
+        let edges: Vec<Edge> = self
+            .matrices
+            .iter()
+            .enumerate()
+            .map(|(k, matrices)| {
+                let points: Vec<Vector4> = matrices.iter().map(|matrix| matrix * point).collect();
+                let control_points = interpolate(&self.interpolation, &points);
+                let control_points = control_points.into_iter().map(Point3::from_homogeneous);
+                let curve = BSplineCurve::new(self.knot_vec.clone(), control_points.collect());
+                Edge::new(&vertices[k], &vertices[k + 1], Curve::BSplineCurve(curve))
+            })
+            .collect();
+        self.trajectories.insert(vertex.id(), edges.clone());
+        edges
+    }
+
+    // the sections of the absolute edge
+    fn sections(&mut self, edge: &Edge) -> Vec<Edge> {
+        if let Some(edges) = self.sections.get(&edge.id()) {
+            return edges.clone();
+        }
+        let fronts = self.vertices(edge.absolute_front());
+        let backs = self.vertices(edge.absolute_back());
+        let curve = edge.curve();
+        let mut edges: Vec<Edge> = (0..self.matrices.len())
+            .map(|k| Edge::new(&fronts[k], &backs[k], curve.transformed(self.matrix(k))))
+            .collect();
+        let last = match self.closed {
+            true => edges[0].clone(),
+            false => {
+                let k = edges.len();
+                Edge::new(&fronts[k], &backs[k], curve.transformed(self.matrix(k)))
+            }
+        };
+        edges.push(last);
+        self.sections.insert(edge.id(), edges.clone());
+        edges
+    }
+
+    // the faces swept from the edge, oriented as the edge
+    fn faces(&mut self, edge: &Edge) -> Result<Vec<Face>, SweepError> {
+        let faces = match self.faces.get(&edge.id()) {
+            Some(faces) => faces.clone(),
+            None => {
+                let curve = nurbs(&edge.curve()).ok_or(SweepError::UnsupportedCurve)?;
+                let sections = self.sections(edge);
+                let fronts = self.trajectories(edge.absolute_front());
+                let backs = self.trajectories(edge.absolute_back());
+                let faces: Vec<Face> = self
+                    .matrices
+                    .iter()
+                    .enumerate()
+                    .map(|(k, matrices)| {
+                        // The control points of the sections are interpolated along the path.
+                        let control_points = curve
+                            .control_points()
+                            .iter()
+                            .map(|point| {
+                                let points: Vec<Vector4> =
+                                    matrices.iter().map(|matrix| matrix * point).collect();
+                                interpolate(&self.interpolation, &points)
+                            })
+                            .collect();
+                        let knot_vecs = (curve.knot_vec().clone(), self.knot_vec.clone());
+                        let surface =
+                            NurbsSurface::new(BSplineSurface::new(knot_vecs, control_points));
+                        let wire: Wire = vec![
+                            sections[k].clone(),
+                            backs[k].clone(),
+                            sections[k + 1].inverse(),
+                            fronts[k].inverse(),
+                        ]
+                        .into();
+                        Face::new(vec![wire], Surface::NurbsSurface(surface))
+                    })
+                    .collect();
+                self.faces.insert(edge.id(), faces.clone());
+                faces
+            }
+        };
+        Ok(match edge.orientation() {
+            true => faces,
+            false => faces.iter().map(Face::inverse).collect(),
+        })
+    }
+
+    // the face moved to the `k`th vertex of the path
+    fn cap(&mut self, face: &Face, k: usize) -> Face {
+        let wires: Vec<Wire> = face
+            .absolute_boundaries()
+            .iter()
+            .map(|wire| {
+                wire.edge_iter()
+                    .map(|edge| {
+                        let section = &self.sections(edge)[k];
+                        match edge.orientation() {
+                            true => section.clone(),
+                            false => section.inverse(),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut cap = Face::new(wires, face.surface().transformed(self.matrix(k)));
+        if !face.orientation() {
+            cap.invert();
+        }
+        cap
+    }
+}
+
+// the number of the samples of the frames in an interval between the sections
+const SUBDIVISION: usize = 8;
+
+// Returns the points and the frames, whose columns are the normal, the binormal and the
+// tangent, at the samples of the path.
+fn frames(
+    curves: &[Curve],
+    options: &SweepOptions,
+    closed: bool,
+) -> Result<Vec<(Point3, Matrix3)>, SweepError> {
+    let division = options.division * SUBDIVISION;
+    let mut samples: Vec<(Point3, Vector3, Vector3)> = Vec::new();
+    for (k, curve) in curves.iter().enumerate() {
+        let (t0, t1) = curve.range_tuple();
+        for i in 0..=division {
+            let t = t0 + (t1 - t0) * i as f64 / division as f64;
+            let derivation = curve.der(t);
+            if derivation.so_small() {
+                return Err(SweepError::InvalidPath);
+            }
+            let tangent = derivation.normalize();
+            // The last sample of the edge is the first one of the next edge.
+            if i == 0 && k > 0 {
+                let (_, previous, _) = samples.pop().unwrap();
+                if !(tangent - previous).so_small() {
+                    return Err(SweepError::InvalidPath);
+                }
+            }
+            samples.push((curve.subs(t), tangent, curve.der2(t)));
+        }
+    }
+    if closed && !(samples[0].1 - samples.last().unwrap().1).so_small() {
+        return Err(SweepError::InvalidPath);
+    }
+    let frenet = |tangent: Vector3, second: Vector3| {
+        let normal = second - tangent * second.dot(tangent);
+        (!normal.so_small()).then(|| normal.normalize())
+    };
+    let normals: Vec<Vector3> = match options.frame {
+        FrameMode::Frenet => samples
+            .iter()
+            .map(|(_, tangent, second)| frenet(*tangent, *second))
+            .collect::<Option<_>>()
+            .ok_or(SweepError::UndefinedFrame)?,
+        FrameMode::FixedBinormal(vector) => samples
+            .iter()
+            .map(|(_, tangent, _)| {
+                let binormal = vector - tangent * vector.dot(*tangent);
+                (!binormal.so_small()).then(|| binormal.normalize().cross(*tangent))
+            })
+            .collect::<Option<_>>()
+            .ok_or(SweepError::UndefinedFrame)?,
+        FrameMode::RotationMinimizing => {
+            let (_, tangent, second) = samples[0];
+            let normal = frenet(tangent, second).unwrap_or_else(|| {
+                let vector = match tangent.x.abs() < 0.5 {
+                    true => Vector3::unit_x(),
+                    false => Vector3::unit_y(),
+                };
+                (vector - tangent * vector.dot(tangent)).normalize()
+            });
+            let mut normals = vec![normal];
+            for pair in samples.windows(2) {
+                let [(p0, t0, _), (p1, t1, _)] = [pair[0], pair[1]];
+                let normal = *normals.last().unwrap();
+                // the reflection by the bisector plane of the points, and the one which
+                // matches the tangents
+                let (normal, tangent) = (reflect(normal, p1 - p0), reflect(t0, p1 - p0));
+                normals.push(reflect(normal, t1 - tangent));
+            }
+            // The normals of the closed path are rotated gradually to meet at the ends.
+            if closed {
+                let (first, last) = (normals[0], *normals.last().unwrap());
+                let angle = f64::atan2(last.cross(first).dot(samples[0].1), last.dot(first));
+                let n = normals.len() - 1;
+                for (i, (normal, (_, tangent, _))) in normals.iter_mut().zip(&samples).enumerate() {
+                    let angle = angle * i as f64 / n as f64;
+                    *normal = *normal * angle.cos() + tangent.cross(*normal) * angle.sin();
+                }
+            }
+            normals
+        }
+    };
+    let frames = samples
+        .iter()
+        .zip(normals)
+        .map(|((point, tangent, _), normal)| {
+            (
+                *point,
+                Matrix3::from_cols(normal, tangent.cross(normal), *tangent),
+            )
+        })
+        .collect();
+    Ok(frames)
+}
+
+// Reflects the vector by the plane perpendicular to `normal`, or keeps it if `normal`
+// vanishes.
+fn reflect(vector: Vector3, normal: Vector3) -> Vector3 {
+    let length2 = normal.magnitude2();
+    match length2 < TOLERANCE2 * TOLERANCE2 {
+        true => vector,
+        false => vector - normal * (2.0 * normal.dot(vector) / length2),
+    }
+}
+
+/// Returns the NURBS curve of the curve, or `None` if it is an intersection curve or
+/// unclamped.
+pub fn nurbs(curve: &Curve) -> Option<NurbsCurve<Vector4>> {
+    let curve = match curve {
+        Curve::Line(Line(p, q)) => BSplineCurve::new(KnotVec::bezier_knot(1), vec![*p, *q]).into(),
+        Curve::BSplineCurve(curve) => curve.clone().into(),
+        Curve::NurbsCurve(curve) => curve.clone(),
+        Curve::IntersectionCurve(_) => return None,
+    };
+    curve.is_clamped().then_some(curve)
+}
+
+/// Returns the knot vector and the inverse of the matrix of the interpolation at the
+/// increasing parameters from `0.0` to `1.0` by the B-spline of the degree, which must be
+/// less than the number of the parameters. The knots are the averages of the parameters.
+pub fn interpolation(params: &[f64], degree: usize) -> (KnotVec, Vec<Vec<f64>>) {
+    let n = params.len() - 1;
+    let mut knots = vec![0.0; degree + 1];
+    knots.extend(
+        (1..=n - degree).map(|j| params[j..j + degree].iter().sum::<f64>() / degree as f64),
+    );
+    knots.extend(vec![1.0; degree + 1]);
+    let knot_vec = KnotVec::from(knots);
+    let mut matrix: Vec<Vec<f64>> = params
+        .iter()
+        .map(|t| knot_vec.bspline_basis_functions(degree, *t))
+        .collect();
+    // The basis functions vanish at the end of the range.
+    matrix[n] = (0..=n).map(|j| if j == n { 1.0 } else { 0.0 }).collect();
+    (knot_vec, invert(matrix))
+}
+
+/// Returns the control points interpolating the points by the inverse of the matrix of the
+/// interpolation.
+pub fn interpolate(inverse: &[Vec<f64>], points: &[Vector4]) -> Vec<Vector4> {
+    inverse
+        .iter()
+        .map(|row| {
+            row.iter()
+                .zip(points)
+                .fold(Vector4::zero(), |sum, (a, p)| sum + p * *a)
+        })
+        .collect()
+}
+
+// Returns the inverse of the regular matrix by the Gauss-Jordan elimination.
+fn invert(mut matrix: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|a, b| matrix[*a][col].abs().total_cmp(&matrix[*b][col].abs()))
+            .unwrap();
+        matrix.swap(col, pivot);
+        inverse.swap(col, pivot);
+        let value = matrix[col][col];
+        matrix[col].iter_mut().for_each(|a| *a /= value);
+        inverse[col].iter_mut().for_each(|a| *a /= value);
+        for row in 0..n {
+            let factor = matrix[row][col];
+            if row != col && factor != 0.0 {
+                for j in 0..n {
+                    matrix[row][j] -= factor * matrix[col][j];
+                    inverse[row][j] -= factor * inverse[col][j];
+                }
+            }
+        }
+    }
+    inverse
+}
@@ -0,0 +1,178 @@
+use chapter3::sweep::*;
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+use truck_stepio::out::*;
+
+fn save_shape(solid: &Solid, filename: &str) {
+    // output to polygonmesh
+    let mesh_with_topology = solid.triangulation(0.01);
+    let mesh = mesh_with_topology.to_polygon();
+    let obj_path = filename.to_string() + ".obj";
+    let mut obj = std::fs::File::create(&obj_path).unwrap();
+    obj::write(&mesh, &mut obj).unwrap();
+
+    // compress solid data.
+    let compressed = solid.compress();
+
+    // step format display
+    let display = CompleteStepDisplay::new(StepModel::from(&compressed), Default::default());
+    // content of step file
+    let step_string: String = display.to_string();
+    let step_path = filename.to_string() + ".step";
+    std::fs::write(&step_path, &step_string).unwrap();
+}
+
+// the disk of the radius around the axis through the center
+fn disk(center: Point3, axis: Vector3, radius: f64) -> Face {
+    // the radial direction perpendicular to the axis
+    let radial = match axis.cross(Vector3::unit_y()).so_small() {
+        true => Vector3::unit_x(),
+        false => axis.cross(Vector3::unit_y()).normalize(),
+    };
+    let vertex = builder::vertex(center + radial * radius);
+    let circle = builder::rsweep(&vertex, center, axis, Rad(7.0));
+    builder::try_attach_plane(&[circle]).unwrap()
+}
+
+// Checks that the swept solid is closed and has the volume.
+fn check(name: &str, solid: &Solid, volume: f64) {
+    let shell = &solid.boundaries()[0];
+    assert_eq!(shell.shell_condition(), ShellCondition::Closed, "{name}");
+    assert!(shell.singular_vertices().is_empty(), "{name}");
+    let mut mesh = solid.triangulation(0.001).to_polygon();
+    mesh.put_together_same_attrs(10.0 * TOLERANCE)
+        .remove_degenerate_faces();
+    assert_eq!(mesh.shell_condition(), ShellCondition::Closed, "{name}");
+    println!("{name}: volume {:.5} (exact: {volume:.5})", mesh.volume());
+    assert!(f64::abs(mesh.volume() - volume) < 0.005 * volume, "{name}");
+}
+
+fn main() {
+    let options = SweepOptions::default();
+
+    // the tube along the line and the quarter arc
+    let v0 = builder::vertex(Point3::origin());
+    let v1 = builder::vertex(Point3::new(0.0, 0.0, 1.0));
+    let v2 = builder::vertex(Point3::new(1.0, 0.0, 2.0));
+    let transit = Point3::new(1.0 - f64::sqrt(0.5), 0.0, 1.0 + f64::sqrt(0.5));
+    let path: Wire = vec![
+        builder::line(&v0, &v1),
+        builder::circle_arc(&v1, &v2, transit),
+    ]
+    .into();
+    let r = 0.1;
+    let profile = disk(Point3::origin(), Vector3::unit_z(), r);
+    let tube = sweep(&profile, &path, &options).unwrap();
+    check("tube", &tube, PI * r * r * (1.0 + PI / 2.0));
+    save_shape(&tube, "tube");
+
+    // The swept vertex is the wire, and the swept wire is the shell.
+    let point = builder::vertex(Point3::new(r, 0.0, 0.0));
+    let wire = sweep(&point, &path, &options).unwrap();
+    assert_eq!(wire.len(), 2);
+    assert!(wire
+        .back_vertex()
+        .unwrap()
+        .point()
+        .near(&Point3::new(1.0, 0.0, 2.0 - r)));
+    let shell = sweep(&profile.boundaries()[0], &path, &options).unwrap();
+    assert_eq!(shell.shell_condition(), ShellCondition::Oriented);
+
+    // the ring along the closed circle, by the Frenet frames
+    let (radius, r) = (1.0, 0.2);
+    let start = builder::vertex(Point3::new(radius, 0.0, 0.0));
+    let circle = builder::rsweep(&start, Point3::origin(), Vector3::unit_z(), Rad(7.0));
+    let profile = disk(Point3::new(radius, 0.0, 0.0), Vector3::unit_y(), r);
+    let frenet = SweepOptions {
+        frame: FrameMode::Frenet,
+        ..options
+    };
+    let ring = sweep(&profile, &circle, &frenet).unwrap();
+    let sides = profile.boundaries()[0].len() * circle.len();
+    assert_eq!(ring.boundaries()[0].len(), sides);
+    check("ring", &ring, 2.0 * PI * PI * radius * r * r);
+    save_shape(&ring, "ring");
+    // The rotation-minimizing frames are rotated to meet at the ends of the closed path.
+    let ring = sweep(&profile, &circle, &options).unwrap();
+    check(
+        "ring by rotation-minimizing frames",
+        &ring,
+        2.0 * PI * PI * radius * r * r,
+    );
+
+    // the square bar twisted by 90 degrees and tapered to the half
+    let (a, length) = (0.5, 2.0);
+    let vertex = builder::vertex(Point3::new(-a / 2.0, -a / 2.0, 0.0));
+    let edge = builder::tsweep(&vertex, Vector3::new(a, 0.0, 0.0));
+    let square = builder::tsweep(&edge, Vector3::new(0.0, a, 0.0));
+    let top = builder::vertex(Point3::new(0.0, 0.0, length));
+    let line: Wire = vec![builder::line(&builder::vertex(Point3::origin()), &top)].into();
+    let twisted = SweepOptions {
+        frame: FrameMode::FixedBinormal(Vector3::unit_y()),
+        twist: Law::Linear(0.0, PI / 2.0),
+        scale: Law::Linear(1.0, 0.5),
+        ..options
+    };
+    let bar = sweep(&square, &line, &twisted).unwrap();
+    check(
+        "twisted bar",
+        &bar,
+        a * a * length * (1.0 + 0.5 + 0.25) / 3.0,
+    );
+    let corner = bar
+        .vertex_iter()
+        .map(|vertex| vertex.point())
+        .find(|p| p.z == length && p.x > 0.0 && p.y > 0.0)
+        .unwrap();
+    assert!(corner.near(&Point3::new(a / 4.0, a / 4.0, length)));
+    save_shape(&bar, "twisted-bar");
+
+    // the handle of the bottle along the half circle, whose section swells in the middle
+    let (radius, r) = (0.3, 0.05);
+    let v0 = builder::vertex(Point3::origin());
+    let v1 = builder::vertex(Point3::new(0.0, 0.0, 2.0 * radius));
+    let arc = builder::circle_arc(&v0, &v1, Point3::new(radius, 0.0, radius));
+    let profile = disk(Point3::origin(), Vector3::unit_x(), r);
+    let swelling = SweepOptions {
+        scale: Law::Function(|s| 1.0 + 0.5 * f64::sin(PI * s)),
+        ..options
+    };
+    let handle = sweep(&profile, &vec![arc].into(), &swelling).unwrap();
+    // the integral of (1 + sin(pi s) / 2)^2 on [0, 1]
+    let ratio = 1.0 + 2.0 / PI + 1.0 / 8.0;
+    check("handle", &handle, PI * r * r * PI * radius * ratio);
+    save_shape(&handle, "handle");
+
+    // errors
+    let frenet_line = sweep(&square, &line, &frenet);
+    assert_eq!(frenet_line.err(), Some(SweepError::UndefinedFrame));
+    let vertical = SweepOptions {
+        frame: FrameMode::FixedBinormal(Vector3::unit_z()),
+        ..options
+    };
+    let parallel = sweep(&square, &line, &vertical);
+    assert_eq!(parallel.err(), Some(SweepError::UndefinedFrame));
+    let side = builder::line(&top, &builder::vertex(Point3::new(1.0, 0.0, length)));
+    let sharp: Wire = vec![line[0].clone(), side].into();
+    let sharp_path = sweep(&square, &sharp, &options);
+    assert_eq!(sharp_path.err(), Some(SweepError::InvalidPath));
+    let vanishing = SweepOptions {
+        scale: Law::Linear(1.0, 0.0),
+        ..options
+    };
+    let vanished = sweep(&square, &line, &vanishing);
+    assert_eq!(vanished.err(), Some(SweepError::InvalidScale));
+    let open_twist = SweepOptions {
+        twist: Law::Linear(0.0, 1.0),
+        ..frenet
+    };
+    let mismatched = sweep(&profile, &circle, &open_twist);
+    assert_eq!(mismatched.err(), Some(SweepError::MismatchedEnds));
+    let no_division = SweepOptions {
+        division: 0,
+        ..options
+    };
+    let undivided = sweep(&square, &line, &no_division);
+    assert_eq!(undivided.err(), Some(SweepError::InvalidDivision));
+}
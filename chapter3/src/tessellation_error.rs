@@ -0,0 +1,58 @@
+use chapter3::deviation::*;
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+
+// modeling a cylinder
+// # Arguments
+// - bottom: y-coordinate of the bottom disk
+// - height: height of the cylinder
+// - radius: radius of the bottom disk
+fn cylinder(bottom: f64, height: f64, radius: f64) -> Solid {
+    let vertex = builder::vertex(Point3::new(0.0, bottom, radius));
+    let circle = builder::rsweep(&vertex, Point3::origin(), Vector3::unit_y(), Rad(7.0));
+    let disk = builder::try_attach_plane(&[circle]).unwrap();
+    builder::tsweep(&disk, Vector3::new(0.0, height, 0.0))
+}
+
+// modeling a torus
+// # Arguments
+// - major: the distance from the axis to the center of the tube
+// - minor: the radius of the tube
+fn torus(major: f64, minor: f64) -> Solid {
+    let vertex = builder::vertex(Point3::new(major, 0.0, minor));
+    let circle = builder::rsweep(
+        &vertex,
+        Point3::new(major, 0.0, 0.0),
+        Vector3::unit_y(),
+        Rad(7.0),
+    );
+    let shell = builder::rsweep(&circle, Point3::origin(), Vector3::unit_z(), Rad(7.0));
+    Solid::new(vec![shell])
+}
+
+fn main() {
+    let solids = [
+        ("cylinder", cylinder(-1.0, 2.0, 1.0)),
+        ("torus", torus(1.0, 0.3)),
+    ];
+    for (name, solid) in &solids {
+        for tol in [0.1, 0.05, 0.01] {
+            let meshed = solid.triangulation(tol);
+            let deviation = solid_deviation(solid, &meshed, 4).unwrap();
+            let Deviation { max, mean, rms } = deviation.total;
+            println!("{name} (tol = {tol}): max = {max:.6}, mean = {mean:.6}, rms = {rms:.6}");
+        }
+    }
+
+    // The sagitta of the side of the cylinder is the deviation at the middle of each chord.
+    let meshed = solids[0].1.triangulation(0.01);
+    let deviation = solid_deviation(&solids[0].1, &meshed, 2).unwrap();
+    let side = deviation
+        .faces
+        .iter()
+        .map(|face| face.max)
+        .fold(0.0, f64::max);
+    let division = (PI / f64::acos(1.0 - side)).round();
+    println!("the side of the cylinder is divided into about {division} segments");
+}
@@ -0,0 +1,257 @@
+//! Helices and threads.
+//!
+//! The helix is the cubic B-spline interpolating the points of the exact helix, and is split
+//! at every turn. The thread is the profile swept along the helix by the frames whose binormal
+//! is the axis, which move the profile by the screw motion around the axis.
+//!
+//! The profile is drawn on the xz-plane of the local coordinates of the thread: the x-axis is
+//! along the normal of the cylindrical face, so that the thread protrudes from the face, and
+//! the z-axis is along the axis. The origin is on the face at the start of the helix, which
+//! is placed so that the thread is centered on the face along the axis, and around the axis
+//! so that the caps of the thread are far from the vertices of the face.
+//!
+//! The profile should sink into the face a little, so that the thread can be united with the
+//! solid of the face by `truck-shapeops`, whose success depends on the tolerance and the
+//! number of the turns.
+
+use crate::primitive::Frame;
+use crate::shelling::{classify, perpendicular, Quadric};
+use crate::sweep::{interpolate, interpolation, sweep, FrameMode, SweepError, SweepOptions};
+use std::f64::consts::PI;
+use std::result::Result;
+use truck_modeling::*;
+
+/// The directions of the rotation of the helices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Handedness {
+    /// counterclockwise seen from the end of the axis, as the usual screws
+    Right,
+    /// clockwise seen from the end of the axis
+    Left,
+}
+
+/// The profiles of the threads.
+#[derive(Clone, Debug)]
+pub enum ThreadProfile {
+    /// the basic profile of the ISO metric threads, whose flanks make 60 degrees and whose
+    /// height is `5/8` of the fundamental triangle, sunk into the face by `1/8` of the pitch
+    Metric,
+    /// the closed planar wire on the xz-plane of the local coordinates
+    Custom(Wire),
+}
+
+/// The options of the threads.
+#[derive(Clone, Debug)]
+pub struct ThreadOptions {
+    /// the distance along the axis between the adjacent turns
+    pub pitch: f64,
+    /// the number of the turns, which may be fractional
+    pub turns: f64,
+    /// the direction of the rotation
+    pub handedness: Handedness,
+    /// the profile swept around the face
+    pub profile: ThreadProfile,
+}
+
+/// The errors of the helices and the threads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThreadError {
+    /// The dimension of the name is not positive or not finite.
+    InvalidDimension(&'static str),
+    /// The face is not on a cylinder.
+    NotCylinder,
+    /// The profile is not a closed planar wire.
+    InvalidProfile,
+    /// The thread is longer than the face along the axis.
+    TooLong,
+    /// The sweep of the profile failed.
+    Sweep(SweepError),
+}
+
+impl std::fmt::Display for ThreadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThreadError::InvalidDimension(name) => write!(f, "invalid {name}"),
+            ThreadError::NotCylinder => f.write_str("the face is not on a cylinder"),
+            ThreadError::InvalidProfile => f.write_str("the profile is not a closed planar wire"),
+            ThreadError::TooLong => f.write_str("the thread is longer than the face"),
+            ThreadError::Sweep(error) => write!(f, "the sweep failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ThreadError {}
+
+// the number of the interpolated points in a turn of the helices
+const SAMPLES: usize = 32;
+
+fn check(name: &'static str, value: f64) -> Result<(), ThreadError> {
+    match value.is_finite() && value > 0.0 {
+        true => Ok(()),
+        false => Err(ThreadError::InvalidDimension(name)),
+    }
+}
+
+/// Returns the helix of `turns` around the z-axis of the frame, which starts on the x-axis at
+/// `radius` and rises by `pitch` in every turn. Every edge is a turn, except the last one of
+/// the fractional turns.
+pub fn helix(
+    frame: &Frame,
+    radius: f64,
+    pitch: f64,
+    turns: f64,
+    handedness: Handedness,
+) -> Result<Wire, ThreadError> {
+    check("radius", radius)?;
+    check("pitch", pitch)?;
+    check("turns", turns)?;
+    let sign = match handedness {
+        Handedness::Right => 1.0,
+        Handedness::Left => -1.0,
+    };
+    let n = usize::max((turns * SAMPLES as f64).ceil() as usize, 3);
+    let params: Vec<f64> = (0..=n).map(|i| i as f64 / n as f64).collect();
+    let points: Vec<Vector4> = params
+        .iter()
+        .map(|t| {
+            let angle = 2.0 * PI * turns * t;
+            let local = Point3::new(
+                radius * angle.cos(),
+                sign * radius * angle.sin(),
+                pitch * turns * t,
+            );
+            frame.transform_point(local).to_homogeneous()
+        })
+        .collect();
+    let (knot_vec, inverse) = interpolation(&params, 3);
+    let control_points = interpolate(&inverse, &points)
+        .into_iter()
+        .map(Point3::from_homogeneous)
+        .collect();
+    let mut curve = BSplineCurve::new(knot_vec, control_points);
+    // The curve is cut at the ends of the turns from the last one.
+    let ends: Vec<f64> = (1..)
+        .map(|k| k as f64 / turns)
+        .take_while(|t| *t < 1.0 && !(1.0 - t).so_small())
+        .collect();
+    let mut tails: Vec<BSplineCurve<Point3>> = ends.iter().rev().map(|t| curve.cut(*t)).collect();
+    tails.push(curve);
+    tails.reverse();
+    let mut vertices = vec![builder::vertex(tails[0].front())];
+    vertices.extend(tails.iter().map(|curve| builder::vertex(curve.back())));
+    let edges = tails
+        .into_iter()
+        .enumerate()
+        .map(|(k, curve)| Edge::new(&vertices[k], &vertices[k + 1], Curve::BSplineCurve(curve)));
+    Ok(edges.collect())
+}
+
+/// Returns the local profile of the ISO metric thread of the pitch.
+pub fn metric_profile(pitch: f64) -> Wire {
+    // the height of the thread and the depth sunk into the face
+    let height = 5.0 * f64::sqrt(3.0) / 16.0 * pitch;
+    let depth = pitch / 8.0;
+    // The flanks are 30 degrees from the normal, and the crest is `1/8` of the pitch.
+    let base = 3.0 * pitch / 8.0 + depth * f64::tan(PI / 6.0);
+    let points = [
+        Point3::new(-depth, 0.0, -base),
+        Point3::new(height, 0.0, -pitch / 16.0),
+        Point3::new(height, 0.0, pitch / 16.0),
+        Point3::new(-depth, 0.0, base),
+    ];
+    let vertices: Vec<Vertex> = points.iter().map(|p| builder::vertex(*p)).collect();
+    (0..4)
+        .map(|i| builder::line(&vertices[i], &vertices[(i + 1) % 4]))
+        .collect()
+}
+
+/// Returns the thread around the cylindrical face, which protrudes from the face along its
+/// normal and is centered on the face along the axis. The thread is outside the convex faces,
+/// e.g. the necks of the bottles, and inside the concave ones, e.g. the holes of the caps.
+pub fn thread(face: &Face, options: &ThreadOptions) -> Result<Solid, ThreadError> {
+    check("pitch", options.pitch)?;
+    check("turns", options.turns)?;
+    let (center, axis, radius) = match classify(&face.oriented_surface()) {
+        Some(Quadric::Cylinder(center, axis, radius)) => (center, axis, radius),
+        _ => return Err(ThreadError::NotCylinder),
+    };
+    let profile = match &options.profile {
+        ThreadProfile::Metric => metric_profile(options.pitch),
+        ThreadProfile::Custom(wire) => wire.clone(),
+    };
+    if profile.is_empty() || !profile.is_closed() {
+        return Err(ThreadError::InvalidProfile);
+    }
+    // the range of the face and the one of the thread along the axis
+    let heights = |vertices: &mut dyn Iterator<Item = Point3>, origin: Point3| {
+        vertices.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+            let height = (p - origin).dot(axis);
+            (f64::min(min, height), f64::max(max, height))
+        })
+    };
+    let (a0, a1) = heights(&mut face.vertex_iter().map(|v| v.point()), center);
+    let (z0, z1) = heights(
+        &mut profile.vertex_iter().map(|v| v.point()),
+        Point3::origin(),
+    );
+    let length = options.pitch * options.turns;
+    if length + (z1 - z0) > a1 - a0 + TOLERANCE {
+        return Err(ThreadError::TooLong);
+    }
+    // The profile is centered between the ends of the face at the middle turn.
+    let start = (a0 + a1) / 2.0 - (z0 + z1) / 2.0 - length / 2.0;
+    // The caps of the thread are placed as far as possible from the vertices of the face, so
+    // that they do not contain the seams of the cylinder.
+    let base = Frame::new(center, axis, perpendicular(axis)).unwrap();
+    let [bx, by, _] = base.axes();
+    let angles: Vec<f64> = face
+        .vertex_iter()
+        .map(|v| {
+            let p = v.point() - center;
+            f64::atan2(p.dot(by), p.dot(bx))
+        })
+        .collect();
+    let clearance = |angle: f64| {
+        angles.iter().fold(PI, |min, a| {
+            let difference = (angle - a).rem_euclid(2.0 * PI);
+            f64::min(min, f64::min(difference, 2.0 * PI - difference))
+        })
+    };
+    let sign = match options.handedness {
+        Handedness::Right => 1.0,
+        Handedness::Left => -1.0,
+    };
+    let sweep_angle = sign * 2.0 * PI * options.turns;
+    let angle = (0..SAMPLES)
+        .map(|i| 2.0 * PI * (i as f64 + 0.5) / SAMPLES as f64)
+        .max_by(|a, b| {
+            let [a, b] = [a, b].map(|a| f64::min(clearance(*a), clearance(a + sweep_angle)));
+            a.total_cmp(&b)
+        })
+        .unwrap();
+    let radial = bx * angle.cos() + by * angle.sin();
+    let frame = Frame::new(center + axis * start, axis, radial).unwrap();
+    let [x, _, z] = frame.axes();
+    // The x-axis of the profile is along the normal of the face.
+    let normal = x * radius.signum();
+    let matrix = Matrix4::from_cols(
+        normal.extend(0.0),
+        z.cross(normal).extend(0.0),
+        z.extend(0.0),
+        (frame.origin() + x * radius.abs()).to_homogeneous(),
+    );
+    let profile = builder::transformed(&profile, matrix);
+    let profile = builder::try_attach_plane(&[profile]).map_err(|_| ThreadError::InvalidProfile)?;
+    let path = helix(
+        &frame,
+        radius.abs(),
+        options.pitch,
+        options.turns,
+        options.handedness,
+    )?;
+    let sweep_options = SweepOptions {
+        frame: FrameMode::FixedBinormal(axis),
+        ..Default::default()
+    };
+    sweep(&profile, &path, &sweep_options).map_err(ThreadError::Sweep)
+}
@@ -0,0 +1,207 @@
+use chapter3::{
+    primitive::{self, *},
+    shapeops,
+    thread::*,
+};
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+use truck_stepio::out::*;
+
+fn save_shape(solid: &Solid, filename: &str) {
+    // output to polygonmesh
+    let mesh_with_topology = solid.triangulation(0.01);
+    let mesh = mesh_with_topology.to_polygon();
+    let obj_path = filename.to_string() + ".obj";
+    let mut obj = std::fs::File::create(&obj_path).unwrap();
+    obj::write(&mesh, &mut obj).unwrap();
+
+    // compress solid data.
+    let compressed = solid.compress();
+
+    // step format display
+    let display = CompleteStepDisplay::new(StepModel::from(&compressed), Default::default());
+    // content of step file
+    let step_string: String = display.to_string();
+    let step_path = filename.to_string() + ".step";
+    std::fs::write(&step_path, &step_string).unwrap();
+}
+
+// the lateral face of the cylinder
+fn lateral(solid: &Solid) -> Face {
+    solid
+        .face_iter()
+        .find(|face| !matches!(face.surface(), Surface::Plane(_)))
+        .unwrap()
+        .clone()
+}
+
+// The volume of the thread by Pappus's theorem: the area of the profile times the length of
+// the helix of its centroid projected to the plane perpendicular to the axis.
+fn thread_volume(profile: &Wire, radius: f64, turns: f64) -> f64 {
+    let points: Vec<Point3> = profile.vertex_iter().map(|v| v.point()).collect();
+    let (mut area, mut moment) = (0.0, 0.0);
+    for (i, p) in points.iter().enumerate() {
+        let q = points[(i + 1) % points.len()];
+        let cross = p.x * q.z - q.x * p.z;
+        area += cross / 2.0;
+        moment += (p.x + q.x) * cross / 6.0;
+    }
+    area.abs() * 2.0 * PI * (radius + moment / area) * turns
+}
+
+// Checks that the thread is closed and has the volume.
+fn check(name: &str, solid: &Solid, volume: f64) {
+    let shell = &solid.boundaries()[0];
+    assert_eq!(shell.shell_condition(), ShellCondition::Closed, "{name}");
+    assert!(shell.singular_vertices().is_empty(), "{name}");
+    let mut mesh = solid.triangulation(0.003).to_polygon();
+    mesh.put_together_same_attrs(10.0 * TOLERANCE)
+        .remove_degenerate_faces();
+    assert_eq!(mesh.shell_condition(), ShellCondition::Closed, "{name}");
+    println!("{name}: volume {:.6} (exact: {volume:.6})", mesh.volume());
+    assert!(f64::abs(mesh.volume() - volume) < 0.005 * volume, "{name}");
+}
+
+fn main() {
+    // the helix of two and a half turns
+    let (radius, pitch) = (1.0, 0.5);
+    let right = helix(&Frame::default(), radius, pitch, 2.5, Handedness::Right).unwrap();
+    assert_eq!(right.len(), 3);
+    let end = right.back_vertex().unwrap().point();
+    assert!(end.near(&Point3::new(-radius, 0.0, 2.5 * pitch)));
+    for edge in right.edge_iter() {
+        let curve = edge.oriented_curve();
+        let (t0, t1) = curve.range_tuple();
+        for i in 0..=10 {
+            let p = curve.subs(t0 + (t1 - t0) * i as f64 / 10.0);
+            assert!(f64::abs(p.to_vec().truncate().magnitude() - radius) < 1.0e-4);
+        }
+    }
+    // The right-handed helix goes to the y-axis at the quarter turn, the left-handed one
+    // goes to the opposite side.
+    let quarter = |handedness| {
+        let wire = helix(&Frame::default(), radius, pitch, 0.25, handedness).unwrap();
+        wire.back_vertex().unwrap().point()
+    };
+    assert!(quarter(Handedness::Right).near(&Point3::new(0.0, radius, pitch / 4.0)));
+    assert!(quarter(Handedness::Left).near(&Point3::new(0.0, -radius, pitch / 4.0)));
+
+    // the metric thread around the cylinder
+    let rod = primitive::cylinder(&Frame::default(), radius, 3.0).unwrap();
+    let options = ThreadOptions {
+        pitch,
+        turns: 4.0,
+        handedness: Handedness::Right,
+        profile: ThreadProfile::Metric,
+    };
+    let screw = thread(&lateral(&rod), &options).unwrap();
+    let volume = thread_volume(&metric_profile(pitch), radius, 4.0);
+    check("metric thread", &screw, volume);
+
+    // the left-handed thread of the custom square profile
+    let square = {
+        let (depth, half) = (0.05, 0.15);
+        let v = [(-depth, -half), (0.1, -half), (0.1, half), (-depth, half)]
+            .map(|(x, z)| builder::vertex(Point3::new(x, 0.0, z)));
+        let wire: Wire = (0..4)
+            .map(|i| builder::line(&v[i], &v[(i + 1) % 4]))
+            .collect();
+        wire
+    };
+    let custom = ThreadOptions {
+        handedness: Handedness::Left,
+        profile: ThreadProfile::Custom(square.clone()),
+        turns: 3.5,
+        ..options.clone()
+    };
+    let screw = thread(&lateral(&rod), &custom).unwrap();
+    check("square thread", &screw, thread_volume(&square, radius, 3.5));
+
+    // The internal thread in the hole of the cap, which protrudes toward the axis.
+    let outer = builder::rsweep(
+        &builder::vertex(Point3::new(1.5, 0.0, 0.0)),
+        Point3::origin(),
+        Vector3::unit_z(),
+        Rad(7.0),
+    );
+    let inner = builder::rsweep(
+        &builder::vertex(Point3::new(radius, 0.0, 0.0)),
+        Point3::origin(),
+        Vector3::unit_z(),
+        Rad(7.0),
+    );
+    let annulus = builder::try_attach_plane(&[outer, inner.inverse()]).unwrap();
+    let tube = builder::tsweep(&annulus, Vector3::new(0.0, 0.0, 2.0));
+    let hole = tube
+        .face_iter()
+        .find(|face| {
+            let point = face.vertex_iter().next().unwrap().point();
+            !matches!(face.surface(), Surface::Plane(_)) && point.x.hypot(point.y).near(&radius)
+        })
+        .unwrap()
+        .clone();
+    let short = ThreadOptions {
+        turns: 3.0,
+        ..options.clone()
+    };
+    let internal = thread(&hole, &short).unwrap();
+    // The centroid of the profile is inside the hole.
+    let volume = -thread_volume(&metric_profile(pitch), -radius, 3.0);
+    check("internal thread", &internal, volume);
+    let crest = internal
+        .vertex_iter()
+        .map(|v| v.point().x.hypot(v.point().y))
+        .fold(f64::INFINITY, f64::min);
+    assert!(crest < radius);
+
+    // the thread on the neck of the bottle, united with the neck
+    let (height, thickness) = (1.4, 0.6);
+    let frame = Frame::new(
+        Point3::new(0.0, height / 2.0, 0.0),
+        Vector3::unit_y(),
+        Vector3::unit_z(),
+    )
+    .unwrap();
+    let neck = primitive::cylinder(&frame, thickness / 4.0, height / 10.0).unwrap();
+    let neck_options = ThreadOptions {
+        pitch: 0.03,
+        turns: 3.0,
+        ..options.clone()
+    };
+    let neck_thread = thread(&lateral(&neck), &neck_options).unwrap();
+    // The union by truck-shapeops depends on the tolerance, which is chosen for the neck.
+    let threaded = shapeops::union(&neck, &neck_thread, 0.004).unwrap();
+    let shell = &threaded.boundaries()[0];
+    assert_eq!(shell.shell_condition(), ShellCondition::Closed);
+    save_shape(&threaded, "threaded-neck");
+
+    // errors
+    let no_pitch = ThreadOptions {
+        pitch: 0.0,
+        ..options.clone()
+    };
+    assert_eq!(
+        thread(&lateral(&rod), &no_pitch).err(),
+        Some(ThreadError::InvalidDimension("pitch"))
+    );
+    let top = rod.face_iter().last().unwrap();
+    assert_eq!(thread(top, &options).err(), Some(ThreadError::NotCylinder));
+    let long = ThreadOptions {
+        turns: 6.0,
+        ..options.clone()
+    };
+    assert_eq!(
+        thread(&lateral(&rod), &long).err(),
+        Some(ThreadError::TooLong)
+    );
+    let open: Wire = square.edge_iter().take(3).cloned().collect();
+    let open_profile = ThreadOptions {
+        profile: ThreadProfile::Custom(open),
+        ..options
+    };
+    assert_eq!(
+        thread(&lateral(&rod), &open_profile).err(),
+        Some(ThreadError::InvalidProfile)
+    );
+}
@@ -0,0 +1,65 @@
+use chapter2::voxel::*;
+use std::f64::consts::PI;
+use truck_meshalgo::prelude::*;
+use truck_modeling::*;
+
+// modeling a torus
+// # Arguments
+// - major: the distance from the axis to the center of the tube
+// - minor: the radius of the tube
+fn torus(major: f64, minor: f64) -> Solid {
+    let vertex = builder::vertex(Point3::new(major, 0.0, minor));
+    let circle = builder::rsweep(
+        &vertex,
+        Point3::new(major, 0.0, 0.0),
+        Vector3::unit_y(),
+        Rad(7.0),
+    );
+    let shell = builder::rsweep(&circle, Point3::origin(), Vector3::unit_z(), Rad(7.0));
+    Solid::new(vec![shell])
+}
+
+fn main() {
+    // the unit cube, whose faces are on the boundaries of the voxels
+    let vertex = builder::vertex(Point3::origin());
+    let edge = builder::tsweep(&vertex, Vector3::unit_x());
+    let face = builder::tsweep(&edge, Vector3::unit_y());
+    let cube = builder::tsweep(&face, Vector3::unit_z());
+    let solid = VoxelGrid::from_shape(&cube, 0.01, 0.25, VoxelMode::Solid).unwrap();
+    let surface = VoxelGrid::from_shape(&cube, 0.01, 0.25, VoxelMode::Surface).unwrap();
+    assert_eq!(solid.dims, [4, 4, 4]);
+    assert_eq!(solid.count(), 64);
+    let interior = VoxelGrid::from_shape(&cube, 0.01, 0.25, VoxelMode::Interior).unwrap();
+    assert_eq!(interior.count(), 64);
+    // The inner 2 x 2 x 2 voxels do not touch the faces.
+    assert_eq!(surface.count(), 64 - 8);
+
+    // The voxels of the torus approximate its volume. The voxels crossing the surface are in
+    // the layer whose thickness is the diagonal of the voxels, which bounds the errors.
+    let (major, minor) = (1.0, 0.3);
+    let exact = 2.0 * PI * PI * major * minor * minor;
+    let area = 4.0 * PI * PI * major * minor;
+    // The torus is triangulated once, in the same way as `VoxelGrid::from_shape`.
+    let mut mesh = torus(major, minor).triangulation(0.005).to_polygon();
+    mesh.put_together_same_attrs(10.0 * TOLERANCE);
+    let voxelize = |size: f64, mode: VoxelMode| {
+        let grid = VoxelGrid::from_mesh(&mesh, size, mode).unwrap();
+        let volume = grid.count() as f64 * f64::powi(size, 3);
+        println!("torus: {mode:?} voxel volume {volume:.4} of size {size} (exact: {exact:.4})");
+        (grid, volume)
+    };
+    let [_, solid] = [0.04, 0.02].map(|size| {
+        let diagonal = f64::sqrt(3.0) * size;
+        // The solid voxels cover the torus.
+        let (solid, volume) = voxelize(size, VoxelMode::Solid);
+        assert!(volume > exact && volume < exact + area * diagonal);
+        let (_, volume) = voxelize(size, VoxelMode::Interior);
+        assert!(f64::abs(volume - exact) < area * diagonal / 2.0);
+        solid
+    });
+    // The hole of the torus is empty.
+    assert!(!solid.contains(Point3::origin()));
+    assert!(solid.contains(Point3::new(major, 0.0, 0.0)));
+    let mut vtk = std::fs::File::create("torus-voxels.vtk").unwrap();
+    solid.write_vtk(&mut vtk).unwrap();
+}
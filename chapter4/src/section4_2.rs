@@ -34,9 +34,11 @@ impl App for MyApp {
         .unwrap();
 
         // Use default setting except the position
-        let mut light: Light = Light::default();
-        // It is safe to place the camera in the same position as the flash.
-        light.position = camera.position();
+        let light: Light = Light {
+            // It is safe to place the camera in the same position as the flash.
+            position: camera.position(),
+            ..Default::default()
+        };
 
         // the setting of scene
         let scene_desc = WindowSceneDescriptor {
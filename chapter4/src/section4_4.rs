@@ -1,5 +1,6 @@
 mod app; // Load the dropped submodule
 use app::*; // Use the trait app::App
+use chapter2::vertex_cache::*;
 use std::f64::consts::PI;
 use std::sync::Arc;
 use truck_platform::*;
@@ -77,33 +78,27 @@ impl App for MyApp {
 
     /// Processing when the mouse wheel is moved.
     fn mouse_wheel(&mut self, delta: MouseScrollDelta, _: TouchPhase) -> ControlFlow {
-        match delta {
-            // use only y-delta
-            MouseScrollDelta::LineDelta(_, y) => {
-                // get the mutable references to camera and light
-                let studio = self.scene.studio_config_mut();
-                let (camera, light) = (&mut studio.camera, &mut studio.lights[0]);
-                // Translation to the eye direction by 0.2 times the value obtained from the wheel.
-                let trans = Matrix4::from_translation(camera.eye_direction() * 0.2 * y as f64);
-                // move the camera and light
-                camera.matrix = trans * camera.matrix;
-                light.position = camera.position();
-            }
-            _ => {}
-        };
+        // use only y-delta
+        if let MouseScrollDelta::LineDelta(_, y) = delta {
+            // get the mutable references to camera and light
+            let studio = self.scene.studio_config_mut();
+            let (camera, light) = (&mut studio.camera, &mut studio.lights[0]);
+            // Translation to the eye direction by 0.2 times the value obtained from the wheel.
+            let trans = Matrix4::from_translation(camera.eye_direction() * 0.2 * y as f64);
+            // move the camera and light
+            camera.matrix = trans * camera.matrix;
+            light.position = camera.position();
+        }
         // Return a command to wait 1/60 second.
         Self::default_control_flow()
     }
 
     // Called when the mouse button is pressed and released.
     fn mouse_input(&mut self, state: ElementState, button: MouseButton) -> ControlFlow {
-        match button {
-            // Behavior when the left button is pressed or unpressed
-            MouseButton::Left => {
-                // pressed => start dragging, released => end dragging.
-                self.rotate_flag = state == ElementState::Pressed;
-            }
-            _ => {}
+        // Behavior when the left button is pressed or unpressed
+        if button == MouseButton::Left {
+            // pressed => start dragging, released => end dragging.
+            self.rotate_flag = state == ElementState::Pressed;
         }
         // Return a command to wait 1/60 second.
         Self::default_control_flow()
@@ -170,6 +165,9 @@ impl App for MyApp {
         mesh.positions_mut().iter_mut().for_each(|p| {
             *p = (*p - center) / (diameter / 2.0);
         });
+        // reorder the triangles and the vertices for the vertex cache of GPU
+        let (mesh, report) = optimize_for_rendering(&mesh, CacheOptimization::default());
+        println!("ACMR: {:.3} -> {:.3}", report.before, report.after);
         // create instance
         let instance: PolygonInstance = self.scene.instance_creator().create_instance(
             &mesh,